@@ -0,0 +1,54 @@
+use a320_systems::A320;
+use harness::Benchmarks;
+use std::time::Duration;
+use systems::simulation::{test::SimulationTestBed, StartingState};
+
+#[path = "../../systems/benches/harness/mod.rs"]
+mod harness;
+
+/// The number of ticks for which the time spent per system is reported after
+/// each benchmark.
+const PROFILED_TICKS: u32 = 1000;
+
+fn main() {
+    let benchmarks = Benchmarks::from_arguments();
+
+    for (name, state) in &[
+        ("a320_tick_cold_and_dark", StartingState::ColdAndDark),
+        ("a320_tick_gate_with_apu", StartingState::GateWithApu),
+        ("a320_tick_cruise", StartingState::Cruise),
+    ] {
+        let (mut aircraft, mut test_bed) = initialised_aircraft(*state);
+        let measurement = benchmarks.run(name, || test_bed.run_aircraft(&mut aircraft));
+
+        if measurement.is_some() {
+            let (mut aircraft, mut test_bed) = initialised_aircraft(*state);
+            aircraft.enable_profiling();
+            for _ in 0..PROFILED_TICKS {
+                test_bed.run_aircraft(&mut aircraft);
+            }
+
+            println!("{}", aircraft.profiler());
+        }
+    }
+}
+
+fn initialised_aircraft(state: StartingState) -> (A320, SimulationTestBed) {
+    let mut aircraft = A320::new();
+    // A tick as it occurs in the simulator at 20 frames per second.
+    let mut test_bed = SimulationTestBed::new_with_delta(Duration::from_millis(50));
+    test_bed.write_bool("UNLIMITED FUEL", true);
+    test_bed.set_on_ground(!state.is_in_flight());
+    test_bed.write_f64(
+        "GEAR CENTER POSITION",
+        if state.is_in_flight() { 0. } else { 100. },
+    );
+
+    let n2 = if state.engines_are_running() { 80. } else { 0. };
+    test_bed.write_f64("TURB ENG CORRECTED N2:1", n2);
+    test_bed.write_f64("TURB ENG CORRECTED N2:2", n2);
+
+    test_bed.initialise_aircraft(&mut aircraft, state);
+
+    (aircraft, test_bed)
+}
@@ -1,5 +1,72 @@
-use super::{alternating_current::A320AlternatingCurrentElectrical, A320ElectricalOverheadPanel};
-use systems::simulation::UpdateContext;
+use super::A320ElectricalOverheadPanel;
+use systems::{
+    electrical::{ElectricalBus, PotentialOrigin, PotentialSource},
+    simulation::UpdateContext,
+};
+
+/// AC BUS 1 and AC BUS 2, whose sources determine whether or not the galleys are shed.
+pub(super) struct MainAcBuses<'a> {
+    ac_bus_1: &'a ElectricalBus,
+    ac_bus_2: &'a ElectricalBus,
+}
+impl<'a> MainAcBuses<'a> {
+    pub fn new(ac_bus_1: &'a ElectricalBus, ac_bus_2: &'a ElectricalBus) -> Self {
+        Self { ac_bus_1, ac_bus_2 }
+    }
+
+    /// Whether or not AC BUS 1 and AC BUS 2 are powered by a single engine
+    /// generator exclusively. Also returns true when one of the buses is
+    /// unpowered and the other bus is powered by an engine generator.
+    fn powered_by_single_engine_generator_only(&self) -> bool {
+        (self.ac_bus_1.is_unpowered() && self.ac_bus_2.output().is_single_engine_generator())
+            || (self.ac_bus_1.output().is_single_engine_generator() && self.ac_bus_2.is_unpowered())
+            || (self
+                .ac_bus_1
+                .output()
+                .is_single(PotentialOrigin::EngineGenerator(1))
+                && self
+                    .ac_bus_2
+                    .output()
+                    .is_single(PotentialOrigin::EngineGenerator(1)))
+            || (self
+                .ac_bus_1
+                .output()
+                .is_single(PotentialOrigin::EngineGenerator(2))
+                && self
+                    .ac_bus_2
+                    .output()
+                    .is_single(PotentialOrigin::EngineGenerator(2)))
+    }
+
+    /// Whether or not AC BUS 1 and AC BUS 2 are powered by the APU generator
+    /// exclusively. Also returns true when one of the buses is unpowered and
+    /// the other bus is powered by the APU generator.
+    fn powered_by_apu_generator_only(&self) -> bool {
+        (self.ac_bus_1.is_unpowered()
+            && self
+                .ac_bus_2
+                .output()
+                .is_single(PotentialOrigin::ApuGenerator(1)))
+            || (self
+                .ac_bus_1
+                .output()
+                .is_single(PotentialOrigin::ApuGenerator(1))
+                && self.ac_bus_2.is_unpowered())
+            || (self
+                .ac_bus_1
+                .output()
+                .is_single(PotentialOrigin::ApuGenerator(1))
+                && self
+                    .ac_bus_2
+                    .output()
+                    .is_single(PotentialOrigin::ApuGenerator(1)))
+    }
+
+    /// Whether or not both AC BUS 1 and AC BUS 2 are unpowered.
+    fn unpowered(&self) -> bool {
+        self.ac_bus_1.is_unpowered() && self.ac_bus_2.is_unpowered()
+    }
+}
 
 pub(super) struct MainGalley {
     is_shed: bool,
@@ -16,13 +83,12 @@ impl MainGalley {
     pub fn update(
         &mut self,
         context: &UpdateContext,
-        alternating_current: &A320AlternatingCurrentElectrical,
+        main_ac_buses: &MainAcBuses,
         overhead: &A320ElectricalOverheadPanel,
     ) {
-        self.is_shed = alternating_current.main_ac_buses_unpowered()
-            || alternating_current.main_ac_buses_powered_by_single_engine_generator_only()
-            || (alternating_current.main_ac_buses_powered_by_apu_generator_only()
-                && context.is_in_flight())
+        self.is_shed = main_ac_buses.unpowered()
+            || main_ac_buses.powered_by_single_engine_generator_only()
+            || (main_ac_buses.powered_by_apu_generator_only() && context.is_in_flight())
             || overhead.commercial_is_off()
             || overhead.galy_and_cab_is_off();
     }
@@ -40,12 +106,8 @@ impl SecondaryGalley {
         self.is_shed
     }
 
-    pub fn update(
-        &mut self,
-        alternating_current: &A320AlternatingCurrentElectrical,
-        overhead: &A320ElectricalOverheadPanel,
-    ) {
-        self.is_shed = alternating_current.main_ac_buses_unpowered()
+    pub fn update(&mut self, main_ac_buses: &MainAcBuses, overhead: &A320ElectricalOverheadPanel) {
+        self.is_shed = main_ac_buses.unpowered()
             || overhead.commercial_is_off()
             || overhead.galy_and_cab_is_off();
     }
@@ -5,22 +5,28 @@ mod galley;
 use self::{
     alternating_current::A320AlternatingCurrentElectrical,
     direct_current::A320DirectCurrentElectrical,
-    galley::{MainGalley, SecondaryGalley},
+    galley::{MainAcBuses, MainGalley, SecondaryGalley},
 };
+#[cfg(test)]
+use systems::electrical::TransformerRectifier;
 use systems::{
     electrical::{
-        consumption::SuppliedPower, ElectricalBus, ElectricalSystem,
-        EngineGeneratorUpdateArguments, ExternalPowerSource, Potential, PotentialSource,
-        StaticInverter, TransformerRectifier,
+        consumption::SuppliedPower, ElectricalBus, ElectricalBusType, ElectricalNetwork,
+        ElectricalNetworkElements, ElectricalNetworkError, ElectricalSystem, ElectricalTopology,
+        ElectricalTopologyError, EngineGeneratorUpdateArguments, ExternalPowerSource, Potential,
+        PotentialSource,
     },
     overhead::{
         AutoOffFaultPushButton, FaultReleasePushButton, NormalAltnFaultPushButton,
         OnOffAvailablePushButton, OnOffFaultPushButton,
     },
     shared::AuxiliaryPowerUnitElectrical,
-    simulation::{SimulationElement, SimulationElementVisitor, SimulatorWriter, UpdateContext},
+    simulation::{
+        SimulationElement, SimulationElementVisitor, SimulatorWriter, StartingState, UpdateContext,
+        VariableIdentifier, VariableUnit,
+    },
 };
-use uom::si::f64::*;
+use uom::si::{f64::*, velocity::knot};
 
 pub(super) struct A320ElectricalUpdateArguments<'a> {
     engine_corrected_n2: [Ratio; 2],
@@ -52,14 +58,10 @@ impl<'a> A320ElectricalUpdateArguments<'a> {
         }
     }
 
-    fn apu(&mut self) -> &mut dyn AuxiliaryPowerUnitElectrical {
+    fn apu(&self) -> &dyn AuxiliaryPowerUnitElectrical {
         self.apu
     }
 
-    fn should_close_apu_start_contactors(&self) -> bool {
-        self.apu.should_close_start_contactors()
-    }
-
     fn apu_start_motor_powered_by(&mut self, source: Potential) {
         self.apu.start_motor_powered_by(source);
     }
@@ -95,6 +97,8 @@ impl<'a> EngineGeneratorUpdateArguments for A320ElectricalUpdateArguments<'a> {
 }
 
 pub(super) struct A320Electrical {
+    galley_is_shed_id: VariableIdentifier<bool>,
+    network: ElectricalNetwork,
     alternating_current: A320AlternatingCurrentElectrical,
     direct_current: A320DirectCurrentElectrical,
     main_galley: MainGalley,
@@ -103,6 +107,10 @@ pub(super) struct A320Electrical {
 impl A320Electrical {
     pub fn new() -> A320Electrical {
         A320Electrical {
+            galley_is_shed_id: VariableIdentifier::new("ELEC_GALLEY_IS_SHED", VariableUnit::Bool),
+            network: ElectricalNetwork::new(
+                topology().expect("The A320 electrical topology is invalid."),
+            ),
             alternating_current: A320AlternatingCurrentElectrical::new(),
             direct_current: A320DirectCurrentElectrical::new(),
             main_galley: MainGalley::new(),
@@ -118,81 +126,138 @@ impl A320Electrical {
         emergency_overhead: &A320EmergencyElectricalOverheadPanel,
         arguments: &mut A320ElectricalUpdateArguments<'a>,
     ) {
-        self.alternating_current
-            .update(context, ext_pwr, overhead, emergency_overhead, arguments);
+        let result = self.update_network(context, ext_pwr, overhead, emergency_overhead, arguments);
+        debug_assert!(
+            result.is_ok(),
+            "The electrical network failed to update: {:?}",
+            result
+        );
+
+        arguments.apu_start_motor_powered_by(self.network.contactor("10KA_AND_5KA").output());
 
-        self.direct_current.update_with_alternating_current_state(
+        let main_ac_buses = MainAcBuses::new(
+            self.network.bus(ElectricalBusType::AlternatingCurrent(1)),
+            self.network.bus(ElectricalBusType::AlternatingCurrent(2)),
+        );
+        self.main_galley.update(context, &main_ac_buses, overhead);
+        self.secondary_galley.update(&main_ac_buses, overhead);
+
+        self.debug_assert_invariants();
+    }
+
+    /// Updates the stages of the topology, along with the elements which
+    /// depend on the state of the buses of earlier stages.
+    fn update_network(
+        &mut self,
+        context: &UpdateContext,
+        ext_pwr: &ExternalPowerSource,
+        overhead: &A320ElectricalOverheadPanel,
+        emergency_overhead: &A320EmergencyElectricalOverheadPanel,
+        arguments: &A320ElectricalUpdateArguments,
+    ) -> Result<(), ElectricalNetworkError> {
+        self.alternating_current.update(context, arguments);
+
+        let stage = |electrical: &mut A320Electrical, name: &str| {
+            electrical.network.update_stage(
+                name,
+                &mut A320ElectricalNetworkElements {
+                    context,
+                    ext_pwr,
+                    overhead,
+                    emergency_overhead,
+                    apu: arguments.apu(),
+                    alternating_current: &mut electrical.alternating_current,
+                    direct_current: &mut electrical.direct_current,
+                },
+            )
+        };
+
+        stage(self, "main_power_sources")?;
+        self.alternating_current.update_with_main_ac_buses(
+            context,
+            self.network.bus(ElectricalBusType::AlternatingCurrent(1)),
+            self.network.bus(ElectricalBusType::AlternatingCurrent(2)),
+        );
+
+        stage(self, "main_buses")?;
+        // The battery charge limiters measure the DC BAT BUS as it is powered by
+        // DC BUS 1 and DC BUS 2, before the batteries are connected to it.
+        let dc_bat_bus = self
+            .network
+            .output("1PC1")
+            .merge(&self.network.output("1PC2"));
+        self.direct_current.update_battery_charge_limiters(
             context,
             overhead,
-            &self.alternating_current,
             arguments,
+            self.ac_bus_1().is_unpowered() && self.ac_bus_2().is_unpowered(),
+            self.alternating_current.emergency_generator_available(),
+            &dc_bat_bus,
         );
 
-        self.alternating_current
-            .update_with_direct_current_state(context, &self.direct_current);
-
-        self.main_galley
-            .update(context, &self.alternating_current, overhead);
-        self.secondary_galley
-            .update(&self.alternating_current, overhead);
-
-        self.debug_assert_invariants();
+        stage(self, "batteries_and_essential_buses")
     }
 
     fn ac_bus_1(&self) -> &ElectricalBus {
-        self.alternating_current.ac_bus_1()
+        self.network.bus(ElectricalBusType::AlternatingCurrent(1))
     }
 
     fn ac_bus_2(&self) -> &ElectricalBus {
-        self.alternating_current.ac_bus_2()
+        self.network.bus(ElectricalBusType::AlternatingCurrent(2))
     }
 
     fn ac_ess_bus(&self) -> &ElectricalBus {
-        self.alternating_current.ac_ess_bus()
+        self.network
+            .bus(ElectricalBusType::AlternatingCurrentEssential)
     }
 
     fn ac_ess_shed_bus(&self) -> &ElectricalBus {
-        self.alternating_current.ac_ess_shed_bus()
+        self.network
+            .bus(ElectricalBusType::AlternatingCurrentEssentialShed)
     }
 
     fn ac_stat_inv_bus(&self) -> &ElectricalBus {
-        self.alternating_current.ac_stat_inv_bus()
+        self.network
+            .bus(ElectricalBusType::AlternatingCurrentStaticInverter)
     }
 
     fn ac_gnd_flt_service_bus(&self) -> &ElectricalBus {
-        self.alternating_current.ac_gnd_flt_service_bus()
+        self.network
+            .bus(ElectricalBusType::AlternatingCurrentGndFltService)
     }
 
     fn dc_bus_1(&self) -> &ElectricalBus {
-        self.direct_current.dc_bus_1()
+        self.network.bus(ElectricalBusType::DirectCurrent(1))
     }
 
     fn dc_bus_2(&self) -> &ElectricalBus {
-        self.direct_current.dc_bus_2()
+        self.network.bus(ElectricalBusType::DirectCurrent(2))
     }
 
     fn dc_ess_bus(&self) -> &ElectricalBus {
-        self.direct_current.dc_ess_bus()
+        self.network.bus(ElectricalBusType::DirectCurrentEssential)
     }
 
     fn dc_ess_shed_bus(&self) -> &ElectricalBus {
-        self.direct_current.dc_ess_shed_bus()
+        self.network
+            .bus(ElectricalBusType::DirectCurrentEssentialShed)
     }
 
     fn dc_bat_bus(&self) -> &ElectricalBus {
-        self.direct_current.dc_bat_bus()
+        self.network.bus(ElectricalBusType::DirectCurrentBattery)
     }
 
     fn hot_bus_1(&self) -> &ElectricalBus {
-        self.direct_current.hot_bus_1()
+        self.network.bus(ElectricalBusType::DirectCurrentHot(1))
     }
 
     fn hot_bus_2(&self) -> &ElectricalBus {
-        self.direct_current.hot_bus_2()
+        self.network.bus(ElectricalBusType::DirectCurrentHot(2))
     }
 
     fn dc_gnd_flt_service_bus(&self) -> &ElectricalBus {
-        self.direct_current.dc_gnd_flt_service_bus()
+        self.network
+            .bus(ElectricalBusType::DirectCurrentGndFltService)
     }
 
     fn galley_is_shed(&self) -> bool {
@@ -200,18 +265,39 @@ impl A320Electrical {
     }
 
     fn debug_assert_invariants(&self) {
-        self.alternating_current.debug_assert_invariants();
-        self.direct_current.debug_assert_invariants();
+        debug_assert!(self.static_inverter_or_emergency_gen_powers_ac_ess_bus());
+        debug_assert!(self.battery_never_powers_dc_ess_shed());
+        debug_assert!(self.max_one_source_powers_dc_ess_bus());
+        debug_assert!(
+            self.batteries_power_both_static_inv_and_dc_ess_bus_at_the_same_time_or_not_at_all()
+        );
     }
 
-    #[cfg(test)]
-    fn fail_tr_1(&mut self) {
-        self.alternating_current.fail_tr_1();
+    fn static_inverter_or_emergency_gen_powers_ac_ess_bus(&self) -> bool {
+        !(self.contactor_is_closed("15XE2") && self.contactor_is_closed("15XE1"))
     }
 
-    #[cfg(test)]
-    fn fail_tr_2(&mut self) {
-        self.alternating_current.fail_tr_2();
+    fn battery_never_powers_dc_ess_shed(&self) -> bool {
+        !(self.contactor_is_closed("2XB2") && self.contactor_is_closed("8PH"))
+    }
+
+    fn max_one_source_powers_dc_ess_bus(&self) -> bool {
+        (!self.contactor_is_closed("2XB2")
+            && !self.contactor_is_closed("4PC")
+            && !self.contactor_is_closed("3PE"))
+            || (self.contactor_is_closed("2XB2")
+                ^ self.contactor_is_closed("4PC")
+                ^ self.contactor_is_closed("3PE"))
+    }
+
+    fn batteries_power_both_static_inv_and_dc_ess_bus_at_the_same_time_or_not_at_all(
+        &self,
+    ) -> bool {
+        self.contactor_is_closed("2XB1") == self.contactor_is_closed("2XB2")
+    }
+
+    fn contactor_is_closed(&self, id: &str) -> bool {
+        self.network.contactor(id).is_closed()
     }
 
     #[cfg(test)]
@@ -255,11 +341,11 @@ impl A320Electrical {
     }
 
     pub fn gen_1_contactor_open(&self) -> bool {
-        self.alternating_current.gen_1_contactor_open()
+        self.network.contactor("9XU1").is_open()
     }
 
     pub fn gen_2_contactor_open(&self) -> bool {
-        self.alternating_current.gen_2_contactor_open()
+        self.network.contactor("9XU2").is_open()
     }
 }
 impl ElectricalSystem for A320Electrical {
@@ -285,29 +371,74 @@ impl ElectricalSystem for A320Electrical {
 }
 impl SimulationElement for A320Electrical {
     fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+        visitor.enter_children();
         self.alternating_current.accept(visitor);
         self.direct_current.accept(visitor);
+        self.network.accept(visitor);
+        visitor.leave_children();
+
         visitor.visit(self);
     }
 
+    fn identifier(&self) -> String {
+        "electrical".to_owned()
+    }
+
     fn write(&self, writer: &mut SimulatorWriter) {
-        writer.write_bool("ELEC_GALLEY_IS_SHED", self.galley_is_shed())
+        writer.write(&self.galley_is_shed_id, self.galley_is_shed())
     }
 }
 
-trait DirectCurrentState {
-    fn static_inverter(&self) -> &StaticInverter;
+fn topology() -> Result<ElectricalTopology, ElectricalTopologyError> {
+    ElectricalTopology::parse(include_str!("electrical.topology"))
 }
 
-trait AlternatingCurrentState {
-    fn ac_bus_1_and_2_unpowered(&self) -> bool;
-    fn ac_bus_2_powered(&self) -> bool;
-    fn tr_1_and_2_available(&self) -> bool;
-    fn ac_1_and_2_and_emergency_gen_unpowered(&self) -> bool;
-    fn emergency_generator_available(&self) -> bool;
-    fn tr_1(&self) -> &TransformerRectifier;
-    fn tr_2(&self) -> &TransformerRectifier;
-    fn tr_ess(&self) -> &TransformerRectifier;
+/// Provides the sources, converters and signals which the A320 electrical
+/// topology refers to.
+struct A320ElectricalNetworkElements<'a, 'b> {
+    context: &'a UpdateContext<'b>,
+    ext_pwr: &'a ExternalPowerSource,
+    overhead: &'a A320ElectricalOverheadPanel,
+    emergency_overhead: &'a A320EmergencyElectricalOverheadPanel,
+    apu: &'a dyn AuxiliaryPowerUnitElectrical,
+    alternating_current: &'a mut A320AlternatingCurrentElectrical,
+    direct_current: &'a mut A320DirectCurrentElectrical,
+}
+impl<'a, 'b> ElectricalNetworkElements for A320ElectricalNetworkElements<'a, 'b> {
+    fn source_potential(&self, name: &str) -> Option<Potential> {
+        match name {
+            "APU_GEN" => Some(self.apu.output()),
+            "EXT_PWR" => Some(self.ext_pwr.output()),
+            _ => self.alternating_current.source_potential(name),
+        }
+    }
+
+    fn convert(&mut self, name: &str, input: Potential) -> Option<Potential> {
+        match self.alternating_current.convert(name, input) {
+            Some(output) => Some(output),
+            None => self.direct_current.convert(name, input),
+        }
+    }
+
+    fn signal(&self, name: &str) -> Option<bool> {
+        match name {
+            "apu_gen" => Some(
+                self.overhead.apu_generator_is_on() && self.apu.output_within_normal_parameters(),
+            ),
+            "ext_pwr" => Some(
+                self.overhead.external_power_is_on()
+                    && self.ext_pwr.output_within_normal_parameters(),
+            ),
+            "apu_start" => Some(self.apu.should_close_start_contactors()),
+            "at_least_50_knots" => {
+                Some(self.context.indicated_airspeed() >= Velocity::new::<knot>(50.))
+            }
+            _ => self
+                .alternating_current
+                .signal(name, self.overhead, self.emergency_overhead)
+                .or_else(|| self.direct_current.signal(name)),
+        }
+    }
 }
 
 pub(super) struct A320ElectricalOverheadPanel {
@@ -410,6 +541,7 @@ impl A320ElectricalOverheadPanel {
 }
 impl SimulationElement for A320ElectricalOverheadPanel {
     fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+        visitor.enter_children();
         self.bat_1.accept(visitor);
         self.bat_2.accept(visitor);
         self.idg_1.accept(visitor);
@@ -422,9 +554,19 @@ impl SimulationElement for A320ElectricalOverheadPanel {
         self.galy_and_cab.accept(visitor);
         self.ext_pwr.accept(visitor);
         self.commercial.accept(visitor);
+        visitor.leave_children();
 
         visitor.visit(self);
     }
+
+    fn identifier(&self) -> String {
+        "electrical_overhead".to_owned()
+    }
+    fn initialise(&mut self, state: StartingState) {
+        self.bat_1.set_auto(!state.is_cold_and_dark());
+        self.bat_2.set_auto(!state.is_cold_and_dark());
+        self.ext_pwr.set_on(false);
+    }
 }
 
 pub(super) struct A320EmergencyElectricalOverheadPanel {
@@ -444,10 +586,16 @@ impl A320EmergencyElectricalOverheadPanel {
 }
 impl SimulationElement for A320EmergencyElectricalOverheadPanel {
     fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+        visitor.enter_children();
         self.gen_1_line.accept(visitor);
+        visitor.leave_children();
 
         visitor.visit(self);
     }
+
+    fn identifier(&self) -> String {
+        "emergency_electrical_overhead".to_owned()
+    }
 }
 
 #[cfg(test)]
@@ -465,18 +613,226 @@ mod a320_electrical {
     }
 }
 
+#[cfg(test)]
+mod a320_main_power_sources_tests {
+    use super::*;
+    use std::collections::HashSet;
+    use systems::electrical::{Contactor, PotentialOrigin, PotentialTarget};
+    use uom::si::electric_potential::volt;
+
+    /// Provides the sources and signals used by the main power sources stage.
+    struct MainPowerSourcesElements {
+        gen_1: Potential,
+        gen_2: Potential,
+        apu_gen: Potential,
+        ext_pwr: Potential,
+        gen_1_on: bool,
+        gen_2_on: bool,
+        apu_gen_on: bool,
+        ext_pwr_on: bool,
+        bus_tie_auto: bool,
+    }
+    impl ElectricalNetworkElements for MainPowerSourcesElements {
+        fn source_potential(&self, name: &str) -> Option<Potential> {
+            match name {
+                "GEN_1" => Some(self.gen_1),
+                "GEN_2" => Some(self.gen_2),
+                "APU_GEN" => Some(self.apu_gen),
+                "EXT_PWR" => Some(self.ext_pwr),
+                _ => None,
+            }
+        }
+
+        fn convert(&mut self, _: &str, _: Potential) -> Option<Potential> {
+            None
+        }
+
+        fn signal(&self, name: &str) -> Option<bool> {
+            match name {
+                "gen_1" => Some(self.gen_1_on),
+                "gen_2" => Some(self.gen_2_on),
+                "apu_gen" => Some(self.apu_gen_on),
+                "ext_pwr" => Some(self.ext_pwr_on),
+                "bus_tie_auto" => Some(self.bus_tie_auto),
+                _ => None,
+            }
+        }
+    }
+
+    /// The main power sources as they were wired by hand, before they were
+    /// described by a topology.
+    struct HandWiredMainPowerSources {
+        engine_1_gen_contactor: Contactor,
+        engine_2_gen_contactor: Contactor,
+        bus_tie_1_contactor: Contactor,
+        bus_tie_2_contactor: Contactor,
+        apu_gen_contactor: Contactor,
+        ext_pwr_contactor: Contactor,
+    }
+    impl HandWiredMainPowerSources {
+        fn new() -> Self {
+            Self {
+                engine_1_gen_contactor: Contactor::new("9XU1"),
+                engine_2_gen_contactor: Contactor::new("9XU2"),
+                bus_tie_1_contactor: Contactor::new("11XU1"),
+                bus_tie_2_contactor: Contactor::new("11XU2"),
+                apu_gen_contactor: Contactor::new("3XS"),
+                ext_pwr_contactor: Contactor::new("3XG"),
+            }
+        }
+
+        fn update(&mut self, elements: &MainPowerSourcesElements) {
+            let gen_1_provides_power = elements.gen_1_on;
+            let gen_2_provides_power = elements.gen_2_on;
+            let only_one_engine_gen_is_powered = gen_1_provides_power ^ gen_2_provides_power;
+            let both_engine_gens_provide_power = gen_1_provides_power && gen_2_provides_power;
+            let ext_pwr_provides_power = elements.ext_pwr_on && !both_engine_gens_provide_power;
+            let apu_gen_provides_power =
+                elements.apu_gen_on && !ext_pwr_provides_power && !both_engine_gens_provide_power;
+
+            self.engine_1_gen_contactor.close_when(gen_1_provides_power);
+            self.engine_2_gen_contactor.close_when(gen_2_provides_power);
+            self.apu_gen_contactor.close_when(apu_gen_provides_power);
+            self.ext_pwr_contactor.close_when(ext_pwr_provides_power);
+
+            let apu_or_ext_pwr_provides_power = ext_pwr_provides_power || apu_gen_provides_power;
+            self.bus_tie_1_contactor.close_when(
+                elements.bus_tie_auto
+                    && ((only_one_engine_gen_is_powered && !apu_or_ext_pwr_provides_power)
+                        || (apu_or_ext_pwr_provides_power && !gen_1_provides_power)),
+            );
+            self.bus_tie_2_contactor.close_when(
+                elements.bus_tie_auto
+                    && ((only_one_engine_gen_is_powered && !apu_or_ext_pwr_provides_power)
+                        || (apu_or_ext_pwr_provides_power && !gen_2_provides_power)),
+            );
+
+            self.apu_gen_contactor.powered_by(&elements.apu_gen);
+            self.ext_pwr_contactor.powered_by(&elements.ext_pwr);
+
+            self.engine_1_gen_contactor.powered_by(&elements.gen_1);
+            self.bus_tie_1_contactor
+                .powered_by(&self.engine_1_gen_contactor);
+            self.bus_tie_1_contactor
+                .or_powered_by(&self.apu_gen_contactor);
+            self.bus_tie_1_contactor
+                .or_powered_by(&self.ext_pwr_contactor);
+
+            self.engine_2_gen_contactor.powered_by(&elements.gen_2);
+            self.bus_tie_2_contactor
+                .powered_by(&self.engine_2_gen_contactor);
+            self.bus_tie_2_contactor
+                .or_powered_by(&self.apu_gen_contactor);
+            self.bus_tie_2_contactor
+                .or_powered_by(&self.ext_pwr_contactor);
+
+            self.bus_tie_1_contactor
+                .or_powered_by(&self.bus_tie_2_contactor);
+            self.bus_tie_2_contactor
+                .or_powered_by(&self.bus_tie_1_contactor);
+        }
+
+        fn contactor(&self, id: &str) -> &Contactor {
+            match id {
+                "9XU1" => &self.engine_1_gen_contactor,
+                "9XU2" => &self.engine_2_gen_contactor,
+                "11XU1" => &self.bus_tie_1_contactor,
+                "11XU2" => &self.bus_tie_2_contactor,
+                "3XS" => &self.apu_gen_contactor,
+                _ => &self.ext_pwr_contactor,
+            }
+        }
+
+        fn ac_bus_1_electric_sources(&self) -> Potential {
+            self.engine_1_gen_contactor
+                .output()
+                .merge(&self.bus_tie_1_contactor.output())
+        }
+
+        fn ac_bus_2_electric_sources(&self) -> Potential {
+            self.engine_2_gen_contactor
+                .output()
+                .merge(&self.bus_tie_2_contactor.output())
+        }
+    }
+
+    fn elements(signals: u8) -> MainPowerSourcesElements {
+        let potential = |origin| Potential::single(origin, ElectricPotential::new::<volt>(115.));
+
+        MainPowerSourcesElements {
+            gen_1: potential(PotentialOrigin::EngineGenerator(1)),
+            gen_2: potential(PotentialOrigin::EngineGenerator(2)),
+            apu_gen: potential(PotentialOrigin::ApuGenerator(1)),
+            ext_pwr: potential(PotentialOrigin::External),
+            gen_1_on: signals & 1 != 0,
+            gen_2_on: signals & 2 != 0,
+            apu_gen_on: signals & 4 != 0,
+            ext_pwr_on: signals & 8 != 0,
+            bus_tie_auto: signals & 16 != 0,
+        }
+    }
+
+    fn origins(potential: &Potential) -> HashSet<PotentialOrigin> {
+        potential.origins().collect()
+    }
+
+    #[test]
+    fn topology_matches_the_hand_wired_main_power_sources() {
+        for signals in 0..32 {
+            let mut elements = elements(signals);
+            let mut network = ElectricalNetwork::new(topology().unwrap());
+            network
+                .update_stage("main_power_sources", &mut elements)
+                .unwrap();
+            let mut hand_wired = HandWiredMainPowerSources::new();
+            hand_wired.update(&elements);
+
+            for id in &["9XU1", "9XU2", "3XS", "3XG", "11XU1", "11XU2"] {
+                assert_eq!(
+                    network.contactor(id).is_closed(),
+                    hand_wired.contactor(id).is_closed(),
+                    "{} with signals {:05b}",
+                    id,
+                    signals
+                );
+            }
+
+            assert_eq!(
+                origins(
+                    &network
+                        .bus(ElectricalBusType::AlternatingCurrent(1))
+                        .output()
+                ),
+                origins(&hand_wired.ac_bus_1_electric_sources()),
+                "AC_1 with signals {:05b}",
+                signals
+            );
+            assert_eq!(
+                origins(
+                    &network
+                        .bus(ElectricalBusType::AlternatingCurrent(2))
+                        .output()
+                ),
+                origins(&hand_wired.ac_bus_2_electric_sources()),
+                "AC_2 with signals {:05b}",
+                signals
+            );
+        }
+    }
+}
+
 #[cfg(test)]
 mod a320_electrical_circuit_tests {
     use std::time::Duration;
     use uom::si::{electric_potential::volt, ratio::percent};
 
-    use super::alternating_current::A320AcEssFeedContactors;
     use super::*;
     use systems::{
         electrical::{
             ElectricalBusType, ExternalPowerSource, PotentialOrigin,
             INTEGRATED_DRIVE_GENERATOR_STABILIZATION_TIME_IN_MILLISECONDS,
         },
+        failures::FailureType,
         shared::ApuStartContactorsController,
         simulation::{test::SimulationTestBed, Aircraft},
     };
@@ -2152,14 +2508,6 @@ mod a320_electrical_circuit_tests {
             self.elec.empty_battery_2();
         }
 
-        fn failed_tr_1(&mut self) {
-            self.elec.fail_tr_1();
-        }
-
-        fn failed_tr_2(&mut self) {
-            self.elec.fail_tr_2();
-        }
-
         fn running_emergency_generator(&mut self) {
             self.elec.attempt_emergency_gen_start();
         }
@@ -2220,10 +2568,12 @@ mod a320_electrical_circuit_tests {
     }
     impl SimulationElement for A320ElectricalTestAircraft {
         fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+            visitor.enter_children();
             self.ext_pwr.accept(visitor);
             self.elec.accept(visitor);
             self.overhead.accept(visitor);
             self.emergency_overhead.accept(visitor);
+            visitor.leave_children();
 
             visitor.visit(self);
         }
@@ -2316,12 +2666,14 @@ mod a320_electrical_circuit_tests {
         }
 
         fn failed_tr_1(mut self) -> Self {
-            self.aircraft.failed_tr_1();
+            self.simulation_test_bed
+                .activate_failure(&FailureType::TransformerRectifier(1));
             self
         }
 
         fn failed_tr_2(mut self) -> Self {
-            self.aircraft.failed_tr_2();
+            self.simulation_test_bed
+                .activate_failure(&FailureType::TransformerRectifier(2));
             self
         }
 
@@ -2624,12 +2976,14 @@ mod a320_electrical_circuit_tests {
         }
 
         fn run_waiting_for_ac_ess_feed_transition(self) -> Self {
-            self.run_waiting_for(A320AcEssFeedContactors::AC_ESS_FEED_TO_AC_BUS_2_DELAY_IN_SECONDS)
+            self.run_waiting_for(
+                A320AlternatingCurrentElectrical::AC_ESS_FEED_TO_AC_BUS_2_DELAY_IN_SECONDS,
+            )
         }
 
         fn run_waiting_until_just_before_ac_ess_feed_transition(self) -> Self {
             self.run_waiting_for(
-                A320AcEssFeedContactors::AC_ESS_FEED_TO_AC_BUS_2_DELAY_IN_SECONDS
+                A320AlternatingCurrentElectrical::AC_ESS_FEED_TO_AC_BUS_2_DELAY_IN_SECONDS
                     - Duration::from_millis(1),
             )
         }
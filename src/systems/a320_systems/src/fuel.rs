@@ -1,16 +1,22 @@
-use systems::simulation::{SimulationElement, SimulatorReader};
-use uom::si::{
-    f64::*,
-    mass::{kilogram, pound},
+use systems::simulation::{
+    DescriptionWriter, SimulationElement, SimulatorReader, VariableIdentifier, VariableUnit,
 };
+use uom::si::{f64::*, mass::kilogram};
 
 pub struct A320Fuel {
+    unlimited_fuel_id: VariableIdentifier<bool>,
+    left_inner_tank_fuel_quantity_id: VariableIdentifier<Mass>,
     unlimited_fuel: bool,
     left_inner_tank_fuel_quantity: Mass,
 }
 impl A320Fuel {
     pub fn new() -> Self {
         A320Fuel {
+            unlimited_fuel_id: VariableIdentifier::new("UNLIMITED FUEL", VariableUnit::Bool),
+            left_inner_tank_fuel_quantity_id: VariableIdentifier::new(
+                "FUEL TANK LEFT MAIN QUANTITY",
+                VariableUnit::Pound,
+            ),
             unlimited_fuel: false,
             left_inner_tank_fuel_quantity: Mass::new::<kilogram>(0.),
         }
@@ -21,9 +27,23 @@ impl A320Fuel {
     }
 }
 impl SimulationElement for A320Fuel {
+    fn identifier(&self) -> String {
+        "fuel".to_owned()
+    }
+
+    fn describe(&self, writer: &mut DescriptionWriter) {
+        writer.write("unlimited", self.unlimited_fuel);
+        writer.write(
+            "left_inner_tank",
+            format!(
+                "{:.1} kg",
+                self.left_inner_tank_fuel_quantity.get::<kilogram>()
+            ),
+        );
+    }
+
     fn read(&mut self, reader: &mut SimulatorReader) {
-        self.unlimited_fuel = reader.read_bool("UNLIMITED FUEL");
-        self.left_inner_tank_fuel_quantity =
-            Mass::new::<pound>(reader.read_f64("FUEL TANK LEFT MAIN QUANTITY"));
+        self.unlimited_fuel = reader.read(&self.unlimited_fuel_id);
+        self.left_inner_tank_fuel_quantity = reader.read(&self.left_inner_tank_fuel_quantity_id);
     }
 }
@@ -17,4 +17,8 @@ impl A320Hydraulic {
 
     pub fn update(&mut self, _: &UpdateContext) {}
 }
-impl SimulationElement for A320Hydraulic {}
+impl SimulationElement for A320Hydraulic {
+    fn identifier(&self) -> String {
+        "hydraulic".to_owned()
+    }
+}
@@ -19,7 +19,7 @@ use systems::{
     electrical::{consumption::SuppliedPower, ElectricalSystem, ExternalPowerSource},
     engine::Engine,
     landing_gear::LandingGear,
-    simulation::{Aircraft, SimulationElement, SimulationElementVisitor, UpdateContext},
+    simulation::{Aircraft, Profiler, SimulationElement, SimulationElementVisitor, UpdateContext},
 };
 
 pub struct A320 {
@@ -37,6 +37,7 @@ pub struct A320 {
     ext_pwr: ExternalPowerSource,
     hydraulic: A320Hydraulic,
     landing_gear: LandingGear,
+    profiler: Profiler,
 }
 impl A320 {
     pub fn new() -> A320 {
@@ -55,8 +56,18 @@ impl A320 {
             ext_pwr: ExternalPowerSource::new(),
             hydraulic: A320Hydraulic::new(),
             landing_gear: LandingGear::new(),
+            profiler: Profiler::new(),
         }
     }
+
+    /// Measures the time spent updating each system from now on.
+    pub fn enable_profiling(&mut self) {
+        self.profiler.enable();
+    }
+
+    pub fn profiler(&self) -> &Profiler {
+        &self.profiler
+    }
 }
 impl Default for A320 {
     fn default() -> Self {
@@ -65,6 +76,7 @@ impl Default for A320 {
 }
 impl Aircraft for A320 {
     fn update_before_power_distribution(&mut self, context: &UpdateContext) {
+        self.profiler.begin("apu");
         self.apu.update_before_electrical(
             context,
             &self.apu_overhead,
@@ -79,6 +91,7 @@ impl Aircraft for A320 {
             self.fuel.left_inner_tank_has_fuel_remaining(),
         );
 
+        self.profiler.begin("electrical");
         self.electrical.update(
             context,
             &self.ext_pwr,
@@ -98,16 +111,22 @@ impl Aircraft for A320 {
             ),
         );
 
+        self.profiler.begin("apu");
         self.apu.update_after_electrical();
 
+        self.profiler.begin("overhead panels");
         self.electrical_overhead
             .update_after_electrical(&self.electrical);
         self.apu_overhead.update_after_apu(&self.apu);
+        self.profiler.end();
     }
 
     fn update_after_power_distribution(&mut self, context: &UpdateContext) {
+        self.profiler.begin("hydraulic");
         self.hydraulic.update(context);
+        self.profiler.begin("power consumption");
         self.power_consumption.update(context);
+        self.profiler.end();
     }
 
     fn get_supplied_power(&mut self) -> SuppliedPower {
@@ -116,6 +135,7 @@ impl Aircraft for A320 {
 }
 impl SimulationElement for A320 {
     fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+        visitor.enter_children();
         self.apu.accept(visitor);
         self.apu_fire_overhead.accept(visitor);
         self.apu_overhead.accept(visitor);
@@ -126,10 +146,619 @@ impl SimulationElement for A320 {
         self.engine_1.accept(visitor);
         self.engine_2.accept(visitor);
         self.electrical.accept(visitor);
+        self.hydraulic.accept(visitor);
         self.power_consumption.accept(visitor);
         self.ext_pwr.accept(visitor);
         self.landing_gear.accept(visitor);
+        visitor.leave_children();
 
         visitor.visit(self);
     }
+
+    fn identifier(&self) -> String {
+        "A320".to_owned()
+    }
+}
+
+#[cfg(test)]
+mod a320_tests {
+    use super::*;
+    use std::{collections::HashMap, time::Duration};
+    use systems::{
+        failures::FailureType,
+        simulation::{
+            replay, test::SimulationTestBed, ElementDescription, RandomNumberGenerator, Recording,
+            RecordingHeader, RecordingReaderWriter, Simulation, SimulatorReaderWriter,
+            StartingState, VariableAudit, VariableDirection, VariableRegistry,
+            VariableRegistryError, VariableUnit,
+        },
+    };
+
+    struct A320TestBed {
+        aircraft: A320,
+        test_bed: SimulationTestBed,
+    }
+    impl A320TestBed {
+        fn new() -> Self {
+            let mut test_bed = Self {
+                aircraft: A320::new(),
+                test_bed: SimulationTestBed::new_with_delta(Duration::from_millis(50)),
+            };
+            test_bed.test_bed.write_bool("UNLIMITED FUEL", true);
+
+            test_bed
+        }
+
+        fn initialised_to(mut self, state: StartingState) -> Self {
+            self.test_bed.set_on_ground(!state.is_in_flight());
+            self.test_bed.write_f64(
+                "GEAR CENTER POSITION",
+                if state.is_in_flight() { 0. } else { 100. },
+            );
+
+            let n2 = if state.engines_are_running() { 80. } else { 0. };
+            self.test_bed.write_f64("TURB ENG CORRECTED N2:1", n2);
+            self.test_bed.write_f64("TURB ENG CORRECTED N2:2", n2);
+
+            self.test_bed.initialise_aircraft(&mut self.aircraft, state);
+            self
+        }
+
+        fn run(mut self) -> Self {
+            self.test_bed.run_aircraft(&mut self.aircraft);
+            self
+        }
+
+        fn key_event(mut self, name: &str) -> Self {
+            self.test_bed.trigger_key_event(name);
+            self
+        }
+
+        fn failed(mut self, failure_type: FailureType) -> Self {
+            self.test_bed.activate_failure(&failure_type);
+            self
+        }
+
+        fn profiled(mut self) -> Self {
+            self.aircraft.enable_profiling();
+            self
+        }
+
+        fn profiled_sections(&self) -> Vec<(&'static str, u32)> {
+            self.aircraft
+                .profiler()
+                .sections()
+                .iter()
+                .map(|section| (section.name(), section.count()))
+                .collect()
+        }
+
+        fn register_variables(&mut self) -> Result<VariableRegistry, Vec<VariableRegistryError>> {
+            self.test_bed
+                .register_aircraft_variables(&mut self.aircraft)
+        }
+
+        fn register_failures(&mut self) -> Vec<FailureType> {
+            self.test_bed.register_aircraft_failures(&mut self.aircraft)
+        }
+
+        fn describe(&mut self) -> ElementDescription {
+            self.test_bed.describe_aircraft(&mut self.aircraft)
+        }
+
+        fn apu_is_available(&mut self) -> bool {
+            self.test_bed.read_bool("OVHD_APU_START_PB_IS_AVAILABLE")
+        }
+
+        fn apu_n(&mut self) -> f64 {
+            self.test_bed.read_f64("APU_N")
+        }
+
+        fn air_intake_flap_open_percentage(&mut self) -> f64 {
+            self.test_bed.read_f64("APU_FLAP_OPEN_PERCENTAGE")
+        }
+
+        fn battery_push_buttons_are_auto(&mut self) -> bool {
+            self.test_bed.read_bool("OVHD_ELEC_BAT_10_PB_IS_AUTO")
+                && self.test_bed.read_bool("OVHD_ELEC_BAT_11_PB_IS_AUTO")
+        }
+
+        fn bus_is_powered(&mut self, name: &str) -> bool {
+            self.test_bed
+                .read_bool(&format!("ELEC_{}_BUS_IS_POWERED", name))
+        }
+
+        fn apu_gen_contactor_is_closed(&mut self) -> bool {
+            self.test_bed.read_bool("ELEC_CONTACTOR_3XS_IS_CLOSED")
+        }
+
+        fn engine_gen_contactors_are_closed(&mut self) -> bool {
+            self.test_bed.read_bool("ELEC_CONTACTOR_9XU1_IS_CLOSED")
+                && self.test_bed.read_bool("ELEC_CONTACTOR_9XU2_IS_CLOSED")
+        }
+    }
+
+    fn test_bed() -> A320TestBed {
+        A320TestBed::new()
+    }
+
+    #[test]
+    fn cold_and_dark_has_no_power() {
+        let mut test_bed = test_bed().initialised_to(StartingState::ColdAndDark).run();
+
+        assert!(!test_bed.battery_push_buttons_are_auto());
+        assert!(!test_bed.bus_is_powered("DC_BAT"));
+        assert!(!test_bed.bus_is_powered("AC_1"));
+    }
+
+    #[test]
+    fn battery_push_buttons_are_pushed_by_key_events() {
+        let mut test_bed = test_bed()
+            .initialised_to(StartingState::ColdAndDark)
+            .key_event("OVHD_ELEC_BAT_10_PB_PUSH")
+            .key_event("OVHD_ELEC_BAT_11_PB_PUSH")
+            .run();
+
+        assert!(test_bed.battery_push_buttons_are_auto());
+    }
+
+    #[test]
+    fn gate_with_apu_has_apu_supplying_power_from_the_first_tick() {
+        let mut test_bed = test_bed().initialised_to(StartingState::GateWithApu).run();
+
+        assert!(test_bed.apu_is_available());
+        assert!((test_bed.apu_n() - 100.).abs() < f64::EPSILON);
+        assert!((test_bed.air_intake_flap_open_percentage() - 100.).abs() < f64::EPSILON);
+        assert!(test_bed.apu_gen_contactor_is_closed());
+        assert!(test_bed.bus_is_powered("AC_1"));
+        assert!(test_bed.bus_is_powered("AC_2"));
+        assert!(test_bed.bus_is_powered("DC_BAT"));
+    }
+
+    #[test]
+    fn does_not_profile_unless_profiling_is_enabled() {
+        let test_bed = test_bed().run();
+
+        assert!(test_bed.profiled_sections().is_empty());
+    }
+
+    #[test]
+    fn profiling_measures_each_system_during_every_update() {
+        let test_bed = test_bed().profiled().run().run();
+
+        assert_eq!(
+            test_bed.profiled_sections(),
+            vec![
+                ("apu", 4),
+                ("electrical", 2),
+                ("overhead panels", 2),
+                ("hydraulic", 2),
+                ("power consumption", 2)
+            ]
+        );
+    }
+
+    #[test]
+    fn runway_ready_has_engine_generators_supplying_power_from_the_first_tick() {
+        let mut test_bed = test_bed().initialised_to(StartingState::RunwayReady).run();
+
+        assert!(!test_bed.apu_is_available());
+        assert!(test_bed.engine_gen_contactors_are_closed());
+        assert!(test_bed.bus_is_powered("AC_1"));
+        assert!(test_bed.bus_is_powered("AC_2"));
+        assert!(test_bed.bus_is_powered("DC_BAT"));
+    }
+
+    #[test]
+    fn cruise_has_engine_generators_supplying_power_from_the_first_tick() {
+        let mut test_bed = test_bed().initialised_to(StartingState::Cruise).run();
+
+        assert!(test_bed.battery_push_buttons_are_auto());
+        assert!(!test_bed.apu_gen_contactor_is_closed());
+        assert!(test_bed.engine_gen_contactors_are_closed());
+        assert!(test_bed.bus_is_powered("AC_1"));
+        assert!(test_bed.bus_is_powered("AC_2"));
+        assert!(test_bed.bus_is_powered("AC_ESS"));
+        assert!(test_bed.bus_is_powered("DC_ESS"));
+    }
+
+    #[test]
+    fn engine_generator_failing_mid_flight_opens_its_contactor() {
+        let mut test_bed = test_bed()
+            .initialised_to(StartingState::Cruise)
+            .run()
+            .failed(FailureType::EngineGenerator(1))
+            .run();
+
+        assert!(!test_bed.test_bed.read_bool("ELEC_CONTACTOR_9XU1_IS_CLOSED"));
+        assert!(test_bed.bus_is_powered("AC_1"));
+    }
+
+    #[test]
+    fn registers_the_failures_of_the_aircraft() {
+        let mut test_bed = test_bed();
+
+        let failures = test_bed.register_failures();
+
+        assert!(failures.contains(&FailureType::TransformerRectifier(1)));
+        assert!(failures.contains(&FailureType::EngineGenerator(2)));
+        assert!(failures.contains(&FailureType::Battery(10)));
+        assert!(failures.contains(&FailureType::ContactorStuck("9XU1".to_owned())));
+        assert!(failures.contains(&FailureType::Apu));
+    }
+
+    #[test]
+    fn describes_the_live_values_of_its_elements() {
+        let mut test_bed = test_bed().initialised_to(StartingState::ColdAndDark).run();
+
+        let description = test_bed.describe();
+
+        assert_eq!(description.identifier(), "A320");
+        let battery = description
+            .find("electrical/direct_current/battery_10")
+            .unwrap();
+        assert_eq!(battery.element_type(), "Battery");
+        assert_eq!(battery.value("charge"), Some("23.0 Ah"));
+        assert!(description
+            .to_string()
+            .lines()
+            .any(|line| line.starts_with("A320/electrical/direct_current/battery_10: charge=")));
+    }
+
+    #[test]
+    fn each_variable_is_written_by_a_single_element() {
+        let mut test_bed = test_bed();
+
+        assert!(test_bed.register_variables().is_ok());
+    }
+
+    #[test]
+    fn registers_the_variables_read_and_written_by_the_aircraft() {
+        let mut test_bed = test_bed();
+
+        let registry = test_bed.register_variables().unwrap();
+
+        let push_button = registry.get("OVHD_ELEC_BAT_10_PB_IS_AUTO").unwrap();
+        assert_eq!(push_button.unit(), VariableUnit::Bool);
+        assert_eq!(push_button.direction(), VariableDirection::ReadWrite);
+
+        let apu_n = registry.get("APU_N").unwrap();
+        assert_eq!(apu_n.unit(), VariableUnit::Percent);
+        assert_eq!(apu_n.direction(), VariableDirection::Write);
+
+        assert!(registry.contains("TURB ENG CORRECTED N2:1"));
+        assert!(registry
+            .key_events()
+            .any(|name| name == "OVHD_APU_MASTER_SW_PB_PUSH"));
+    }
+
+    #[test]
+    fn every_element_is_reached_by_the_visitor() {
+        let mut aircraft = A320::new();
+        let mut test_bed = SimulationTestBed::new();
+        let registry = test_bed.register_aircraft_variables(&mut aircraft).unwrap();
+        let element_tree = test_bed.describe_aircraft(&mut aircraft);
+
+        let report = VariableAudit::new(&registry)
+            .with_constructed_elements(&element_tree, constructed_elements(&aircraft))
+            .report();
+
+        assert!(report.unreached_elements().is_empty(), "{}", report);
+    }
+
+    fn constructed_elements(aircraft: &A320) -> Vec<String> {
+        vec![
+            aircraft.apu.identifier(),
+            aircraft.apu_fire_overhead.identifier(),
+            aircraft.apu_overhead.identifier(),
+            aircraft.pneumatic_overhead.identifier(),
+            aircraft.electrical_overhead.identifier(),
+            aircraft.emergency_electrical_overhead.identifier(),
+            aircraft.fuel.identifier(),
+            aircraft.engine_1.identifier(),
+            aircraft.engine_2.identifier(),
+            aircraft.electrical.identifier(),
+            aircraft.power_consumption.identifier(),
+            aircraft.ext_pwr.identifier(),
+            aircraft.hydraulic.identifier(),
+            aircraft.landing_gear.identifier(),
+        ]
+    }
+
+    #[derive(Default)]
+    struct MemoryReaderWriter {
+        variables: HashMap<String, f64>,
+    }
+    impl SimulatorReaderWriter for MemoryReaderWriter {
+        fn read(&mut self, name: &str) -> f64 {
+            *self.variables.get(name).unwrap_or(&0.)
+        }
+
+        fn write(&mut self, name: &str, value: f64) {
+            self.variables.insert(name.to_owned(), value);
+        }
+    }
+
+    #[test]
+    fn recorded_apu_start_replays_without_differences() {
+        let maximum_step_size = Duration::from_millis(100);
+        let header =
+            RecordingHeader::new(7, Some(StartingState::ColdAndDark), Some(maximum_step_size));
+        let mut aircraft = A320::new();
+        let mut reader_writer =
+            RecordingReaderWriter::new(MemoryReaderWriter::default(), header, vec![]).unwrap();
+        reader_writer.inner_mut().write("SIM ON GROUND", 1.);
+        reader_writer.inner_mut().write("UNLIMITED FUEL", 1.);
+
+        let mut simulation = Simulation::new_with_random_number_generator(
+            &mut aircraft,
+            &mut reader_writer,
+            RandomNumberGenerator::from_seed(header.seed()),
+        );
+        simulation.set_maximum_step_size(maximum_step_size);
+        simulation.initialise(StartingState::ColdAndDark);
+
+        for tick in 0..400 {
+            let inputs = simulation.simulator_reader_writer().inner_mut();
+            match tick {
+                10 => {
+                    inputs.write("OVHD_ELEC_BAT_10_PB_IS_AUTO", 1.);
+                    inputs.write("OVHD_ELEC_BAT_11_PB_IS_AUTO", 1.);
+                }
+                20 => inputs.write("OVHD_APU_MASTER_SW_PB_IS_ON", 1.),
+                60 => inputs.write("OVHD_APU_START_PB_IS_ON", 1.),
+                _ => {}
+            }
+
+            // Alternates between a single step and multiple steps per tick.
+            simulation.tick(Duration::from_millis(if tick % 2 == 0 { 50 } else { 130 }));
+        }
+
+        let recording = Recording::read_from(&mut reader_writer.into_writer().as_slice()).unwrap();
+        assert!(recording
+            .ticks()
+            .iter()
+            .flat_map(|tick| tick.writes())
+            .any(|(name, value)| name == "APU_N" && *value > 0.));
+
+        assert_eq!(replay(&mut A320::new(), &recording), vec![]);
+    }
+}
+
+#[cfg(test)]
+mod a320_property_tests {
+    use super::*;
+    use std::time::Duration;
+    use systems::simulation::{
+        property_test::{Generator, PropertyTest, Shrink},
+        test::SimulationTestBed,
+        StartingState,
+    };
+    use uom::si::{f64::*, velocity::knot};
+
+    const PUSH_BUTTONS: [&str; 14] = [
+        "OVHD_ELEC_BAT_10_PB_IS_AUTO",
+        "OVHD_ELEC_BAT_11_PB_IS_AUTO",
+        "OVHD_ELEC_IDG_1_PB_IS_RELEASED",
+        "OVHD_ELEC_IDG_2_PB_IS_RELEASED",
+        "OVHD_ELEC_ENG_GEN_1_PB_IS_ON",
+        "OVHD_ELEC_ENG_GEN_2_PB_IS_ON",
+        "OVHD_ELEC_APU_GEN_PB_IS_ON",
+        "OVHD_ELEC_BUS_TIE_PB_IS_AUTO",
+        "OVHD_ELEC_AC_ESS_FEED_PB_IS_NORMAL",
+        "OVHD_ELEC_GALY_AND_CAB_PB_IS_AUTO",
+        "OVHD_ELEC_EXT_PWR_PB_IS_ON",
+        "OVHD_ELEC_COMMERCIAL_PB_IS_ON",
+        "OVHD_EMER_ELEC_GEN_1_LINE_PB_IS_ON",
+        "OVHD_PNEU_APU_BLEED_PB_IS_ON",
+    ];
+
+    /// The time steps cover. Short steps catch transients, while long steps
+    /// allow the APU and batteries to change state.
+    const DURATIONS: [Duration; 4] = [
+        Duration::from_millis(50),
+        Duration::from_secs(1),
+        Duration::from_secs(10),
+        Duration::from_secs(30),
+    ];
+
+    #[derive(Clone, Debug, PartialEq)]
+    enum Input {
+        PushButton(&'static str, bool),
+        EngineN2(usize, f64),
+        Airspeed(f64),
+        ApuMasterAndStart(bool, bool),
+        ApuFire(bool),
+        ExternalPowerAvailable(bool),
+    }
+
+    /// Changes an input and then runs the aircraft for the given duration.
+    #[derive(Clone, Debug, PartialEq)]
+    struct Step {
+        input: Option<Input>,
+        duration: Duration,
+    }
+    impl Step {
+        fn generate(generator: &mut Generator) -> Self {
+            let input = match generator.between(0., 12.) as u32 {
+                0..=2 => {
+                    let name = generator.choose(&PUSH_BUTTONS);
+
+                    Some(Input::PushButton(name, generator.chance(0.5)))
+                }
+                // Without batteries little happens, thus they are changed more often.
+                3 | 4 => {
+                    let name = generator.choose(&PUSH_BUTTONS[..2]);
+
+                    Some(Input::PushButton(name, generator.chance(0.8)))
+                }
+                5 | 6 => {
+                    let number = *generator.choose(&[1, 2]);
+                    // Engines are most often off or running, but sometimes starting.
+                    let starting_n2 = generator.between(0., 80.);
+                    let n2 = *generator.choose(&[0., 80., starting_n2]);
+
+                    Some(Input::EngineN2(number, n2))
+                }
+                7 => Some(Input::Airspeed(generator.between(0., 350.))),
+                // The APU only starts when its master switch remains on for some time.
+                8 | 9 => Some(Input::ApuMasterAndStart(
+                    generator.chance(0.9),
+                    generator.chance(0.7),
+                )),
+                10 => Some(Input::ApuFire(generator.chance(0.1))),
+                _ => Some(Input::ExternalPowerAvailable(generator.chance(0.5))),
+            };
+
+            Self {
+                // Some steps only let time pass.
+                input: input.filter(|_| generator.chance(0.8)),
+                duration: *generator.choose(&DURATIONS),
+            }
+        }
+    }
+    impl Shrink for Step {
+        fn simplifications(&self) -> Vec<Self> {
+            let mut simplifications = vec![];
+
+            if self.input.is_some() {
+                simplifications.push(Step {
+                    input: None,
+                    duration: self.duration,
+                });
+            }
+
+            if let Some(index) = DURATIONS
+                .iter()
+                .position(|duration| *duration == self.duration)
+                .filter(|index| *index > 0)
+            {
+                simplifications.push(Step {
+                    input: self.input.clone(),
+                    duration: DURATIONS[index - 1],
+                });
+            }
+
+            simplifications
+        }
+    }
+
+    /// Runs the steps on a cold and dark aircraft. In debug builds, the electrical system
+    /// asserts its invariants during every update.
+    fn run_aircraft(steps: &[Step]) {
+        let mut aircraft = A320::new();
+        let mut test_bed = SimulationTestBed::seeded_with(&mut aircraft);
+        test_bed.set_random_seed(1);
+        // Matches the maximum step size used within the simulator.
+        test_bed.set_maximum_step_size(Duration::from_millis(100));
+        test_bed.set_on_ground(true);
+        test_bed.set_indicated_airspeed(Velocity::new::<knot>(0.));
+        test_bed.write_f64("GEAR CENTER POSITION", 100.);
+        test_bed.write_bool("UNLIMITED FUEL", true);
+        test_bed.initialise_aircraft(&mut aircraft, StartingState::ColdAndDark);
+
+        for step in steps {
+            match &step.input {
+                Some(Input::PushButton(name, is_on)) => test_bed.write_bool(name, *is_on),
+                Some(Input::EngineN2(number, n2)) => {
+                    test_bed.write_f64(&format!("TURB ENG CORRECTED N2:{}", number), *n2)
+                }
+                Some(Input::Airspeed(airspeed)) => {
+                    test_bed.set_indicated_airspeed(Velocity::new::<knot>(*airspeed))
+                }
+                Some(Input::ApuMasterAndStart(master_is_on, start_is_on)) => {
+                    test_bed.write_bool("OVHD_APU_MASTER_SW_PB_IS_ON", *master_is_on);
+                    test_bed.write_bool("OVHD_APU_START_PB_IS_ON", *start_is_on);
+                }
+                Some(Input::ApuFire(is_released)) => {
+                    test_bed.write_bool("FIRE_BUTTON_APU", *is_released)
+                }
+                Some(Input::ExternalPowerAvailable(is_available)) => {
+                    test_bed.write_bool("EXTERNAL POWER AVAILABLE:1", *is_available)
+                }
+                None => {}
+            }
+
+            test_bed.set_delta(step.duration);
+            test_bed.run_aircraft(&mut aircraft);
+        }
+    }
+
+    #[test]
+    fn electrical_invariants_hold_for_any_sequence_of_inputs() {
+        PropertyTest::new().check(Step::generate, run_aircraft);
+    }
+}
+
+#[cfg(test)]
+mod a320_golden_tests {
+    use super::*;
+    use std::time::Duration;
+    use systems::simulation::{golden_file::GoldenFile, test::SimulationTestBed, StartingState};
+    use uom::si::{f64::*, length::foot, velocity::knot};
+
+    struct GoldenTestBed {
+        aircraft: A320,
+        test_bed: SimulationTestBed,
+        golden_file: GoldenFile,
+        time: Duration,
+    }
+    impl GoldenTestBed {
+        fn new() -> Self {
+            let mut test_bed = SimulationTestBed::new_with_delta(Duration::from_millis(100));
+            test_bed.set_random_seed(1);
+            test_bed.set_on_ground(true);
+            test_bed.set_indicated_airspeed(Velocity::new::<knot>(0.));
+            test_bed.set_indicated_altitude(Length::new::<foot>(0.));
+            test_bed.write_f64("GEAR CENTER POSITION", 100.);
+            test_bed.write_bool("UNLIMITED FUEL", true);
+
+            let mut aircraft = A320::new();
+            test_bed.initialise_aircraft(&mut aircraft, StartingState::ColdAndDark);
+
+            Self {
+                aircraft,
+                test_bed,
+                golden_file: GoldenFile::new(),
+                time: Duration::from_secs(0),
+            }
+        }
+
+        fn set(mut self, name: &str, value: bool) -> Self {
+            self.test_bed.write_bool(name, value);
+            self
+        }
+
+        fn run_and_record_after(mut self, duration: Duration) -> Self {
+            self.test_bed.run_aircraft_for(&mut self.aircraft, duration);
+            self.time += duration;
+            self.golden_file
+                .record(self.time, self.test_bed.written_variables());
+
+            self
+        }
+
+        fn assert_matches(&self, name: &str) {
+            self.golden_file.assert_matches(format!(
+                "{}/golden/{}.golden",
+                env!("CARGO_MANIFEST_DIR"),
+                name
+            ));
+        }
+    }
+
+    #[test]
+    fn apu_start_from_cold_and_dark() {
+        GoldenTestBed::new()
+            .run_and_record_after(Duration::from_millis(100))
+            .set("OVHD_ELEC_BAT_10_PB_IS_AUTO", true)
+            .set("OVHD_ELEC_BAT_11_PB_IS_AUTO", true)
+            .run_and_record_after(Duration::from_secs(5))
+            .set("OVHD_APU_MASTER_SW_PB_IS_ON", true)
+            .run_and_record_after(Duration::from_secs(5))
+            .set("OVHD_APU_START_PB_IS_ON", true)
+            .run_and_record_after(Duration::from_secs(30))
+            .run_and_record_after(Duration::from_secs(30))
+            .assert_matches("apu_start_from_cold_and_dark");
+    }
 }
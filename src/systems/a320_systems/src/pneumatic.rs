@@ -1,6 +1,6 @@
 use systems::{
     overhead::OnOffFaultPushButton,
-    simulation::{SimulationElement, SimulationElementVisitor},
+    simulation::{SimulationElement, SimulationElementVisitor, StartingState},
 };
 
 pub struct A320PneumaticOverheadPanel {
@@ -19,8 +19,18 @@ impl A320PneumaticOverheadPanel {
 }
 impl SimulationElement for A320PneumaticOverheadPanel {
     fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+        visitor.enter_children();
         self.apu_bleed.accept(visitor);
+        visitor.leave_children();
 
         visitor.visit(self);
     }
+
+    fn identifier(&self) -> String {
+        "pneumatic_overhead".to_owned()
+    }
+
+    fn initialise(&mut self, state: StartingState) {
+        self.apu_bleed.set_on(state.apu_is_running());
+    }
 }
@@ -310,6 +310,7 @@ impl A320PowerConsumption {
 }
 impl SimulationElement for A320PowerConsumption {
     fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+        visitor.enter_children();
         self.ac_bus_1_consumer.accept(visitor);
         self.ac_bus_2_consumer.accept(visitor);
         self.ac_ess_bus_consumer.accept(visitor);
@@ -324,9 +325,14 @@ impl SimulationElement for A320PowerConsumption {
         self.dc_hot_bus_1_consumer.accept(visitor);
         self.dc_hot_bus_2_consumer.accept(visitor);
         self.dc_gnd_flt_service_consumer.accept(visitor);
+        visitor.leave_children();
 
         visitor.visit(self);
     }
+
+    fn identifier(&self) -> String {
+        "power_consumption".to_owned()
+    }
 }
 impl Default for A320PowerConsumption {
     fn default() -> Self {
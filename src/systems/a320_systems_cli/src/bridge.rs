@@ -0,0 +1,517 @@
+use crate::{
+    runner::{MemoryReaderWriter, MAXIMUM_STEP_SIZE},
+    scenario::{format_time, parse_assignment, parse_starting_state, parse_time, split_keyword},
+};
+use a320_systems::A320;
+use std::{
+    collections::HashMap,
+    io,
+    net::{SocketAddr, ToSocketAddrs, UdpSocket},
+    time::{Duration, Instant},
+};
+use systems::simulation::{
+    KeyEvent, RandomNumberGenerator, Simulation, SimulatorReaderWriter, StartingState,
+};
+
+/// The largest datagram the bridge receives. Longer datagrams are truncated.
+const MAXIMUM_DATAGRAM_SIZE: usize = 65_507;
+
+/// Drives the A320 on behalf of clients which exchange text datagrams with it over UDP,
+/// such as home cockpit hardware, a test rig or a notebook.
+///
+/// Every line of a datagram contains one of the following commands:
+///
+/// ```text
+/// set SIM ON GROUND=1              # Sets a variable, replies with `ok`.
+/// get APU_N                        # Replies with the variable's value, e.g. `APU_N=12.5`.
+/// subscribe APU_N                  # Adds a variable to the client's outputs, replies with `ok`.
+/// unsubscribe APU_N                # Removes a variable from the client's outputs, replies with `ok`.
+/// event OVHD_ELEC_BAT_10_PB_PUSH   # Raises a key event during the next tick, replies with `ok`.
+/// event OVHD_ELEC_BAT_10_PB_SET=1  # Raises a key event with a value, replies with `ok`.
+/// initialise GateWithApu           # Initialises the aircraft to a starting state, replies with `ok`.
+/// step 500ms                       # Advances the simulation by the given amount of simulated time.
+/// run                              # Advances the simulation in real time until stopped, replies with `ok`.
+/// stop                             # Stops advancing the simulation in real time, replies with `ok`.
+/// shutdown                         # Stops the bridge, replies with `ok`.
+/// ```
+///
+/// After the simulation advanced, every subscribed client receives its outputs on a
+/// single line of the form `t=10s APU_N=12.5 ...`, as emitted by the scenario runner.
+/// The client which requested a step always receives such a line, even when it didn't
+/// subscribe to any variables. An invalid command is replied to with `error: <message>`.
+///
+/// Variable names are those used within the aircraft system simulation, thus without
+/// the `A32NX_` prefix.
+pub struct Bridge {
+    socket: UdpSocket,
+    random: RandomNumberGenerator,
+    delta: Duration,
+    maximum_step_size: Duration,
+}
+impl Bridge {
+    /// Binds the bridge to the given address. While running in real time, the simulation
+    /// advances by `delta` in every tick.
+    pub fn bind<A: ToSocketAddrs>(
+        address: A,
+        delta: Duration,
+        random: RandomNumberGenerator,
+    ) -> io::Result<Self> {
+        assert!(
+            delta > Duration::from_secs(0),
+            "The tick duration must be greater than zero."
+        );
+
+        Ok(Self {
+            socket: UdpSocket::bind(address)?,
+            random,
+            delta,
+            maximum_step_size: MAXIMUM_STEP_SIZE,
+        })
+    }
+
+    pub fn local_address(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    /// Handles commands until a client requests a shutdown. Every call
+    /// simulates a newly created aircraft.
+    pub fn serve(&mut self) -> io::Result<()> {
+        let mut aircraft = A320::new();
+        let mut reader_writer = MemoryReaderWriter::new();
+        let mut simulation = Simulation::new_with_random_number_generator(
+            &mut aircraft,
+            &mut reader_writer,
+            self.random.clone(),
+        );
+        simulation.set_maximum_step_size(self.maximum_step_size);
+
+        BridgeSession::new(&self.socket, simulation, self.delta).serve()
+    }
+}
+
+/// The state of a [`Bridge`] while it serves its clients.
+///
+/// [`Bridge`]: struct.Bridge.html
+struct BridgeSession<'a> {
+    socket: &'a UdpSocket,
+    simulation: Simulation<'a, A320, MemoryReaderWriter>,
+    delta: Duration,
+    now: Duration,
+    subscriptions: HashMap<SocketAddr, Vec<String>>,
+    next_free_running_tick: Option<Instant>,
+    is_shut_down: bool,
+}
+impl<'a> BridgeSession<'a> {
+    fn new(
+        socket: &'a UdpSocket,
+        simulation: Simulation<'a, A320, MemoryReaderWriter>,
+        delta: Duration,
+    ) -> Self {
+        Self {
+            socket,
+            simulation,
+            delta,
+            now: Duration::from_secs(0),
+            subscriptions: HashMap::new(),
+            next_free_running_tick: None,
+            is_shut_down: false,
+        }
+    }
+
+    fn serve(&mut self) -> io::Result<()> {
+        let mut buffer = vec![0; MAXIMUM_DATAGRAM_SIZE];
+
+        while !self.is_shut_down {
+            self.socket
+                .set_read_timeout(self.time_until_free_running_tick())?;
+
+            match self.socket.recv_from(&mut buffer) {
+                Ok((length, client)) => self.receive(&buffer[..length], client),
+                // Some platforms report a client which went away on the next receive.
+                Err(error)
+                    if matches!(
+                        error.kind(),
+                        io::ErrorKind::WouldBlock
+                            | io::ErrorKind::TimedOut
+                            | io::ErrorKind::ConnectionReset
+                    ) => {}
+                Err(error) => return Err(error),
+            }
+
+            self.tick_when_free_running();
+        }
+
+        Ok(())
+    }
+
+    fn time_until_free_running_tick(&self) -> Option<Duration> {
+        // A read timeout of zero is invalid, thus the timeout is at least a millisecond.
+        self.next_free_running_tick.map(|next_tick| {
+            next_tick
+                .saturating_duration_since(Instant::now())
+                .max(Duration::from_millis(1))
+        })
+    }
+
+    fn tick_when_free_running(&mut self) {
+        if let Some(next_tick) = self.next_free_running_tick {
+            let now = Instant::now();
+            if now >= next_tick {
+                self.tick(self.delta);
+                self.send_outputs(None);
+
+                // When the host can't keep up, the simulation falls behind instead of
+                // trying to catch up with a burst of ticks.
+                self.next_free_running_tick = Some((next_tick + self.delta).max(now));
+            }
+        }
+    }
+
+    fn receive(&mut self, datagram: &[u8], client: SocketAddr) {
+        let text = match std::str::from_utf8(datagram) {
+            Ok(text) => text,
+            Err(_) => {
+                self.send(client, "error: the datagram isn't valid UTF-8");
+                return;
+            }
+        };
+
+        for line in text.lines().map(|line| line.trim()) {
+            if line.is_empty() {
+                continue;
+            }
+
+            match Command::parse(line) {
+                Ok(command) => self.execute(command, client),
+                Err(message) => self.send(client, &format!("error: {}", message)),
+            }
+        }
+    }
+
+    fn execute(&mut self, command: Command, client: SocketAddr) {
+        match command {
+            Command::Set(name, value) => {
+                self.simulation
+                    .simulator_reader_writer()
+                    .write(&name, value);
+                self.send(client, "ok");
+            }
+            Command::KeyEvent(name, value) => {
+                self.simulation
+                    .simulator_reader_writer()
+                    .trigger_key_event(KeyEvent::new(name, value));
+                self.send(client, "ok");
+            }
+            Command::Get(name) => {
+                let value = self.simulation.simulator_reader_writer().read(&name);
+                self.send(client, &format!("{}={}", name, value));
+            }
+            Command::Subscribe(name) => {
+                let outputs = self.subscriptions.entry(client).or_default();
+                if !outputs.contains(&name) {
+                    outputs.push(name);
+                }
+                self.send(client, "ok");
+            }
+            Command::Unsubscribe(name) => {
+                if let Some(outputs) = self.subscriptions.get_mut(&client) {
+                    outputs.retain(|output| output != &name);
+                    if outputs.is_empty() {
+                        self.subscriptions.remove(&client);
+                    }
+                }
+                self.send(client, "ok");
+            }
+            Command::Initialise(starting_state) => {
+                self.simulation.initialise(starting_state);
+                self.send(client, "ok");
+            }
+            Command::Step(duration) => {
+                let mut remaining = duration;
+                while remaining > Duration::from_secs(0) {
+                    let delta = remaining.min(self.delta);
+                    self.tick(delta);
+                    remaining -= delta;
+                }
+                self.send_outputs(Some(client));
+            }
+            Command::Run => {
+                if self.next_free_running_tick.is_none() {
+                    self.next_free_running_tick = Some(Instant::now() + self.delta);
+                }
+                self.send(client, "ok");
+            }
+            Command::Stop => {
+                self.next_free_running_tick = None;
+                self.send(client, "ok");
+            }
+            Command::Shutdown => {
+                self.is_shut_down = true;
+                self.send(client, "ok");
+            }
+        }
+    }
+
+    fn tick(&mut self, delta: Duration) {
+        self.simulation.tick(delta);
+        self.now += delta;
+    }
+
+    /// Sends the outputs to every subscribed client and to the given client.
+    fn send_outputs(&mut self, requesting_client: Option<SocketAddr>) {
+        let mut clients: Vec<SocketAddr> = self.subscriptions.keys().copied().collect();
+        if let Some(client) = requesting_client {
+            if !clients.contains(&client) {
+                clients.push(client);
+            }
+        }
+
+        for client in clients {
+            let mut line = format!("t={}", format_time(self.now));
+            for name in self.subscriptions.get(&client).into_iter().flatten() {
+                line.push_str(&format!(
+                    " {}={}",
+                    name,
+                    self.simulation.simulator_reader_writer().read(name)
+                ));
+            }
+
+            self.send(client, &line);
+        }
+    }
+
+    fn send(&mut self, client: SocketAddr, line: &str) {
+        // A client which cannot be reached no longer receives outputs, such that
+        // clients which went away don't slow down the bridge.
+        if self.socket.send_to(line.as_bytes(), client).is_err() {
+            self.subscriptions.remove(&client);
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Command {
+    Set(String, f64),
+    KeyEvent(String, f64),
+    Get(String),
+    Subscribe(String),
+    Unsubscribe(String),
+    Initialise(StartingState),
+    Step(Duration),
+    Run,
+    Stop,
+    Shutdown,
+}
+impl Command {
+    fn parse(line: &str) -> Result<Self, String> {
+        let (keyword, argument) = split_keyword(line);
+        let name = || {
+            if argument.is_empty() {
+                Err(format!("'{}' requires a variable name", keyword))
+            } else {
+                Ok(argument.to_owned())
+            }
+        };
+
+        match keyword {
+            "set" => {
+                let (name, value) = parse_assignment(argument)?;
+                Ok(Command::Set(name, value))
+            }
+            "event" if argument.contains('=') => {
+                let (name, value) = parse_assignment(argument)?;
+                Ok(Command::KeyEvent(name, value))
+            }
+            "event" => Ok(Command::KeyEvent(name()?, 0.)),
+            "get" => Ok(Command::Get(name()?)),
+            "subscribe" => Ok(Command::Subscribe(name()?)),
+            "unsubscribe" => Ok(Command::Unsubscribe(name()?)),
+            "initialise" => Ok(Command::Initialise(parse_starting_state(argument)?)),
+            "step" => Ok(Command::Step(parse_time(argument)?)),
+            "run" if argument.is_empty() => Ok(Command::Run),
+            "stop" if argument.is_empty() => Ok(Command::Stop),
+            "shutdown" if argument.is_empty() => Ok(Command::Shutdown),
+            _ => Err(format!("unknown command '{}'", line)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod bridge_tests {
+    use super::*;
+    use std::{sync::mpsc, thread, thread::JoinHandle};
+
+    /// Stands in for a client such as home cockpit hardware.
+    struct Client {
+        socket: UdpSocket,
+        bridge_address: SocketAddr,
+        bridge: Option<JoinHandle<()>>,
+    }
+    impl Client {
+        fn connected_to_new_bridge() -> Self {
+            let (sender, receiver) = mpsc::channel();
+            let bridge = thread::spawn(move || {
+                let mut bridge = Bridge::bind(
+                    "127.0.0.1:0",
+                    Duration::from_millis(50),
+                    RandomNumberGenerator::from_seed(1),
+                )
+                .unwrap();
+                sender.send(bridge.local_address().unwrap()).unwrap();
+
+                bridge.serve().unwrap();
+            });
+
+            let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+            socket
+                .set_read_timeout(Some(Duration::from_secs(10)))
+                .unwrap();
+
+            Self {
+                socket,
+                bridge_address: receiver.recv().unwrap(),
+                bridge: Some(bridge),
+            }
+        }
+
+        fn send(&self, command: &str) {
+            self.socket
+                .send_to(command.as_bytes(), self.bridge_address)
+                .unwrap();
+        }
+
+        fn request(&self, command: &str) -> String {
+            self.send(command);
+            self.receive()
+        }
+
+        /// Receives until the given reply, skipping any outputs pushed in the meantime.
+        fn receive_until(&self, reply: &str) {
+            while self.receive() != reply {}
+        }
+
+        fn receive(&self) -> String {
+            let mut buffer = [0; 1024];
+            let (length, _) = self.socket.recv_from(&mut buffer).unwrap();
+
+            String::from_utf8(buffer[..length].to_vec()).unwrap()
+        }
+    }
+    impl Drop for Client {
+        fn drop(&mut self) {
+            if let Some(bridge) = self.bridge.take() {
+                if !thread::panicking() {
+                    self.send("shutdown");
+                    self.receive_until("ok");
+                    bridge.join().unwrap();
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn parses_commands() {
+        assert_eq!(
+            Command::parse("set SIM ON GROUND=1"),
+            Ok(Command::Set("SIM ON GROUND".to_owned(), 1.))
+        );
+        assert_eq!(
+            Command::parse("step 1.5s"),
+            Ok(Command::Step(Duration::from_millis(1500)))
+        );
+        assert_eq!(
+            Command::parse("event OVHD_ELEC_BAT_10_PB_PUSH"),
+            Ok(Command::KeyEvent("OVHD_ELEC_BAT_10_PB_PUSH".to_owned(), 0.))
+        );
+        assert_eq!(
+            Command::parse("event OVHD_ELEC_BAT_10_PB_SET=1"),
+            Ok(Command::KeyEvent("OVHD_ELEC_BAT_10_PB_SET".to_owned(), 1.))
+        );
+        assert!(Command::parse("subscribe").is_err());
+        assert!(Command::parse("run fast").is_err());
+    }
+
+    #[test]
+    fn gets_the_value_which_was_set() {
+        let client = Client::connected_to_new_bridge();
+
+        assert_eq!(client.request("set AIRSPEED INDICATED=150"), "ok");
+        assert_eq!(
+            client.request("get AIRSPEED INDICATED"),
+            "AIRSPEED INDICATED=150"
+        );
+    }
+
+    #[test]
+    fn replies_with_the_subscribed_outputs_after_a_step() {
+        let client = Client::connected_to_new_bridge();
+        client.request("set SIM ON GROUND=1");
+        client.request("subscribe SIM ON GROUND");
+
+        assert_eq!(client.request("step 1.5s"), "t=1.5s SIM ON GROUND=1");
+    }
+
+    #[test]
+    fn replies_with_the_time_after_a_step_without_subscriptions() {
+        let client = Client::connected_to_new_bridge();
+
+        assert_eq!(client.request("step 120ms"), "t=0.12s");
+    }
+
+    #[test]
+    fn steps_the_aircraft() {
+        let client = Client::connected_to_new_bridge();
+        client.request("initialise ColdAndDark");
+        client.request("set SIM ON GROUND=1");
+        client.request("set OVHD_ELEC_BAT_10_PB_IS_AUTO=1");
+        client.request("subscribe ELEC_DC_BAT_BUS_IS_POWERED");
+
+        assert_eq!(
+            client.request("step 1s"),
+            "t=1s ELEC_DC_BAT_BUS_IS_POWERED=1"
+        );
+    }
+
+    #[test]
+    fn raises_key_events_during_the_next_step() {
+        let client = Client::connected_to_new_bridge();
+        client.request("initialise ColdAndDark");
+        client.request("set SIM ON GROUND=1");
+        client.request("subscribe OVHD_ELEC_BAT_10_PB_IS_AUTO");
+
+        assert_eq!(client.request("event OVHD_ELEC_BAT_10_PB_PUSH"), "ok");
+        assert_eq!(
+            client.request("step 100ms"),
+            "t=0.1s OVHD_ELEC_BAT_10_PB_IS_AUTO=1"
+        );
+        assert_eq!(
+            client.request("step 100ms"),
+            "t=0.2s OVHD_ELEC_BAT_10_PB_IS_AUTO=1"
+        );
+    }
+
+    #[test]
+    fn pushes_outputs_to_subscribers_while_running() {
+        let client = Client::connected_to_new_bridge();
+        client.request("subscribe SIM ON GROUND");
+
+        assert_eq!(client.request("run"), "ok");
+        assert_eq!(client.receive(), "t=0.05s SIM ON GROUND=0");
+        assert_eq!(client.receive(), "t=0.1s SIM ON GROUND=0");
+
+        client.send("stop");
+        client.receive_until("ok");
+
+        assert_eq!(client.request("get SIM ON GROUND"), "SIM ON GROUND=0");
+    }
+
+    #[test]
+    fn replies_with_an_error_to_an_unknown_command() {
+        let client = Client::connected_to_new_bridge();
+
+        assert_eq!(
+            client.request("jump 10s"),
+            "error: unknown command 'jump 10s'"
+        );
+    }
+}
@@ -0,0 +1,407 @@
+use crate::scenario::split_assignment;
+use std::{error::Error, fmt, time::Duration};
+
+/// Flight data captured by the flight data recorder in the simulator, as converted to CSV
+/// by `src/fdr2csv`. The first line contains the column names, followed by a line of values
+/// for every recorded frame. Values are separated by the delimiter passed to `fdr2csv`,
+/// which is a comma by default. Semicolons and tabs are recognised as well.
+#[derive(Debug, PartialEq)]
+pub struct FlightData {
+    columns: Vec<String>,
+    frames: Vec<Vec<f64>>,
+    time_column: usize,
+}
+impl FlightData {
+    /// The simulation time in seconds at which the frame was recorded.
+    pub const TIME_COLUMN: &'static str = "fbw.sim.time.simulation_time";
+    const DELIMITERS: [char; 3] = [',', ';', '\t'];
+
+    pub fn parse(text: &str) -> Result<Self, FlightDataError> {
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty());
+
+        let header = match lines.next() {
+            Some((_, header)) => header,
+            None => {
+                return Err(FlightDataError::new(
+                    0,
+                    "the flight data doesn't contain a header".to_owned(),
+                ))
+            }
+        };
+        let delimiter = FlightData::DELIMITERS
+            .iter()
+            .copied()
+            .max_by_key(|delimiter| header.matches(*delimiter).count())
+            .unwrap_or(',');
+
+        let columns: Vec<String> = split_fields(header, delimiter)
+            .map(|column| column.to_owned())
+            .collect();
+        let time_column = columns
+            .iter()
+            .position(|column| column == FlightData::TIME_COLUMN)
+            .ok_or_else(|| {
+                FlightDataError::new(
+                    1,
+                    format!("the header doesn't contain '{}'", FlightData::TIME_COLUMN),
+                )
+            })?;
+
+        let mut frames = vec![];
+        for (index, line) in lines {
+            let line_number = index + 1;
+            let frame = split_fields(line, delimiter)
+                .map(|value| {
+                    value.parse().map_err(|_| {
+                        FlightDataError::new(
+                            line_number,
+                            format!("'{}' is not a valid value", value),
+                        )
+                    })
+                })
+                .collect::<Result<Vec<f64>, _>>()?;
+
+            if frame.len() != columns.len() {
+                return Err(FlightDataError::new(
+                    line_number,
+                    format!(
+                        "the frame contains {} values, while the header contains {} columns",
+                        frame.len(),
+                        columns.len()
+                    ),
+                ));
+            }
+
+            frames.push(frame);
+        }
+
+        Ok(Self {
+            columns,
+            frames,
+            time_column,
+        })
+    }
+
+    pub fn column_index(&self, name: &str) -> Option<usize> {
+        self.columns.iter().position(|column| column == name)
+    }
+
+    /// The recorded frames, each containing a value for every column.
+    pub fn frames(&self) -> &[Vec<f64>] {
+        &self.frames
+    }
+
+    /// The simulation time at which the given frame was recorded.
+    pub fn time_of(&self, frame: &[f64]) -> Duration {
+        Duration::from_secs_f64(frame[self.time_column].max(0.))
+    }
+}
+
+/// `fdr2csv` writes the delimiter after every field, including the last.
+fn split_fields(line: &str, delimiter: char) -> impl Iterator<Item = &str> {
+    let line = line.trim();
+    line.strip_suffix(delimiter)
+        .unwrap_or(line)
+        .split(delimiter)
+        .map(|field| field.trim())
+}
+
+/// Maps the columns of the [`FlightData`] to the variables read by the aircraft.
+///
+/// Mappings are line based. Empty lines and everything following a `#` is ignored.
+/// Each line assigns a column to a variable. The column's value can be multiplied
+/// by a factor, which is either a number or the value of another column:
+///
+/// ```text
+/// AIRSPEED INDICATED = ap_sm.data.V_ias_kn
+/// ACCELERATION BODY Z = ap_sm.data.bx_m_s2 * 3.28084
+/// FUEL TANK LEFT MAIN QUANTITY = engine.fuelTankQuantityMainLeft * engine.fuelWeightPerGallon
+/// ```
+///
+/// [`FlightData`]: struct.FlightData.html
+#[derive(Debug, PartialEq)]
+pub struct ColumnMapping {
+    assignments: Vec<ColumnAssignment>,
+}
+impl ColumnMapping {
+    /// Maps the flight data recorded by the current version of the flight data recorder.
+    /// The recorder doesn't capture the engines' N2, the landing gear's position or the state
+    /// of the cockpit's push buttons, which thus can't be mapped. The replay holds the N2 and
+    /// the landing gear's position at the value of its starting state instead.
+    const DEFAULT: &'static str = "
+        # The flight data recorder stores the variables the way the simulator provides them.
+        AIRSPEED INDICATED = ap_sm.data.V_ias_kn
+        AIRSPEED TRUE = ap_sm.data.V_tas_kn
+        GROUND VELOCITY = ap_sm.data.V_gnd_kn
+        VERTICAL SPEED = ap_sm.data.H_dot_ft_min
+        INDICATED ALTITUDE = ap_sm.data.H_ind_ft
+        PRESSURE ALTITUDE = ap_sm.data.H_ft
+        PLANE PITCH DEGREES = ap_sm.data.Theta_deg
+        PLANE BANK DEGREES = ap_sm.data.Phi_deg
+        SIM ON GROUND = ap_sm.data.on_ground
+        AMBIENT TEMPERATURE = fbw.sim.data.ambient_temperature_celsius
+        # Millibars to inches of mercury.
+        AMBIENT PRESSURE = fbw.sim.data.ambient_pressure_mbar * 0.02953
+        # Metres to feet per second squared. The recorder's x axis is the simulator's z axis.
+        ACCELERATION BODY Z = ap_sm.data.bx_m_s2 * 3.28084
+        ACCELERATION BODY Y = ap_sm.data.bz_m_s2 * 3.28084
+        # Gallons to pounds.
+        FUEL TANK LEFT MAIN QUANTITY = engine.fuelTankQuantityMainLeft * engine.fuelWeightPerGallon
+    ";
+
+    pub fn parse(text: &str) -> Result<Self, FlightDataError> {
+        let mut assignments = vec![];
+        for (index, line) in text.lines().enumerate() {
+            let line = match line.find('#') {
+                Some(comment_start) => &line[..comment_start],
+                None => line,
+            }
+            .trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            assignments.push(
+                ColumnAssignment::parse(line)
+                    .map_err(|message| FlightDataError::new(index + 1, message))?,
+            );
+        }
+
+        Ok(Self { assignments })
+    }
+
+    /// The variables written by the mapping.
+    pub fn variables(&self) -> impl Iterator<Item = &str> {
+        self.assignments
+            .iter()
+            .map(|assignment| assignment.variable.as_str())
+    }
+
+    /// The value of every mapped variable in the given frame.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when the flight data doesn't contain one of the mapped columns.
+    pub fn values(
+        &self,
+        flight_data: &FlightData,
+        frame: &[f64],
+    ) -> Result<Vec<(&str, f64)>, FlightDataError> {
+        self.assignments
+            .iter()
+            .map(|assignment| {
+                Ok((
+                    assignment.variable.as_str(),
+                    assignment.value(flight_data, frame)?,
+                ))
+            })
+            .collect()
+    }
+}
+impl Default for ColumnMapping {
+    fn default() -> Self {
+        ColumnMapping::parse(ColumnMapping::DEFAULT).unwrap()
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct ColumnAssignment {
+    variable: String,
+    column: String,
+    factor: Option<Factor>,
+}
+impl ColumnAssignment {
+    fn parse(text: &str) -> Result<Self, String> {
+        let (variable, expression) = split_assignment(text)?;
+        let mut operands = expression.splitn(2, '*').map(|operand| operand.trim());
+
+        let column = match operands.next() {
+            Some(column) if !column.is_empty() => column.to_owned(),
+            _ => return Err(format!("'{}' doesn't assign a column", text)),
+        };
+        let factor = match operands.next() {
+            Some("") => return Err(format!("'{}' doesn't specify the factor", text)),
+            Some(factor) => Some(match factor.parse() {
+                Ok(number) => Factor::Number(number),
+                Err(_) => Factor::Column(factor.to_owned()),
+            }),
+            None => None,
+        };
+
+        Ok(Self {
+            variable,
+            column,
+            factor,
+        })
+    }
+
+    fn value(&self, flight_data: &FlightData, frame: &[f64]) -> Result<f64, FlightDataError> {
+        let value = column_value(flight_data, frame, &self.column)?;
+
+        Ok(match &self.factor {
+            Some(Factor::Number(factor)) => value * factor,
+            Some(Factor::Column(column)) => value * column_value(flight_data, frame, column)?,
+            None => value,
+        })
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Factor {
+    Number(f64),
+    Column(String),
+}
+
+fn column_value(
+    flight_data: &FlightData,
+    frame: &[f64],
+    column: &str,
+) -> Result<f64, FlightDataError> {
+    flight_data
+        .column_index(column)
+        .map(|index| frame[index])
+        .ok_or_else(|| {
+            FlightDataError::new(
+                0,
+                format!("the flight data doesn't contain the column '{}'", column),
+            )
+        })
+}
+
+#[derive(Debug, PartialEq)]
+pub struct FlightDataError {
+    line: usize,
+    message: String,
+}
+impl FlightDataError {
+    fn new(line: usize, message: String) -> Self {
+        Self { line, message }
+    }
+}
+impl fmt::Display for FlightDataError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.line > 0 {
+            write!(f, "line {}: {}", self.line, self.message)
+        } else {
+            write!(f, "{}", self.message)
+        }
+    }
+}
+impl Error for FlightDataError {}
+
+#[cfg(test)]
+mod flight_data_tests {
+    use super::*;
+
+    const FLIGHT_DATA: &str = "\
+fbw.sim.time.simulation_time,ap_sm.data.V_ias_kn,engine.fuelTankQuantityMainLeft,engine.fuelWeightPerGallon,
+10,0,100,6.7,
+10.5,12.5,99.5,6.7,
+";
+
+    #[test]
+    fn parses_the_columns_and_frames() {
+        let flight_data = FlightData::parse(FLIGHT_DATA).unwrap();
+
+        assert_eq!(
+            flight_data.column_index("engine.fuelWeightPerGallon"),
+            Some(3)
+        );
+        assert_eq!(flight_data.column_index("ap_sm.data.V_ias_kn"), Some(1));
+        assert_eq!(flight_data.frames()[1], vec![10.5, 12.5, 99.5, 6.7]);
+        assert_eq!(
+            flight_data.time_of(&flight_data.frames()[1]),
+            Duration::from_millis(10500)
+        );
+    }
+
+    #[test]
+    fn recognises_other_delimiters() {
+        let flight_data =
+            FlightData::parse("fbw.sim.time.simulation_time;ap_sm.data.V_ias_kn\n1;150\n").unwrap();
+
+        assert_eq!(flight_data.frames(), &[vec![1., 150.]]);
+    }
+
+    #[test]
+    fn flight_data_without_time_is_an_error() {
+        assert_eq!(
+            FlightData::parse("ap_sm.data.V_ias_kn,\n150,\n")
+                .unwrap_err()
+                .to_string(),
+            "line 1: the header doesn't contain 'fbw.sim.time.simulation_time'"
+        );
+    }
+
+    #[test]
+    fn frame_with_missing_values_is_an_error() {
+        assert_eq!(
+            FlightData::parse("fbw.sim.time.simulation_time,ap_sm.data.V_ias_kn,\n1,\n")
+                .unwrap_err()
+                .to_string(),
+            "line 2: the frame contains 1 values, while the header contains 2 columns"
+        );
+    }
+
+    #[test]
+    fn maps_columns_to_variables() {
+        let flight_data = FlightData::parse(FLIGHT_DATA).unwrap();
+        let mapping = ColumnMapping::parse(
+            "
+            # Knots.
+            AIRSPEED INDICATED = ap_sm.data.V_ias_kn
+            AIRSPEED TRUE = ap_sm.data.V_ias_kn * 2
+            FUEL TANK LEFT MAIN QUANTITY = engine.fuelTankQuantityMainLeft * engine.fuelWeightPerGallon
+            ",
+        )
+        .unwrap();
+
+        assert_eq!(
+            mapping
+                .values(&flight_data, &flight_data.frames()[0])
+                .unwrap(),
+            vec![
+                ("AIRSPEED INDICATED", 0.),
+                ("AIRSPEED TRUE", 0.),
+                ("FUEL TANK LEFT MAIN QUANTITY", 670.)
+            ]
+        );
+    }
+
+    #[test]
+    fn mapping_a_missing_column_is_an_error() {
+        let flight_data = FlightData::parse(FLIGHT_DATA).unwrap();
+        let mapping = ColumnMapping::parse("AIRSPEED TRUE = ap_sm.data.V_tas_kn").unwrap();
+
+        assert_eq!(
+            mapping
+                .values(&flight_data, &flight_data.frames()[0])
+                .unwrap_err()
+                .to_string(),
+            "the flight data doesn't contain the column 'ap_sm.data.V_tas_kn'"
+        );
+    }
+
+    #[test]
+    fn invalid_mapping_is_an_error() {
+        assert_eq!(
+            ColumnMapping::parse("\nAIRSPEED INDICATED = ap_sm.data.V_ias_kn *")
+                .unwrap_err()
+                .to_string(),
+            "line 2: 'AIRSPEED INDICATED = ap_sm.data.V_ias_kn *' doesn't specify the factor"
+        );
+    }
+
+    #[test]
+    fn default_mapping_maps_the_variables_read_by_the_update_context() {
+        let mapping = ColumnMapping::default();
+        let variables: Vec<_> = mapping.variables().collect();
+
+        assert!(variables.contains(&"AIRSPEED INDICATED"));
+        assert!(variables.contains(&"PLANE PITCH DEGREES"));
+        assert!(variables.contains(&"SIM ON GROUND"));
+    }
+}
@@ -0,0 +1,292 @@
+//! Runs the A320 aircraft system simulation from a scenario file, without the simulator.
+//!
+//! ```text
+//! a320_systems_cli <scenario> [--rate <ticks per second>] [--seed <seed>] [--output <variable>]... [--events]
+//!     [--csv <path>] [--telemetry <path>] [--profile]
+//! a320_systems_cli --bridge <address> [--rate <ticks per second>] [--seed <seed>]
+//! a320_systems_cli --replay <flight data> [--mapping <path>] [--initialise <state>] [--interval <time>]
+//!     [--seed <seed>] [--output <variable>]... [--csv <path>] [--telemetry <path>]
+//! ```
+//!
+//! See the [`scenario`] module for the format of the scenario file. The output variables
+//! are sampled every interval and can be exported as CSV or as compressed columnar telemetry.
+//! With `--profile`, the time spent updating each system is reported after the run.
+//!
+//! With `--bridge`, the simulation is instead driven by clients sending commands over UDP
+//! to the given address, e.g. `127.0.0.1:5760`. See the [`bridge`] module for the commands.
+//!
+//! With `--replay`, a flight captured by the flight data recorder and converted to CSV by
+//! `fdr2csv` is replayed through the simulation. The columns are mapped to variables as
+//! described in the [`flight_data`] module, unless a mapping is given. The variables read
+//! by the aircraft which the mapping doesn't provide are listed before the replay.
+use bridge::Bridge;
+use flight_data::{ColumnMapping, FlightData};
+use replay::FlightDataReplay;
+use runner::ScenarioRunner;
+use scenario::{parse_starting_state, parse_time, Scenario};
+use std::{
+    env,
+    error::Error,
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    process,
+    time::Duration,
+};
+use systems::simulation::{RandomNumberGenerator, StartingState, Telemetry};
+
+mod bridge;
+mod flight_data;
+mod replay;
+mod runner;
+mod scenario;
+
+const USAGE: &str = "Usage: a320_systems_cli <scenario> [--rate <ticks per second>] [--seed <seed>] [--output <variable>]... [--events] [--csv <path>] [--telemetry <path>] [--profile]
+       a320_systems_cli --bridge <address> [--rate <ticks per second>] [--seed <seed>]
+       a320_systems_cli --replay <flight data> [--mapping <path>] [--initialise <state>] [--interval <time>] [--seed <seed>] [--output <variable>]... [--csv <path>] [--telemetry <path>]";
+const DEFAULT_TICKS_PER_SECOND: u32 = 20;
+
+struct Arguments {
+    scenario_path: Option<String>,
+    bridge_address: Option<String>,
+    replay_path: Option<String>,
+    mapping_path: Option<String>,
+    starting_state: Option<StartingState>,
+    interval: Option<Duration>,
+    ticks_per_second: u32,
+    seed: Option<u64>,
+    outputs: Vec<String>,
+    emit_events: bool,
+    csv_path: Option<String>,
+    telemetry_path: Option<String>,
+    profile: bool,
+}
+impl Arguments {
+    fn parse(mut arguments: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut scenario_path = None;
+        let mut bridge_address = None;
+        let mut replay_path = None;
+        let mut mapping_path = None;
+        let mut starting_state = None;
+        let mut interval = None;
+        let mut ticks_per_second = DEFAULT_TICKS_PER_SECOND;
+        let mut seed = None;
+        let mut outputs = vec![];
+        let mut emit_events = false;
+        let mut csv_path = None;
+        let mut telemetry_path = None;
+        let mut profile = false;
+
+        while let Some(argument) = arguments.next() {
+            let mut value_of = |option: &str| {
+                arguments
+                    .next()
+                    .ok_or_else(|| format!("{} requires a value", option))
+            };
+
+            match argument.as_str() {
+                "--rate" => {
+                    ticks_per_second = match value_of("--rate")?.parse() {
+                        Ok(rate) if rate > 0 => rate,
+                        _ => return Err("--rate requires a positive whole number".to_owned()),
+                    }
+                }
+                "--seed" => {
+                    seed = Some(
+                        value_of("--seed")?
+                            .parse()
+                            .map_err(|_| "--seed requires a whole number".to_owned())?,
+                    )
+                }
+                "--output" => outputs.push(value_of("--output")?),
+                "--events" => emit_events = true,
+                "--csv" => csv_path = Some(value_of("--csv")?),
+                "--telemetry" => telemetry_path = Some(value_of("--telemetry")?),
+                "--profile" => profile = true,
+                "--bridge" => bridge_address = Some(value_of("--bridge")?),
+                "--replay" => replay_path = Some(value_of("--replay")?),
+                "--mapping" => mapping_path = Some(value_of("--mapping")?),
+                "--initialise" => {
+                    starting_state = Some(parse_starting_state(&value_of("--initialise")?)?)
+                }
+                "--interval" => interval = Some(parse_time(&value_of("--interval")?)?),
+                _ if scenario_path.is_none() && !argument.starts_with("--") => {
+                    scenario_path = Some(argument)
+                }
+                _ => return Err(format!("unexpected argument '{}'", argument)),
+            }
+        }
+
+        match (&scenario_path, &bridge_address, &replay_path) {
+            (None, None, None) => return Err("no scenario given".to_owned()),
+            (Some(_), Some(_), _) => {
+                return Err("a scenario cannot be combined with --bridge".to_owned())
+            }
+            (Some(_), _, Some(_)) | (_, Some(_), Some(_)) => {
+                return Err("--replay cannot be combined with a scenario or --bridge".to_owned())
+            }
+            _ => {}
+        }
+
+        if replay_path.is_none()
+            && (mapping_path.is_some() || starting_state.is_some() || interval.is_some())
+        {
+            return Err("--mapping, --initialise and --interval require --replay".to_owned());
+        }
+        if interval == Some(Duration::from_secs(0)) {
+            return Err("--interval must be greater than zero".to_owned());
+        }
+
+        Ok(Self {
+            scenario_path,
+            bridge_address,
+            replay_path,
+            mapping_path,
+            starting_state,
+            interval,
+            ticks_per_second,
+            seed,
+            outputs,
+            emit_events,
+            csv_path,
+            telemetry_path,
+            profile,
+        })
+    }
+}
+
+fn main() {
+    let arguments = match Arguments::parse(env::args().skip(1)) {
+        Ok(arguments) => arguments,
+        Err(message) => {
+            eprintln!("{}\n{}", message, USAGE);
+            process::exit(2);
+        }
+    };
+
+    if let Err(error) = run(arguments) {
+        eprintln!("{}", error);
+        process::exit(1);
+    }
+}
+
+fn run(arguments: Arguments) -> Result<(), Box<dyn Error>> {
+    match (
+        &arguments.scenario_path,
+        &arguments.bridge_address,
+        &arguments.replay_path,
+    ) {
+        (Some(scenario_path), _, _) => run_scenario(scenario_path, &arguments),
+        (None, Some(bridge_address), _) => run_bridge(bridge_address, &arguments),
+        (None, None, Some(replay_path)) => run_replay(replay_path, &arguments),
+        (None, None, None) => Ok(()),
+    }
+}
+
+/// A run can be reproduced by providing the same seed.
+fn random_number_generator(seed: Option<u64>) -> RandomNumberGenerator {
+    match seed {
+        Some(seed) => RandomNumberGenerator::from_seed(seed),
+        None => {
+            let random = RandomNumberGenerator::from_entropy();
+            eprintln!("Using random seed {}.", random.seed());
+            random
+        }
+    }
+}
+
+fn run_bridge(bridge_address: &str, arguments: &Arguments) -> Result<(), Box<dyn Error>> {
+    let mut bridge = Bridge::bind(
+        bridge_address,
+        Duration::from_secs(1) / arguments.ticks_per_second,
+        random_number_generator(arguments.seed),
+    )
+    .map_err(|error| format!("cannot bind to '{}': {}", bridge_address, error))?;
+    eprintln!("Listening on {}.", bridge.local_address()?);
+
+    bridge.serve()?;
+
+    Ok(())
+}
+
+fn run_scenario(scenario_path: &str, arguments: &Arguments) -> Result<(), Box<dyn Error>> {
+    let text = fs::read_to_string(scenario_path)
+        .map_err(|error| format!("cannot read '{}': {}", scenario_path, error))?;
+    let mut scenario =
+        Scenario::parse(&text).map_err(|error| format!("{}: {}", scenario_path, error))?;
+    for output in &arguments.outputs {
+        scenario.add_output(output);
+    }
+
+    let random = random_number_generator(arguments.seed.or_else(|| scenario.seed()));
+
+    let mut runner = ScenarioRunner::new(
+        &scenario,
+        Duration::from_secs(1) / arguments.ticks_per_second,
+    );
+    runner.emit_events(arguments.emit_events);
+    runner.profile(arguments.profile);
+
+    let stdout = io::stdout();
+    let telemetry = runner.run(random, &mut stdout.lock())?;
+
+    write_telemetry(&telemetry, arguments)
+}
+
+fn run_replay(replay_path: &str, arguments: &Arguments) -> Result<(), Box<dyn Error>> {
+    let text = fs::read_to_string(replay_path)
+        .map_err(|error| format!("cannot read '{}': {}", replay_path, error))?;
+    let flight_data =
+        FlightData::parse(&text).map_err(|error| format!("{}: {}", replay_path, error))?;
+    let mapping = match &arguments.mapping_path {
+        Some(path) => {
+            let text = fs::read_to_string(path)
+                .map_err(|error| format!("cannot read '{}': {}", path, error))?;
+            ColumnMapping::parse(&text).map_err(|error| format!("{}: {}", path, error))?
+        }
+        None => ColumnMapping::default(),
+    };
+
+    let mut replay = FlightDataReplay::new(&flight_data, &mapping);
+    if let Some(starting_state) = arguments.starting_state {
+        replay.initialise(starting_state);
+    }
+    if let Some(interval) = arguments.interval {
+        replay.set_interval(interval);
+    }
+    for output in &arguments.outputs {
+        replay.add_output(output);
+    }
+
+    let unmapped_variables = replay.unmapped_variables();
+    if !unmapped_variables.is_empty() {
+        eprintln!(
+            "The aircraft reads 0 for the variables not provided by the flight data: {}.",
+            unmapped_variables.join(", ")
+        );
+    }
+
+    let stdout = io::stdout();
+    let telemetry = replay
+        .run(random_number_generator(arguments.seed), &mut stdout.lock())
+        .map_err(|error| format!("{}: {}", replay_path, error))?;
+
+    write_telemetry(&telemetry, arguments)
+}
+
+fn write_telemetry(telemetry: &Telemetry, arguments: &Arguments) -> Result<(), Box<dyn Error>> {
+    if let Some(path) = &arguments.csv_path {
+        let mut file = BufWriter::new(
+            File::create(path).map_err(|error| format!("cannot create '{}': {}", path, error))?,
+        );
+        telemetry.write_csv(&mut file)?;
+        file.flush()?;
+    }
+
+    if let Some(path) = &arguments.telemetry_path {
+        let file =
+            File::create(path).map_err(|error| format!("cannot create '{}': {}", path, error))?;
+        telemetry.write_columnar(BufWriter::new(file))?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,376 @@
+use crate::{
+    flight_data::{ColumnMapping, FlightData, FlightDataError},
+    runner::{MemoryReaderWriter, MAXIMUM_STEP_SIZE},
+    scenario::format_time,
+};
+use a320_systems::A320;
+use std::{error::Error, io::Write, time::Duration};
+use systems::simulation::{
+    RandomNumberGenerator, Simulation, SimulatorReaderWriter, StartingState, Telemetry,
+    TelemetryReaderWriter, VariableAudit, VariableRegistry,
+};
+
+/// Replays [`FlightData`] through the A320, writing the output variables to the given writer.
+///
+/// Before every recorded frame is simulated, the mapped variables are set to their recorded
+/// values. The aircraft is then updated by the time passed since the previous frame, such
+/// that the outputs can be compared against what was shown in the simulator at that time.
+/// Frames recorded while the simulation was paused don't advance the time.
+///
+/// Every emitted line has the form `t=10s APU_N=12.5 ...`, where the time is relative to
+/// the first frame. When no outputs are chosen, every variable written by the aircraft is
+/// emitted. The outputs are also sampled as [`Telemetry`] at the chosen interval.
+///
+/// [`FlightData`]: ../flight_data/struct.FlightData.html
+pub struct FlightDataReplay<'a> {
+    flight_data: &'a FlightData,
+    mapping: &'a ColumnMapping,
+    starting_state: Option<StartingState>,
+    outputs: Vec<String>,
+    interval: Duration,
+    maximum_step_size: Duration,
+}
+impl<'a> FlightDataReplay<'a> {
+    pub fn new(flight_data: &'a FlightData, mapping: &'a ColumnMapping) -> Self {
+        Self {
+            flight_data,
+            mapping,
+            starting_state: None,
+            outputs: vec![],
+            interval: Duration::from_secs(1),
+            maximum_step_size: MAXIMUM_STEP_SIZE,
+        }
+    }
+
+    /// Initialises the aircraft to the given state. When no state is given, the aircraft
+    /// starts in cruise when the first frame is recorded in flight, and at the gate with
+    /// the APU running otherwise.
+    pub fn initialise(&mut self, starting_state: StartingState) {
+        self.starting_state = Some(starting_state);
+    }
+
+    pub fn add_output(&mut self, name: &str) {
+        if !self.outputs.iter().any(|output| output == name) {
+            self.outputs.push(name.to_owned());
+        }
+    }
+
+    pub fn set_interval(&mut self, interval: Duration) {
+        self.interval = interval;
+    }
+
+    /// The variables read by the aircraft which aren't mapped to a column of the flight
+    /// data, nor held at the starting state's value. The aircraft reads 0.0 for these
+    /// variables during the replay.
+    pub fn unmapped_variables(&self) -> Vec<String> {
+        let held_variables = held_variables(StartingState::ColdAndDark);
+        VariableAudit::new(&registry(&mut A320::new()))
+            .with_simulator_variables(
+                self.mapping
+                    .variables()
+                    .chain(held_variables.iter().map(|(name, _)| *name)),
+            )
+            .report()
+            .read_but_never_written()
+            .iter()
+            .map(|variable| variable.name().to_owned())
+            .collect()
+    }
+
+    pub fn run<W: Write>(
+        &self,
+        random: RandomNumberGenerator,
+        output: &mut W,
+    ) -> Result<Telemetry, Box<dyn Error>> {
+        let mut aircraft = A320::new();
+        let outputs: Vec<String> = if self.outputs.is_empty() {
+            registry(&mut aircraft)
+                .iter()
+                .filter(|definition| definition.direction().is_written())
+                .map(|definition| definition.name().to_owned())
+                .collect()
+        } else {
+            self.outputs.clone()
+        };
+        let output_names: Vec<&str> = outputs.iter().map(|name| name.as_str()).collect();
+
+        let mut reader_writer = TelemetryReaderWriter::new_with_sampling_interval(
+            MemoryReaderWriter::new(),
+            &output_names,
+            self.interval,
+        );
+
+        let frames = self.flight_data.frames();
+        let first_frame = match frames.first() {
+            Some(frame) => frame,
+            None => return Ok(reader_writer.into_telemetry()),
+        };
+
+        self.write_frame(first_frame, &mut reader_writer)?;
+        let starting_state = self
+            .starting_state
+            .unwrap_or_else(|| inferred_starting_state(&mut reader_writer));
+        for (name, value) in held_variables(starting_state) {
+            if !self.mapping.variables().any(|variable| variable == name) {
+                reader_writer.write(name, value);
+            }
+        }
+
+        let mut simulation =
+            Simulation::new_with_random_number_generator(&mut aircraft, &mut reader_writer, random);
+        simulation.set_maximum_step_size(self.maximum_step_size);
+        simulation.initialise(starting_state);
+
+        let mut previous_time = self.flight_data.time_of(first_frame);
+        for frame in &frames[1..] {
+            self.write_frame(frame, simulation.simulator_reader_writer())?;
+
+            let time = self.flight_data.time_of(frame);
+            if time <= previous_time {
+                continue;
+            }
+
+            simulation.tick(time - previous_time);
+            previous_time = time;
+        }
+
+        let telemetry = reader_writer.into_telemetry();
+        write_samples(output, &telemetry)?;
+
+        Ok(telemetry)
+    }
+
+    fn write_frame<T: SimulatorReaderWriter>(
+        &self,
+        frame: &[f64],
+        reader_writer: &mut T,
+    ) -> Result<(), FlightDataError> {
+        for (name, value) in self.mapping.values(self.flight_data, frame)? {
+            reader_writer.write(name, value);
+        }
+
+        Ok(())
+    }
+}
+
+fn registry(aircraft: &mut A320) -> VariableRegistry {
+    Simulation::new(aircraft, &mut MemoryReaderWriter::new())
+        .register_variables()
+        .expect("The A320 reads and writes its variables without conflicts.")
+}
+
+fn inferred_starting_state<T: SimulatorReaderWriter>(reader_writer: &mut T) -> StartingState {
+    if reader_writer.read("SIM ON GROUND") > 0. {
+        StartingState::GateWithApu
+    } else {
+        StartingState::Cruise
+    }
+}
+
+/// The variables which the flight data recorder doesn't capture, while the aircraft's systems
+/// only agree with the starting state when they are set. Unless a column is mapped to them,
+/// they are held at the starting state's value throughout the replay.
+fn held_variables(starting_state: StartingState) -> [(&'static str, f64); 3] {
+    let n2 = if starting_state.engines_are_running() {
+        80.
+    } else {
+        0.
+    };
+    let gear_position = if starting_state.is_in_flight() {
+        0.
+    } else {
+        100.
+    };
+
+    [
+        ("TURB ENG CORRECTED N2:1", n2),
+        ("TURB ENG CORRECTED N2:2", n2),
+        ("GEAR CENTER POSITION", gear_position),
+    ]
+}
+
+fn write_samples<W: Write>(output: &mut W, telemetry: &Telemetry) -> std::io::Result<()> {
+    for (index, time) in telemetry.times().iter().enumerate() {
+        write!(output, "t={}", format_time(*time))?;
+        for name in telemetry.names() {
+            if let Some(column) = telemetry.column(name) {
+                write!(output, " {}={}", name, column[index])?;
+            }
+        }
+
+        writeln!(output)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod flight_data_replay_tests {
+    use super::*;
+
+    fn flight_data(frames: &[(f64, f64)]) -> FlightData {
+        recorded_flight_data(frames, true)
+    }
+
+    fn flight_data_in_flight(frames: &[(f64, f64)]) -> FlightData {
+        recorded_flight_data(frames, false)
+    }
+
+    fn recorded_flight_data(frames: &[(f64, f64)], on_ground: bool) -> FlightData {
+        let mut text =
+            "fbw.sim.time.simulation_time,ap_sm.data.V_ias_kn,ap_sm.data.on_ground,engine.fuelTankQuantityMainLeft,\n"
+                .to_owned();
+        for (time, airspeed) in frames {
+            text.push_str(&format!(
+                "{},{},{},5000,\n",
+                time, airspeed, on_ground as u8
+            ));
+        }
+
+        FlightData::parse(&text).unwrap()
+    }
+
+    fn mapping() -> ColumnMapping {
+        ColumnMapping::parse(
+            "
+            AIRSPEED INDICATED = ap_sm.data.V_ias_kn
+            SIM ON GROUND = ap_sm.data.on_ground
+            FUEL TANK LEFT MAIN QUANTITY = engine.fuelTankQuantityMainLeft
+            ",
+        )
+        .unwrap()
+    }
+
+    fn run(replay: &FlightDataReplay) -> (Vec<String>, Telemetry) {
+        let mut output = vec![];
+        let telemetry = replay
+            .run(RandomNumberGenerator::from_seed(1), &mut output)
+            .unwrap();
+
+        (
+            String::from_utf8(output)
+                .unwrap()
+                .lines()
+                .map(|line| line.to_owned())
+                .collect(),
+            telemetry,
+        )
+    }
+
+    #[test]
+    fn replays_the_recorded_values_at_the_recorded_times() {
+        let flight_data = flight_data(&[(100., 0.), (100.5, 10.), (101., 20.), (102., 30.)]);
+        let mapping = mapping();
+        let mut replay = FlightDataReplay::new(&flight_data, &mapping);
+        replay.add_output("AIRSPEED INDICATED");
+
+        let (lines, _) = run(&replay);
+
+        assert_eq!(
+            lines,
+            vec!["t=1s AIRSPEED INDICATED=20", "t=2s AIRSPEED INDICATED=30"]
+        );
+    }
+
+    #[test]
+    fn paused_frames_do_not_advance_the_time() {
+        let flight_data = flight_data(&[(0., 0.), (1., 10.), (1., 15.), (1., 20.), (2., 30.)]);
+        let mapping = mapping();
+        let mut replay = FlightDataReplay::new(&flight_data, &mapping);
+        replay.add_output("AIRSPEED INDICATED");
+
+        let (_, telemetry) = run(&replay);
+
+        assert_eq!(
+            telemetry.times(),
+            &[Duration::from_secs(1), Duration::from_secs(2)]
+        );
+    }
+
+    #[test]
+    fn emits_the_variables_written_by_the_aircraft_by_default() {
+        let flight_data = flight_data(&[(0., 0.), (1., 0.)]);
+        let mapping = mapping();
+        let replay = FlightDataReplay::new(&flight_data, &mapping);
+
+        let (_, telemetry) = run(&replay);
+
+        assert!(telemetry.column("APU_N").is_some());
+        assert!(telemetry.column("ELEC_AC_1_BUS_IS_POWERED").is_some());
+    }
+
+    #[test]
+    fn starts_at_the_gate_with_the_apu_running_when_recorded_on_the_ground() {
+        let flight_data = flight_data(&[(0., 0.), (1., 0.)]);
+        let mapping = mapping();
+        let mut replay = FlightDataReplay::new(&flight_data, &mapping);
+        replay.add_output("OVHD_APU_START_PB_IS_AVAILABLE");
+
+        let (lines, _) = run(&replay);
+
+        assert_eq!(lines, vec!["t=1s OVHD_APU_START_PB_IS_AVAILABLE=1"]);
+    }
+
+    #[test]
+    fn powers_the_ac_buses_from_the_engine_generators_when_recorded_in_flight() {
+        let flight_data = flight_data_in_flight(&[(0., 250.), (5., 250.), (10., 250.)]);
+        let mapping = mapping();
+        let mut replay = FlightDataReplay::new(&flight_data, &mapping);
+        replay.add_output("ELEC_AC_1_BUS_IS_POWERED");
+        replay.add_output("ELEC_AC_2_BUS_IS_POWERED");
+        replay.set_interval(Duration::from_secs(5));
+
+        let (lines, _) = run(&replay);
+
+        assert_eq!(
+            lines,
+            vec![
+                "t=5s ELEC_AC_1_BUS_IS_POWERED=1 ELEC_AC_2_BUS_IS_POWERED=1",
+                "t=10s ELEC_AC_1_BUS_IS_POWERED=1 ELEC_AC_2_BUS_IS_POWERED=1"
+            ]
+        );
+    }
+
+    #[test]
+    fn holds_the_variables_which_are_not_recorded_at_the_starting_state_value() {
+        let flight_data = flight_data_in_flight(&[(0., 250.), (1., 250.)]);
+        let mapping = mapping();
+        let mut replay = FlightDataReplay::new(&flight_data, &mapping);
+        replay.add_output("TURB ENG CORRECTED N2:1");
+        replay.add_output("GEAR CENTER POSITION");
+
+        let (lines, _) = run(&replay);
+
+        assert_eq!(
+            lines,
+            vec!["t=1s TURB ENG CORRECTED N2:1=80 GEAR CENTER POSITION=0"]
+        );
+    }
+
+    #[test]
+    fn lists_the_variables_read_by_the_aircraft_which_are_not_mapped() {
+        let flight_data = flight_data(&[]);
+        let default_mapping = ColumnMapping::default();
+        let mapping = mapping();
+
+        let unmapped = FlightDataReplay::new(&flight_data, &mapping).unmapped_variables();
+        assert!(unmapped.contains(&"PLANE PITCH DEGREES".to_owned()));
+        assert!(!unmapped.contains(&"AIRSPEED INDICATED".to_owned()));
+
+        let unmapped = FlightDataReplay::new(&flight_data, &default_mapping).unmapped_variables();
+        assert!(!unmapped.contains(&"PLANE PITCH DEGREES".to_owned()));
+        assert!(!unmapped.contains(&"TURB ENG CORRECTED N2:1".to_owned()));
+        assert!(!unmapped.contains(&"GEAR CENTER POSITION".to_owned()));
+    }
+
+    #[test]
+    fn replaying_with_a_column_missing_from_the_flight_data_is_an_error() {
+        let flight_data = flight_data(&[(0., 0.)]);
+        let mapping = ColumnMapping::parse("AIRSPEED TRUE = ap_sm.data.V_tas_kn").unwrap();
+        let replay = FlightDataReplay::new(&flight_data, &mapping);
+
+        assert!(replay
+            .run(RandomNumberGenerator::from_seed(1), &mut vec![])
+            .is_err());
+    }
+}
@@ -0,0 +1,365 @@
+use crate::scenario::{format_time, Input, Scenario};
+use a320_systems::A320;
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+    time::Duration,
+};
+use systems::simulation::{
+    KeyEvent, RandomNumberGenerator, Simulation, SimulationEvent, SimulatorReaderWriter, Telemetry,
+    TelemetryReaderWriter,
+};
+
+/// The maximum step size used within the simulator.
+pub const MAXIMUM_STEP_SIZE: Duration = Duration::from_millis(100);
+
+/// Runs a [`Scenario`] on the A320, writing the output variables to the given writer.
+///
+/// Every emitted line has the form `t=10s APU_N=12.5 ...`, such that it can be
+/// copied into a scenario. When events are requested, each event is emitted on
+/// a separate line of the form `t=10s event: contactor 3XS closed`.
+///
+/// Durations and intervals which aren't a whole number of ticks are rounded up, thus
+/// the outputs are emitted after the tick which reaches or passes the next interval.
+/// The output variables are also sampled as [`Telemetry`] at the scenario's interval.
+/// When profiling, the time spent updating each system is written to the standard
+/// error stream after the run.
+///
+/// [`Scenario`]: ../scenario/struct.Scenario.html
+pub struct ScenarioRunner<'a> {
+    scenario: &'a Scenario,
+    delta: Duration,
+    maximum_step_size: Duration,
+    emit_events: bool,
+    profile: bool,
+}
+impl<'a> ScenarioRunner<'a> {
+    pub fn new(scenario: &'a Scenario, delta: Duration) -> Self {
+        assert!(
+            delta > Duration::from_secs(0),
+            "The tick duration must be greater than zero."
+        );
+
+        Self {
+            scenario,
+            delta,
+            maximum_step_size: MAXIMUM_STEP_SIZE,
+            emit_events: false,
+            profile: false,
+        }
+    }
+
+    pub fn emit_events(&mut self, emit_events: bool) {
+        self.emit_events = emit_events;
+    }
+
+    pub fn profile(&mut self, profile: bool) {
+        self.profile = profile;
+    }
+
+    pub fn run<W: Write>(
+        &self,
+        random: RandomNumberGenerator,
+        output: &mut W,
+    ) -> io::Result<Telemetry> {
+        let mut aircraft = A320::new();
+        if self.profile {
+            aircraft.enable_profiling();
+        }
+
+        let outputs: Vec<&str> = self
+            .scenario
+            .outputs()
+            .iter()
+            .map(|name| name.as_str())
+            .collect();
+        let mut reader_writer = TelemetryReaderWriter::new_with_sampling_interval(
+            MemoryReaderWriter::new(),
+            &outputs,
+            self.scenario.interval(),
+        );
+        for (name, value) in self.scenario.initial_values() {
+            reader_writer.write(name, *value);
+        }
+
+        let mut simulation =
+            Simulation::new_with_random_number_generator(&mut aircraft, &mut reader_writer, random);
+        simulation.set_maximum_step_size(self.maximum_step_size);
+        if let Some(starting_state) = self.scenario.starting_state() {
+            simulation.initialise(starting_state);
+        }
+
+        let total_ticks = self.ticks_per(self.scenario.duration());
+        let mut pending_inputs: Vec<&Input> = self.scenario.inputs().iter().collect();
+        let mut now = Duration::from_secs(0);
+        let mut next_output_time = self.scenario.interval();
+
+        for _ in 0..total_ticks {
+            let inputs = simulation.simulator_reader_writer();
+            pending_inputs.retain(|input| {
+                if let Some(value) = input.value_at(now) {
+                    inputs.write(input.name(), value);
+                }
+
+                !input.is_finished_at(now)
+            });
+
+            simulation.tick(self.delta);
+            now += self.delta;
+
+            if self.emit_events {
+                self.write_events(output, now, simulation.events())?;
+            }
+
+            if now >= next_output_time {
+                self.write_outputs(output, now, simulation.simulator_reader_writer())?;
+                while next_output_time <= now {
+                    next_output_time += self.scenario.interval();
+                }
+            }
+        }
+
+        if self.profile {
+            eprint!("{}", aircraft.profiler());
+        }
+
+        Ok(reader_writer.into_telemetry())
+    }
+
+    /// The number of ticks needed to simulate at least the given duration.
+    fn ticks_per(&self, duration: Duration) -> u32 {
+        let (duration, delta) = (duration.as_nanos(), self.delta.as_nanos());
+        let whole_ticks = duration / delta;
+        if duration % delta == 0 {
+            whole_ticks as u32
+        } else {
+            whole_ticks as u32 + 1
+        }
+    }
+
+    fn write_events<W: Write>(
+        &self,
+        output: &mut W,
+        now: Duration,
+        events: &[SimulationEvent],
+    ) -> io::Result<()> {
+        for event in events {
+            writeln!(output, "t={} event: {}", format_time(now), event)?;
+        }
+
+        Ok(())
+    }
+
+    fn write_outputs<W: Write, T: SimulatorReaderWriter>(
+        &self,
+        output: &mut W,
+        now: Duration,
+        reader_writer: &mut T,
+    ) -> io::Result<()> {
+        write!(output, "t={}", format_time(now))?;
+        for name in self.scenario.outputs() {
+            write!(output, " {}={}", name, reader_writer.read(name))?;
+        }
+
+        writeln!(output)
+    }
+}
+
+/// Stores the variables in memory in place of the simulator.
+/// Variables which were never written are read as `0.`, as is the case in the simulator.
+pub struct MemoryReaderWriter {
+    variables: HashMap<String, f64>,
+    key_events: Vec<KeyEvent>,
+}
+impl MemoryReaderWriter {
+    pub fn new() -> Self {
+        Self {
+            variables: HashMap::new(),
+            key_events: vec![],
+        }
+    }
+
+    /// Queues a key event, which is handed to the aircraft during the next tick.
+    pub fn trigger_key_event(&mut self, key_event: KeyEvent) {
+        self.key_events.push(key_event);
+    }
+}
+impl SimulatorReaderWriter for MemoryReaderWriter {
+    fn read(&mut self, name: &str) -> f64 {
+        *self.variables.get(name).unwrap_or(&0.)
+    }
+
+    fn write(&mut self, name: &str, value: f64) {
+        self.variables.insert(name.to_owned(), value);
+    }
+
+    fn read_key_events(&mut self) -> Vec<KeyEvent> {
+        self.key_events.drain(..).collect()
+    }
+}
+
+#[cfg(test)]
+mod scenario_runner_tests {
+    use super::*;
+
+    fn run_with_telemetry(scenario: &str, emit_events: bool) -> (Vec<String>, Telemetry) {
+        let scenario = Scenario::parse(scenario).unwrap();
+        let mut runner = ScenarioRunner::new(&scenario, Duration::from_millis(50));
+        runner.emit_events(emit_events);
+
+        let mut output = vec![];
+        let telemetry = runner
+            .run(RandomNumberGenerator::from_seed(1), &mut output)
+            .unwrap();
+
+        (
+            String::from_utf8(output)
+                .unwrap()
+                .lines()
+                .map(|line| line.to_owned())
+                .collect(),
+            telemetry,
+        )
+    }
+
+    fn run(scenario: &str, emit_events: bool) -> Vec<String> {
+        run_with_telemetry(scenario, emit_events).0
+    }
+
+    #[test]
+    fn emits_the_outputs_every_interval() {
+        let lines = run(
+            "
+            duration 2s
+            interval 500ms
+            output SIM ON GROUND
+            SIM ON GROUND=1
+            ",
+            false,
+        );
+
+        assert_eq!(
+            lines,
+            vec![
+                "t=0.5s SIM ON GROUND=1",
+                "t=1s SIM ON GROUND=1",
+                "t=1.5s SIM ON GROUND=1",
+                "t=2s SIM ON GROUND=1"
+            ]
+        );
+    }
+
+    #[test]
+    fn rounds_a_duration_up_to_a_whole_number_of_ticks() {
+        let lines = run(
+            "
+            duration 1.01s
+            interval 1.01s
+            output SIM ON GROUND
+            ",
+            false,
+        );
+
+        assert_eq!(lines, vec!["t=1.05s SIM ON GROUND=0"]);
+    }
+
+    #[test]
+    fn emits_the_outputs_after_the_tick_which_passes_the_interval() {
+        let lines = run(
+            "
+            duration 300ms
+            interval 75ms
+            output SIM ON GROUND
+            ",
+            false,
+        );
+
+        assert_eq!(
+            lines,
+            vec![
+                "t=0.1s SIM ON GROUND=0",
+                "t=0.15s SIM ON GROUND=0",
+                "t=0.25s SIM ON GROUND=0",
+                "t=0.3s SIM ON GROUND=0"
+            ]
+        );
+    }
+
+    #[test]
+    fn samples_the_outputs_as_telemetry_every_interval() {
+        let (_, telemetry) = run_with_telemetry(
+            "
+            duration 2s
+            interval 1s
+            output AIRSPEED INDICATED
+            AIRSPEED INDICATED=50
+            t=1s AIRSPEED INDICATED=100
+            ",
+            false,
+        );
+
+        assert_eq!(
+            telemetry.times(),
+            &[Duration::from_secs(1), Duration::from_secs(2)]
+        );
+        assert_eq!(
+            telemetry.column("AIRSPEED INDICATED"),
+            Some([50., 100.].as_ref())
+        );
+    }
+
+    #[test]
+    fn applies_inputs_at_their_time() {
+        let lines = run(
+            "
+            duration 2s
+            output AIRSPEED INDICATED
+            t=1s AIRSPEED INDICATED=100
+            ",
+            false,
+        );
+
+        assert_eq!(
+            lines,
+            vec!["t=1s AIRSPEED INDICATED=0", "t=2s AIRSPEED INDICATED=100"]
+        );
+    }
+
+    #[test]
+    fn starts_the_apu() {
+        let lines = run(
+            "
+            initialise ColdAndDark
+            duration 60s
+            interval 60s
+            output OVHD_APU_START_PB_IS_AVAILABLE
+            SIM ON GROUND=1
+            UNLIMITED FUEL=1
+            t=1s OVHD_ELEC_BAT_10_PB_IS_AUTO=1
+            t=1s OVHD_ELEC_BAT_11_PB_IS_AUTO=1
+            t=2s OVHD_APU_MASTER_SW_PB_IS_ON=1
+            t=5s OVHD_APU_START_PB_IS_ON=1
+            ",
+            false,
+        );
+
+        assert_eq!(lines, vec!["t=60s OVHD_APU_START_PB_IS_AVAILABLE=1"]);
+    }
+
+    #[test]
+    fn emits_events() {
+        let lines = run(
+            "
+            duration 3s
+            interval 10s
+            SIM ON GROUND=1
+            t=1s OVHD_ELEC_BAT_10_PB_IS_AUTO=1
+            ",
+            true,
+        );
+
+        assert!(lines
+            .iter()
+            .any(|line| line.ends_with("event: contactor 6PB1 closed")));
+    }
+}
@@ -0,0 +1,427 @@
+use std::{error::Error, fmt, time::Duration};
+use systems::simulation::StartingState;
+
+/// A scenario describes a run of the aircraft system simulation without the simulator.
+///
+/// Scenario files are line based. Empty lines and everything following a `#` is ignored.
+/// Lines contain one of the following:
+///
+/// ```text
+/// initialise GateWithApu          # Initialises the aircraft to a starting state.
+/// duration 120s                   # The amount of simulated time.
+/// interval 500ms                  # How often the output variables are emitted.
+/// seed 42                         # Seeds the random number generator.
+/// output APU_N                    # A variable to emit.
+/// SIM ON GROUND=1                 # Sets a variable before the aircraft is initialised.
+/// t=10s OVHD_APU_START_PB_IS_ON=1 # Sets a variable at the given time.
+/// t=0s..60s AIRSPEED INDICATED=0..150 # Linearly changes a variable over the given period.
+/// ```
+///
+/// Variable names are those used within the aircraft system simulation, thus without the
+/// `A32NX_` prefix. Variable names may contain spaces, as is the case for aircraft variables.
+#[derive(Debug, PartialEq)]
+pub struct Scenario {
+    starting_state: Option<StartingState>,
+    duration: Duration,
+    interval: Duration,
+    seed: Option<u64>,
+    outputs: Vec<String>,
+    initial_values: Vec<(String, f64)>,
+    inputs: Vec<Input>,
+}
+impl Scenario {
+    const DEFAULT_INTERVAL_SECS: u64 = 1;
+
+    pub fn parse(text: &str) -> Result<Self, ScenarioError> {
+        let mut starting_state = None;
+        let mut duration = None;
+        let mut interval = Duration::from_secs(Scenario::DEFAULT_INTERVAL_SECS);
+        let mut seed = None;
+        let mut outputs = vec![];
+        let mut initial_values = vec![];
+        let mut inputs = vec![];
+
+        for (index, line) in text.lines().enumerate() {
+            let line_number = index + 1;
+            let error = |message: String| ScenarioError::new(line_number, message);
+
+            let line = match line.find('#') {
+                Some(comment_start) => &line[..comment_start],
+                None => line,
+            }
+            .trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some(input) = line.strip_prefix("t=") {
+                inputs.push(Input::parse(input).map_err(error)?);
+            } else if line.contains('=') {
+                initial_values.push(parse_assignment(line).map_err(error)?);
+            } else {
+                let (keyword, argument) = split_keyword(line);
+                match keyword {
+                    "initialise" => {
+                        starting_state = Some(parse_starting_state(argument).map_err(error)?)
+                    }
+                    "duration" => duration = Some(parse_time(argument).map_err(error)?),
+                    "interval" => interval = parse_time(argument).map_err(error)?,
+                    "seed" => {
+                        seed =
+                            Some(argument.parse().map_err(|_| {
+                                error(format!("'{}' is not a valid seed", argument))
+                            })?)
+                    }
+                    "output" if !argument.is_empty() => outputs.push(argument.to_owned()),
+                    _ => return Err(error(format!("unknown instruction '{}'", line))),
+                }
+            }
+        }
+
+        let duration = duration.ok_or_else(|| {
+            ScenarioError::new(0, "the scenario doesn't specify a duration".to_owned())
+        })?;
+        if interval == Duration::from_secs(0) {
+            return Err(ScenarioError::new(
+                0,
+                "the interval must be greater than zero".to_owned(),
+            ));
+        }
+
+        Ok(Self {
+            starting_state,
+            duration,
+            interval,
+            seed,
+            outputs,
+            initial_values,
+            inputs,
+        })
+    }
+
+    pub fn starting_state(&self) -> Option<StartingState> {
+        self.starting_state
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    pub fn outputs(&self) -> &[String] {
+        &self.outputs
+    }
+
+    /// Adds a variable to emit in addition to those listed in the scenario.
+    pub fn add_output(&mut self, name: &str) {
+        if !self.outputs.iter().any(|output| output == name) {
+            self.outputs.push(name.to_owned());
+        }
+    }
+
+    pub fn initial_values(&self) -> &[(String, f64)] {
+        &self.initial_values
+    }
+
+    pub fn inputs(&self) -> &[Input] {
+        &self.inputs
+    }
+}
+
+/// A change to a variable made at a given time during the scenario.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Input {
+    Set {
+        time: Duration,
+        name: String,
+        value: f64,
+    },
+    Ramp {
+        start: Duration,
+        end: Duration,
+        name: String,
+        from: f64,
+        to: f64,
+    },
+}
+impl Input {
+    fn parse(text: &str) -> Result<Self, String> {
+        let (time, assignment) = split_keyword(text);
+        let (name, value) = split_assignment(assignment)?;
+
+        if time.contains("..") {
+            let (start, end) = parse_range(time, parse_time)?;
+            let (from, to) = parse_range(value, parse_value)?;
+            if end <= start {
+                return Err(format!("the ramp '{}' doesn't end after it starts", time));
+            }
+
+            Ok(Input::Ramp {
+                start,
+                end,
+                name,
+                from,
+                to,
+            })
+        } else {
+            Ok(Input::Set {
+                time: parse_time(time)?,
+                name,
+                value: parse_value(value)?,
+            })
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            Input::Set { name, .. } | Input::Ramp { name, .. } => name,
+        }
+    }
+
+    /// The value of the variable at the given time, or `None` when the input hasn't started yet.
+    pub fn value_at(&self, now: Duration) -> Option<f64> {
+        match self {
+            Input::Set { time, value, .. } if now >= *time => Some(*value),
+            Input::Ramp {
+                start,
+                end,
+                from,
+                to,
+                ..
+            } if now >= *start => {
+                let progress =
+                    ((now - *start).as_secs_f64() / (*end - *start).as_secs_f64()).min(1.);
+                Some(from + (to - from) * progress)
+            }
+            _ => None,
+        }
+    }
+
+    /// Indicates if the input no longer changes the variable after the given time.
+    pub fn is_finished_at(&self, now: Duration) -> bool {
+        match self {
+            Input::Set { time, .. } => now >= *time,
+            Input::Ramp { end, .. } => now >= *end,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ScenarioError {
+    line: usize,
+    message: String,
+}
+impl ScenarioError {
+    fn new(line: usize, message: String) -> Self {
+        Self { line, message }
+    }
+}
+impl fmt::Display for ScenarioError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.line > 0 {
+            write!(f, "line {}: {}", self.line, self.message)
+        } else {
+            write!(f, "{}", self.message)
+        }
+    }
+}
+impl Error for ScenarioError {}
+
+pub fn split_keyword(line: &str) -> (&str, &str) {
+    match line.find(char::is_whitespace) {
+        Some(index) => (&line[..index], line[index..].trim()),
+        None => (line, ""),
+    }
+}
+
+pub fn split_assignment(text: &str) -> Result<(String, &str), String> {
+    match text.rfind('=') {
+        Some(index) if !text[..index].trim().is_empty() => {
+            Ok((text[..index].trim().to_owned(), text[index + 1..].trim()))
+        }
+        _ => Err(format!(
+            "'{}' is not an assignment of the form NAME=VALUE",
+            text
+        )),
+    }
+}
+
+pub fn parse_assignment(text: &str) -> Result<(String, f64), String> {
+    let (name, value) = split_assignment(text)?;
+    Ok((name, parse_value(value)?))
+}
+
+fn parse_range<T>(text: &str, parse: fn(&str) -> Result<T, String>) -> Result<(T, T), String> {
+    let mut parts = text.splitn(2, "..");
+    match (parts.next(), parts.next()) {
+        (Some(start), Some(end)) => Ok((parse(start)?, parse(end)?)),
+        _ => Err(format!("'{}' is not a range of the form START..END", text)),
+    }
+}
+
+fn parse_value(text: &str) -> Result<f64, String> {
+    match text {
+        "true" => Ok(1.),
+        "false" => Ok(0.),
+        _ => text
+            .parse()
+            .map_err(|_| format!("'{}' is not a valid value", text)),
+    }
+}
+
+/// Parses a time such as `10s`, `1.5s` or `500ms`.
+pub fn parse_time(text: &str) -> Result<Duration, String> {
+    let error = || format!("'{}' is not a valid time, e.g. 10s or 500ms", text);
+
+    let (number, millis_per_unit) = if let Some(number) = text.strip_suffix("ms") {
+        (number, 1.)
+    } else if let Some(number) = text.strip_suffix('s') {
+        (number, 1000.)
+    } else {
+        return Err(error());
+    };
+
+    match number.parse::<f64>() {
+        Ok(number) if number >= 0. => Ok(Duration::from_millis(
+            (number * millis_per_unit).round() as u64
+        )),
+        _ => Err(error()),
+    }
+}
+
+/// Formats the time the same way it is written in a scenario.
+pub fn format_time(time: Duration) -> String {
+    format!("{}s", time.as_millis() as f64 / 1000.)
+}
+
+pub fn parse_starting_state(text: &str) -> Result<StartingState, String> {
+    match text {
+        "ColdAndDark" => Ok(StartingState::ColdAndDark),
+        "GateWithApu" => Ok(StartingState::GateWithApu),
+        "RunwayReady" => Ok(StartingState::RunwayReady),
+        "Cruise" => Ok(StartingState::Cruise),
+        _ => Err(format!(
+            "'{}' is not a starting state, use one of ColdAndDark, GateWithApu, RunwayReady or Cruise",
+            text
+        )),
+    }
+}
+
+#[cfg(test)]
+mod scenario_tests {
+    use super::*;
+
+    #[test]
+    fn parses_instructions() {
+        let scenario = Scenario::parse(
+            "
+            # The APU starts at the gate.
+            initialise ColdAndDark
+            duration 2.5s
+            interval 500ms
+            seed 42
+            output APU_N
+            ",
+        )
+        .unwrap();
+
+        assert_eq!(scenario.starting_state(), Some(StartingState::ColdAndDark));
+        assert_eq!(scenario.duration(), Duration::from_millis(2500));
+        assert_eq!(scenario.interval(), Duration::from_millis(500));
+        assert_eq!(scenario.seed(), Some(42));
+        assert_eq!(scenario.outputs(), &["APU_N".to_owned()]);
+    }
+
+    #[test]
+    fn parses_initial_values_with_spaces_in_their_name() {
+        let scenario = Scenario::parse(
+            "
+            duration 1s
+            AMBIENT TEMPERATURE=-10 # Winter.
+            SIM ON GROUND=true
+            ",
+        )
+        .unwrap();
+
+        assert_eq!(
+            scenario.initial_values(),
+            &[
+                ("AMBIENT TEMPERATURE".to_owned(), -10.),
+                ("SIM ON GROUND".to_owned(), 1.)
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_timed_inputs() {
+        let scenario = Scenario::parse(
+            "
+            duration 1s
+            t=10s OVHD_APU_MASTER_SW_PB_IS_ON=1
+            t=0s..60s AIRSPEED INDICATED=0..150
+            ",
+        )
+        .unwrap();
+
+        assert_eq!(
+            scenario.inputs(),
+            &[
+                Input::Set {
+                    time: Duration::from_secs(10),
+                    name: "OVHD_APU_MASTER_SW_PB_IS_ON".to_owned(),
+                    value: 1.
+                },
+                Input::Ramp {
+                    start: Duration::from_secs(0),
+                    end: Duration::from_secs(60),
+                    name: "AIRSPEED INDICATED".to_owned(),
+                    from: 0.,
+                    to: 150.
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn requires_a_duration() {
+        assert!(Scenario::parse("output APU_N").is_err());
+    }
+
+    #[test]
+    fn reports_the_line_of_an_error() {
+        let error =
+            Scenario::parse("duration 1s\nt=ten OVHD_APU_MASTER_SW_PB_IS_ON=1").unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "line 2: 'ten' is not a valid time, e.g. 10s or 500ms"
+        );
+    }
+
+    #[test]
+    fn ramp_interpolates_between_its_values() {
+        let ramp = Input::parse("10s..20s AIRSPEED INDICATED=100..200").unwrap();
+
+        assert_eq!(ramp.value_at(Duration::from_secs(5)), None);
+        assert_eq!(ramp.value_at(Duration::from_secs(15)), Some(150.));
+        assert_eq!(ramp.value_at(Duration::from_secs(25)), Some(200.));
+        assert!(!ramp.is_finished_at(Duration::from_secs(15)));
+        assert!(ramp.is_finished_at(Duration::from_secs(20)));
+    }
+
+    #[test]
+    fn formats_time_like_it_is_parsed() {
+        assert_eq!(format_time(Duration::from_millis(10050)), "10.05s");
+        assert_eq!(
+            parse_time(&format_time(Duration::from_millis(10050))),
+            Ok(Duration::from_millis(10050))
+        );
+    }
+}
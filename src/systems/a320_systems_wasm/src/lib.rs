@@ -2,31 +2,189 @@
 use a320_systems::A320;
 use msfs::{
     legacy::{execute_calculator_code, AircraftVariable, NamedVariable},
+    sim_connect::{SimConnect, SimConnectRecv},
     MSFSEvent,
 };
-use std::collections::HashMap;
-use systems::simulation::{Simulation, SimulatorReaderWriter};
+use num_traits::FromPrimitive;
+use std::{cell::RefCell, collections::HashMap, fs::File, io::BufWriter, rc::Rc, time::Duration};
+use systems::simulation::{
+    KeyEvent, RandomNumberGenerator, RecordingHeader, RecordingReaderWriter, Simulation,
+    SimulatorReaderWriter, StartingState, VariableRegistry, WriteOnChangeReaderWriter,
+};
+
+/// The file to which the simulator reads and writes are recorded when recording is enabled.
+const RECORDING_PATH: &str = "\\work\\systems_recording.bin";
+
+/// The named variables read by the aircraft which are written by other gauges of the A32NX.
+const EXTERNALLY_WRITTEN_NAMED_VARIABLES: &[&str] = &["FWC_FLIGHT_PHASE"];
 
 #[msfs::gauge(name=systems)]
-async fn systems(mut gauge: msfs::Gauge) -> Result<(), Box<dyn std::error::Error>> {
+async fn systems(gauge: msfs::Gauge) -> Result<(), Box<dyn std::error::Error>> {
     let mut reader_writer = A320SimulatorReaderWriter::new()?;
     let mut a320 = A320::new();
-    let mut simulation = Simulation::new(&mut a320, &mut reader_writer);
+    let registry = Simulation::new(&mut a320, &mut reader_writer)
+        .register_variables()
+        .map_err(|errors| {
+            errors
+                .iter()
+                .map(|error| error.to_string())
+                .collect::<Vec<_>>()
+                .join("\n")
+        })?;
+    reader_writer.verify_aircraft_variables(&registry)?;
+    // Key bindings and home cockpit hardware raise the key events handled by the aircraft.
+    let mut sim_connect = gauge.open_simconnect("systems")?;
+    let key_events =
+        SimConnectKeyEvents::new(&mut sim_connect, &registry, reader_writer.key_events())?;
+
+    // When no starting state is requested, the state is determined by the *.flt files.
+    let starting_state = StartingState::from_f64(reader_writer.read("SYSTEMS_STARTING_STATE"));
+    // A bug report containing the random seed can be reproduced by providing the same seed.
+    let random = match reader_writer.read("SYSTEMS_RANDOM_SEED") as u64 {
+        0 => RandomNumberGenerator::from_entropy(),
+        seed => RandomNumberGenerator::from_seed(seed),
+    };
+    reader_writer.write("SYSTEMS_RANDOM_SEED", random.seed() as f64);
+    let should_record = reader_writer.read("SYSTEMS_RECORDING_ENABLED") > 0.;
+    // Most variables don't change every tick. Skipping those writes saves setting named variables.
+    let reader_writer = WriteOnChangeReaderWriter::new_with_epsilon(reader_writer, 0.0001);
+    // Long frames, e.g. while loading, would otherwise cause systems to skip over thresholds.
+    let maximum_step_size = Duration::from_millis(100);
+
+    if should_record {
+        // The recording is replayed natively to reproduce a bug outside of the simulator.
+        let header = RecordingHeader::new(random.seed(), starting_state, Some(maximum_step_size));
+        let mut reader_writer = RecordingReaderWriter::new(
+            reader_writer,
+            header,
+            BufWriter::new(File::create(RECORDING_PATH)?),
+        )?;
+        run(
+            gauge,
+            Simulation::new_with_random_number_generator(&mut a320, &mut reader_writer, random),
+            &key_events,
+            starting_state,
+            maximum_step_size,
+        )
+        .await
+    } else {
+        let mut reader_writer = reader_writer;
+        run(
+            gauge,
+            Simulation::new_with_random_number_generator(&mut a320, &mut reader_writer, random),
+            &key_events,
+            starting_state,
+            maximum_step_size,
+        )
+        .await
+    }
+}
 
+async fn run<T: SimulatorReaderWriter>(
+    mut gauge: msfs::Gauge,
+    mut simulation: Simulation<'_, A320, T>,
+    key_events: &SimConnectKeyEvents,
+    starting_state: Option<StartingState>,
+    maximum_step_size: Duration,
+) -> Result<(), Box<dyn std::error::Error>> {
+    simulation.set_maximum_step_size(maximum_step_size);
+    if let Some(starting_state) = starting_state {
+        simulation.initialise(starting_state);
+    }
+
+    let mut simulation_clock = SimulationClock::new()?;
     while let Some(event) = gauge.next_event().await {
-        if let MSFSEvent::PreDraw(d) = event {
-            simulation.tick(d.delta_time());
+        match event {
+            MSFSEvent::PreDraw(d) => {
+                simulation_clock.update();
+                simulation.set_simulation_rate(simulation_clock.simulation_rate());
+                simulation.set_paused(simulation_clock.is_paused());
+                simulation.tick(d.delta_time());
+            }
+            MSFSEvent::SimConnect(SimConnectRecv::Event(event)) => {
+                key_events.receive(event.id(), event.data())
+            }
+            _ => {}
         }
     }
 
     Ok(())
 }
 
+/// The frame time passed to the gauge is real time. This determines how simulated
+/// time relates to it, as the simulation might run at a different rate or be paused.
+struct SimulationClock {
+    simulation_rate: AircraftVariable,
+    simulation_time: Option<f64>,
+    is_paused: bool,
+}
+impl SimulationClock {
+    fn new() -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(Self {
+            simulation_rate: AircraftVariable::from("SIMULATION RATE", "Number", 0)?,
+            simulation_time: None,
+            is_paused: false,
+        })
+    }
+
+    fn update(&mut self) {
+        // The simulation time doesn't advance while the simulator is paused.
+        let simulation_time = execute_calculator_code::<f64>("(E:SIMULATION TIME, seconds)");
+        self.is_paused = match self.simulation_time {
+            Some(previous) => (simulation_time - previous).abs() < f64::EPSILON,
+            None => false,
+        };
+        self.simulation_time = Some(simulation_time);
+    }
+
+    fn simulation_rate(&self) -> f64 {
+        // The rate isn't available while loading, during which it reads as 0.
+        self.simulation_rate.get().max(f64::EPSILON)
+    }
+
+    fn is_paused(&self) -> bool {
+        self.is_paused
+    }
+}
+
+/// Maps the key events handled by the aircraft to SimConnect client events, and queues
+/// the events received through SimConnect until they're read at the start of the next tick.
+struct SimConnectKeyEvents {
+    names: HashMap<u32, String>,
+    queue: Rc<RefCell<Vec<KeyEvent>>>,
+}
+impl SimConnectKeyEvents {
+    fn new(
+        sim_connect: &mut SimConnect,
+        registry: &VariableRegistry,
+        queue: Rc<RefCell<Vec<KeyEvent>>>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut names = HashMap::new();
+        for name in registry.key_events() {
+            // Custom events are distinguished from the simulator's events by a period.
+            let id = sim_connect.map_client_event_to_sim_event(&format!("A32NX.{}", name), true)?;
+            names.insert(id, name.to_owned());
+        }
+
+        Ok(Self { names, queue })
+    }
+
+    fn receive(&self, id: u32, data: u32) {
+        if let Some(name) = self.names.get(&id) {
+            self.queue
+                .borrow_mut()
+                .push(KeyEvent::new(name.clone(), data as f64));
+        }
+    }
+}
+
 struct A320SimulatorReaderWriter {
     dynamic_named_variables: HashMap<String, NamedVariable>,
     electrical_bus_connections: ElectricalBusConnections,
+    key_events: Rc<RefCell<Vec<KeyEvent>>>,
 
     ambient_temperature: AircraftVariable,
+    ambient_pressure: AircraftVariable,
     apu_generator_pb_on: AircraftVariable,
     external_power_available: AircraftVariable,
     external_power_pb_on: AircraftVariable,
@@ -36,7 +194,15 @@ struct A320SimulatorReaderWriter {
     turb_eng_corrected_n2_1: AircraftVariable,
     turb_eng_corrected_n2_2: AircraftVariable,
     airspeed_indicated: AircraftVariable,
+    airspeed_true: AircraftVariable,
+    ground_velocity: AircraftVariable,
+    vertical_speed: AircraftVariable,
     indicated_altitude: AircraftVariable,
+    pressure_altitude: AircraftVariable,
+    plane_pitch_degrees: AircraftVariable,
+    plane_bank_degrees: AircraftVariable,
+    acceleration_body_z: AircraftVariable,
+    acceleration_body_y: AircraftVariable,
     fuel_tank_left_main_quantity: AircraftVariable,
     sim_on_ground: AircraftVariable,
     unlimited_fuel: AircraftVariable,
@@ -46,8 +212,10 @@ impl A320SimulatorReaderWriter {
         Ok(A320SimulatorReaderWriter {
             dynamic_named_variables: HashMap::new(),
             electrical_bus_connections: ElectricalBusConnections::new(),
+            key_events: Rc::new(RefCell::new(vec![])),
 
             ambient_temperature: AircraftVariable::from("AMBIENT TEMPERATURE", "celsius", 0)?,
+            ambient_pressure: AircraftVariable::from("AMBIENT PRESSURE", "inHg", 0)?,
             apu_generator_pb_on: AircraftVariable::from("APU GENERATOR SWITCH", "Bool", 0)?,
             external_power_available: AircraftVariable::from(
                 "EXTERNAL POWER AVAILABLE",
@@ -69,7 +237,23 @@ impl A320SimulatorReaderWriter {
             turb_eng_corrected_n2_1: AircraftVariable::from("TURB ENG CORRECTED N2", "Percent", 1)?,
             turb_eng_corrected_n2_2: AircraftVariable::from("TURB ENG CORRECTED N2", "Percent", 2)?,
             airspeed_indicated: AircraftVariable::from("AIRSPEED INDICATED", "Knots", 0)?,
+            airspeed_true: AircraftVariable::from("AIRSPEED TRUE", "Knots", 0)?,
+            ground_velocity: AircraftVariable::from("GROUND VELOCITY", "Knots", 0)?,
+            vertical_speed: AircraftVariable::from("VERTICAL SPEED", "Feet per minute", 0)?,
             indicated_altitude: AircraftVariable::from("INDICATED ALTITUDE", "Feet", 0)?,
+            pressure_altitude: AircraftVariable::from("PRESSURE ALTITUDE", "Feet", 0)?,
+            plane_pitch_degrees: AircraftVariable::from("PLANE PITCH DEGREES", "Degrees", 0)?,
+            plane_bank_degrees: AircraftVariable::from("PLANE BANK DEGREES", "Degrees", 0)?,
+            acceleration_body_z: AircraftVariable::from(
+                "ACCELERATION BODY Z",
+                "Feet per second squared",
+                0,
+            )?,
+            acceleration_body_y: AircraftVariable::from(
+                "ACCELERATION BODY Y",
+                "Feet per second squared",
+                0,
+            )?,
             fuel_tank_left_main_quantity: AircraftVariable::from(
                 "FUEL TANK LEFT MAIN QUANTITY",
                 "Pounds",
@@ -79,16 +263,48 @@ impl A320SimulatorReaderWriter {
             unlimited_fuel: AircraftVariable::from("UNLIMITED FUEL", "Bool", 0)?,
         })
     }
-}
-impl SimulatorReaderWriter for A320SimulatorReaderWriter {
-    fn read(&mut self, name: &str) -> f64 {
-        match name {
+
+    /// The queue into which the key events received from the simulator are pushed.
+    fn key_events(&self) -> Rc<RefCell<Vec<KeyEvent>>> {
+        Rc::clone(&self.key_events)
+    }
+
+    /// Every variable read by the aircraft must either be mapped in `read_aircraft_variable`,
+    /// be written by the aircraft itself or be written by another gauge. Any other variable
+    /// is most likely misspelled, and would silently read 0.
+    fn verify_aircraft_variables(
+        &self,
+        registry: &VariableRegistry,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let unknown: Vec<&str> = registry
+            .iter()
+            .filter(|definition| {
+                let direction = definition.direction();
+                direction.is_read() && !direction.is_written()
+            })
+            .map(|definition| definition.name())
+            .filter(|name| {
+                self.read_aircraft_variable(name).is_none()
+                    && !EXTERNALLY_WRITTEN_NAMED_VARIABLES.contains(name)
+            })
+            .collect();
+
+        if unknown.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("Unknown aircraft variables: {}.", unknown.join(", ")).into())
+        }
+    }
+
+    fn read_aircraft_variable(&self, name: &str) -> Option<f64> {
+        Some(match name {
             "OVHD_ELEC_APU_GEN_PB_IS_ON" => self.apu_generator_pb_on.get(),
             "OVHD_ELEC_EXT_PWR_PB_IS_AVAILABLE" => self.external_power_available.get(),
             "OVHD_ELEC_EXT_PWR_PB_IS_ON" => self.external_power_pb_on.get(),
             "OVHD_ELEC_ENG_GEN_1_PB_IS_ON" => self.engine_generator_1_pb_on.get(),
             "OVHD_ELEC_ENG_GEN_2_PB_IS_ON" => self.engine_generator_2_pb_on.get(),
             "AMBIENT TEMPERATURE" => self.ambient_temperature.get(),
+            "AMBIENT PRESSURE" => self.ambient_pressure.get(),
             "EXTERNAL POWER AVAILABLE:1" => self.external_power_available.get(),
             "GEAR CENTER POSITION" => self.gear_center_position.get(),
             "TURB ENG CORRECTED N2:1" => self.turb_eng_corrected_n2_1.get(),
@@ -96,9 +312,25 @@ impl SimulatorReaderWriter for A320SimulatorReaderWriter {
             "FUEL TANK LEFT MAIN QUANTITY" => self.fuel_tank_left_main_quantity.get(),
             "UNLIMITED FUEL" => self.unlimited_fuel.get(),
             "AIRSPEED INDICATED" => self.airspeed_indicated.get(),
+            "AIRSPEED TRUE" => self.airspeed_true.get(),
+            "GROUND VELOCITY" => self.ground_velocity.get(),
+            "VERTICAL SPEED" => self.vertical_speed.get(),
             "INDICATED ALTITUDE" => self.indicated_altitude.get(),
+            "PRESSURE ALTITUDE" => self.pressure_altitude.get(),
+            "PLANE PITCH DEGREES" => self.plane_pitch_degrees.get(),
+            "PLANE BANK DEGREES" => self.plane_bank_degrees.get(),
+            "ACCELERATION BODY Z" => self.acceleration_body_z.get(),
+            "ACCELERATION BODY Y" => self.acceleration_body_y.get(),
             "SIM ON GROUND" => self.sim_on_ground.get(),
-            _ => {
+            _ => return None,
+        })
+    }
+}
+impl SimulatorReaderWriter for A320SimulatorReaderWriter {
+    fn read(&mut self, name: &str) -> f64 {
+        match self.read_aircraft_variable(name) {
+            Some(value) => value,
+            None => {
                 lookup_named_variable(&mut self.dynamic_named_variables, "A32NX_", name).get_value()
             }
         }
@@ -114,6 +346,10 @@ impl SimulatorReaderWriter for A320SimulatorReaderWriter {
 
         named_variable.set_value(value);
     }
+
+    fn read_key_events(&mut self) -> Vec<KeyEvent> {
+        self.key_events.borrow_mut().drain(..).collect()
+    }
 }
 
 fn lookup_named_variable<'a>(
@@ -0,0 +1,125 @@
+use harness::{black_box, Benchmarks};
+use std::time::Duration;
+use systems::{
+    apu::{ShutdownAps3200Turbine, Turbine, TurbineController, TurbineState},
+    simulation::{RandomNumberGenerator, UpdateContext},
+};
+use uom::si::{
+    acceleration::foot_per_second_squared, angle::degree, f64::*, length::foot,
+    pressure::inch_of_mercury, thermodynamic_temperature::degree_celsius, velocity::knot,
+};
+
+mod harness;
+
+struct Controller {
+    should_start: bool,
+}
+impl TurbineController for Controller {
+    fn should_start(&self) -> bool {
+        self.should_start
+    }
+
+    fn should_stop(&self) -> bool {
+        !self.should_start
+    }
+}
+
+fn main() {
+    let benchmarks = Benchmarks::from_arguments();
+    let random = RandomNumberGenerator::from_seed(1);
+    let start = Controller { should_start: true };
+    let stop = Controller {
+        should_start: false,
+    };
+
+    // Updates without delta keep the turbine at the same point in its start or
+    // stop sequence, while still evaluating the polynomials describing N and EGT.
+    let no_time_passes = context(Duration::from_secs(0), &random);
+
+    let mut turbine = Some(run_until(
+        Box::new(ShutdownAps3200Turbine::new()),
+        TurbineState::Starting,
+        Duration::from_secs(20),
+        &start,
+        &random,
+    ));
+    benchmarks.run("apu_turbine_starting_update", || {
+        update(&mut turbine, &no_time_passes, &start)
+    });
+
+    let running = run_until(
+        turbine.take().unwrap(),
+        TurbineState::Running,
+        Duration::from_secs(60),
+        &start,
+        &random,
+    );
+    let mut turbine = Some(run_until(
+        running,
+        TurbineState::Stopping,
+        Duration::from_secs(10),
+        &stop,
+        &random,
+    ));
+    benchmarks.run("apu_turbine_stopping_update", || {
+        update(&mut turbine, &no_time_passes, &stop)
+    });
+}
+
+fn update(
+    turbine: &mut Option<Box<dyn Turbine>>,
+    context: &UpdateContext,
+    controller: &Controller,
+) -> Ratio {
+    let updated = turbine
+        .take()
+        .unwrap()
+        .update(black_box(context), false, false, controller);
+    let n = updated.n();
+    *turbine = Some(updated);
+
+    n
+}
+
+/// Updates the turbine for the given duration and asserts it is in the expected state.
+fn run_until(
+    turbine: Box<dyn Turbine>,
+    state: TurbineState,
+    duration: Duration,
+    controller: &Controller,
+    random: &RandomNumberGenerator,
+) -> Box<dyn Turbine> {
+    let delta = Duration::from_millis(100);
+    let context = context(delta, random);
+    let mut turbine = Some(turbine);
+    for _ in 0..(duration.as_millis() / delta.as_millis()) {
+        update(&mut turbine, &context, controller);
+    }
+
+    let turbine = turbine.unwrap();
+    assert_eq!(turbine.state(), state);
+
+    turbine
+}
+
+fn context(delta: Duration, random: &RandomNumberGenerator) -> UpdateContext<'_> {
+    UpdateContext::new(
+        delta,
+        1.,
+        false,
+        Velocity::new::<knot>(0.),
+        Velocity::new::<knot>(0.),
+        Velocity::new::<knot>(0.),
+        Velocity::new::<knot>(0.),
+        Length::new::<foot>(0.),
+        Length::new::<foot>(0.),
+        ThermodynamicTemperature::new::<degree_celsius>(15.),
+        Pressure::new::<inch_of_mercury>(29.92),
+        Angle::new::<degree>(0.),
+        Angle::new::<degree>(0.),
+        Acceleration::new::<foot_per_second_squared>(0.),
+        Acceleration::new::<foot_per_second_squared>(0.),
+        true,
+        random,
+    )
+}
@@ -0,0 +1,40 @@
+use harness::{black_box, Benchmarks};
+use std::time::Duration;
+use systems::electrical::{consumption::PowerConsumption, Potential, PotentialOrigin};
+use uom::si::{electric_potential::volt, f64::*, power::watt};
+
+mod harness;
+
+fn main() {
+    let benchmarks = Benchmarks::from_arguments();
+
+    let battery_1 = battery(10);
+    let battery_2 = battery(11);
+    benchmarks.run("potential_merge", || {
+        black_box(battery_1).merge(&black_box(battery_2))
+    });
+
+    // The measurement quoted in the documentation of Potential's fields.
+    benchmarks.run("potential_create_merge_twice_and_clone", || {
+        let potential = battery(10).merge(&battery(11)).merge(&Potential::single(
+            PotentialOrigin::TransformerRectifier(1),
+            ElectricPotential::new::<volt>(28.),
+        ));
+
+        #[allow(clippy::clone_on_copy)]
+        black_box(potential.clone())
+    });
+
+    let merged = battery_1.merge(&battery_2);
+    let mut consumption = PowerConsumption::new(Duration::from_millis(50));
+    benchmarks.run("power_consumption_add", || {
+        consumption.add(black_box(&merged), Power::new::<watt>(100.))
+    });
+}
+
+fn battery(number: usize) -> Potential {
+    Potential::single(
+        PotentialOrigin::Battery(number),
+        ElectricPotential::new::<volt>(28.),
+    )
+}
@@ -0,0 +1,134 @@
+//! Measures how long code takes to run, for the `cargo bench` targets of the workspace.
+//!
+//! The built-in benchmark harness is unstable, thus the targets disable it and call
+//! [`Benchmarks`] from their `main` function instead. Targets outside of this directory
+//! include this module by its path:
+//!
+//! ```ignore
+//! #[path = "../../systems/benches/harness/mod.rs"]
+//! mod harness;
+//! ```
+//!
+//! Run all benchmarks with `cargo bench`, or only those whose name contains a filter
+//! with e.g. `cargo bench -- merge`.
+use std::{
+    env, fmt,
+    time::{Duration, Instant},
+};
+
+/// Runs benchmarks and prints their measurements.
+pub struct Benchmarks {
+    filter: Option<String>,
+    measurement_time: Duration,
+}
+impl Benchmarks {
+    const MEASUREMENT_TIME: Duration = Duration::from_secs(2);
+    const SAMPLES: u32 = 20;
+
+    /// Creates benchmarks which only run when their name contains the first
+    /// command line argument which isn't an option.
+    pub fn from_arguments() -> Self {
+        Self {
+            filter: env::args()
+                .skip(1)
+                .find(|argument| !argument.starts_with("--")),
+            measurement_time: Benchmarks::MEASUREMENT_TIME,
+        }
+    }
+
+    /// Calls the function repeatedly and prints the mean time it takes per call.
+    /// Returns `None` when the benchmark doesn't match the filter.
+    pub fn run<T, F: FnMut() -> T>(&self, name: &str, mut function: F) -> Option<Measurement> {
+        if !self.includes(name) {
+            return None;
+        }
+
+        let calls_per_sample = self.calls_per_sample(&mut function);
+        let samples: Vec<f64> = (0..Benchmarks::SAMPLES)
+            .map(|_| {
+                let start = Instant::now();
+                for _ in 0..calls_per_sample {
+                    black_box(function());
+                }
+
+                start.elapsed().as_secs_f64() / calls_per_sample as f64
+            })
+            .collect();
+
+        let measurement =
+            Measurement::from_samples(&samples, calls_per_sample * Benchmarks::SAMPLES as u64);
+        println!("{:<50} {}", name, measurement);
+
+        Some(measurement)
+    }
+
+    fn includes(&self, name: &str) -> bool {
+        match &self.filter {
+            Some(filter) => name.contains(filter.as_str()),
+            None => true,
+        }
+    }
+
+    /// Warms up by calling the function for a tenth of the measurement time. The number
+    /// of calls made while warming up determines how many calls fit in a sample.
+    fn calls_per_sample<T, F: FnMut() -> T>(&self, function: &mut F) -> u64 {
+        let warm_up_time = self.measurement_time / 10;
+        let start = Instant::now();
+        let mut calls: u64 = 0;
+        while start.elapsed() < warm_up_time {
+            black_box(function());
+            calls += 1;
+        }
+
+        let time_per_call = start.elapsed().as_secs_f64() / calls as f64;
+        let time_per_sample = self.measurement_time.as_secs_f64() / Benchmarks::SAMPLES as f64;
+
+        (time_per_sample / time_per_call).max(1.) as u64
+    }
+}
+
+/// The time a benchmarked function takes per call.
+pub struct Measurement {
+    mean: Duration,
+    standard_deviation: Duration,
+    calls: u64,
+}
+impl Measurement {
+    fn from_samples(samples: &[f64], calls: u64) -> Self {
+        let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+        let variance = samples
+            .iter()
+            .map(|sample| (sample - mean).powi(2))
+            .sum::<f64>()
+            / samples.len() as f64;
+
+        Self {
+            mean: Duration::from_secs_f64(mean),
+            standard_deviation: Duration::from_secs_f64(variance.sqrt()),
+            calls,
+        }
+    }
+}
+impl fmt::Display for Measurement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:>10.2?} ± {:.2?} per call ({} calls)",
+            self.mean, self.standard_deviation, self.calls
+        )
+    }
+}
+
+/// Returns the value, while preventing the compiler from optimising away its
+/// computation when the value is otherwise unused.
+pub fn black_box<T>(value: T) -> T {
+    // SAFETY: The value is read from a valid reference, after which the original is
+    // forgotten, such that it is neither dropped twice nor used after the read. A
+    // volatile read is never optimised away, thus the value must be computed.
+    unsafe {
+        let result = std::ptr::read_volatile(&value);
+        std::mem::forget(value);
+
+        result
+    }
+}
@@ -1,31 +1,30 @@
 use super::AirIntakeFlapController;
-use crate::{shared::random_number, simulation::UpdateContext};
+use crate::simulation::{SnapshotReader, SnapshotWriter, StartingState, UpdateContext};
 use std::time::Duration;
 use uom::si::{f64::*, ratio::percent};
 
 pub struct AirIntakeFlap {
     open_amount: Ratio,
-    delay: Duration,
+    /// The travel time is randomly chosen by the first update,
+    /// as random numbers are provided by the update context.
+    delay: Option<Duration>,
 }
 impl AirIntakeFlap {
     const MINIMUM_TRAVEL_TIME_SECS: u8 = 6;
     const MAXIMUM_TRAVEL_TIME_SECS: u8 = 12;
 
     pub fn new() -> AirIntakeFlap {
-        let random_above_minimum_mod =
-            AirIntakeFlap::MAXIMUM_TRAVEL_TIME_SECS - AirIntakeFlap::MINIMUM_TRAVEL_TIME_SECS + 1;
-        let delay = Duration::from_secs(
-            (AirIntakeFlap::MINIMUM_TRAVEL_TIME_SECS + (random_number() % random_above_minimum_mod))
-                as u64,
-        );
-
         AirIntakeFlap {
             open_amount: Ratio::new::<percent>(0.),
-            delay,
+            delay: None,
         }
     }
 
     pub fn update<T: AirIntakeFlapController>(&mut self, context: &UpdateContext, controller: &T) {
+        if self.delay.is_none() {
+            self.delay = Some(AirIntakeFlap::random_delay(context));
+        }
+
         if controller.should_open_air_intake_flap()
             && self.open_amount < Ratio::new::<percent>(100.)
         {
@@ -43,8 +42,22 @@ impl AirIntakeFlap {
         }
     }
 
+    fn random_delay(context: &UpdateContext) -> Duration {
+        let random_above_minimum_mod =
+            AirIntakeFlap::MAXIMUM_TRAVEL_TIME_SECS - AirIntakeFlap::MINIMUM_TRAVEL_TIME_SECS + 1;
+
+        Duration::from_secs(
+            (AirIntakeFlap::MINIMUM_TRAVEL_TIME_SECS
+                + (context.random_number() % random_above_minimum_mod)) as u64,
+        )
+    }
+
     fn get_flap_change_for_delta(&self, context: &UpdateContext) -> f64 {
-        100. * (context.delta().as_secs_f64() / self.delay.as_secs_f64())
+        let delay = self
+            .delay
+            .expect("The delay is chosen before the flap moves.");
+
+        100. * (context.delta().as_secs_f64() / delay.as_secs_f64())
     }
 
     pub fn is_fully_open(&self) -> bool {
@@ -55,9 +68,27 @@ impl AirIntakeFlap {
         self.open_amount
     }
 
-    #[cfg(test)]
-    pub fn set_delay(&mut self, delay: Duration) {
-        self.delay = delay;
+    pub fn initialise(&mut self, state: StartingState) {
+        self.open_amount = Ratio::new::<percent>(if state.apu_is_running() { 100. } else { 0. });
+    }
+
+    pub fn snapshot(&self, writer: &mut SnapshotWriter) {
+        writer.write_f64(
+            "APU_FLAP_OPEN_PERCENTAGE",
+            self.open_amount.get::<percent>(),
+        );
+        if let Some(delay) = self.delay {
+            writer.write_duration("APU_FLAP_TRAVEL_DELAY", delay);
+        }
+    }
+
+    pub fn restore(&mut self, reader: &SnapshotReader) {
+        if let Some(open_amount) = reader.read_f64("APU_FLAP_OPEN_PERCENTAGE") {
+            self.open_amount = Ratio::new::<percent>(open_amount);
+        }
+        if let Some(delay) = reader.read_duration("APU_FLAP_TRAVEL_DELAY") {
+            self.delay = Some(delay);
+        }
     }
 }
 
@@ -220,4 +251,20 @@ mod air_intake_flap_tests {
 
         assert_eq!(aircraft.flap_is_fully_open(), true)
     }
+
+    #[test]
+    fn travel_time_is_the_same_for_the_same_random_seed() {
+        let open_amount_for_seed = |seed: u64| {
+            let mut aircraft = TestAircraft::new(AirIntakeFlap::new(), TestFlapController::new());
+            let mut test_bed = SimulationTestBed::new_with_delta(Duration::from_secs(3));
+            test_bed.set_random_seed(seed);
+
+            aircraft.command_flap_open();
+            test_bed.run_aircraft(&mut aircraft);
+
+            aircraft.flap_open_amount()
+        };
+
+        assert_eq!(open_amount_for_seed(42), open_amount_for_seed(42));
+    }
 }
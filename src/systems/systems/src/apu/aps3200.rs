@@ -5,15 +5,78 @@ use crate::{
         ElectricalStateWriter, Potential, PotentialOrigin, PotentialSource, PotentialTarget,
         ProvideFrequency, ProvideLoad, ProvidePotential,
     },
-    shared::{calculate_towards_target_temperature, random_number},
-    simulation::{SimulationElement, SimulatorWriter, UpdateContext},
+    shared::calculate_towards_target_temperature,
+    simulation::{
+        DescriptionWriter, SimulationElement, SimulatorWriter, SnapshotReader, SnapshotWriter,
+        StartingState, UpdateContext,
+    },
 };
+use num_traits::FromPrimitive;
 use std::time::Duration;
 use uom::si::{
     electric_potential::volt, f64::*, frequency::hertz, power::watt, ratio::percent,
     temperature_interval, thermodynamic_temperature::degree_celsius,
 };
 
+const TURBINE_STATE_ID: &str = "APU_TURBINE_STATE";
+const TURBINE_EGT_ID: &str = "APU_TURBINE_EGT";
+const TURBINE_N_ID: &str = "APU_TURBINE_N";
+const TURBINE_SINCE_ID: &str = "APU_TURBINE_SINCE";
+
+fn snapshot_turbine(writer: &mut SnapshotWriter, turbine: &dyn Turbine) {
+    writer.write_f64(TURBINE_STATE_ID, turbine.state() as u8 as f64);
+    writer.write_f64(TURBINE_EGT_ID, turbine.egt().get::<degree_celsius>());
+    writer.write_f64(TURBINE_N_ID, turbine.n().get::<percent>());
+}
+
+/// Creates the turbine state found in the snapshot. When the snapshot
+/// doesn't contain a turbine state, the given turbine is returned as is.
+fn restore_turbine(turbine: Box<dyn Turbine>, reader: &SnapshotReader) -> Box<dyn Turbine> {
+    let state = match reader
+        .read_f64(TURBINE_STATE_ID)
+        .and_then(TurbineState::from_f64)
+    {
+        Some(state) => state,
+        None => return turbine,
+    };
+
+    let egt = reader
+        .read_f64(TURBINE_EGT_ID)
+        .map(ThermodynamicTemperature::new::<degree_celsius>)
+        .unwrap_or_else(|| turbine.egt());
+    let n = reader
+        .read_f64(TURBINE_N_ID)
+        .map(Ratio::new::<percent>)
+        .unwrap_or_else(|| turbine.n());
+
+    match state {
+        TurbineState::Shutdown => Box::new(ShutdownAps3200Turbine::new_with_egt(egt)),
+        TurbineState::Starting => {
+            let mut starting = Starting::new(egt);
+            starting.restore_state(reader, n);
+            Box::new(starting)
+        }
+        TurbineState::Running => {
+            let mut running = Running::nominal(egt);
+            running.restore_state(reader);
+            Box::new(running)
+        }
+        TurbineState::Stopping => {
+            let mut stopping = Stopping::new(egt, n);
+            stopping.restore_state(reader, n);
+            Box::new(stopping)
+        }
+    }
+}
+
+fn initialise_turbine(state: StartingState) -> Box<dyn Turbine> {
+    if state.apu_is_running() {
+        Box::new(Running::stabilised())
+    } else {
+        Box::new(ShutdownAps3200Turbine::new())
+    }
+}
+
 pub struct ShutdownAps3200Turbine {
     egt: ThermodynamicTemperature,
 }
@@ -28,6 +91,11 @@ impl ShutdownAps3200Turbine {
         ShutdownAps3200Turbine { egt }
     }
 }
+impl Default for ShutdownAps3200Turbine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 impl Turbine for ShutdownAps3200Turbine {
     fn update(
         mut self: Box<Self>,
@@ -56,6 +124,18 @@ impl Turbine for ShutdownAps3200Turbine {
     fn state(&self) -> TurbineState {
         TurbineState::Shutdown
     }
+
+    fn snapshot(&self, writer: &mut SnapshotWriter) {
+        snapshot_turbine(writer, self);
+    }
+
+    fn restore(self: Box<Self>, reader: &SnapshotReader) -> Box<dyn Turbine> {
+        restore_turbine(self, reader)
+    }
+
+    fn initialise(self: Box<Self>, state: StartingState) -> Box<dyn Turbine> {
+        initialise_turbine(state)
+    }
 }
 
 struct Starting {
@@ -74,6 +154,16 @@ impl Starting {
         }
     }
 
+    fn restore_state(&mut self, reader: &SnapshotReader, n: Ratio) {
+        self.n = n;
+        if let Some(since) = reader.read_duration(TURBINE_SINCE_ID) {
+            self.since = since;
+        }
+        if let Some(ignore) = reader.read_bool("APU_TURBINE_IGNORE_CALCULATED_EGT") {
+            self.ignore_calculated_egt = ignore;
+        }
+    }
+
     fn calculate_egt(&mut self, context: &UpdateContext) -> ThermodynamicTemperature {
         // Refer to APS3200.md for details on the values below and source data.
         const APU_N_TEMP_CONST: f64 = -92.3417137705543;
@@ -187,7 +277,7 @@ impl Turbine for Starting {
         if controller.should_stop() {
             Box::new(Stopping::new(self.egt, self.n))
         } else if (self.n.get::<percent>() - 100.).abs() < f64::EPSILON {
-            Box::new(Running::new(self.egt))
+            Box::new(Running::new(context, self.egt))
         } else {
             self
         }
@@ -204,6 +294,23 @@ impl Turbine for Starting {
     fn state(&self) -> TurbineState {
         TurbineState::Starting
     }
+
+    fn snapshot(&self, writer: &mut SnapshotWriter) {
+        snapshot_turbine(writer, self);
+        writer.write_duration(TURBINE_SINCE_ID, self.since);
+        writer.write_bool(
+            "APU_TURBINE_IGNORE_CALCULATED_EGT",
+            self.ignore_calculated_egt,
+        );
+    }
+
+    fn restore(self: Box<Self>, reader: &SnapshotReader) -> Box<dyn Turbine> {
+        restore_turbine(self, reader)
+    }
+
+    fn initialise(self: Box<Self>, state: StartingState) -> Box<dyn Turbine> {
+        initialise_turbine(state)
+    }
 }
 
 struct BleedAirUsageEgtDelta {
@@ -213,9 +320,9 @@ struct BleedAirUsageEgtDelta {
     min: f64,
 }
 impl BleedAirUsageEgtDelta {
-    fn new() -> Self {
-        let randomisation = 0.95 + ((random_number() % 101) as f64 / 1000.);
+    const NOMINAL_RANDOMISATION: f64 = 1.;
 
+    fn new(randomisation: f64) -> Self {
         Self {
             current: 0.,
             target: 0.,
@@ -224,6 +331,14 @@ impl BleedAirUsageEgtDelta {
         }
     }
 
+    fn random(context: &UpdateContext) -> Self {
+        Self::new(0.95 + ((context.random_number() % 101) as f64 / 1000.))
+    }
+
+    fn nominal() -> Self {
+        Self::new(BleedAirUsageEgtDelta::NOMINAL_RANDOMISATION)
+    }
+
     fn update(&mut self, context: &UpdateContext, apu_bleed_is_used: bool) {
         self.target = if apu_bleed_is_used {
             self.max
@@ -246,6 +361,20 @@ impl BleedAirUsageEgtDelta {
         TemperatureInterval::new::<temperature_interval::degree_celsius>(self.current)
     }
 
+    fn snapshot(&self, writer: &mut SnapshotWriter) {
+        writer.write_f64("APU_TURBINE_BLEED_AIR_USAGE_EGT_DELTA", self.current);
+        writer.write_f64("APU_TURBINE_BLEED_AIR_USAGE_EGT_DELTA_MAX", self.max);
+    }
+
+    fn restore(&mut self, reader: &SnapshotReader) {
+        if let Some(current) = reader.read_f64("APU_TURBINE_BLEED_AIR_USAGE_EGT_DELTA") {
+            self.current = current;
+        }
+        if let Some(max) = reader.read_f64("APU_TURBINE_BLEED_AIR_USAGE_EGT_DELTA_MAX") {
+            self.max = max;
+        }
+    }
+
     fn delta_per_second(&self) -> f64 {
         // Loosely based on bleed on data provided in a video by Komp.
         // The very much relates to pneumatics and thus could be improved further
@@ -285,14 +414,24 @@ struct ApuGenUsageEgtDelta {
 impl ApuGenUsageEgtDelta {
     // We just assume it takes 10 seconds to get to our target.
     const SECONDS_TO_REACH_TARGET: u64 = 10;
-    fn new() -> Self {
+    const NOMINAL_TARGET_EGT_DELTA: f64 = 12.5;
+
+    fn new(target_egt_delta: f64) -> Self {
         Self {
             time: Duration::from_secs(0),
-            base_egt_delta_per_second: (10. + ((random_number() % 6) as f64))
+            base_egt_delta_per_second: target_egt_delta
                 / ApuGenUsageEgtDelta::SECONDS_TO_REACH_TARGET as f64,
         }
     }
 
+    fn random(context: &UpdateContext) -> Self {
+        Self::new(10. + ((context.random_number() % 6) as f64))
+    }
+
+    fn nominal() -> Self {
+        Self::new(ApuGenUsageEgtDelta::NOMINAL_TARGET_EGT_DELTA)
+    }
+
     fn update(&mut self, context: &UpdateContext, apu_gen_is_used: bool) {
         self.time = if apu_gen_is_used {
             (self.time + context.delta()).min(Duration::from_secs(
@@ -310,6 +449,24 @@ impl ApuGenUsageEgtDelta {
             self.time.as_secs_f64() * self.base_egt_delta_per_second,
         )
     }
+
+    fn snapshot(&self, writer: &mut SnapshotWriter) {
+        writer.write_duration("APU_TURBINE_APU_GEN_USAGE_TIME", self.time);
+        writer.write_f64(
+            "APU_TURBINE_APU_GEN_USAGE_BASE_EGT_DELTA_PER_SECOND",
+            self.base_egt_delta_per_second,
+        );
+    }
+
+    fn restore(&mut self, reader: &SnapshotReader) {
+        if let Some(time) = reader.read_duration("APU_TURBINE_APU_GEN_USAGE_TIME") {
+            self.time = time;
+        }
+        if let Some(delta) = reader.read_f64("APU_TURBINE_APU_GEN_USAGE_BASE_EGT_DELTA_PER_SECOND")
+        {
+            self.base_egt_delta_per_second = delta;
+        }
+    }
 }
 
 struct Running {
@@ -320,22 +477,71 @@ struct Running {
     apu_gen_usage: ApuGenUsageEgtDelta,
 }
 impl Running {
-    fn new(egt: ThermodynamicTemperature) -> Running {
-        let base_egt = 340. + ((random_number() % 11) as f64);
+    const NOMINAL_BASE_EGT: f64 = 345.;
+
+    fn new(context: &UpdateContext, egt: ThermodynamicTemperature) -> Running {
+        Running::new_with_usage(
+            egt,
+            ThermodynamicTemperature::new::<degree_celsius>(
+                340. + ((context.random_number() % 11) as f64),
+            ),
+            BleedAirUsageEgtDelta::random(context),
+            ApuGenUsageEgtDelta::random(context),
+        )
+    }
+
+    /// Creates a running turbine using the nominal values of the otherwise randomised
+    /// base EGT and EGT deltas. This is used when no update context is available,
+    /// for example when restoring a snapshot which then overwrites these values.
+    fn nominal(egt: ThermodynamicTemperature) -> Running {
+        Running::new_with_usage(
+            egt,
+            ThermodynamicTemperature::new::<degree_celsius>(Running::NOMINAL_BASE_EGT),
+            BleedAirUsageEgtDelta::nominal(),
+            ApuGenUsageEgtDelta::nominal(),
+        )
+    }
+
+    fn new_with_usage(
+        egt: ThermodynamicTemperature,
+        base_egt: ThermodynamicTemperature,
+        bleed_air_usage: BleedAirUsageEgtDelta,
+        apu_gen_usage: ApuGenUsageEgtDelta,
+    ) -> Running {
         Running {
             egt,
-            base_egt: ThermodynamicTemperature::new::<degree_celsius>(base_egt),
+            base_egt,
             // This contains the deviation from the base EGT at the moment of entering the running state.
             // This code assumes the base EGT is lower than the EGT at this point in time, which is always the case.
             // Should this change in the future, then changes have to be made here.
             base_egt_deviation: TemperatureInterval::new::<temperature_interval::degree_celsius>(
-                egt.get::<degree_celsius>() - base_egt,
+                egt.get::<degree_celsius>() - base_egt.get::<degree_celsius>(),
             ),
-            bleed_air_usage: BleedAirUsageEgtDelta::new(),
-            apu_gen_usage: ApuGenUsageEgtDelta::new(),
+            bleed_air_usage,
+            apu_gen_usage,
         }
     }
 
+    /// Creates a turbine which has been running long enough for the EGT
+    /// to have settled at the base EGT.
+    fn stabilised() -> Running {
+        Running::nominal(ThermodynamicTemperature::new::<degree_celsius>(
+            Running::NOMINAL_BASE_EGT,
+        ))
+    }
+
+    fn restore_state(&mut self, reader: &SnapshotReader) {
+        if let Some(base_egt) = reader.read_f64("APU_TURBINE_BASE_EGT") {
+            self.base_egt = ThermodynamicTemperature::new::<degree_celsius>(base_egt);
+        }
+        if let Some(deviation) = reader.read_f64("APU_TURBINE_BASE_EGT_DEVIATION") {
+            self.base_egt_deviation =
+                TemperatureInterval::new::<temperature_interval::degree_celsius>(deviation);
+        }
+        self.bleed_air_usage.restore(reader);
+        self.apu_gen_usage.restore(reader);
+    }
+
     fn calculate_egt(
         &mut self,
         context: &UpdateContext,
@@ -388,6 +594,29 @@ impl Turbine for Running {
     fn state(&self) -> TurbineState {
         TurbineState::Running
     }
+
+    fn snapshot(&self, writer: &mut SnapshotWriter) {
+        snapshot_turbine(writer, self);
+        writer.write_f64(
+            "APU_TURBINE_BASE_EGT",
+            self.base_egt.get::<degree_celsius>(),
+        );
+        writer.write_f64(
+            "APU_TURBINE_BASE_EGT_DEVIATION",
+            self.base_egt_deviation
+                .get::<temperature_interval::degree_celsius>(),
+        );
+        self.bleed_air_usage.snapshot(writer);
+        self.apu_gen_usage.snapshot(writer);
+    }
+
+    fn restore(self: Box<Self>, reader: &SnapshotReader) -> Box<dyn Turbine> {
+        restore_turbine(self, reader)
+    }
+
+    fn initialise(self: Box<Self>, state: StartingState) -> Box<dyn Turbine> {
+        initialise_turbine(state)
+    }
 }
 
 struct Stopping {
@@ -418,6 +647,24 @@ impl Stopping {
         }
     }
 
+    fn restore_state(&mut self, reader: &SnapshotReader, n: Ratio) {
+        self.n = n;
+        if let Some(since) = reader.read_duration(TURBINE_SINCE_ID) {
+            self.since = since;
+        }
+        if let Some(base_temperature) = reader.read_f64("APU_TURBINE_BASE_TEMPERATURE") {
+            self.base_temperature =
+                ThermodynamicTemperature::new::<degree_celsius>(base_temperature);
+        }
+        if let Some(n_factor) = reader.read_f64("APU_TURBINE_N_FACTOR") {
+            self.n_factor = n_factor;
+        }
+        if let Some(egt_delta) = reader.read_f64("APU_TURBINE_EGT_DELTA_AT_ENTRY") {
+            self.egt_delta_at_entry =
+                TemperatureInterval::new::<temperature_interval::degree_celsius>(egt_delta);
+        }
+    }
+
     fn calculate_egt_delta(n: Ratio) -> TemperatureInterval {
         // Refer to APS3200.md for details on the values below and source data.
         const APU_N_TEMP_DELTA_CONST: f64 = -125.73137672208446;
@@ -520,6 +767,29 @@ impl Turbine for Stopping {
     fn state(&self) -> TurbineState {
         TurbineState::Stopping
     }
+
+    fn snapshot(&self, writer: &mut SnapshotWriter) {
+        snapshot_turbine(writer, self);
+        writer.write_duration(TURBINE_SINCE_ID, self.since);
+        writer.write_f64(
+            "APU_TURBINE_BASE_TEMPERATURE",
+            self.base_temperature.get::<degree_celsius>(),
+        );
+        writer.write_f64("APU_TURBINE_N_FACTOR", self.n_factor);
+        writer.write_f64(
+            "APU_TURBINE_EGT_DELTA_AT_ENTRY",
+            self.egt_delta_at_entry
+                .get::<temperature_interval::degree_celsius>(),
+        );
+    }
+
+    fn restore(self: Box<Self>, reader: &SnapshotReader) -> Box<dyn Turbine> {
+        restore_turbine(self, reader)
+    }
+
+    fn initialise(self: Box<Self>, state: StartingState) -> Box<dyn Turbine> {
+        initialise_turbine(state)
+    }
 }
 
 fn calculate_towards_ambient_egt(
@@ -615,6 +885,20 @@ impl Aps3200ApuGenerator {
         }
     }
 
+    fn update_potential_and_frequency(&mut self) {
+        self.output_potential = if self.should_provide_output() {
+            self.calculate_potential(self.n)
+        } else {
+            ElectricPotential::new::<volt>(0.)
+        };
+
+        self.output_frequency = if self.should_provide_output() {
+            self.calculate_frequency(self.n)
+        } else {
+            Frequency::new::<hertz>(0.)
+        };
+    }
+
     fn should_provide_output(&self) -> bool {
         !self.is_emergency_shutdown
             && self.n.get::<percent>() >= Aps3200ApuGenerator::APU_GEN_POWERED_N
@@ -652,22 +936,27 @@ impl PotentialSource for Aps3200ApuGenerator {
     }
 }
 impl SimulationElement for Aps3200ApuGenerator {
+    fn identifier(&self) -> String {
+        format!("apu_generator_{}", self.number)
+    }
+
+    fn describe(&self, writer: &mut DescriptionWriter) {
+        self.writer.describe_alternating_with_load(self, writer);
+    }
+
     fn write(&self, writer: &mut SimulatorWriter) {
         self.writer.write_alternating_with_load(self, writer);
     }
 
-    fn process_power_consumption_report<T: PowerConsumptionReport>(&mut self, report: &T) {
-        self.output_potential = if self.should_provide_output() {
-            self.calculate_potential(self.n)
-        } else {
-            ElectricPotential::new::<volt>(0.)
-        };
+    fn initialise(&mut self, state: StartingState) {
+        self.n = Ratio::new::<percent>(if state.apu_is_running() { 100. } else { 0. });
+        self.is_emergency_shutdown = false;
+        self.update_potential_and_frequency();
+        self.load = Ratio::new::<percent>(0.);
+    }
 
-        self.output_frequency = if self.should_provide_output() {
-            self.calculate_frequency(self.n)
-        } else {
-            Frequency::new::<hertz>(0.)
-        };
+    fn process_power_consumption_report<T: PowerConsumptionReport>(&mut self, report: &T) {
+        self.update_potential_and_frequency();
 
         let power_consumption = report
             .total_consumption_of(PotentialOrigin::ApuGenerator(self.number))
@@ -694,6 +983,10 @@ impl Aps3200StartMotor {
 }
 impl ApuStartMotor for Aps3200StartMotor {}
 impl SimulationElement for Aps3200StartMotor {
+    fn identifier(&self) -> String {
+        "apu_start_motor".to_owned()
+    }
+
     fn consume_power(&mut self, consumption: &mut PowerConsumption) {
         if self.input_potential.is_unpowered() {
             self.powered_since = Duration::from_secs(0);
@@ -4,10 +4,13 @@ use super::{
 };
 use crate::{
     electrical::PotentialSource,
+    failures::Failure,
     pneumatic::{BleedAirValveController, Valve},
     shared::ApuStartContactorsController,
-    simulation::UpdateContext,
+    simulation::{SnapshotReader, SnapshotWriter, UpdateContext},
 };
+use num_derive::FromPrimitive;
+use num_traits::FromPrimitive;
 use std::time::Duration;
 use uom::si::{f64::*, length::foot, ratio::percent, thermodynamic_temperature::degree_celsius};
 
@@ -121,6 +124,12 @@ impl ElectronicControlBox {
         }
     }
 
+    pub fn update_failure_state(&mut self, failure: &Failure) {
+        if self.fault.is_none() && self.master_is_on && failure.is_active() {
+            self.fault = Some(ApuFault::InternalFault);
+        }
+    }
+
     fn calculate_egt_warning_temperature(
         context: &UpdateContext,
         turbine_state: &TurbineState,
@@ -208,6 +217,76 @@ impl ElectronicControlBox {
     pub fn is_starting(&self) -> bool {
         self.turbine_state == TurbineState::Starting
     }
+
+    /// Initialises the state derived from the turbine and air intake flap, such that
+    /// the ECB agrees with them before the first update.
+    pub fn initialise(&mut self, turbine: &dyn Turbine, air_intake_flap: &AirIntakeFlap) {
+        *self = ElectronicControlBox::new();
+        self.turbine_state = turbine.state();
+        self.n = turbine.n();
+        self.egt = turbine.egt();
+        self.air_intake_flap_fully_open = air_intake_flap.is_fully_open();
+    }
+
+    pub fn snapshot(&self, writer: &mut SnapshotWriter) {
+        writer.write_f64("APU_ECB_TURBINE_STATE", self.turbine_state as u8 as f64);
+        writer.write_bool(
+            "APU_ECB_START_MOTOR_IS_POWERED",
+            self.start_motor_is_powered,
+        );
+        writer.write_f64("APU_ECB_N", self.n.get::<percent>());
+        writer.write_duration(
+            "APU_ECB_BLEED_AIR_VALVE_LAST_OPEN_TIME_AGO",
+            self.bleed_air_valve_last_open_time_ago,
+        );
+        writer.write_f64(
+            "APU_ECB_FAULT",
+            self.fault.map_or(0., |fault| fault as u8 as f64),
+        );
+        writer.write_bool(
+            "APU_ECB_AIR_INTAKE_FLAP_FULLY_OPEN",
+            self.air_intake_flap_fully_open,
+        );
+        writer.write_f64("APU_ECB_EGT", self.egt.get::<degree_celsius>());
+        writer.write_f64(
+            "APU_ECB_EGT_WARNING_TEMPERATURE",
+            self.egt_warning_temperature.get::<degree_celsius>(),
+        );
+        writer.write_duration("APU_ECB_N_ABOVE_95_DURATION", self.n_above_95_duration);
+    }
+
+    pub fn restore(&mut self, reader: &SnapshotReader) {
+        if let Some(state) = reader
+            .read_f64("APU_ECB_TURBINE_STATE")
+            .and_then(TurbineState::from_f64)
+        {
+            self.turbine_state = state;
+        }
+        if let Some(is_powered) = reader.read_bool("APU_ECB_START_MOTOR_IS_POWERED") {
+            self.start_motor_is_powered = is_powered;
+        }
+        if let Some(n) = reader.read_f64("APU_ECB_N") {
+            self.n = Ratio::new::<percent>(n);
+        }
+        if let Some(time_ago) = reader.read_duration("APU_ECB_BLEED_AIR_VALVE_LAST_OPEN_TIME_AGO") {
+            self.bleed_air_valve_last_open_time_ago = time_ago;
+        }
+        if let Some(fault) = reader.read_f64("APU_ECB_FAULT") {
+            self.fault = ApuFault::from_f64(fault);
+        }
+        if let Some(fully_open) = reader.read_bool("APU_ECB_AIR_INTAKE_FLAP_FULLY_OPEN") {
+            self.air_intake_flap_fully_open = fully_open;
+        }
+        if let Some(egt) = reader.read_f64("APU_ECB_EGT") {
+            self.egt = ThermodynamicTemperature::new::<degree_celsius>(egt);
+        }
+        if let Some(egt) = reader.read_f64("APU_ECB_EGT_WARNING_TEMPERATURE") {
+            self.egt_warning_temperature = ThermodynamicTemperature::new::<degree_celsius>(egt);
+        }
+        if let Some(duration) = reader.read_duration("APU_ECB_N_ABOVE_95_DURATION") {
+            self.n_above_95_duration = duration;
+        }
+    }
 }
 impl ApuStartContactorsController for ElectronicControlBox {
     /// Indicates if the APU start contactor should be closed.
@@ -267,9 +346,12 @@ impl BleedAirValveController for ElectronicControlBox {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+/// The discriminants are used to identify the fault within a snapshot,
+/// where 0 represents the absence of a fault.
+#[derive(Clone, Copy, Debug, FromPrimitive, PartialEq)]
 enum ApuFault {
-    ApuFire,
-    FuelLowPressure,
-    DcPowerLoss,
+    ApuFire = 1,
+    FuelLowPressure = 2,
+    DcPowerLoss = 3,
+    InternalFault = 4,
 }
@@ -1,21 +1,22 @@
-use self::{
-    air_intake_flap::AirIntakeFlap, aps3200::ShutdownAps3200Turbine,
-    electronic_control_box::ElectronicControlBox,
-};
+use self::{air_intake_flap::AirIntakeFlap, electronic_control_box::ElectronicControlBox};
 use crate::{
     electrical::{Potential, PotentialSource, PotentialTarget, ProvideFrequency, ProvidePotential},
+    failures::{Failure, FailureType},
     overhead::{FirePushButton, OnOffAvailablePushButton, OnOffFaultPushButton},
     pneumatic::{BleedAirValve, BleedAirValveState, Valve},
     shared::{ApuStartContactorsController, AuxiliaryPowerUnitElectrical},
-    simulation::{SimulationElement, SimulationElementVisitor, SimulatorWriter, UpdateContext},
+    simulation::{
+        DescriptionWriter, EventCollector, SimulationElement, SimulationElementVisitor,
+        SimulationEvent, SimulatorWriter, SnapshotReader, SnapshotWriter, StartingState,
+        UpdateContext, VariableIdentifier, VariableUnit,
+    },
 };
-#[cfg(test)]
-use std::time::Duration;
+use num_derive::FromPrimitive;
 use uom::si::{f64::*, ratio::percent, thermodynamic_temperature::degree_celsius};
 
 mod air_intake_flap;
 mod aps3200;
-pub use aps3200::{Aps3200ApuGenerator, Aps3200StartMotor};
+pub use aps3200::{Aps3200ApuGenerator, Aps3200StartMotor, ShutdownAps3200Turbine};
 mod electronic_control_box;
 
 pub struct AuxiliaryPowerUnitFactory {}
@@ -69,6 +70,16 @@ pub trait TurbineController {
 }
 
 pub struct AuxiliaryPowerUnit<T: ApuGenerator, U: ApuStartMotor> {
+    flap_open_percentage_id: VariableIdentifier<Ratio>,
+    bleed_air_valve_open_id: VariableIdentifier<bool>,
+    egt_caution_id: VariableIdentifier<ThermodynamicTemperature>,
+    egt_id: VariableIdentifier<ThermodynamicTemperature>,
+    inoperable_id: VariableIdentifier<bool>,
+    is_auto_shutdown_id: VariableIdentifier<bool>,
+    is_emergency_shutdown_id: VariableIdentifier<bool>,
+    low_fuel_pressure_fault_id: VariableIdentifier<bool>,
+    n_id: VariableIdentifier<Ratio>,
+    egt_warning_id: VariableIdentifier<ThermodynamicTemperature>,
     turbine: Option<Box<dyn Turbine>>,
     generator: T,
     ecb: ElectronicControlBox,
@@ -76,10 +87,37 @@ pub struct AuxiliaryPowerUnit<T: ApuGenerator, U: ApuStartMotor> {
     air_intake_flap: AirIntakeFlap,
     bleed_air_valve: BleedAirValve,
     fuel_pressure_switch: FuelPressureSwitch,
+    failure: Failure,
+    events: Vec<SimulationEvent>,
 }
 impl<T: ApuGenerator, U: ApuStartMotor> AuxiliaryPowerUnit<T, U> {
     pub fn new(turbine: Box<dyn Turbine>, generator: T, start_motor: U) -> Self {
         AuxiliaryPowerUnit {
+            flap_open_percentage_id: VariableIdentifier::new(
+                "APU_FLAP_OPEN_PERCENTAGE",
+                VariableUnit::Percent,
+            ),
+            bleed_air_valve_open_id: VariableIdentifier::new(
+                "APU_BLEED_AIR_VALVE_OPEN",
+                VariableUnit::Bool,
+            ),
+            egt_caution_id: VariableIdentifier::new("APU_EGT_CAUTION", VariableUnit::DegreeCelsius),
+            egt_id: VariableIdentifier::new("APU_EGT", VariableUnit::DegreeCelsius),
+            inoperable_id: VariableIdentifier::new("ECAM_INOP_SYS_APU", VariableUnit::Bool),
+            is_auto_shutdown_id: VariableIdentifier::new(
+                "APU_IS_AUTO_SHUTDOWN",
+                VariableUnit::Bool,
+            ),
+            is_emergency_shutdown_id: VariableIdentifier::new(
+                "APU_IS_EMERGENCY_SHUTDOWN",
+                VariableUnit::Bool,
+            ),
+            low_fuel_pressure_fault_id: VariableIdentifier::new(
+                "APU_LOW_FUEL_PRESSURE_FAULT",
+                VariableUnit::Bool,
+            ),
+            n_id: VariableIdentifier::new("APU_N", VariableUnit::Percent),
+            egt_warning_id: VariableIdentifier::new("APU_EGT_WARNING", VariableUnit::DegreeCelsius),
             turbine: Some(turbine),
             generator,
             ecb: ElectronicControlBox::new(),
@@ -87,6 +125,8 @@ impl<T: ApuGenerator, U: ApuStartMotor> AuxiliaryPowerUnit<T, U> {
             air_intake_flap: AirIntakeFlap::new(),
             bleed_air_valve: BleedAirValve::new(),
             fuel_pressure_switch: FuelPressureSwitch::new(),
+            failure: Failure::new(FailureType::Apu),
+            events: vec![],
         }
     }
 
@@ -104,6 +144,7 @@ impl<T: ApuGenerator, U: ApuStartMotor> AuxiliaryPowerUnit<T, U> {
         self.fuel_pressure_switch.update(has_fuel_remaining);
         self.ecb
             .update_fuel_pressure_switch_state(&self.fuel_pressure_switch);
+        self.ecb.update_failure_state(&self.failure);
         self.bleed_air_valve.update(&self.ecb);
         self.ecb
             .update_bleed_air_valve_state(context, &self.bleed_air_valve);
@@ -111,6 +152,7 @@ impl<T: ApuGenerator, U: ApuStartMotor> AuxiliaryPowerUnit<T, U> {
         self.ecb.update_air_intake_flap_state(&self.air_intake_flap);
 
         if let Some(turbine) = self.turbine.take() {
+            let previous_state = turbine.state();
             let mut updated_turbine = turbine.update(
                 context,
                 self.bleed_air_valve.is_open(),
@@ -120,6 +162,13 @@ impl<T: ApuGenerator, U: ApuStartMotor> AuxiliaryPowerUnit<T, U> {
 
             self.ecb.update(context, updated_turbine.as_mut());
 
+            if previous_state != updated_turbine.state() {
+                self.events.push(SimulationEvent::ApuTurbineStateChanged {
+                    from: previous_state,
+                    to: updated_turbine.state(),
+                });
+            }
+
             self.turbine = Some(updated_turbine);
         }
 
@@ -155,11 +204,6 @@ impl<T: ApuGenerator, U: ApuStartMotor> AuxiliaryPowerUnit<T, U> {
     fn set_turbine(&mut self, turbine: Option<Box<dyn Turbine>>) {
         self.turbine = turbine;
     }
-
-    #[cfg(test)]
-    fn set_air_intake_flap_opening_delay(&mut self, duration: Duration) {
-        self.air_intake_flap.set_delay(duration);
-    }
 }
 impl<T: ApuGenerator, U: ApuStartMotor> AuxiliaryPowerUnitElectrical for AuxiliaryPowerUnit<T, U> {
     fn start_motor_powered_by(&mut self, source: Potential) {
@@ -186,34 +230,79 @@ impl<T: ApuGenerator, U: ApuStartMotor> PotentialSource for AuxiliaryPowerUnit<T
 }
 impl<T: ApuGenerator, U: ApuStartMotor> SimulationElement for AuxiliaryPowerUnit<T, U> {
     fn accept<V: SimulationElementVisitor>(&mut self, visitor: &mut V) {
+        visitor.enter_children();
         self.generator.accept(visitor);
         self.start_motor.accept(visitor);
+        self.failure.accept(visitor);
+        visitor.leave_children();
+
         visitor.visit(self);
     }
 
+    fn identifier(&self) -> String {
+        "apu".to_owned()
+    }
+
+    fn describe(&self, writer: &mut DescriptionWriter) {
+        writer.write("n", format!("{:.1} %", self.n().get::<percent>()));
+        writer.write(
+            "egt",
+            format!("{:.1} °C", self.ecb.egt().get::<degree_celsius>()),
+        );
+        writer.write("available", self.is_available());
+    }
+
     fn write(&self, writer: &mut SimulatorWriter) {
-        writer.write_f64(
-            "APU_FLAP_OPEN_PERCENTAGE",
-            self.air_intake_flap.open_amount().get::<percent>(),
+        writer.write(
+            &self.flap_open_percentage_id,
+            self.air_intake_flap.open_amount(),
         );
-        writer.write_bool("APU_BLEED_AIR_VALVE_OPEN", self.bleed_air_valve_is_open());
-        writer.write_f64(
-            "APU_EGT_CAUTION",
-            self.ecb.egt_caution_temperature().get::<degree_celsius>(),
+        writer.write(
+            &self.bleed_air_valve_open_id,
+            self.bleed_air_valve_is_open(),
         );
-        writer.write_f64("APU_EGT", self.ecb.egt().get::<degree_celsius>());
-        writer.write_bool("ECAM_INOP_SYS_APU", self.ecb.is_inoperable());
-        writer.write_bool("APU_IS_AUTO_SHUTDOWN", self.ecb.is_auto_shutdown());
-        writer.write_bool("APU_IS_EMERGENCY_SHUTDOWN", self.is_emergency_shutdown());
-        writer.write_bool(
-            "APU_LOW_FUEL_PRESSURE_FAULT",
+        writer.write(&self.egt_caution_id, self.ecb.egt_caution_temperature());
+        writer.write(&self.egt_id, self.ecb.egt());
+        writer.write(&self.inoperable_id, self.ecb.is_inoperable());
+        writer.write(&self.is_auto_shutdown_id, self.ecb.is_auto_shutdown());
+        writer.write(&self.is_emergency_shutdown_id, self.is_emergency_shutdown());
+        writer.write(
+            &self.low_fuel_pressure_fault_id,
             self.ecb.has_fuel_low_pressure_fault(),
         );
-        writer.write_f64("APU_N", self.n().get::<percent>());
-        writer.write_f64(
-            "APU_EGT_WARNING",
-            self.ecb.egt_warning_temperature().get::<degree_celsius>(),
-        );
+        writer.write(&self.n_id, self.n());
+        writer.write(&self.egt_warning_id, self.ecb.egt_warning_temperature());
+    }
+
+    fn snapshot(&self, writer: &mut SnapshotWriter) {
+        if let Some(turbine) = self.turbine.as_ref() {
+            turbine.snapshot(writer);
+        }
+
+        self.ecb.snapshot(writer);
+        self.air_intake_flap.snapshot(writer);
+    }
+
+    fn restore(&mut self, reader: &SnapshotReader) {
+        if let Some(turbine) = self.turbine.take() {
+            self.turbine = Some(turbine.restore(reader));
+        }
+
+        self.ecb.restore(reader);
+        self.air_intake_flap.restore(reader);
+    }
+
+    fn initialise(&mut self, state: StartingState) {
+        self.air_intake_flap.initialise(state);
+        if let Some(turbine) = self.turbine.take() {
+            let turbine = turbine.initialise(state);
+            self.ecb.initialise(turbine.as_ref(), &self.air_intake_flap);
+            self.turbine = Some(turbine);
+        }
+    }
+
+    fn collect_events(&mut self, collector: &mut EventCollector) {
+        collector.collect(&mut self.events);
     }
 }
 impl<T: ApuGenerator, U: ApuStartMotor> BleedAirValveState for AuxiliaryPowerUnit<T, U> {
@@ -233,9 +322,16 @@ pub trait Turbine {
     fn n(&self) -> Ratio;
     fn egt(&self) -> ThermodynamicTemperature;
     fn state(&self) -> TurbineState;
+    fn snapshot(&self, writer: &mut SnapshotWriter);
+    /// Restores the turbine's state from the snapshot. As the turbine
+    /// is a state machine, this can return a turbine in a different state.
+    fn restore(self: Box<Self>, reader: &SnapshotReader) -> Box<dyn Turbine>;
+    /// Initialises the turbine to the given starting state. As the turbine
+    /// is a state machine, this can return a turbine in a different state.
+    fn initialise(self: Box<Self>, state: StartingState) -> Box<dyn Turbine>;
 }
 
-#[derive(PartialEq)]
+#[derive(Clone, Copy, Debug, FromPrimitive, PartialEq)]
 pub enum TurbineState {
     Shutdown,
     Starting,
@@ -266,10 +362,16 @@ impl AuxiliaryPowerUnitFireOverheadPanel {
 }
 impl SimulationElement for AuxiliaryPowerUnitFireOverheadPanel {
     fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+        visitor.enter_children();
         self.apu_fire_button.accept(visitor);
+        visitor.leave_children();
 
         visitor.visit(self);
     }
+
+    fn identifier(&self) -> String {
+        "apu_fire_overhead".to_owned()
+    }
 }
 impl Default for AuxiliaryPowerUnitFireOverheadPanel {
     fn default() -> Self {
@@ -315,11 +417,24 @@ impl AuxiliaryPowerUnitOverheadPanel {
 }
 impl SimulationElement for AuxiliaryPowerUnitOverheadPanel {
     fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+        visitor.enter_children();
         self.master.accept(visitor);
         self.start.accept(visitor);
+        visitor.leave_children();
 
         visitor.visit(self);
     }
+
+    fn identifier(&self) -> String {
+        "apu_overhead".to_owned()
+    }
+
+    fn initialise(&mut self, state: StartingState) {
+        self.master.set_on(state.apu_is_running());
+        self.master.set_fault(false);
+        self.start.turn_off();
+        self.start.set_available(state.apu_is_running());
+    }
 }
 impl Default for AuxiliaryPowerUnitOverheadPanel {
     fn default() -> Self {
@@ -334,7 +449,7 @@ pub mod tests {
             consumption::{PowerConsumer, SuppliedPower},
             ElectricalBusType, PotentialOrigin,
         },
-        simulation::{test::SimulationTestBed, Aircraft},
+        simulation::{test::SimulationTestBed, Aircraft, SimulationSnapshot},
     };
 
     use super::*;
@@ -382,6 +497,16 @@ pub mod tests {
         fn state(&self) -> TurbineState {
             TurbineState::Starting
         }
+
+        fn snapshot(&self, _: &mut SnapshotWriter) {}
+
+        fn restore(self: Box<Self>, _: &SnapshotReader) -> Box<dyn Turbine> {
+            self
+        }
+
+        fn initialise(self: Box<Self>, _: StartingState) -> Box<dyn Turbine> {
+            self
+        }
     }
 
     struct AuxiliaryPowerUnitTestAircraft {
@@ -413,10 +538,6 @@ pub mod tests {
             }
         }
 
-        fn set_air_intake_flap_opening_delay(&mut self, duration: Duration) {
-            self.apu.set_air_intake_flap_opening_delay(duration);
-        }
-
         fn set_apu_gen_is_used(&mut self, value: bool) {
             self.apu_gen_is_used = value;
         }
@@ -507,11 +628,13 @@ pub mod tests {
     }
     impl SimulationElement for AuxiliaryPowerUnitTestAircraft {
         fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+            visitor.enter_children();
             self.apu.accept(visitor);
             self.apu_overhead.accept(visitor);
             self.apu_fire_overhead.accept(visitor);
             self.apu_bleed.accept(visitor);
             self.power_consumer.accept(visitor);
+            visitor.leave_children();
 
             visitor.visit(self);
         }
@@ -550,8 +673,8 @@ pub mod tests {
             apu_test_bed
         }
 
-        fn air_intake_flap_that_opens_in(mut self, duration: Duration) -> Self {
-            self.aircraft.set_air_intake_flap_opening_delay(duration);
+        fn random_seed(mut self, seed: u64) -> Self {
+            self.simulation_test_bed.set_random_seed(seed);
             self
         }
 
@@ -611,6 +734,11 @@ pub mod tests {
             self
         }
 
+        fn failed_apu(mut self) -> Self {
+            self.simulation_test_bed.activate_failure(&FailureType::Apu);
+            self
+        }
+
         pub fn released_apu_fire_pb(mut self) -> Self {
             self.simulation_test_bed.write_bool("FIRE_BUTTON_APU", true);
             self
@@ -703,6 +831,11 @@ pub mod tests {
             self
         }
 
+        fn paused(mut self, is_paused: bool) -> Self {
+            self.simulation_test_bed.set_paused(is_paused);
+            self
+        }
+
         pub fn run(mut self, delta: Duration) -> Self {
             self.simulation_test_bed.set_delta(delta);
             self.simulation_test_bed
@@ -729,6 +862,27 @@ pub mod tests {
             self
         }
 
+        fn events(&self) -> &[SimulationEvent] {
+            self.simulation_test_bed.events()
+        }
+
+        fn snapshot(&mut self) -> SimulationSnapshot {
+            self.simulation_test_bed
+                .snapshot_aircraft(&mut self.aircraft)
+        }
+
+        fn restored_from(mut self, snapshot: &SimulationSnapshot) -> Self {
+            self.simulation_test_bed
+                .restore_aircraft(&mut self.aircraft, snapshot);
+            self
+        }
+
+        fn initialised_to(mut self, state: StartingState) -> Self {
+            self.simulation_test_bed
+                .initialise_aircraft(&mut self.aircraft, state);
+            self
+        }
+
         fn run_until_n_decreases(mut self, delta_per_run: Duration) -> Self {
             let mut previous_n = 0.;
             loop {
@@ -888,6 +1042,28 @@ pub mod tests {
 
         const APPROXIMATE_STARTUP_TIME: u64 = 49;
 
+        #[test]
+        fn records_an_event_when_the_turbine_state_changes() {
+            let mut test_bed = test_bed_with()
+                .apu_ready_to_start()
+                .then_continue_with()
+                .start_on();
+
+            let mut events = vec![];
+            for _ in 0..2 {
+                test_bed = test_bed.run(Duration::from_secs(0));
+                events.extend_from_slice(test_bed.events());
+            }
+
+            assert_eq!(
+                events,
+                vec![SimulationEvent::ApuTurbineStateChanged {
+                    from: TurbineState::Shutdown,
+                    to: TurbineState::Starting
+                }]
+            );
+        }
+
         #[test]
         fn when_apu_master_sw_turned_on_air_intake_flap_opens() {
             let mut test_bed = test_bed_with().master_on().run(Duration::from_secs(20));
@@ -897,13 +1073,14 @@ pub mod tests {
 
         #[test]
         fn when_apu_master_sw_turned_on_and_air_intake_flap_not_yet_open_apu_does_not_start() {
+            // With this seed the air intake flap takes its maximum travel time of 12 seconds.
             let mut test_bed = test_bed_with()
-                .air_intake_flap_that_opens_in(Duration::from_secs(20))
+                .random_seed(9)
                 .master_on()
                 .run(Duration::from_millis(1))
                 .then_continue_with()
                 .start_on()
-                .run(Duration::from_secs(15));
+                .run(Duration::from_secs(11));
 
             assert_about_eq!(test_bed.n().get::<percent>(), 0.);
         }
@@ -938,6 +1115,26 @@ pub mod tests {
             assert_about_eq!(test_bed.n().get::<percent>(), 100.);
         }
 
+        #[test]
+        fn while_paused_the_start_sequence_does_not_progress() {
+            let mut test_bed = test_bed_with().starting_apu().run(Duration::from_secs(10));
+            let n = test_bed.n().get::<percent>();
+
+            test_bed = test_bed
+                .then_continue_with()
+                .paused(true)
+                .run(Duration::from_secs(10));
+
+            assert_about_eq!(test_bed.n().get::<percent>(), n);
+
+            test_bed = test_bed
+                .then_continue_with()
+                .paused(false)
+                .run(Duration::from_secs(APPROXIMATE_STARTUP_TIME));
+
+            assert_about_eq!(test_bed.n().get::<percent>(), 100.);
+        }
+
         #[test]
         fn one_and_a_half_seconds_after_starting_sequence_commences_ignition_starts() {
             let mut test_bed = test_bed_with()
@@ -1020,6 +1217,20 @@ pub mod tests {
             assert!(test_bed.start_shows_available());
         }
 
+        #[test]
+        fn apu_is_available_within_60_seconds_of_starting() {
+            let mut test_bed = test_bed_with().starting_apu();
+            test_bed
+                .simulation_test_bed
+                .set_delta(Duration::from_millis(100));
+
+            test_bed.simulation_test_bed.assert_becomes_true_within(
+                &mut test_bed.aircraft,
+                "OVHD_APU_START_PB_IS_AVAILABLE",
+                ..=Duration::from_secs(60),
+            );
+        }
+
         #[test]
         fn start_sw_on_light_turns_off_when_apu_not_yet_starting_and_master_sw_turned_off() {
             let mut test_bed = test_bed_with()
@@ -1621,6 +1832,20 @@ pub mod tests {
             assert!(!test_bed.start_is_on());
         }
 
+        #[test]
+        #[timeout(500)]
+        fn running_apu_shuts_down_when_failed() {
+            let mut test_bed = test_bed_with()
+                .running_apu()
+                .and()
+                .failed_apu()
+                .run_until_n_decreases(Duration::from_millis(50));
+
+            assert!(!test_bed.apu_is_available());
+            assert!(test_bed.is_auto_shutdown());
+            assert!(test_bed.master_has_fault());
+        }
+
         #[test]
         fn when_no_fuel_is_available_and_apu_is_running_auto_shutdown_is_true() {
             let mut test_bed = test_bed_with()
@@ -1769,5 +1994,67 @@ pub mod tests {
 
             assert!(maximum_power < Power::new::<watt>(10000.));
         }
+
+        #[test]
+        fn restores_running_apu_from_snapshot() {
+            let snapshot = test_bed_with().running_apu().snapshot();
+
+            let mut test_bed = test_bed()
+                .restored_from(&snapshot)
+                .run(Duration::from_secs(1));
+
+            assert!(test_bed.apu_is_available());
+            assert!((test_bed.n().get::<percent>() - 100.).abs() < f64::EPSILON);
+            assert!(test_bed.is_air_intake_flap_fully_open());
+        }
+
+        #[test]
+        fn restores_starting_apu_from_snapshot() {
+            let mut original = test_bed_with().starting_apu().run(Duration::from_secs(10));
+            let snapshot = original.snapshot();
+            let n = original.n();
+
+            let mut test_bed = test_bed()
+                .restored_from(&snapshot)
+                .run(Duration::from_millis(0));
+
+            assert!(!test_bed.apu_is_available());
+            assert!((test_bed.n() - n).get::<percent>().abs() < 0.001);
+        }
+
+        #[test]
+        fn initialised_at_gate_with_apu_is_available_from_the_first_tick() {
+            let mut test_bed = test_bed()
+                .initialised_to(StartingState::GateWithApu)
+                .run(Duration::from_millis(1));
+
+            assert!(test_bed.apu_is_available());
+            assert!(test_bed.is_air_intake_flap_fully_open());
+            assert!(test_bed.potential_within_normal_range());
+            assert!(test_bed.frequency_within_normal_range());
+        }
+
+        #[test]
+        fn initialised_at_gate_with_apu_keeps_running() {
+            let mut test_bed = test_bed()
+                .initialised_to(StartingState::GateWithApu)
+                .run(Duration::from_secs(60));
+
+            assert!(test_bed.apu_is_available());
+            assert!((test_bed.n().get::<percent>() - 100.).abs() < f64::EPSILON);
+        }
+
+        #[test]
+        fn initialised_cold_and_dark_shuts_down_a_running_apu() {
+            let mut test_bed = test_bed_with()
+                .running_apu()
+                .initialised_to(StartingState::ColdAndDark)
+                .run(Duration::from_millis(1));
+
+            assert!(!test_bed.apu_is_available());
+            assert!(!test_bed.start_is_on());
+            assert!(test_bed.is_air_intake_flap_fully_closed());
+            assert_eq!(test_bed.n().get::<percent>(), 0.);
+        }
     }
 }
@@ -3,7 +3,13 @@ use super::{
     ElectricalStateWriter, Potential, PotentialOrigin, PotentialSource, PotentialTarget,
     ProvideCurrent, ProvidePotential,
 };
-use crate::simulation::{SimulationElement, SimulatorWriter};
+use crate::{
+    failures::{Failure, FailureType},
+    simulation::{
+        DescriptionWriter, SimulationElement, SimulationElementVisitor, SimulatorWriter,
+        SnapshotReader, SnapshotWriter,
+    },
+};
 use uom::si::{
     electric_charge::ampere_hour, electric_current::ampere, electric_potential::volt,
     electrical_resistance::ohm, f64::*, time::second,
@@ -12,6 +18,8 @@ use uom::si::{
 pub struct Battery {
     number: usize,
     writer: ElectricalStateWriter,
+    charge_id: String,
+    failure: Failure,
     input_potential: Potential,
     charge: ElectricCharge,
     output_potential: ElectricPotential,
@@ -42,6 +50,8 @@ impl Battery {
         Self {
             number,
             writer: ElectricalStateWriter::new(&format!("BAT_{}", number)),
+            charge_id: format!("ELEC_BAT_{}_CHARGE", number),
+            failure: Failure::new(FailureType::Battery(number)),
             input_potential: Potential::none(),
             charge,
             output_potential: Battery::calculate_output_potential_for_charge(charge),
@@ -54,7 +64,8 @@ impl Battery {
     }
 
     fn is_powered_by_other_potential(&self) -> bool {
-        self.input_potential.raw() > self.output_potential
+        // A failed battery neither provides nor accepts a charge.
+        !self.failure.is_active() && self.input_potential.raw() > self.output_potential
     }
 
     pub fn input_potential(&self) -> Potential {
@@ -119,7 +130,7 @@ impl Battery {
 potential_target!(Battery);
 impl PotentialSource for Battery {
     fn output(&self) -> Potential {
-        if self.output_potential > ElectricPotential::new::<volt>(0.) {
+        if !self.failure.is_active() && self.output_potential > ElectricPotential::new::<volt>(0.) {
             Potential::single(PotentialOrigin::Battery(self.number), self.output_potential)
         } else {
             Potential::none()
@@ -147,10 +158,41 @@ impl ProvidePotential for Battery {
     }
 }
 impl SimulationElement for Battery {
+    fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+        visitor.enter_children();
+        self.failure.accept(visitor);
+        visitor.leave_children();
+
+        visitor.visit(self);
+    }
+
+    fn identifier(&self) -> String {
+        format!("battery_{}", self.number)
+    }
+
+    fn describe(&self, writer: &mut DescriptionWriter) {
+        writer.write(
+            "charge",
+            format!("{:.1} Ah", self.charge.get::<ampere_hour>()),
+        );
+        self.writer.describe_direct(self, writer);
+    }
+
     fn write(&self, writer: &mut SimulatorWriter) {
         self.writer.write_direct(self, writer);
     }
 
+    fn snapshot(&self, writer: &mut SnapshotWriter) {
+        writer.write_f64(&self.charge_id, self.charge.get::<ampere_hour>());
+    }
+
+    fn restore(&mut self, reader: &SnapshotReader) {
+        if let Some(charge) = reader.read_f64(&self.charge_id) {
+            self.charge = ElectricCharge::new::<ampere_hour>(charge);
+            self.output_potential = Battery::calculate_output_potential_for_charge(self.charge);
+        }
+    }
+
     fn consume_power(&mut self, consumption: &mut PowerConsumption) {
         if self.is_powered_by_other_potential() {
             self.current = Battery::calculate_charging_current(
@@ -200,7 +242,8 @@ mod tests {
                 Contactor, ElectricalBus, ElectricalBusType,
             },
             simulation::{
-                test::SimulationTestBed, Aircraft, SimulationElementVisitor, UpdateContext,
+                test::SimulationTestBed, Aircraft, SimulationElementVisitor, SimulationSnapshot,
+                UpdateContext,
             },
         };
         use std::time::Duration;
@@ -224,6 +267,19 @@ mod tests {
                 self.test_bed.run_aircraft(aircraft);
             }
 
+            fn snapshot<T: Aircraft>(&mut self, aircraft: &mut T) -> SimulationSnapshot {
+                self.test_bed.snapshot_aircraft(aircraft)
+            }
+
+            fn restore<T: Aircraft>(&mut self, aircraft: &mut T, snapshot: &SimulationSnapshot) {
+                self.test_bed.restore_aircraft(aircraft, snapshot);
+            }
+
+            fn fail_battery(&mut self, number: usize) {
+                self.test_bed
+                    .activate_failure(&FailureType::Battery(number));
+            }
+
             fn current_is_normal(&mut self, number: usize) -> bool {
                 self.test_bed
                     .read_bool(&format!("ELEC_BAT_{}_CURRENT_NORMAL", number))
@@ -362,12 +418,14 @@ mod tests {
         }
         impl SimulationElement for TestAircraft {
             fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+                visitor.enter_children();
                 self.bat_bus.accept(visitor);
                 self.battery_1.accept(visitor);
                 self.battery_1_contactor.accept(visitor);
                 self.battery_2.accept(visitor);
                 self.battery_2_contactor.accept(visitor);
                 self.consumer.accept(visitor);
+                visitor.leave_children();
 
                 visitor.visit(self);
             }
@@ -404,6 +462,17 @@ mod tests {
             assert!(test_bed.potential_is_normal(1));
         }
 
+        #[test]
+        fn when_failed_provides_no_output() {
+            let mut aircraft = TestAircraft::with_full_batteries();
+            let mut test_bed = BatteryTestBed::new();
+
+            test_bed.fail_battery(1);
+            test_bed.run_aircraft(&mut aircraft);
+
+            assert!(!aircraft.bat_bus_is_powered());
+        }
+
         #[test]
         fn when_empty_has_no_potential() {
             let mut aircraft = TestAircraft::with_empty_batteries();
@@ -643,5 +712,32 @@ mod tests {
                     < ElectricCharge::new::<ampere_hour>(0.001)
             );
         }
+
+        #[test]
+        fn restores_charge_from_snapshot() {
+            let mut test_bed = BatteryTestBed::new();
+            let snapshot = test_bed.snapshot(&mut TestAircraft::with_half_charged_batteries());
+
+            let mut aircraft = TestAircraft::with_full_batteries();
+            test_bed.restore(&mut aircraft, &snapshot);
+
+            assert_eq!(
+                aircraft.battery_1_charge(),
+                ElectricCharge::new::<ampere_hour>(Battery::RATED_CAPACITY_AMPERE_HOURS / 2.)
+            );
+            assert_eq!(aircraft.battery_2_charge(), aircraft.battery_1_charge());
+        }
+
+        #[test]
+        fn restored_charge_determines_potential() {
+            let mut test_bed = BatteryTestBed::new();
+            let snapshot = test_bed.snapshot(&mut TestAircraft::with_empty_batteries());
+
+            let mut aircraft = TestAircraft::with_full_batteries();
+            test_bed.restore(&mut aircraft, &snapshot);
+            test_bed.run_aircraft(&mut aircraft);
+
+            assert_eq!(test_bed.potential(1), ElectricPotential::new::<volt>(0.));
+        }
     }
 }
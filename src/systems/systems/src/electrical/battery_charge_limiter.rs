@@ -1,9 +1,14 @@
+use num_derive::FromPrimitive;
+use num_traits::FromPrimitive;
 use std::time::Duration;
 
 use super::{PotentialSource, ProvideCurrent};
 use crate::{
     shared::DelayedTrueLogicGate,
-    simulation::{SimulationElement, SimulatorWriter, UpdateContext},
+    simulation::{
+        EventCollector, SimulationElement, SimulationEvent, SimulatorWriter, SnapshotReader,
+        SnapshotWriter, StartingState, UpdateContext, VariableIdentifier, VariableUnit,
+    },
 };
 use uom::si::{electric_current::ampere, electric_potential::volt, f64::*, velocity::knot};
 
@@ -88,25 +93,31 @@ impl BatteryChargeLimiterArguments {
 }
 
 pub struct BatteryChargeLimiter {
-    should_show_arrow_when_contactor_closed_id: String,
+    snapshot_id: String,
+    should_show_arrow_when_contactor_closed_id: VariableIdentifier<bool>,
+    contactor_id: String,
     arrow: ArrowBetweenBatteryAndBatBus,
     observer: Option<Box<dyn BatteryStateObserver>>,
+    events: Vec<SimulationEvent>,
 }
 impl BatteryChargeLimiter {
     const CHARGE_DISCHARGE_ARROW_DISPLAYED_AFTER_SECONDS: u64 = 15;
 
     pub fn new(contactor_id: &str) -> Self {
         Self {
-            should_show_arrow_when_contactor_closed_id: format!(
-                "ELEC_CONTACTOR_{}_SHOW_ARROW_WHEN_CLOSED",
-                contactor_id
+            snapshot_id: format!("ELEC_BCL_{}", contactor_id),
+            should_show_arrow_when_contactor_closed_id: VariableIdentifier::new(
+                format!("ELEC_CONTACTOR_{}_SHOW_ARROW_WHEN_CLOSED", contactor_id),
+                VariableUnit::Bool,
             ),
+            contactor_id: contactor_id.to_owned(),
             arrow: ArrowBetweenBatteryAndBatBus::new(),
             // We start in an open state, because electrical tests assume this to be the starting state.
             // This state might not be correct for all starting situations (*.flt files) in the simulator.
-            // When an initialisation phase is added to the system code we can overwrite this starting state
+            // When the simulation is initialised to a starting state, this state is overwritten
             // with the appropriate one for the given starting situation.
             observer: Some(Box::new(OpenContactorObserver::for_initial_bcl_state())),
+            events: vec![],
         }
     }
 
@@ -114,7 +125,17 @@ impl BatteryChargeLimiter {
         self.arrow.update(context, arguments);
 
         if let Some(observer) = self.observer.take() {
-            self.observer = Some(observer.update(context, arguments));
+            let was_closed = observer.should_close_contactor();
+            let observer = observer.update(context, arguments);
+            if was_closed && observer.is_open_due_to_discharge_protection() {
+                self.events.push(
+                    SimulationEvent::BatteryContactorOpenedDueToDischargeProtection {
+                        contactor_id: self.contactor_id.clone(),
+                    },
+                );
+            }
+
+            self.observer = Some(observer);
         }
     }
 
@@ -123,23 +144,96 @@ impl BatteryChargeLimiter {
     }
 }
 impl SimulationElement for BatteryChargeLimiter {
+    fn identifier(&self) -> String {
+        format!("battery_charge_limiter_{}", self.contactor_id)
+    }
+
     fn write(&self, writer: &mut SimulatorWriter) {
-        writer.write_bool(
+        writer.write(
             &self.should_show_arrow_when_contactor_closed_id,
             self.arrow.should_show_when_contactor_closed(),
         );
     }
+
+    fn snapshot(&self, writer: &mut SnapshotWriter) {
+        self.arrow.snapshot(writer, &self.snapshot_id);
+        if let Some(observer) = self.observer.as_ref() {
+            observer.snapshot(writer, &self.snapshot_id);
+        }
+    }
+
+    fn restore(&mut self, reader: &SnapshotReader) {
+        self.arrow.restore(reader, &self.snapshot_id);
+        if let Some(observer) = restore_observer(reader, &self.snapshot_id) {
+            self.observer = Some(observer);
+        }
+    }
+
+    fn initialise(&mut self, state: StartingState) {
+        self.arrow = ArrowBetweenBatteryAndBatBus::new();
+        self.observer = if state.is_cold_and_dark() {
+            // The BAT push buttons are OFF when cold and dark.
+            Some(Box::new(OffPushButtonObserver::new()))
+        } else {
+            // The batteries are charged and other sources power the BAT bus.
+            Some(Box::new(OpenContactorObserver::for_initial_bcl_state()))
+        };
+    }
+
+    fn collect_events(&mut self, collector: &mut EventCollector) {
+        collector.collect(&mut self.events);
+    }
 }
 
 /// Observes the battery, battery contactor and related systems
 /// to determine if the battery contactor should open or close.
 trait BatteryStateObserver {
     fn should_close_contactor(&self) -> bool;
+    fn is_open_due_to_discharge_protection(&self) -> bool {
+        false
+    }
     fn update(
         self: Box<Self>,
         context: &UpdateContext,
         arguments: &BatteryChargeLimiterArguments,
     ) -> Box<dyn BatteryStateObserver>;
+    fn snapshot(&self, writer: &mut SnapshotWriter, id: &str);
+}
+
+/// Identifies the observer within a snapshot.
+#[derive(Clone, Copy, FromPrimitive)]
+enum BatteryStateObserverKind {
+    OffPushButton = 0,
+    OpenContactor = 1,
+    ClosedContactor = 2,
+}
+
+fn observer_kind_id(id: &str) -> String {
+    format!("{}_OBSERVER", id)
+}
+
+fn restore_observer(reader: &SnapshotReader, id: &str) -> Option<Box<dyn BatteryStateObserver>> {
+    let kind = reader
+        .read_f64(&observer_kind_id(id))
+        .and_then(BatteryStateObserverKind::from_f64)?;
+
+    Some(match kind {
+        BatteryStateObserverKind::OffPushButton => {
+            let mut observer = OffPushButtonObserver::new();
+            observer.restore(reader, id);
+            Box::new(observer)
+        }
+        BatteryStateObserverKind::OpenContactor => {
+            let mut observer = OpenContactorObserver::for_initial_bcl_state();
+            observer.restore(reader, id);
+            Box::new(observer)
+        }
+        BatteryStateObserverKind::ClosedContactor => {
+            let mut observer = ClosedContactorObserver::from_off();
+            observer.restore(reader, id);
+            Box::new(observer)
+        }
+    })
 }
 
 /// The BCL is not powered when the BAT push button is in the OFF
@@ -158,6 +252,11 @@ impl OffPushButtonObserver {
             )),
         }
     }
+
+    fn restore(&mut self, reader: &SnapshotReader, id: &str) {
+        self.bcl_startup_delay
+            .restore(reader, &format!("{}_STARTUP_DELAY", id));
+    }
 }
 impl BatteryStateObserver for OffPushButtonObserver {
     fn should_close_contactor(&self) -> bool {
@@ -178,6 +277,15 @@ impl BatteryStateObserver for OffPushButtonObserver {
             self
         }
     }
+
+    fn snapshot(&self, writer: &mut SnapshotWriter, id: &str) {
+        writer.write_f64(
+            &observer_kind_id(id),
+            BatteryStateObserverKind::OffPushButton as u8 as f64,
+        );
+        self.bcl_startup_delay
+            .snapshot(writer, &format!("{}_STARTUP_DELAY", id));
+    }
 }
 
 /// Observes the open battery contactor and related systems
@@ -212,6 +320,21 @@ impl OpenContactorObserver {
         Self::new(EmergencyElec::new(), false, false)
     }
 
+    fn restore(&mut self, reader: &SnapshotReader, id: &str) {
+        self.begin_charging_cycle_delay
+            .restore(reader, &format!("{}_BEGIN_CHARGING_CYCLE_DELAY", id));
+        if let Some(value) = reader.read_bool(&format!("{}_OPEN_DUE_TO_DISCHARGE_PROTECTION", id)) {
+            self.open_due_to_discharge_protection = value;
+        }
+        if let Some(value) = reader.read_bool(&format!(
+            "{}_OPEN_DUE_TO_EXCEEDING_EMERGENCY_ELEC_CLOSING_TIME_ALLOWANCE",
+            id
+        )) {
+            self.open_due_to_exceeding_emergency_elec_closing_time_allowance = value;
+        }
+        self.emergency_elec.restore(reader, id);
+    }
+
     fn from_closed(emergency_elec: EmergencyElec) -> Self {
         Self::new(emergency_elec, false, false)
     }
@@ -288,6 +411,10 @@ impl BatteryStateObserver for OpenContactorObserver {
         false
     }
 
+    fn is_open_due_to_discharge_protection(&self) -> bool {
+        self.open_due_to_discharge_protection
+    }
+
     fn update(
         mut self: Box<Self>,
         context: &UpdateContext,
@@ -303,6 +430,27 @@ impl BatteryStateObserver for OpenContactorObserver {
             self
         }
     }
+
+    fn snapshot(&self, writer: &mut SnapshotWriter, id: &str) {
+        writer.write_f64(
+            &observer_kind_id(id),
+            BatteryStateObserverKind::OpenContactor as u8 as f64,
+        );
+        self.begin_charging_cycle_delay
+            .snapshot(writer, &format!("{}_BEGIN_CHARGING_CYCLE_DELAY", id));
+        writer.write_bool(
+            &format!("{}_OPEN_DUE_TO_DISCHARGE_PROTECTION", id),
+            self.open_due_to_discharge_protection,
+        );
+        writer.write_bool(
+            &format!(
+                "{}_OPEN_DUE_TO_EXCEEDING_EMERGENCY_ELEC_CLOSING_TIME_ALLOWANCE",
+                id
+            ),
+            self.open_due_to_exceeding_emergency_elec_closing_time_allowance,
+        );
+        self.emergency_elec.snapshot(writer, id);
+    }
 }
 
 /// Observes the closed battery contactor and related systems
@@ -340,6 +488,29 @@ impl ClosedContactorObserver {
         Self::new(EmergencyElec::new())
     }
 
+    fn restore(&mut self, reader: &SnapshotReader, id: &str) {
+        if let Some(duration) =
+            reader.read_duration(&format!("{}_BELOW_4_AMPERE_CHARGING_DURATION", id))
+        {
+            self.below_4_ampere_charging_duration = duration;
+        }
+        if let Some(duration) = reader.read_duration(&format!("{}_BELOW_23_VOLT_DURATION", id)) {
+            self.below_23_volt_duration = duration;
+        }
+        if let Some(duration) =
+            reader.read_duration(&format!("{}_APU_MASTER_SW_PB_ON_DURATION", id))
+        {
+            self.apu_master_sw_pb_on_duration = duration;
+        }
+        if let Some(value) = reader.read_bool(&format!("{}_HAD_APU_START", id)) {
+            self.had_apu_start = value;
+        }
+        if let Some(value) = reader.read_bool(&format!("{}_ENTERED_IN_EMERGENCY_ELEC", id)) {
+            self.entered_in_emergency_elec = value;
+        }
+        self.emergency_elec.restore(reader, id);
+    }
+
     fn update_state(&mut self, context: &UpdateContext, arguments: &BatteryChargeLimiterArguments) {
         self.emergency_elec.update(context, arguments);
 
@@ -458,6 +629,31 @@ impl BatteryStateObserver for ClosedContactorObserver {
             self
         }
     }
+
+    fn snapshot(&self, writer: &mut SnapshotWriter, id: &str) {
+        writer.write_f64(
+            &observer_kind_id(id),
+            BatteryStateObserverKind::ClosedContactor as u8 as f64,
+        );
+        writer.write_duration(
+            &format!("{}_BELOW_4_AMPERE_CHARGING_DURATION", id),
+            self.below_4_ampere_charging_duration,
+        );
+        writer.write_duration(
+            &format!("{}_BELOW_23_VOLT_DURATION", id),
+            self.below_23_volt_duration,
+        );
+        writer.write_duration(
+            &format!("{}_APU_MASTER_SW_PB_ON_DURATION", id),
+            self.apu_master_sw_pb_on_duration,
+        );
+        writer.write_bool(&format!("{}_HAD_APU_START", id), self.had_apu_start);
+        writer.write_bool(
+            &format!("{}_ENTERED_IN_EMERGENCY_ELEC", id),
+            self.entered_in_emergency_elec,
+        );
+        self.emergency_elec.snapshot(writer, id);
+    }
 }
 
 fn on_ground_at_low_speed_with_unpowered_ac_buses(
@@ -499,6 +695,21 @@ impl EmergencyElec {
         self.is_active_for_duration
             < Duration::from_secs(EmergencyElec::APU_START_INHIBIT_DELAY_SECONDS)
     }
+
+    fn snapshot(&self, writer: &mut SnapshotWriter, id: &str) {
+        writer.write_duration(
+            &format!("{}_EMERGENCY_ELEC_ACTIVE_DURATION", id),
+            self.is_active_for_duration,
+        );
+    }
+
+    fn restore(&mut self, reader: &SnapshotReader, id: &str) {
+        if let Some(duration) =
+            reader.read_duration(&format!("{}_EMERGENCY_ELEC_ACTIVE_DURATION", id))
+        {
+            self.is_active_for_duration = duration;
+        }
+    }
 }
 
 struct ArrowBetweenBatteryAndBatBus {
@@ -532,6 +743,20 @@ impl ArrowBetweenBatteryAndBatBus {
         self.discharging_above_1_ampere_beyond_time.output()
             || self.charging_above_1_ampere_beyond_time.output()
     }
+
+    fn snapshot(&self, writer: &mut SnapshotWriter, id: &str) {
+        self.discharging_above_1_ampere_beyond_time
+            .snapshot(writer, &format!("{}_ARROW_DISCHARGING", id));
+        self.charging_above_1_ampere_beyond_time
+            .snapshot(writer, &format!("{}_ARROW_CHARGING", id));
+    }
+
+    fn restore(&mut self, reader: &SnapshotReader, id: &str) {
+        self.discharging_above_1_ampere_beyond_time
+            .restore(reader, &format!("{}_ARROW_DISCHARGING", id));
+        self.charging_above_1_ampere_beyond_time
+            .restore(reader, &format!("{}_ARROW_CHARGING", id));
+    }
 }
 
 #[cfg(test)]
@@ -548,7 +773,9 @@ mod tests {
                 Contactor, ElectricalBus, ElectricalBusType, Potential, PotentialOrigin,
                 PotentialTarget,
             },
-            simulation::{test::SimulationTestBed, Aircraft, SimulationElementVisitor},
+            simulation::{
+                test::SimulationTestBed, Aircraft, SimulationElementVisitor, SimulationSnapshot,
+            },
         };
         use std::time::Duration;
         use uom::si::{length::foot, power::watt};
@@ -556,12 +783,14 @@ mod tests {
         struct BatteryChargeLimiterTestBed {
             test_bed: SimulationTestBed,
             aircraft: TestAircraft,
+            events: Vec<SimulationEvent>,
         }
         impl BatteryChargeLimiterTestBed {
             fn new() -> Self {
                 Self {
                     test_bed: SimulationTestBed::new(),
                     aircraft: TestAircraft::new(Battery::half(1)),
+                    events: vec![],
                 }
             }
 
@@ -580,21 +809,27 @@ mod tests {
 
             fn run(mut self, delta: Duration) -> Self {
                 // The battery's current is updated after the BCL, thus we need two ticks.
-                self.test_bed.set_delta(Duration::from_secs(0));
-                self.test_bed.run_aircraft(&mut self.aircraft);
-
-                self.test_bed.set_delta(delta);
-                self.test_bed.run_aircraft(&mut self.aircraft);
+                self.run_aircraft(Duration::from_secs(0));
+                self.run_aircraft(delta);
 
                 // Run once more to detect programming errors where the state goes from
                 // open to closed (or vice versa) and then back to the other state within
                 // the next tick while this shouldn't happen.
-                self.test_bed.set_delta(Duration::from_secs(0));
-                self.test_bed.run_aircraft(&mut self.aircraft);
+                self.run_aircraft(Duration::from_secs(0));
 
                 self
             }
 
+            fn run_aircraft(&mut self, delta: Duration) {
+                self.test_bed.set_delta(delta);
+                self.test_bed.run_aircraft(&mut self.aircraft);
+                self.events.extend_from_slice(self.test_bed.events());
+            }
+
+            fn events(&self) -> &[SimulationEvent] {
+                &self.events
+            }
+
             fn wait_for_closed_contactor(mut self, assert_is_closed: bool) -> Self {
                 self.aircraft.set_battery_bus_at_minimum_charging_voltage();
                 self = self.run(Duration::from_millis(
@@ -612,6 +847,20 @@ mod tests {
                 self
             }
 
+            fn snapshot(&mut self) -> SimulationSnapshot {
+                self.test_bed.snapshot_aircraft(&mut self.aircraft)
+            }
+
+            fn restored_from(mut self, snapshot: &SimulationSnapshot) -> Self {
+                self.test_bed.restore_aircraft(&mut self.aircraft, snapshot);
+                self
+            }
+
+            fn initialised_to(mut self, state: StartingState) -> Self {
+                self.test_bed.initialise_aircraft(&mut self.aircraft, state);
+                self
+            }
+
             fn wait_for_bcl_startup(self) -> Self {
                 self.run(Duration::from_secs(1))
             }
@@ -929,11 +1178,13 @@ mod tests {
         }
         impl SimulationElement for TestAircraft {
             fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+                visitor.enter_children();
                 self.battery.accept(visitor);
                 self.battery_bus.accept(visitor);
                 self.battery_contactor.accept(visitor);
                 self.battery_charge_limiter.accept(visitor);
                 self.consumer.accept(visitor);
+                visitor.leave_children();
 
                 visitor.visit(self);
             }
@@ -1073,6 +1324,20 @@ mod tests {
             assert!(!test_bed.battery_contactor_is_closed());
         }
 
+        #[test]
+        fn contactor_closes_225ms_after_the_battery_bus_reaches_charging_voltage() {
+            let mut test_bed = test_bed_with().battery_bus_at_minimum_charging_voltage();
+            test_bed.test_bed.set_delta(Duration::from_millis(5));
+
+            test_bed.test_bed.assert_becomes_true_within(
+                &mut test_bed.aircraft,
+                "ELEC_CONTACTOR_TEST_IS_CLOSED",
+                Duration::from_millis(
+                    OpenContactorObserver::BATTERY_CHARGING_CLOSE_DELAY_MILLISECONDS,
+                )..=Duration::from_millis(230),
+            );
+        }
+
         #[test]
         fn contactor_not_closed_when_battery_voltage_above_charge_threshold() {
             let test_bed = test_bed_with()
@@ -1346,6 +1611,22 @@ mod tests {
             assert!(!test_bed.battery_contactor_is_closed());
         }
 
+        #[test]
+        fn complete_discharge_protection_records_an_event() {
+            let test_bed =
+                test_bed_with()
+                    .pre_discharge_protection_state()
+                    .run(Duration::from_secs(
+                        ClosedContactorObserver::BATTERY_DISCHARGE_PROTECTION_DELAY_SECONDS,
+                    ));
+
+            assert!(test_bed.events().contains(
+                &SimulationEvent::BatteryContactorOpenedDueToDischargeProtection {
+                    contactor_id: "TEST".to_owned()
+                }
+            ));
+        }
+
         #[test]
         fn complete_discharge_protection_is_reset_by_cycling_the_battery_push_button() {
             let mut test_bed =
@@ -1625,5 +1906,65 @@ mod tests {
 
             assert!(test_bed.battery_contactor_is_closed(),);
         }
+
+        #[test]
+        fn restores_closed_contactor_state_from_snapshot() {
+            let snapshot = test_bed_with().wait_for_closed_contactor(true).snapshot();
+
+            let test_bed = test_bed()
+                .restored_from(&snapshot)
+                .run(Duration::from_secs(0));
+
+            assert!(test_bed.battery_contactor_is_closed());
+        }
+
+        #[test]
+        fn restores_off_push_button_state_from_snapshot() {
+            let snapshot = test_bed_with()
+                .wait_for_closed_contactor(true)
+                .then_continue_with()
+                .battery_push_button_off()
+                .run(Duration::from_secs(0))
+                .snapshot();
+
+            let test_bed = test_bed()
+                .restored_from(&snapshot)
+                .then_continue_with()
+                .battery_push_button_auto()
+                .battery_bus_at_minimum_charging_voltage()
+                .run(Duration::from_millis(
+                    OpenContactorObserver::BATTERY_CHARGING_CLOSE_DELAY_MILLISECONDS,
+                ));
+
+            assert!(
+                !test_bed.battery_contactor_is_closed(),
+                "The BCL should still be starting up after being turned off."
+            );
+        }
+
+        #[test]
+        fn initialised_cold_and_dark_waits_for_bcl_startup() {
+            let test_bed = test_bed()
+                .initialised_to(StartingState::ColdAndDark)
+                .and()
+                .battery_bus_at_minimum_charging_voltage()
+                .run(Duration::from_millis(
+                    OpenContactorObserver::BATTERY_CHARGING_CLOSE_DELAY_MILLISECONDS,
+                ));
+
+            assert!(
+                !test_bed.battery_contactor_is_closed(),
+                "The BCL should still be starting up when starting cold and dark."
+            );
+        }
+
+        #[test]
+        fn initialised_cold_and_dark_closes_contactor_after_bcl_startup() {
+            let test_bed = test_bed()
+                .initialised_to(StartingState::ColdAndDark)
+                .wait_for_bcl_startup();
+
+            assert!(test_bed.battery_contactor_is_closed());
+        }
     }
 }
@@ -20,8 +20,11 @@ use std::{collections::HashMap, time::Duration};
 
 use super::{ElectricalBus, ElectricalBusType, Potential, PotentialOrigin, PotentialSource};
 use crate::{
-    shared::{random_number, FwcFlightPhase},
-    simulation::{SimulationElement, SimulationElementVisitor, SimulatorReader, UpdateContext},
+    shared::FwcFlightPhase,
+    simulation::{
+        DescriptionWriter, SimulationElement, SimulationElementVisitor, SimulatorReader,
+        UpdateContext, VariableIdentifier, VariableUnit,
+    },
 };
 use num_traits::FromPrimitive;
 use uom::si::{f64::*, power::watt};
@@ -57,6 +60,7 @@ impl ElectricPower {
     }
 }
 
+#[derive(Clone)]
 pub struct SuppliedPower {
     state: HashMap<ElectricalBusType, Potential>,
 }
@@ -130,6 +134,18 @@ impl PowerConsumer {
     }
 }
 impl SimulationElement for PowerConsumer {
+    fn identifier(&self) -> String {
+        match self.powered_by.first() {
+            Some(bus_type) => format!("{}_bus_consumer", bus_type.to_string().to_lowercase()),
+            None => "power_consumer".to_owned(),
+        }
+    }
+
+    fn describe(&self, writer: &mut DescriptionWriter) {
+        writer.write("demand", format!("{:.1} W", self.demand.get::<watt>()));
+        writer.write("powered", self.provided_potential.is_powered());
+    }
+
     fn receive_power(&mut self, supplied_power: &SuppliedPower) {
         self.provided_potential = self
             .powered_by
@@ -155,6 +171,7 @@ impl SimulationElement for PowerConsumer {
 pub struct FlightPhasePowerConsumer {
     consumer: PowerConsumer,
     base_demand: [Power; PowerConsumerFlightPhase::TaxiIn as usize + 1],
+    flight_phase_id: VariableIdentifier<f64>,
     current_flight_phase: PowerConsumerFlightPhase,
     update_after: Duration,
 }
@@ -163,6 +180,7 @@ impl FlightPhasePowerConsumer {
         Self {
             consumer: PowerConsumer::from(bus_type),
             base_demand: Default::default(),
+            flight_phase_id: VariableIdentifier::new("FWC_FLIGHT_PHASE", VariableUnit::Number),
             current_flight_phase: PowerConsumerFlightPhase::BeforeStart,
             update_after: Duration::from_secs(0),
         }
@@ -170,10 +188,11 @@ impl FlightPhasePowerConsumer {
 
     pub fn update(&mut self, context: &UpdateContext) {
         if self.update_after <= context.delta() {
-            self.update_after = Duration::from_secs_f64(5. + ((random_number() % 26) as f64));
+            self.update_after =
+                Duration::from_secs_f64(5. + ((context.random_number() % 26) as f64));
             let base_demand = self.base_demand[self.current_flight_phase as usize].get::<watt>();
             self.consumer.demand(Power::new::<watt>(
-                base_demand * ((90. + ((random_number() % 21) as f64)) / 100.),
+                base_demand * ((90. + ((context.random_number() % 21) as f64)) / 100.),
             ));
         } else {
             self.update_after -= context.delta();
@@ -193,14 +212,16 @@ impl FlightPhasePowerConsumer {
 }
 impl SimulationElement for FlightPhasePowerConsumer {
     fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+        visitor.enter_children();
         self.consumer.accept(visitor);
+        visitor.leave_children();
 
         visitor.visit(self);
     }
 
     fn read(&mut self, reader: &mut SimulatorReader) {
         let flight_phase: Option<FwcFlightPhase> =
-            FromPrimitive::from_f64(reader.read_f64("FWC_FLIGHT_PHASE"));
+            FromPrimitive::from_f64(reader.read(&self.flight_phase_id));
         if let Some(phase) = flight_phase {
             self.current_flight_phase = PowerConsumerFlightPhase::from(phase);
         }
@@ -539,7 +560,9 @@ mod tests {
         }
         impl SimulationElement for FlightPhasePowerConsumerTestAircraft {
             fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+                visitor.enter_children();
                 self.consumer.accept(visitor);
+                visitor.leave_children();
 
                 visitor.visit(self);
             }
@@ -605,6 +628,37 @@ mod tests {
             assert!(aircraft.consumption_within_range(input * 0.9, input * 1.1));
         }
 
+        #[test]
+        fn usage_is_the_same_for_the_same_random_seed() {
+            let consumption_for_seed = |seed: u64| {
+                let mut aircraft = FlightPhasePowerConsumerTestAircraft::new(
+                    FlightPhasePowerConsumer::from(ElectricalBusType::AlternatingCurrent(1))
+                        .demand([
+                            (
+                                PowerConsumerFlightPhase::BeforeStart,
+                                Power::new::<watt>(0.),
+                            ),
+                            (PowerConsumerFlightPhase::AfterStart, Power::new::<watt>(0.)),
+                            (PowerConsumerFlightPhase::Takeoff, Power::new::<watt>(0.)),
+                            (PowerConsumerFlightPhase::Flight, Power::new::<watt>(20000.)),
+                            (PowerConsumerFlightPhase::Landing, Power::new::<watt>(0.)),
+                            (PowerConsumerFlightPhase::TaxiIn, Power::new::<watt>(0.)),
+                        ]),
+                    true,
+                );
+
+                let mut test_bed = SimulationTestBed::new();
+                test_bed.set_random_seed(seed);
+                apply_flight_phase(&mut test_bed, FwcFlightPhase::AtOrAbove1500Feet);
+
+                test_bed.run_aircraft(&mut aircraft);
+
+                aircraft.apu_generator_consumption
+            };
+
+            assert_eq!(consumption_for_seed(7), consumption_for_seed(7));
+        }
+
         #[test]
         fn when_flight_phase_does_have_demand_but_consumer_unpowered_usage_is_zero() {
             let mut aircraft = FlightPhasePowerConsumerTestAircraft::new(
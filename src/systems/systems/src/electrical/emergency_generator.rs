@@ -4,7 +4,10 @@ use super::{
     consumption::PowerConsumptionReport, ElectricalStateWriter, Potential, PotentialOrigin,
     PotentialSource, ProvideFrequency, ProvidePotential,
 };
-use crate::simulation::{SimulationElement, SimulatorWriter, UpdateContext};
+use crate::simulation::{
+    DescriptionWriter, SimulationElement, SimulatorWriter, SnapshotReader, SnapshotWriter,
+    StartingState, UpdateContext,
+};
 use uom::si::{electric_potential::volt, f64::*, frequency::hertz};
 
 pub struct EmergencyGenerator {
@@ -71,6 +74,10 @@ impl PotentialSource for EmergencyGenerator {
 provide_frequency!(EmergencyGenerator, (390.0..=410.0));
 provide_potential!(EmergencyGenerator, (110.0..=120.0));
 impl SimulationElement for EmergencyGenerator {
+    fn describe(&self, writer: &mut DescriptionWriter) {
+        self.writer.describe_alternating(self, writer);
+    }
+
     fn process_power_consumption_report<T: PowerConsumptionReport>(&mut self, _report: &T) {
         self.output_frequency = if self.should_provide_output() {
             Frequency::new::<hertz>(400.)
@@ -88,6 +95,33 @@ impl SimulationElement for EmergencyGenerator {
     fn write(&self, writer: &mut SimulatorWriter) {
         self.writer.write_alternating(self, writer);
     }
+
+    fn snapshot(&self, writer: &mut SnapshotWriter) {
+        writer.write_bool(
+            "ELEC_EMER_GEN_STARTING_OR_STARTED",
+            self.starting_or_started,
+        );
+        writer.write_duration("ELEC_EMER_GEN_TIME_SINCE_START", self.time_since_start);
+        writer.write_bool("ELEC_EMER_GEN_SUPPLYING", self.supplying);
+    }
+
+    fn restore(&mut self, reader: &SnapshotReader) {
+        if let Some(starting_or_started) = reader.read_bool("ELEC_EMER_GEN_STARTING_OR_STARTED") {
+            self.starting_or_started = starting_or_started;
+        }
+
+        if let Some(time_since_start) = reader.read_duration("ELEC_EMER_GEN_TIME_SINCE_START") {
+            self.time_since_start = time_since_start;
+        }
+
+        if let Some(supplying) = reader.read_bool("ELEC_EMER_GEN_SUPPLYING") {
+            self.supplying = supplying;
+        }
+    }
+
+    fn initialise(&mut self, _: StartingState) {
+        *self = EmergencyGenerator::new();
+    }
 }
 impl Default for EmergencyGenerator {
     fn default() -> Self {
@@ -177,7 +211,9 @@ mod emergency_generator_tests {
     }
     impl SimulationElement for TestAircraft {
         fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+            visitor.enter_children();
             self.emer_gen.accept(visitor);
+            visitor.leave_children();
 
             visitor.visit(self);
         }
@@ -3,10 +3,15 @@ use super::{
     PotentialSource, ProvideFrequency, ProvideLoad, ProvidePotential,
 };
 use crate::{
+    failures::{Failure, FailureType},
     shared::calculate_towards_target_temperature,
-    simulation::{SimulationElement, SimulationElementVisitor, SimulatorWriter, UpdateContext},
+    simulation::{
+        DescriptionWriter, SimulationElement, SimulationElementVisitor, SimulatorWriter,
+        SnapshotReader, SnapshotWriter, StartingState, UpdateContext, UpdatePeriod,
+        VariableIdentifier, VariableUnit,
+    },
 };
-use std::cmp::min;
+use std::{cmp::min, time::Duration};
 use uom::si::{
     electric_potential::volt, f64::*, frequency::hertz, power::watt, ratio::percent,
     thermodynamic_temperature::degree_celsius,
@@ -23,6 +28,7 @@ pub struct EngineGenerator {
     writer: ElectricalStateWriter,
     number: usize,
     idg: IntegratedDriveGenerator,
+    failure: Failure,
     output_frequency: Frequency,
     output_potential: ElectricPotential,
     load: Ratio,
@@ -33,6 +39,7 @@ impl EngineGenerator {
             writer: ElectricalStateWriter::new(&format!("ENG_GEN_{}", number)),
             number,
             idg: IntegratedDriveGenerator::new(number),
+            failure: Failure::new(FailureType::EngineGenerator(number)),
             output_frequency: Frequency::new::<hertz>(0.),
             output_potential: ElectricPotential::new::<volt>(0.),
             load: Ratio::new::<percent>(0.),
@@ -58,7 +65,21 @@ impl EngineGenerator {
     }
 
     fn should_provide_output(&self) -> bool {
-        self.idg.provides_stable_power_output()
+        !self.failure.is_active() && self.idg.provides_stable_power_output()
+    }
+
+    fn update_potential_and_frequency(&mut self) {
+        self.output_frequency = if self.should_provide_output() {
+            Frequency::new::<hertz>(400.)
+        } else {
+            Frequency::new::<hertz>(0.)
+        };
+
+        self.output_potential = if self.should_provide_output() {
+            ElectricPotential::new::<volt>(115.)
+        } else {
+            ElectricPotential::new::<volt>(0.)
+        };
     }
 }
 impl PotentialSource for EngineGenerator {
@@ -78,23 +99,24 @@ provide_frequency!(EngineGenerator, (390.0..=410.0));
 provide_load!(EngineGenerator);
 impl SimulationElement for EngineGenerator {
     fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+        visitor.enter_children();
         self.idg.accept(visitor);
+        self.failure.accept(visitor);
+        visitor.leave_children();
 
         visitor.visit(self);
     }
 
-    fn process_power_consumption_report<T: PowerConsumptionReport>(&mut self, report: &T) {
-        self.output_frequency = if self.should_provide_output() {
-            Frequency::new::<hertz>(400.)
-        } else {
-            Frequency::new::<hertz>(0.)
-        };
+    fn identifier(&self) -> String {
+        format!("engine_generator_{}", self.number)
+    }
 
-        self.output_potential = if self.should_provide_output() {
-            ElectricPotential::new::<volt>(115.)
-        } else {
-            ElectricPotential::new::<volt>(0.)
-        };
+    fn describe(&self, writer: &mut DescriptionWriter) {
+        self.writer.describe_alternating_with_load(self, writer);
+    }
+
+    fn process_power_consumption_report<T: PowerConsumptionReport>(&mut self, report: &T) {
+        self.update_potential_and_frequency();
 
         let power_consumption = report
             .total_consumption_of(PotentialOrigin::EngineGenerator(self.number))
@@ -109,16 +131,24 @@ impl SimulationElement for EngineGenerator {
     fn write(&self, writer: &mut SimulatorWriter) {
         self.writer.write_alternating_with_load(self, writer);
     }
+
+    fn initialise(&mut self, _: StartingState) {
+        // The IDG is visited first, thus we already know if it provides stable output.
+        self.update_potential_and_frequency();
+        self.load = Ratio::new::<percent>(0.);
+    }
 }
 
 struct IntegratedDriveGenerator {
-    oil_outlet_temperature_id: String,
+    oil_outlet_temperature_id: VariableIdentifier<ThermodynamicTemperature>,
     oil_outlet_temperature: ThermodynamicTemperature,
-    is_connected_id: String,
+    is_connected_id: VariableIdentifier<bool>,
     connected: bool,
     number: usize,
 
+    time_above_threshold_id: String,
     time_above_threshold_in_milliseconds: u64,
+    temperature_update_period: UpdatePeriod,
 }
 impl IntegratedDriveGenerator {
     pub const ENGINE_N2_POWER_UP_OUTPUT_THRESHOLD: f64 = 58.;
@@ -126,16 +156,25 @@ impl IntegratedDriveGenerator {
 
     fn new(number: usize) -> IntegratedDriveGenerator {
         IntegratedDriveGenerator {
-            oil_outlet_temperature_id: format!(
-                "ELEC_ENG_GEN_{}_IDG_OIL_OUTLET_TEMPERATURE",
-                number
+            oil_outlet_temperature_id: VariableIdentifier::new(
+                format!("ELEC_ENG_GEN_{}_IDG_OIL_OUTLET_TEMPERATURE", number),
+                VariableUnit::DegreeCelsius,
             ),
             oil_outlet_temperature: ThermodynamicTemperature::new::<degree_celsius>(0.),
-            is_connected_id: format!("ELEC_ENG_GEN_{}_IDG_IS_CONNECTED", number),
+            is_connected_id: VariableIdentifier::new(
+                format!("ELEC_ENG_GEN_{}_IDG_IS_CONNECTED", number),
+                VariableUnit::Bool,
+            ),
             connected: true,
             number,
 
+            time_above_threshold_id: format!(
+                "ELEC_ENG_GEN_{}_IDG_TIME_ABOVE_THRESHOLD_IN_MILLISECONDS",
+                number
+            ),
             time_above_threshold_in_milliseconds: 0,
+            // The oil temperature changes slowly, thus doesn't need updating every step.
+            temperature_update_period: UpdatePeriod::new(Duration::from_secs(1)),
         }
     }
 
@@ -150,10 +189,12 @@ impl IntegratedDriveGenerator {
         }
 
         self.update_stable_time(context, arguments.engine_corrected_n2(self.number));
-        self.update_temperature(
-            context,
-            self.get_target_temperature(context, arguments.engine_corrected_n2(self.number)),
-        );
+        if let Some(context) = self.temperature_update_period.due(context) {
+            self.update_temperature(
+                &context,
+                self.get_target_temperature(&context, arguments.engine_corrected_n2(self.number)),
+            );
+        }
     }
 
     fn provides_stable_power_output(&self) -> bool {
@@ -230,12 +271,56 @@ impl IntegratedDriveGenerator {
     }
 }
 impl SimulationElement for IntegratedDriveGenerator {
+    fn update_period(&mut self) -> Option<&mut UpdatePeriod> {
+        Some(&mut self.temperature_update_period)
+    }
+
     fn write(&self, writer: &mut SimulatorWriter) {
+        writer.write(&self.oil_outlet_temperature_id, self.oil_outlet_temperature);
+        writer.write(&self.is_connected_id, self.connected);
+    }
+
+    fn snapshot(&self, writer: &mut SnapshotWriter) {
         writer.write_f64(
-            &self.oil_outlet_temperature_id,
+            self.oil_outlet_temperature_id.name(),
             self.oil_outlet_temperature.get::<degree_celsius>(),
         );
-        writer.write_bool(&self.is_connected_id, self.connected);
+        writer.write_bool(self.is_connected_id.name(), self.connected);
+        writer.write_f64(
+            &self.time_above_threshold_id,
+            self.time_above_threshold_in_milliseconds as f64,
+        );
+    }
+
+    fn restore(&mut self, reader: &SnapshotReader) {
+        if let Some(temperature) = reader.read_f64(self.oil_outlet_temperature_id.name()) {
+            self.oil_outlet_temperature =
+                ThermodynamicTemperature::new::<degree_celsius>(temperature);
+        }
+
+        if let Some(connected) = reader.read_bool(self.is_connected_id.name()) {
+            self.connected = connected;
+        }
+
+        if let Some(time) = reader.read_f64(&self.time_above_threshold_id) {
+            self.time_above_threshold_in_milliseconds = clamp(
+                time as u64,
+                0,
+                INTEGRATED_DRIVE_GENERATOR_STABILIZATION_TIME_IN_MILLISECONDS,
+            );
+        }
+
+        self.temperature_update_period.reset();
+    }
+
+    fn initialise(&mut self, state: StartingState) {
+        self.temperature_update_period.reset();
+        self.connected = true;
+        self.time_above_threshold_in_milliseconds = if state.engines_are_running() {
+            INTEGRATED_DRIVE_GENERATOR_STABILIZATION_TIME_IN_MILLISECONDS
+        } else {
+            0
+        };
     }
 }
 
@@ -302,6 +387,15 @@ mod tests {
                 self.test_bed.run_aircraft(aircraft);
             }
 
+            fn initialise_aircraft<T: Aircraft>(&mut self, aircraft: &mut T, state: StartingState) {
+                self.test_bed.initialise_aircraft(aircraft, state);
+            }
+
+            fn fail_generator(&mut self) {
+                self.test_bed
+                    .activate_failure(&FailureType::EngineGenerator(1));
+            }
+
             fn frequency_is_normal(&mut self) -> bool {
                 self.test_bed.read_bool("ELEC_ENG_GEN_1_FREQUENCY_NORMAL")
             }
@@ -404,13 +498,36 @@ mod tests {
         }
         impl SimulationElement for TestAircraft {
             fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+                visitor.enter_children();
                 self.engine_gen.accept(visitor);
                 self.consumer.accept(visitor);
+                visitor.leave_children();
 
                 visitor.visit(self);
             }
         }
 
+        #[test]
+        fn when_initialised_with_running_engines_output_within_normal_parameters_before_first_tick()
+        {
+            let mut aircraft = TestAircraft::with_running_engine();
+            let mut test_bed = EngineGeneratorTestBed::new();
+
+            test_bed.initialise_aircraft(&mut aircraft, StartingState::Cruise);
+
+            assert!(aircraft.generator_output_within_normal_parameters_after_processing_power_consumption_report());
+        }
+
+        #[test]
+        fn when_initialised_with_shutdown_engines_output_not_within_normal_parameters() {
+            let mut aircraft = TestAircraft::with_shutdown_engine();
+            let mut test_bed = EngineGeneratorTestBed::new();
+
+            test_bed.initialise_aircraft(&mut aircraft, StartingState::ColdAndDark);
+
+            assert!(!aircraft.generator_output_within_normal_parameters_after_processing_power_consumption_report());
+        }
+
         #[test]
         fn when_engine_running_provides_output() {
             let mut aircraft = TestAircraft::with_running_engine();
@@ -442,6 +559,17 @@ mod tests {
             assert!(!aircraft.generator_is_powered());
         }
 
+        #[test]
+        fn when_engine_running_but_generator_failed_provides_no_output() {
+            let mut aircraft = TestAircraft::with_running_engine();
+            let mut test_bed = EngineGeneratorTestBed::new();
+
+            test_bed.fail_generator();
+            test_bed.run_aircraft(&mut aircraft);
+
+            assert!(!aircraft.generator_is_powered());
+        }
+
         #[test]
         fn when_engine_shutdown_frequency_not_normal() {
             let mut aircraft = TestAircraft::with_shutdown_engine();
@@ -734,5 +862,75 @@ mod tests {
 
             assert!(idg.oil_outlet_temperature < starting_temperature);
         }
+
+        #[test]
+        fn restores_temperature_connection_and_stability_from_snapshot() {
+            let mut original = idg();
+            let mut test_bed = SimulationTestBed::new_with_delta(Duration::from_secs(10));
+
+            test_bed.run(&mut original, |element, context| {
+                element.update(
+                    context,
+                    &UpdateArguments::new(Ratio::new::<percent>(80.), false),
+                )
+            });
+
+            let snapshot = test_bed.snapshot(&mut original);
+            let mut restored = idg();
+            test_bed.restore(&mut restored, &snapshot);
+
+            assert_eq!(
+                restored.oil_outlet_temperature,
+                original.oil_outlet_temperature
+            );
+            assert_eq!(restored.connected, original.connected);
+            assert!(restored.provides_stable_power_output());
+        }
+
+        #[test]
+        fn restores_disconnected_idg_from_snapshot() {
+            let mut original = idg();
+            let mut test_bed = SimulationTestBed::new();
+
+            test_bed.run(&mut original, |element, context| {
+                element.update(
+                    context,
+                    &UpdateArguments::new(Ratio::new::<percent>(80.), true),
+                )
+            });
+
+            let snapshot = test_bed.snapshot(&mut original);
+            let mut restored = idg();
+            test_bed.restore(&mut restored, &snapshot);
+
+            assert!(!restored.connected);
+        }
+
+        #[test]
+        fn initialised_with_running_engines_provides_stable_power_output() {
+            let mut idg = idg();
+            let mut test_bed = SimulationTestBed::new();
+
+            test_bed.initialise(&mut idg, StartingState::RunwayReady);
+
+            assert!(idg.provides_stable_power_output());
+        }
+
+        #[test]
+        fn initialised_with_shutdown_engines_reconnects_and_does_not_provide_stable_power_output() {
+            let mut idg = idg();
+            let mut test_bed = SimulationTestBed::new();
+            test_bed.run(&mut idg, |element, context| {
+                element.update(
+                    context,
+                    &UpdateArguments::new(Ratio::new::<percent>(80.), true),
+                )
+            });
+
+            test_bed.initialise(&mut idg, StartingState::GateWithApu);
+
+            assert!(idg.connected);
+            assert!(!idg.provides_stable_power_output());
+        }
     }
 }
@@ -1,4 +1,7 @@
-use crate::simulation::{SimulationElement, SimulatorReader, SimulatorWriter, UpdateContext};
+use crate::simulation::{
+    DescriptionWriter, SimulationElement, SimulatorReader, SimulatorWriter, UpdateContext,
+    VariableIdentifier, VariableUnit,
+};
 use uom::si::{electric_potential::volt, f64::*, frequency::hertz};
 
 use super::{
@@ -8,6 +11,7 @@ use super::{
 
 pub struct ExternalPowerSource {
     writer: ElectricalStateWriter,
+    is_connected_id: VariableIdentifier<bool>,
     is_connected: bool,
     output_frequency: Frequency,
     output_potential: ElectricPotential,
@@ -16,6 +20,10 @@ impl ExternalPowerSource {
     pub fn new() -> ExternalPowerSource {
         ExternalPowerSource {
             writer: ElectricalStateWriter::new("EXT_PWR"),
+            is_connected_id: VariableIdentifier::new(
+                "EXTERNAL POWER AVAILABLE:1",
+                VariableUnit::Bool,
+            ),
             is_connected: false,
             output_frequency: Frequency::new::<hertz>(0.),
             output_potential: ElectricPotential::new::<volt>(0.),
@@ -47,8 +55,12 @@ impl PotentialSource for ExternalPowerSource {
 provide_potential!(ExternalPowerSource, (110.0..=120.0));
 provide_frequency!(ExternalPowerSource, (390.0..=410.0));
 impl SimulationElement for ExternalPowerSource {
+    fn describe(&self, writer: &mut DescriptionWriter) {
+        self.writer.describe_alternating(self, writer);
+    }
+
     fn read(&mut self, reader: &mut SimulatorReader) {
-        self.is_connected = reader.read_bool("EXTERNAL POWER AVAILABLE:1");
+        self.is_connected = reader.read(&self.is_connected_id);
     }
 
     fn write(&self, writer: &mut SimulatorWriter) {
@@ -156,7 +168,10 @@ mod external_power_source_tests {
     }
     impl SimulationElement for TestAircraft {
         fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+            visitor.enter_children();
             self.ext_pwr.accept(visitor);
+            visitor.leave_children();
+
             visitor.visit(self);
         }
     }
@@ -3,7 +3,7 @@ use super::{
     ElectricalStateWriter, Potential, PotentialOrigin, PotentialSource, PotentialTarget,
     ProvideFrequency, ProvidePotential,
 };
-use crate::simulation::{SimulationElement, SimulatorWriter};
+use crate::simulation::{DescriptionWriter, SimulationElement, SimulatorWriter};
 use uom::si::{electric_potential::volt, f64::*, frequency::hertz};
 
 pub struct StaticInverter {
@@ -43,6 +43,10 @@ impl PotentialSource for StaticInverter {
 provide_potential!(StaticInverter, (110.0..=120.0));
 provide_frequency!(StaticInverter, (390.0..=410.0));
 impl SimulationElement for StaticInverter {
+    fn describe(&self, writer: &mut DescriptionWriter) {
+        self.writer.describe_alternating(self, writer);
+    }
+
     fn write(&self, writer: &mut SimulatorWriter) {
         self.writer.write_alternating(self, writer);
     }
@@ -184,8 +188,10 @@ mod static_inverter_tests {
     }
     impl SimulationElement for TestAircraft {
         fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+            visitor.enter_children();
             self.static_inverter.accept(visitor);
             self.consumer.accept(visitor);
+            visitor.leave_children();
 
             visitor.visit(self);
         }
@@ -0,0 +1,1380 @@
+use super::{Contactor, ElectricalBus, ElectricalBusType, Potential, PotentialTarget};
+use crate::simulation::{SimulationElement, SimulationElementVisitor};
+use std::{collections::HashMap, error::Error, fmt, ops::Range};
+
+/// Describes the buses, contactors, sources and converters of an electrical system and
+/// the way in which they are connected. An [`ElectricalNetwork`] built from the topology
+/// powers the buses and opens and closes the contactors, such that an aircraft doesn't
+/// have to wire these by hand.
+///
+/// Topologies are line based. Empty lines and everything following a `#` is ignored.
+/// Lines contain one of the following:
+///
+/// ```text
+/// source GEN_1                             # Potential provided by the aircraft.
+/// converter TR_1 from AC_1                 # Converts its input, e.g. a transformer rectifier.
+/// contactor 9XU1 from GEN_1 when gen_1_on  # A contactor with its real identifier.
+/// bus AC_1 from 9XU1, 11XU1                # A bus, named after its `ElectricalBusType`.
+/// ```
+///
+/// An element with multiple inputs merges their potential, in the same way as
+/// `PotentialTarget::or_powered_by` does. Inputs may refer to elements declared further
+/// down the topology, which allows bus tie contactors to power each other.
+///
+/// The closing condition of a contactor combines signals provided by the aircraft,
+/// `powered(NAME)` and `closed(ID)` using `not`, `and`, `or` and parentheses, e.g.
+/// `when gen_1_on and not (closed(3XS) or powered(AC_2))`.
+///
+/// A topology can be split into stages using `stage NAME` lines. The elements following
+/// such a line belong to that stage, and an aircraft updates the stages one by one. This
+/// allows the aircraft to update the parts it models itself, such as a battery charge
+/// limiter, based on the state of the earlier stages. Elements may only refer to elements
+/// of the same or an earlier stage. A topology without stages consists of a single stage.
+///
+/// [`ElectricalNetwork`]: struct.ElectricalNetwork.html
+#[derive(Clone, Debug, PartialEq)]
+pub struct ElectricalTopology {
+    nodes: Vec<Node>,
+    stages: Vec<Stage>,
+}
+impl ElectricalTopology {
+    pub fn parse(text: &str) -> Result<Self, ElectricalTopologyError> {
+        let mut declarations: Vec<Declaration> = vec![];
+        let mut indices = HashMap::new();
+        let mut stages: Vec<Stage> = vec![];
+
+        for (index, line) in text.lines().enumerate() {
+            let line_number = index + 1;
+            let line = match line.find('#') {
+                Some(comment_start) => &line[..comment_start],
+                None => line,
+            }
+            .trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            if let ("stage", name) = split_keyword(line) {
+                Stage::begin(&mut stages, name, line_number, declarations.len())
+                    .map_err(|message| ElectricalTopologyError::new(line_number, message))?;
+                continue;
+            }
+
+            let declaration = Declaration::parse(line_number, line, stages.len())
+                .map_err(|message| ElectricalTopologyError::new(line_number, message))?;
+            if indices
+                .insert(declaration.name, declarations.len())
+                .is_some()
+            {
+                return Err(ElectricalTopologyError::new(
+                    line_number,
+                    format!("'{}' is declared more than once", declaration.name),
+                ));
+            }
+
+            declarations.push(declaration);
+        }
+
+        if let Some(stage) = stages.iter_mut().last() {
+            stage.end = declarations.len();
+        }
+        if let Some(stage) = stages.iter().find(|stage| stage.start == stage.end) {
+            return Err(ElectricalTopologyError::new(
+                stage.line,
+                format!("stage '{}' is empty", stage.name),
+            ));
+        }
+        if stages.is_empty() {
+            stages.push(Stage::new("", 0, 0..declarations.len()));
+        }
+
+        let nodes = declarations
+            .iter()
+            .map(|declaration| {
+                declaration
+                    .resolve(&declarations, &indices)
+                    .map_err(|message| ElectricalTopologyError::new(declaration.line, message))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { nodes, stages })
+    }
+
+    fn index_of(&self, name: &str) -> Option<usize> {
+        self.nodes.iter().position(|node| node.name == name)
+    }
+
+    fn stage(&self, name: &str) -> Option<&Stage> {
+        self.stages.iter().find(|stage| stage.name == name)
+    }
+}
+
+/// A contiguous range of the nodes of a topology, which is updated as a whole.
+#[derive(Clone, Debug, PartialEq)]
+struct Stage {
+    name: String,
+    line: usize,
+    start: usize,
+    end: usize,
+}
+impl Stage {
+    fn new(name: &str, line: usize, nodes: Range<usize>) -> Self {
+        Self {
+            name: name.to_owned(),
+            line,
+            start: nodes.start,
+            end: nodes.end,
+        }
+    }
+
+    /// Ends the current stage and begins a new one at the given node.
+    fn begin(stages: &mut Vec<Stage>, name: &str, line: usize, start: usize) -> Result<(), String> {
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            return Err(format!("'stage {}' is not a valid stage", name));
+        }
+        if stages.iter().any(|stage| stage.name == name) {
+            return Err(format!("stage '{}' is declared more than once", name));
+        }
+        match stages.iter_mut().last() {
+            Some(previous) => previous.end = start,
+            None if start > 0 => {
+                return Err(format!(
+                    "the elements before stage '{}' don't belong to a stage",
+                    name
+                ))
+            }
+            None => {}
+        }
+
+        stages.push(Stage::new(name, line, start..start));
+
+        Ok(())
+    }
+
+    fn nodes(&self) -> Range<usize> {
+        self.start..self.end
+    }
+}
+
+/// The reason an [`ElectricalTopology`] couldn't be parsed.
+///
+/// [`ElectricalTopology`]: struct.ElectricalTopology.html
+#[derive(Clone, Debug, PartialEq)]
+pub struct ElectricalTopologyError {
+    line: usize,
+    message: String,
+}
+impl ElectricalTopologyError {
+    fn new(line: usize, message: String) -> Self {
+        Self { line, message }
+    }
+
+    /// The (1-based) number of the line which contains the error.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+}
+impl fmt::Display for ElectricalTopologyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+impl Error for ElectricalTopologyError {}
+
+/// Provides the parts of an [`ElectricalNetwork`] which are modelled by the aircraft
+/// instead of the topology: the potential of sources, the behaviour of converters and
+/// the signals used in the closing conditions of contactors.
+///
+/// [`ElectricalNetwork`]: struct.ElectricalNetwork.html
+///
+/// Each method returns `None` when the aircraft doesn't provide an element with the
+/// given name, in which case the network fails to update.
+pub trait ElectricalNetworkElements {
+    /// The potential of the source with the given name.
+    fn source_potential(&self, name: &str) -> Option<Potential>;
+
+    /// Powers the converter with the given name and returns its output. A converter can
+    /// be powered multiple times within a single update of the network, as the network
+    /// propagates potential until it no longer changes.
+    fn convert(&mut self, name: &str, input: Potential) -> Option<Potential>;
+
+    /// The value of the signal with the given name.
+    fn signal(&self, name: &str) -> Option<bool>;
+}
+
+/// The reason an [`ElectricalNetwork`] couldn't be updated.
+///
+/// [`ElectricalNetwork`]: struct.ElectricalNetwork.html
+#[derive(Clone, Debug, PartialEq)]
+pub enum ElectricalNetworkError {
+    /// The topology doesn't contain the stage.
+    UnknownStage { stage: String },
+    /// The aircraft doesn't provide the source.
+    MissingSource { name: String },
+    /// The aircraft doesn't provide the converter.
+    MissingConverter { name: String },
+    /// The aircraft doesn't provide the signal.
+    MissingSignal { name: String },
+}
+impl fmt::Display for ElectricalNetworkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ElectricalNetworkError::UnknownStage { stage } => {
+                write!(f, "the topology doesn't contain stage '{}'", stage)
+            }
+            ElectricalNetworkError::MissingSource { name } => {
+                write!(f, "source '{}' isn't provided", name)
+            }
+            ElectricalNetworkError::MissingConverter { name } => {
+                write!(f, "converter '{}' isn't provided", name)
+            }
+            ElectricalNetworkError::MissingSignal { name } => {
+                write!(f, "signal '{}' isn't provided", name)
+            }
+        }
+    }
+}
+impl Error for ElectricalNetworkError {}
+
+/// An electrical network consisting of the buses and contactors described
+/// by an [`ElectricalTopology`].
+///
+/// On every update the network propagates potential from the sources through
+/// the contactors, converters and buses until the state of the network no longer
+/// changes. Only then are the contactors opened or closed, such that they don't
+/// raise events for intermediate states. When the topology consists of multiple stages,
+/// the aircraft can update them one by one using [`update_stage`].
+///
+/// [`ElectricalTopology`]: struct.ElectricalTopology.html
+/// [`update_stage`]: #method.update_stage
+pub struct ElectricalNetwork {
+    topology: ElectricalTopology,
+    elements: Vec<NetworkElement>,
+    inputs: Vec<Potential>,
+    outputs: Vec<Potential>,
+    closed: Vec<bool>,
+}
+impl ElectricalNetwork {
+    pub fn new(topology: ElectricalTopology) -> Self {
+        let elements: Vec<_> = topology
+            .nodes
+            .iter()
+            .map(|node| match node.kind {
+                NodeKind::Contactor(_) => NetworkElement::Contactor(Contactor::new(&node.name)),
+                NodeKind::Bus(bus_type) => NetworkElement::Bus(ElectricalBus::new(bus_type)),
+                NodeKind::Source | NodeKind::Converter => NetworkElement::Other,
+            })
+            .collect();
+        let count = elements.len();
+
+        Self {
+            topology,
+            elements,
+            inputs: vec![Potential::none(); count],
+            outputs: vec![Potential::none(); count],
+            closed: vec![false; count],
+        }
+    }
+
+    /// Updates every stage of the network in order.
+    pub fn update<T: ElectricalNetworkElements>(
+        &mut self,
+        elements: &mut T,
+    ) -> Result<(), ElectricalNetworkError> {
+        for index in 0..self.topology.stages.len() {
+            self.update_nodes(self.topology.stages[index].nodes(), elements)?;
+        }
+
+        Ok(())
+    }
+
+    /// Updates the stage with the given name. The stages which precede it
+    /// should already have been updated.
+    ///
+    /// When the aircraft doesn't provide one of the elements the stage refers to,
+    /// the buses and contactors of the stage keep their state.
+    pub fn update_stage<T: ElectricalNetworkElements>(
+        &mut self,
+        stage: &str,
+        elements: &mut T,
+    ) -> Result<(), ElectricalNetworkError> {
+        let nodes = self
+            .topology
+            .stage(stage)
+            .map(Stage::nodes)
+            .ok_or_else(|| ElectricalNetworkError::UnknownStage {
+                stage: stage.to_owned(),
+            })?;
+
+        self.update_nodes(nodes, elements)
+    }
+
+    fn update_nodes<T: ElectricalNetworkElements>(
+        &mut self,
+        nodes: Range<usize>,
+        elements: &mut T,
+    ) -> Result<(), ElectricalNetworkError> {
+        for index in nodes.clone() {
+            self.outputs[index] = Potential::none();
+            if let NetworkElement::Contactor(contactor) = &self.elements[index] {
+                self.closed[index] = contactor.is_closed();
+            }
+        }
+
+        // A network settles within a few passes when its elements are declared in the
+        // direction in which potential flows. Should the closing conditions keep the
+        // network from settling, the state of the last pass is used.
+        for _ in 0..=nodes.len() {
+            if !self.propagate(nodes.clone(), elements)? {
+                break;
+            }
+        }
+
+        for index in nodes {
+            match &mut self.elements[index] {
+                NetworkElement::Contactor(contactor) => {
+                    contactor.close_when(self.closed[index]);
+                    contactor.powered_by(&self.inputs[index]);
+                }
+                NetworkElement::Bus(bus) => bus.powered_by(&self.outputs[index]),
+                NetworkElement::Other => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Propagates potential through the given nodes once. Returns whether or not
+    /// the state of the network changed.
+    fn propagate<T: ElectricalNetworkElements>(
+        &mut self,
+        nodes: Range<usize>,
+        elements: &mut T,
+    ) -> Result<bool, ElectricalNetworkError> {
+        let mut changed = false;
+        for index in nodes {
+            let node = &self.topology.nodes[index];
+            let input = node
+                .inputs
+                .iter()
+                .fold(Potential::none(), |potential, &input| {
+                    potential.merge(&self.outputs[input])
+                });
+
+            let output = match &node.kind {
+                NodeKind::Source => elements.source_potential(&node.name).ok_or_else(|| {
+                    ElectricalNetworkError::MissingSource {
+                        name: node.name.clone(),
+                    }
+                })?,
+                NodeKind::Converter => elements.convert(&node.name, input).ok_or_else(|| {
+                    ElectricalNetworkError::MissingConverter {
+                        name: node.name.clone(),
+                    }
+                })?,
+                NodeKind::Contactor(condition) => {
+                    let closed = match &self.elements[index] {
+                        NetworkElement::Contactor(contactor) if contactor.is_stuck() => {
+                            contactor.is_closed()
+                        }
+                        _ => condition.evaluate(&self.outputs, &self.closed, elements)?,
+                    };
+                    changed |= closed != self.closed[index];
+                    self.closed[index] = closed;
+
+                    if closed {
+                        input
+                    } else {
+                        Potential::none()
+                    }
+                }
+                NodeKind::Bus(_) => input,
+            };
+
+            changed |= !is_same_potential(&output, &self.outputs[index]);
+            self.inputs[index] = input;
+            self.outputs[index] = output;
+        }
+
+        Ok(changed)
+    }
+
+    /// # Panics
+    ///
+    /// Panics when the topology doesn't contain a bus of the given type.
+    pub fn bus(&self, bus_type: ElectricalBusType) -> &ElectricalBus {
+        self.elements
+            .iter()
+            .find_map(|element| match element {
+                NetworkElement::Bus(bus) if bus.bus_type() == bus_type => Some(bus),
+                _ => None,
+            })
+            .unwrap_or_else(|| panic!("The topology doesn't contain the {} bus.", bus_type))
+    }
+
+    /// # Panics
+    ///
+    /// Panics when the topology doesn't contain a contactor with the given identifier.
+    pub fn contactor(&self, id: &str) -> &Contactor {
+        match self
+            .topology
+            .index_of(id)
+            .map(|index| &self.elements[index])
+        {
+            Some(NetworkElement::Contactor(contactor)) => contactor,
+            _ => panic!("The topology doesn't contain the {} contactor.", id),
+        }
+    }
+
+    /// The potential provided by the element with the given name, as of the last update.
+    ///
+    /// # Panics
+    ///
+    /// Panics when the topology doesn't contain an element with the given name.
+    pub fn output(&self, name: &str) -> Potential {
+        match self.topology.index_of(name) {
+            Some(index) => self.outputs[index],
+            None => panic!("The topology doesn't contain an element named {}.", name),
+        }
+    }
+}
+impl SimulationElement for ElectricalNetwork {
+    fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+        visitor.enter_children();
+        for element in self.elements.iter_mut() {
+            match element {
+                NetworkElement::Contactor(contactor) => contactor.accept(visitor),
+                NetworkElement::Bus(bus) => bus.accept(visitor),
+                NetworkElement::Other => {}
+            }
+        }
+        visitor.leave_children();
+
+        visitor.visit(self);
+    }
+
+    fn identifier(&self) -> String {
+        "electrical_network".to_owned()
+    }
+}
+
+enum NetworkElement {
+    Contactor(Contactor),
+    Bus(ElectricalBus),
+    Other,
+}
+
+/// `Potential` only compares the raw potential, while the network
+/// also has to know when the origins of the potential change.
+fn is_same_potential(left: &Potential, right: &Potential) -> bool {
+    left.raw() == right.raw() && left.origins().eq(right.origins())
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Node {
+    name: String,
+    kind: NodeKind,
+    inputs: Vec<usize>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum NodeKind {
+    Source,
+    Converter,
+    Contactor(Condition),
+    Bus(ElectricalBusType),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Condition {
+    Signal(String),
+    Powered(usize),
+    Closed(usize),
+    Not(Box<Condition>),
+    And(Box<Condition>, Box<Condition>),
+    Or(Box<Condition>, Box<Condition>),
+}
+impl Condition {
+    /// Evaluates both sides of `and` and `or`, such that a signal which isn't
+    /// provided is found regardless of the state of the network.
+    fn evaluate<T: ElectricalNetworkElements>(
+        &self,
+        outputs: &[Potential],
+        closed: &[bool],
+        elements: &T,
+    ) -> Result<bool, ElectricalNetworkError> {
+        Ok(match self {
+            Condition::Signal(name) => elements
+                .signal(name)
+                .ok_or_else(|| ElectricalNetworkError::MissingSignal { name: name.clone() })?,
+            Condition::Powered(index) => outputs[*index].is_powered(),
+            Condition::Closed(index) => closed[*index],
+            Condition::Not(condition) => !condition.evaluate(outputs, closed, elements)?,
+            Condition::And(left, right) => {
+                let left = left.evaluate(outputs, closed, elements)?;
+                left & right.evaluate(outputs, closed, elements)?
+            }
+            Condition::Or(left, right) => {
+                let left = left.evaluate(outputs, closed, elements)?;
+                left | right.evaluate(outputs, closed, elements)?
+            }
+        })
+    }
+}
+
+/// A line of the topology, before the names it refers to are resolved.
+struct Declaration<'a> {
+    line: usize,
+    stage: usize,
+    keyword: &'a str,
+    name: &'a str,
+    inputs: Vec<&'a str>,
+    condition: Option<&'a str>,
+}
+impl<'a> Declaration<'a> {
+    fn parse(line_number: usize, line: &'a str, stage: usize) -> Result<Self, String> {
+        let (keyword, rest) = split_keyword(line);
+        let (name, rest) = split_keyword(rest);
+        if name.is_empty() {
+            return Err(format!("'{}' is missing a name", line));
+        }
+
+        let (inputs, condition) = match rest.strip_prefix("from") {
+            Some(rest) if rest.starts_with(char::is_whitespace) => match find_word(rest, "when") {
+                Some(index) => (&rest[..index], Some(rest[index + "when".len()..].trim())),
+                None => (rest, None),
+            },
+            _ if rest.is_empty() => ("", None),
+            _ => return Err(format!("unexpected '{}'", rest)),
+        };
+        let inputs: Vec<_> = inputs
+            .split(',')
+            .map(str::trim)
+            .filter(|input| !input.is_empty())
+            .collect();
+
+        match keyword {
+            "source" if inputs.is_empty() => {}
+            "source" => return Err(format!("source '{}' cannot have inputs", name)),
+            "converter" | "bus" | "contactor" if inputs.is_empty() => {
+                return Err(format!("{} '{}' is missing its inputs", keyword, name))
+            }
+            "converter" | "bus" | "contactor" => {}
+            _ => return Err(format!("unknown element '{}'", keyword)),
+        }
+
+        match (keyword, condition) {
+            ("contactor", None) | ("contactor", Some("")) => Err(format!(
+                "contactor '{}' is missing its closing condition",
+                name
+            )),
+            ("contactor", _) | (_, None) => Ok(Self {
+                line: line_number,
+                stage,
+                keyword,
+                name,
+                inputs,
+                condition,
+            }),
+            (_, Some(_)) => Err(format!("{} '{}' cannot have a condition", keyword, name)),
+        }
+    }
+
+    fn resolve(
+        &self,
+        declarations: &[Declaration],
+        indices: &HashMap<&str, usize>,
+    ) -> Result<Node, String> {
+        let inputs = self
+            .inputs
+            .iter()
+            .map(|&input| resolve_name(declarations, indices, input, self.stage))
+            .collect::<Result<_, _>>()?;
+
+        let kind = match self.keyword {
+            "source" => NodeKind::Source,
+            "converter" => NodeKind::Converter,
+            "bus" => NodeKind::Bus(
+                parse_bus_type(self.name)
+                    .ok_or_else(|| format!("'{}' is not a known bus type", self.name))?,
+            ),
+            _ => NodeKind::Contactor(
+                ConditionParser::new(
+                    self.condition.unwrap_or(""),
+                    self.stage,
+                    declarations,
+                    indices,
+                )
+                .parse()?,
+            ),
+        };
+
+        Ok(Node {
+            name: self.name.to_owned(),
+            kind,
+            inputs,
+        })
+    }
+}
+
+struct ConditionParser<'a> {
+    tokens: Vec<&'a str>,
+    position: usize,
+    stage: usize,
+    declarations: &'a [Declaration<'a>],
+    indices: &'a HashMap<&'a str, usize>,
+}
+impl<'a> ConditionParser<'a> {
+    fn new(
+        text: &'a str,
+        stage: usize,
+        declarations: &'a [Declaration<'a>],
+        indices: &'a HashMap<&'a str, usize>,
+    ) -> Self {
+        Self {
+            tokens: tokenize(text),
+            position: 0,
+            stage,
+            declarations,
+            indices,
+        }
+    }
+
+    fn parse(mut self) -> Result<Condition, String> {
+        let condition = self.parse_or()?;
+        match self.next() {
+            Some(token) => Err(format!("unexpected '{}' in condition", token)),
+            None => Ok(condition),
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Condition, String> {
+        let mut condition = self.parse_and()?;
+        while self.next_is("or") {
+            condition = Condition::Or(Box::new(condition), Box::new(self.parse_and()?));
+        }
+
+        Ok(condition)
+    }
+
+    fn parse_and(&mut self) -> Result<Condition, String> {
+        let mut condition = self.parse_not()?;
+        while self.next_is("and") {
+            condition = Condition::And(Box::new(condition), Box::new(self.parse_not()?));
+        }
+
+        Ok(condition)
+    }
+
+    fn parse_not(&mut self) -> Result<Condition, String> {
+        if self.next_is("not") {
+            Ok(Condition::Not(Box::new(self.parse_not()?)))
+        } else {
+            self.parse_term()
+        }
+    }
+
+    fn parse_term(&mut self) -> Result<Condition, String> {
+        match self.next() {
+            Some("(") => {
+                let condition = self.parse_or()?;
+                self.expect(")")?;
+                Ok(condition)
+            }
+            Some(function) if self.next_is("(") => {
+                let name = self.next().unwrap_or("");
+                self.expect(")")?;
+                let index = resolve_name(self.declarations, self.indices, name, self.stage)?;
+
+                match function {
+                    "powered" => Ok(Condition::Powered(index)),
+                    "closed" if self.declarations[index].keyword == "contactor" => {
+                        Ok(Condition::Closed(index))
+                    }
+                    "closed" => Err(format!("'{}' is not a contactor", name)),
+                    _ => Err(format!("unknown function '{}'", function)),
+                }
+            }
+            Some(token) if token != ")" && !["and", "or", "not"].contains(&token) => {
+                Ok(Condition::Signal(token.to_owned()))
+            }
+            Some(token) => Err(format!("unexpected '{}' in condition", token)),
+            None => Err("the condition ends unexpectedly".to_owned()),
+        }
+    }
+
+    fn next(&mut self) -> Option<&'a str> {
+        let token = self.tokens.get(self.position).copied();
+        self.position += 1;
+        token
+    }
+
+    fn next_is(&mut self, expected: &str) -> bool {
+        if self.tokens.get(self.position) == Some(&expected) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, expected: &str) -> Result<(), String> {
+        if self.next_is(expected) {
+            Ok(())
+        } else {
+            Err(format!("expected '{}' in condition", expected))
+        }
+    }
+}
+
+fn tokenize(text: &str) -> Vec<&str> {
+    let mut tokens = vec![];
+    let mut word_start = None;
+    for (index, character) in text.char_indices() {
+        if character == '(' || character == ')' || character.is_whitespace() {
+            if let Some(start) = word_start.take() {
+                tokens.push(&text[start..index]);
+            }
+
+            if !character.is_whitespace() {
+                tokens.push(&text[index..index + 1]);
+            }
+        } else if word_start.is_none() {
+            word_start = Some(index);
+        }
+    }
+
+    if let Some(start) = word_start {
+        tokens.push(&text[start..]);
+    }
+
+    tokens
+}
+
+/// Resolves a name referred to by an element of the given stage.
+fn resolve_name(
+    declarations: &[Declaration],
+    indices: &HashMap<&str, usize>,
+    name: &str,
+    stage: usize,
+) -> Result<usize, String> {
+    let index = indices
+        .get(name)
+        .copied()
+        .ok_or_else(|| format!("'{}' is not declared", name))?;
+
+    if declarations[index].stage > stage {
+        Err(format!("'{}' is declared in a later stage", name))
+    } else {
+        Ok(index)
+    }
+}
+
+fn split_keyword(line: &str) -> (&str, &str) {
+    match line.find(char::is_whitespace) {
+        Some(index) => (&line[..index], line[index..].trim()),
+        None => (line, ""),
+    }
+}
+
+/// Finds the given word, surrounded by whitespace, within the text.
+fn find_word(text: &str, word: &str) -> Option<usize> {
+    text.match_indices(word)
+        .map(|(index, _)| index)
+        .find(|&index| {
+            let after = &text[index + word.len()..];
+
+            text[..index].ends_with(char::is_whitespace)
+                && (after.is_empty() || after.starts_with(char::is_whitespace))
+        })
+}
+
+fn parse_bus_type(name: &str) -> Option<ElectricalBusType> {
+    let bus_types_without_number = [
+        ElectricalBusType::AlternatingCurrentEssential,
+        ElectricalBusType::AlternatingCurrentEssentialShed,
+        ElectricalBusType::AlternatingCurrentStaticInverter,
+        ElectricalBusType::AlternatingCurrentGndFltService,
+        ElectricalBusType::DirectCurrentEssential,
+        ElectricalBusType::DirectCurrentEssentialShed,
+        ElectricalBusType::DirectCurrentBattery,
+        ElectricalBusType::DirectCurrentGndFltService,
+    ];
+
+    let number = |prefix: &str| name.strip_prefix(prefix).and_then(|n| n.parse().ok());
+
+    bus_types_without_number
+        .iter()
+        .copied()
+        .find(|bus_type| bus_type.to_string() == name)
+        .or_else(|| number("DC_HOT_").map(ElectricalBusType::DirectCurrentHot))
+        .or_else(|| number("AC_").map(ElectricalBusType::AlternatingCurrent))
+        .or_else(|| number("DC_").map(ElectricalBusType::DirectCurrent))
+}
+
+#[cfg(test)]
+mod electrical_topology_tests {
+    use super::*;
+
+    fn parse_error(text: &str) -> ElectricalTopologyError {
+        ElectricalTopology::parse(text).unwrap_err()
+    }
+
+    #[test]
+    fn parses_a_topology_with_comments_and_empty_lines() {
+        assert!(ElectricalTopology::parse(
+            "# Main power sources.\n\nsource GEN_1\ncontactor 9XU1 from GEN_1 when gen_1_on # GEN 1 line contactor\nbus AC_1 from 9XU1"
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn inputs_may_refer_to_elements_declared_further_down() {
+        assert!(ElectricalTopology::parse(
+            "source GEN_1\ncontactor 11XU1 from GEN_1, 11XU2 when tie\ncontactor 11XU2 from 11XU1 when tie"
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn unknown_element_is_an_error() {
+        assert_eq!(parse_error("source GEN_1\nfuse F1 from GEN_1").line(), 2);
+    }
+
+    #[test]
+    fn undeclared_input_is_an_error() {
+        assert_eq!(parse_error("bus AC_1 from GEN_1").line(), 1);
+    }
+
+    #[test]
+    fn declaring_a_name_twice_is_an_error() {
+        assert_eq!(parse_error("source GEN_1\n\nsource GEN_1").line(), 3);
+    }
+
+    #[test]
+    fn unknown_bus_type_is_an_error() {
+        assert_eq!(parse_error("source GEN_1\nbus AC_X from GEN_1").line(), 2);
+    }
+
+    #[test]
+    fn source_with_inputs_is_an_error() {
+        assert_eq!(
+            parse_error("source GEN_1\nsource GEN_2 from GEN_1").line(),
+            2
+        );
+    }
+
+    #[test]
+    fn contactor_without_closing_condition_is_an_error() {
+        assert_eq!(
+            parse_error("source GEN_1\ncontactor 9XU1 from GEN_1").line(),
+            2
+        );
+    }
+
+    #[test]
+    fn bus_with_closing_condition_is_an_error() {
+        assert_eq!(
+            parse_error("source GEN_1\nbus AC_1 from GEN_1 when x").line(),
+            2
+        );
+    }
+
+    #[test]
+    fn closed_of_an_element_which_isnt_a_contactor_is_an_error() {
+        assert_eq!(
+            parse_error("source GEN_1\ncontactor 9XU1 from GEN_1 when closed(GEN_1)").line(),
+            2
+        );
+    }
+
+    #[test]
+    fn malformed_condition_is_an_error() {
+        assert_eq!(
+            parse_error("source GEN_1\ncontactor 9XU1 from GEN_1 when (a and b").line(),
+            2
+        );
+        assert_eq!(
+            parse_error("source GEN_1\ncontactor 9XU1 from GEN_1 when a and").line(),
+            2
+        );
+        assert_eq!(
+            parse_error("source GEN_1\ncontactor 9XU1 from GEN_1 when a b").line(),
+            2
+        );
+    }
+
+    #[test]
+    fn parses_a_topology_with_stages() {
+        assert!(ElectricalTopology::parse(
+            "stage sources\nsource GEN_1\n\nstage buses\nbus AC_1 from GEN_1"
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn element_before_the_first_stage_is_an_error() {
+        assert_eq!(
+            parse_error("source GEN_1\nstage buses\nbus AC_1 from GEN_1").line(),
+            2
+        );
+    }
+
+    #[test]
+    fn declaring_a_stage_twice_is_an_error() {
+        assert_eq!(
+            parse_error("stage a\nsource GEN_1\nstage a\nbus AC_1 from GEN_1").line(),
+            3
+        );
+    }
+
+    #[test]
+    fn empty_stage_is_an_error() {
+        assert_eq!(parse_error("stage a\nstage b\nsource GEN_1").line(), 1);
+    }
+
+    #[test]
+    fn input_of_a_later_stage_is_an_error() {
+        assert_eq!(
+            parse_error(
+                "stage a\nsource GEN_1\nbus AC_1 from TR_1\nstage b\nconverter TR_1 from AC_1"
+            )
+            .to_string(),
+            "line 3: 'TR_1' is declared in a later stage"
+        );
+    }
+
+    #[test]
+    fn condition_referring_to_a_later_stage_is_an_error() {
+        assert_eq!(
+            parse_error(
+                "stage a\nsource GEN_1\ncontactor 9XU1 from GEN_1 when powered(AC_1)\nstage b\nbus AC_1 from 9XU1"
+            )
+            .line(),
+            3
+        );
+    }
+
+    #[test]
+    fn error_displays_the_line() {
+        assert_eq!(
+            parse_error("bus AC_1 from GEN_1").to_string(),
+            "line 1: 'GEN_1' is not declared"
+        );
+    }
+}
+
+#[cfg(test)]
+mod electrical_network_tests {
+    use super::*;
+    use crate::{
+        electrical::{PotentialOrigin, PotentialSource},
+        failures::FailureType,
+        simulation::{
+            test::SimulationTestBed, Aircraft, SimulationElementVisitor, SimulationEvent,
+            UpdateContext,
+        },
+    };
+    use uom::si::{electric_potential::volt, f64::*};
+
+    const TOPOLOGY: &str = r"
+        source GEN_1
+        source GEN_2
+        source EXT_PWR
+
+        contactor 9XU1 from GEN_1 when gen_1_on
+        contactor 9XU2 from GEN_2 when gen_2_on
+        contactor 3XG from EXT_PWR when ext_pwr_on and not (closed(9XU1) and closed(9XU2))
+        contactor 11XU1 from 9XU1, 3XG, 11XU2 when not (closed(9XU1) and closed(9XU2))
+        contactor 11XU2 from 9XU2, 3XG, 11XU1 when not (closed(9XU1) and closed(9XU2))
+        bus AC_1 from 9XU1, 11XU1
+        bus AC_2 from 9XU2, 11XU2
+
+        converter TR_1 from AC_1
+        contactor 5PU1 from TR_1 when powered(TR_1)
+        bus DC_1 from 5PU1
+    ";
+
+    #[derive(Default)]
+    struct TestElements {
+        powered_sources: Vec<&'static str>,
+        active_signals: Vec<&'static str>,
+        withheld: Vec<&'static str>,
+        tr_1_input: Potential,
+    }
+    impl TestElements {
+        fn power(&mut self, source: &'static str, signal: &'static str) {
+            self.powered_sources.push(source);
+            self.active_signals.push(signal);
+        }
+
+        fn unpower(&mut self, source: &'static str, signal: &'static str) {
+            self.powered_sources.retain(|&powered| powered != source);
+            self.active_signals.retain(|&active| active != signal);
+        }
+    }
+    impl ElectricalNetworkElements for TestElements {
+        fn source_potential(&self, name: &str) -> Option<Potential> {
+            let origin = match name {
+                "GEN_1" => PotentialOrigin::EngineGenerator(1),
+                "GEN_2" => PotentialOrigin::EngineGenerator(2),
+                "EXT_PWR" => PotentialOrigin::External,
+                _ => return None,
+            };
+
+            Some(if self.powered_sources.contains(&name) {
+                Potential::single(origin, ElectricPotential::new::<volt>(115.))
+            } else {
+                Potential::none()
+            })
+        }
+
+        fn convert(&mut self, name: &str, input: Potential) -> Option<Potential> {
+            if name != "TR_1" {
+                return None;
+            }
+
+            self.tr_1_input = input;
+            Some(if input.is_powered() {
+                Potential::single(
+                    PotentialOrigin::TransformerRectifier(1),
+                    ElectricPotential::new::<volt>(28.),
+                )
+            } else {
+                Potential::none()
+            })
+        }
+
+        fn signal(&self, name: &str) -> Option<bool> {
+            if ["gen_1_on", "gen_2_on", "ext_pwr_on"].contains(&name)
+                && !self.withheld.contains(&name)
+            {
+                Some(self.active_signals.contains(&name))
+            } else {
+                None
+            }
+        }
+    }
+
+    struct TestAircraft {
+        network: ElectricalNetwork,
+        elements: TestElements,
+    }
+    impl TestAircraft {
+        fn new() -> Self {
+            Self {
+                network: ElectricalNetwork::new(ElectricalTopology::parse(TOPOLOGY).unwrap()),
+                elements: TestElements::default(),
+            }
+        }
+
+        fn gen_1_on(&mut self) {
+            self.elements.power("GEN_1", "gen_1_on");
+        }
+
+        fn gen_1_off(&mut self) {
+            self.elements.unpower("GEN_1", "gen_1_on");
+        }
+
+        fn gen_2_on(&mut self) {
+            self.elements.power("GEN_2", "gen_2_on");
+        }
+
+        fn ext_pwr_on(&mut self) {
+            self.elements.power("EXT_PWR", "ext_pwr_on");
+        }
+
+        fn bus_output(&self, bus_type: ElectricalBusType) -> Potential {
+            self.network.bus(bus_type).output()
+        }
+    }
+    impl Aircraft for TestAircraft {
+        fn update_before_power_distribution(&mut self, _: &UpdateContext) {
+            self.network.update(&mut self.elements).unwrap();
+        }
+    }
+    impl SimulationElement for TestAircraft {
+        fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+            self.network.accept(visitor);
+            visitor.visit(self);
+        }
+    }
+
+    const STAGED_TOPOLOGY: &str = r"
+        stage main_power_sources
+        source GEN_1
+        contactor 9XU1 from GEN_1 when gen_1_on
+        bus AC_1 from 9XU1
+
+        stage dc_buses
+        converter TR_1 from AC_1
+        contactor 5PU1 from TR_1 when powered(TR_1) and ext_pwr_on
+        bus DC_1 from 5PU1
+    ";
+
+    fn staged_network() -> ElectricalNetwork {
+        ElectricalNetwork::new(ElectricalTopology::parse(STAGED_TOPOLOGY).unwrap())
+    }
+
+    fn closed(id: &str) -> SimulationEvent {
+        SimulationEvent::ContactorClosed {
+            contactor_id: id.to_owned(),
+        }
+    }
+
+    #[test]
+    fn buses_are_unpowered_without_sources() {
+        let mut aircraft = TestAircraft::new();
+        SimulationTestBed::new().run_aircraft(&mut aircraft);
+
+        assert!(aircraft
+            .bus_output(ElectricalBusType::AlternatingCurrent(1))
+            .is_unpowered());
+        assert!(aircraft
+            .bus_output(ElectricalBusType::AlternatingCurrent(2))
+            .is_unpowered());
+        assert!(aircraft
+            .bus_output(ElectricalBusType::DirectCurrent(1))
+            .is_unpowered());
+    }
+
+    #[test]
+    fn engine_generators_power_their_own_ac_bus() {
+        let mut aircraft = TestAircraft::new();
+        aircraft.gen_1_on();
+        aircraft.gen_2_on();
+        SimulationTestBed::new().run_aircraft(&mut aircraft);
+
+        assert!(aircraft
+            .bus_output(ElectricalBusType::AlternatingCurrent(1))
+            .is_single(PotentialOrigin::EngineGenerator(1)));
+        assert!(aircraft
+            .bus_output(ElectricalBusType::AlternatingCurrent(2))
+            .is_single(PotentialOrigin::EngineGenerator(2)));
+        assert!(aircraft.network.contactor("11XU1").is_open());
+        assert!(aircraft.network.contactor("11XU2").is_open());
+    }
+
+    #[test]
+    fn bus_tie_contactors_power_both_ac_buses_from_a_single_engine_generator() {
+        let mut aircraft = TestAircraft::new();
+        aircraft.gen_1_on();
+        SimulationTestBed::new().run_aircraft(&mut aircraft);
+
+        assert!(aircraft
+            .bus_output(ElectricalBusType::AlternatingCurrent(2))
+            .is_single(PotentialOrigin::EngineGenerator(1)));
+    }
+
+    #[test]
+    fn external_power_powers_both_ac_buses() {
+        let mut aircraft = TestAircraft::new();
+        aircraft.ext_pwr_on();
+        SimulationTestBed::new().run_aircraft(&mut aircraft);
+
+        assert!(aircraft
+            .bus_output(ElectricalBusType::AlternatingCurrent(1))
+            .is_single(PotentialOrigin::External));
+        assert!(aircraft
+            .bus_output(ElectricalBusType::AlternatingCurrent(2))
+            .is_single(PotentialOrigin::External));
+    }
+
+    #[test]
+    fn external_power_contactor_opens_when_both_engine_generators_are_on() {
+        let mut aircraft = TestAircraft::new();
+        aircraft.ext_pwr_on();
+        aircraft.gen_1_on();
+        aircraft.gen_2_on();
+        SimulationTestBed::new().run_aircraft(&mut aircraft);
+
+        assert!(aircraft.network.contactor("3XG").is_open());
+    }
+
+    #[test]
+    fn converter_is_powered_by_its_input_and_powers_what_follows() {
+        let mut aircraft = TestAircraft::new();
+        aircraft.gen_1_on();
+        SimulationTestBed::new().run_aircraft(&mut aircraft);
+
+        assert!(aircraft
+            .elements
+            .tr_1_input
+            .is_single(PotentialOrigin::EngineGenerator(1)));
+        assert!(aircraft
+            .bus_output(ElectricalBusType::DirectCurrent(1))
+            .is_single(PotentialOrigin::TransformerRectifier(1)));
+        assert!(aircraft
+            .network
+            .output("TR_1")
+            .is_single(PotentialOrigin::TransformerRectifier(1)));
+    }
+
+    #[test]
+    fn contactors_raise_a_single_event_once_the_network_settled() {
+        let mut aircraft = TestAircraft::new();
+        aircraft.gen_1_on();
+        let mut test_bed = SimulationTestBed::new();
+        test_bed.run_aircraft(&mut aircraft);
+
+        assert_eq!(
+            test_bed.events(),
+            &[
+                closed("9XU1"),
+                closed("11XU1"),
+                closed("11XU2"),
+                closed("5PU1")
+            ]
+        );
+    }
+
+    #[test]
+    fn writes_the_state_of_buses_and_contactors() {
+        let mut aircraft = TestAircraft::new();
+        aircraft.gen_1_on();
+        let mut test_bed = SimulationTestBed::new();
+        test_bed.run_aircraft(&mut aircraft);
+
+        assert!(test_bed.read_bool("ELEC_AC_2_BUS_IS_POWERED"));
+        assert!(test_bed.read_bool("ELEC_CONTACTOR_9XU1_IS_CLOSED"));
+        assert!(!test_bed.read_bool("ELEC_CONTACTOR_9XU2_IS_CLOSED"));
+    }
+
+    #[test]
+    fn stuck_contactor_keeps_its_state() {
+        let mut aircraft = TestAircraft::new();
+        aircraft.gen_1_on();
+        let mut test_bed = SimulationTestBed::new();
+        test_bed.run_aircraft(&mut aircraft);
+
+        test_bed.activate_failure(&FailureType::ContactorStuck("9XU1".to_owned()));
+        aircraft.gen_1_off();
+        test_bed.run_aircraft(&mut aircraft);
+
+        assert!(aircraft.network.contactor("9XU1").is_closed());
+        assert!(aircraft
+            .bus_output(ElectricalBusType::AlternatingCurrent(1))
+            .is_unpowered());
+    }
+
+    #[test]
+    fn updating_a_stage_only_updates_its_own_buses_and_contactors() {
+        let mut network = staged_network();
+        let mut elements = TestElements::default();
+        elements.power("GEN_1", "gen_1_on");
+        elements.active_signals.push("ext_pwr_on");
+
+        network
+            .update_stage("main_power_sources", &mut elements)
+            .unwrap();
+
+        assert!(network
+            .bus(ElectricalBusType::AlternatingCurrent(1))
+            .output()
+            .is_single(PotentialOrigin::EngineGenerator(1)));
+        assert!(network.contactor("5PU1").is_open());
+        assert!(network
+            .bus(ElectricalBusType::DirectCurrent(1))
+            .output()
+            .is_unpowered());
+
+        network.update_stage("dc_buses", &mut elements).unwrap();
+
+        assert!(network.contactor("5PU1").is_closed());
+        assert!(network
+            .bus(ElectricalBusType::DirectCurrent(1))
+            .output()
+            .is_single(PotentialOrigin::TransformerRectifier(1)));
+    }
+
+    #[test]
+    fn later_stage_uses_the_state_of_earlier_stages() {
+        let mut network = staged_network();
+        let mut elements = TestElements::default();
+        elements.power("GEN_1", "gen_1_on");
+        elements.active_signals.push("ext_pwr_on");
+        network.update(&mut elements).unwrap();
+
+        elements.unpower("GEN_1", "gen_1_on");
+        network.update_stage("dc_buses", &mut elements).unwrap();
+
+        assert!(network
+            .bus(ElectricalBusType::DirectCurrent(1))
+            .output()
+            .is_single(PotentialOrigin::TransformerRectifier(1)));
+    }
+
+    #[test]
+    fn updating_an_unknown_stage_is_an_error() {
+        assert_eq!(
+            staged_network().update_stage("ac_buses", &mut TestElements::default()),
+            Err(ElectricalNetworkError::UnknownStage {
+                stage: "ac_buses".to_owned()
+            })
+        );
+    }
+
+    #[test]
+    fn source_which_isnt_provided_is_an_error() {
+        let mut network =
+            ElectricalNetwork::new(ElectricalTopology::parse("source APU_GEN").unwrap());
+
+        assert_eq!(
+            network.update(&mut TestElements::default()),
+            Err(ElectricalNetworkError::MissingSource {
+                name: "APU_GEN".to_owned()
+            })
+        );
+    }
+
+    #[test]
+    fn converter_which_isnt_provided_is_an_error() {
+        let mut network = ElectricalNetwork::new(
+            ElectricalTopology::parse("source GEN_1\nconverter TR_2 from GEN_1").unwrap(),
+        );
+
+        assert_eq!(
+            network.update(&mut TestElements::default()),
+            Err(ElectricalNetworkError::MissingConverter {
+                name: "TR_2".to_owned()
+            })
+        );
+    }
+
+    #[test]
+    fn signal_which_isnt_provided_is_an_error_regardless_of_the_other_signals() {
+        let mut network = ElectricalNetwork::new(
+            ElectricalTopology::parse(
+                "source GEN_1\ncontactor 9XU1 from GEN_1 when gen_1_on and bus_tie_auto",
+            )
+            .unwrap(),
+        );
+
+        assert_eq!(
+            network.update(&mut TestElements::default()),
+            Err(ElectricalNetworkError::MissingSignal {
+                name: "bus_tie_auto".to_owned()
+            })
+        );
+    }
+
+    #[test]
+    fn stage_which_fails_to_update_keeps_its_state() {
+        let mut network = staged_network();
+        let mut elements = TestElements::default();
+        elements.power("GEN_1", "gen_1_on");
+        network.update(&mut elements).unwrap();
+
+        elements.unpower("GEN_1", "gen_1_on");
+        elements.withheld.push("gen_1_on");
+
+        assert!(network
+            .update_stage("main_power_sources", &mut elements)
+            .is_err());
+        assert!(network.contactor("9XU1").is_closed());
+        assert!(network
+            .bus(ElectricalBusType::AlternatingCurrent(1))
+            .output()
+            .is_single(PotentialOrigin::EngineGenerator(1)));
+    }
+}
@@ -3,14 +3,17 @@ use super::{
     ElectricalStateWriter, Potential, PotentialOrigin, PotentialSource, PotentialTarget,
     ProvideCurrent, ProvidePotential,
 };
-use crate::simulation::{SimulationElement, SimulatorWriter};
+use crate::{
+    failures::{Failure, FailureType},
+    simulation::{DescriptionWriter, SimulationElement, SimulationElementVisitor, SimulatorWriter},
+};
 use uom::si::{electric_current::ampere, electric_potential::volt, f64::*};
 
 pub struct TransformerRectifier {
     writer: ElectricalStateWriter,
     number: usize,
     input_potential: Potential,
-    failed: bool,
+    failure: Failure,
     output_potential: ElectricPotential,
     output_current: ElectricCurrent,
 }
@@ -20,18 +23,14 @@ impl TransformerRectifier {
             writer: ElectricalStateWriter::new(&format!("TR_{}", number)),
             number,
             input_potential: Potential::none(),
-            failed: false,
+            failure: Failure::new(FailureType::TransformerRectifier(number)),
             output_potential: ElectricPotential::new::<volt>(0.),
             output_current: ElectricCurrent::new::<ampere>(0.),
         }
     }
 
-    pub fn fail(&mut self) {
-        self.failed = true;
-    }
-
     pub fn failed(&self) -> bool {
-        self.failed
+        self.failure.is_active()
     }
 
     pub fn input_potential(&self) -> Potential {
@@ -39,7 +38,7 @@ impl TransformerRectifier {
     }
 
     fn should_provide_output(&self) -> bool {
-        !self.failed && self.input_potential.is_powered()
+        !self.failed() && self.input_potential.is_powered()
     }
 }
 potential_target!(TransformerRectifier);
@@ -66,6 +65,22 @@ impl ProvideCurrent for TransformerRectifier {
 }
 provide_potential!(TransformerRectifier, (25.0..=31.0));
 impl SimulationElement for TransformerRectifier {
+    fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+        visitor.enter_children();
+        self.failure.accept(visitor);
+        visitor.leave_children();
+
+        visitor.visit(self);
+    }
+
+    fn identifier(&self) -> String {
+        format!("transformer_rectifier_{}", self.number)
+    }
+
+    fn describe(&self, writer: &mut DescriptionWriter) {
+        self.writer.describe_direct(self, writer);
+    }
+
     fn write(&self, writer: &mut SimulatorWriter) {
         self.writer.write_direct(self, writer);
     }
@@ -121,6 +136,11 @@ mod transformer_rectifier_tests {
             self.test_bed.run_aircraft(aircraft);
         }
 
+        fn fail_transformer_rectifier(&mut self) {
+            self.test_bed
+                .activate_failure(&FailureType::TransformerRectifier(1));
+        }
+
         fn current_is_normal(&mut self) -> bool {
             self.test_bed.read_bool("ELEC_TR_1_CURRENT_NORMAL")
         }
@@ -158,10 +178,6 @@ mod transformer_rectifier_tests {
             self
         }
 
-        fn fail_transformer_rectifier(&mut self) {
-            self.transformer_rectifier.fail();
-        }
-
         fn transformer_rectifier_is_powered(&self) -> bool {
             self.transformer_rectifier.is_powered()
         }
@@ -192,8 +208,10 @@ mod transformer_rectifier_tests {
     }
     impl SimulationElement for TestAircraft {
         fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+            visitor.enter_children();
             self.transformer_rectifier.accept(visitor);
             self.consumer.accept(visitor);
+            visitor.leave_children();
 
             visitor.visit(self);
         }
@@ -246,7 +264,7 @@ mod transformer_rectifier_tests {
         let mut aircraft = TestAircraft::new().with_powered_transformer_rectifier();
         let mut test_bed = TransformerRectifierTestBed::new();
 
-        aircraft.fail_transformer_rectifier();
+        test_bed.fail_transformer_rectifier();
         test_bed.run_aircraft(&mut aircraft);
 
         assert!(!aircraft.transformer_rectifier_is_powered());
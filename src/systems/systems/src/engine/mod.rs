@@ -1,15 +1,23 @@
 use uom::si::{f64::*, ratio::percent};
 
-use crate::simulation::{SimulationElement, SimulatorReader, UpdateContext};
+use crate::simulation::{
+    DescriptionWriter, SimulationElement, SimulatorReader, UpdateContext, VariableIdentifier,
+    VariableUnit,
+};
 
 pub struct Engine {
-    corrected_n2_id: String,
+    number: usize,
+    corrected_n2_id: VariableIdentifier<Ratio>,
     corrected_n2: Ratio,
 }
 impl Engine {
     pub fn new(number: usize) -> Engine {
         Engine {
-            corrected_n2_id: format!("TURB ENG CORRECTED N2:{}", number),
+            number,
+            corrected_n2_id: VariableIdentifier::new(
+                format!("TURB ENG CORRECTED N2:{}", number),
+                VariableUnit::Percent,
+            ),
             corrected_n2: Ratio::new::<percent>(0.),
         }
     }
@@ -21,7 +29,18 @@ impl Engine {
     pub fn update(&mut self, _: &UpdateContext) {}
 }
 impl SimulationElement for Engine {
+    fn identifier(&self) -> String {
+        format!("engine_{}", self.number)
+    }
+
+    fn describe(&self, writer: &mut DescriptionWriter) {
+        writer.write(
+            "corrected_n2",
+            format!("{:.1} %", self.corrected_n2.get::<percent>()),
+        );
+    }
+
     fn read(&mut self, reader: &mut SimulatorReader) {
-        self.corrected_n2 = Ratio::new::<percent>(reader.read_f64(&self.corrected_n2_id));
+        self.corrected_n2 = reader.read(&self.corrected_n2_id);
     }
 }
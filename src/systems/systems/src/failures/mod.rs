@@ -0,0 +1,171 @@
+use std::fmt;
+
+use crate::simulation::{
+    DescriptionWriter, SimulationElement, SimulatorReader, SimulatorWriter, SnapshotReader,
+    SnapshotWriter, VariableIdentifier, VariableUnit,
+};
+
+/// The failure modes components can declare.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum FailureType {
+    TransformerRectifier(usize),
+    EngineGenerator(usize),
+    Battery(usize),
+    /// The contactor with the given identifier remains in its current position.
+    ContactorStuck(String),
+    Apu,
+}
+impl FailureType {
+    /// The name of the simulator variable which activates and clears the failure.
+    pub fn variable_name(&self) -> String {
+        match self {
+            FailureType::TransformerRectifier(number) => {
+                format!("FAILURE_ELEC_TR_{}_IS_ACTIVE", number)
+            }
+            FailureType::EngineGenerator(number) => {
+                format!("FAILURE_ELEC_ENG_GEN_{}_IS_ACTIVE", number)
+            }
+            FailureType::Battery(number) => format!("FAILURE_ELEC_BAT_{}_IS_ACTIVE", number),
+            FailureType::ContactorStuck(id) => {
+                format!("FAILURE_ELEC_CONTACTOR_{}_STUCK_IS_ACTIVE", id)
+            }
+            FailureType::Apu => "FAILURE_APU_IS_ACTIVE".to_owned(),
+        }
+    }
+}
+impl fmt::Display for FailureType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FailureType::TransformerRectifier(number) => write!(f, "TR {} failure", number),
+            FailureType::EngineGenerator(number) => {
+                write!(f, "engine generator {} failure", number)
+            }
+            FailureType::Battery(number) => write!(f, "battery {} failure", number),
+            FailureType::ContactorStuck(id) => write!(f, "contactor {} stuck", id),
+            FailureType::Apu => write!(f, "APU failure"),
+        }
+    }
+}
+
+/// A failure mode of a component. The failure is activated and cleared through
+/// a simulator variable, which is read at the start of every tick.
+///
+/// The failure also writes its state, such that restoring a snapshot
+/// which contains an active failure activates the failure in the simulator.
+#[derive(Debug)]
+pub struct Failure {
+    failure_type: FailureType,
+    is_active_id: VariableIdentifier<bool>,
+    is_active: bool,
+}
+impl Failure {
+    pub fn new(failure_type: FailureType) -> Self {
+        Self {
+            is_active_id: VariableIdentifier::new(failure_type.variable_name(), VariableUnit::Bool),
+            failure_type,
+            is_active: false,
+        }
+    }
+
+    pub fn failure_type(&self) -> &FailureType {
+        &self.failure_type
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.is_active
+    }
+}
+impl SimulationElement for Failure {
+    fn describe(&self, writer: &mut DescriptionWriter) {
+        writer.write("active", self.is_active);
+    }
+
+    fn read(&mut self, reader: &mut SimulatorReader) {
+        self.is_active = reader.read(&self.is_active_id);
+    }
+
+    fn write(&self, writer: &mut SimulatorWriter) {
+        writer.write(&self.is_active_id, self.is_active);
+    }
+
+    fn snapshot(&self, writer: &mut SnapshotWriter) {
+        writer.write_bool(self.is_active_id.name(), self.is_active);
+    }
+
+    fn restore(&mut self, reader: &SnapshotReader) {
+        if let Some(is_active) = reader.read_bool(self.is_active_id.name()) {
+            self.is_active = is_active;
+        }
+    }
+
+    fn register_failures(&self, failures: &mut Vec<FailureType>) {
+        failures.push(self.failure_type.clone());
+    }
+}
+
+#[cfg(test)]
+mod failure_tests {
+    use super::*;
+    use crate::simulation::test::SimulationTestBed;
+
+    #[test]
+    fn is_inactive_by_default() {
+        let mut failure = Failure::new(FailureType::TransformerRectifier(1));
+        let mut test_bed = SimulationTestBed::new();
+
+        test_bed.run_without_update(&mut failure);
+
+        assert!(!failure.is_active());
+    }
+
+    #[test]
+    fn is_activated_through_the_simulator() {
+        let mut failure = Failure::new(FailureType::TransformerRectifier(1));
+        let mut test_bed = SimulationTestBed::new();
+        test_bed.write_bool("FAILURE_ELEC_TR_1_IS_ACTIVE", true);
+
+        test_bed.run_without_update(&mut failure);
+
+        assert!(failure.is_active());
+    }
+
+    #[test]
+    fn is_cleared_through_the_simulator() {
+        let mut failure = Failure::new(FailureType::Apu);
+        let mut test_bed = SimulationTestBed::new();
+        test_bed.activate_failure(&FailureType::Apu);
+        test_bed.run_without_update(&mut failure);
+
+        test_bed.deactivate_failure(&FailureType::Apu);
+        test_bed.run_without_update(&mut failure);
+
+        assert!(!failure.is_active());
+    }
+
+    #[test]
+    fn restoring_an_active_failure_activates_it_in_the_simulator() {
+        let mut test_bed = SimulationTestBed::new();
+        let mut failure = Failure::new(FailureType::Battery(1));
+        test_bed.activate_failure(&FailureType::Battery(1));
+        test_bed.run_without_update(&mut failure);
+        let snapshot = test_bed.snapshot(&mut failure);
+
+        let mut test_bed = SimulationTestBed::new();
+        let mut failure = Failure::new(FailureType::Battery(1));
+        test_bed.restore(&mut failure, &snapshot);
+        test_bed.run_without_update(&mut failure);
+
+        assert!(failure.is_active());
+    }
+
+    #[test]
+    fn is_registered() {
+        let mut failure = Failure::new(FailureType::ContactorStuck("9XU1".to_owned()));
+        let mut test_bed = SimulationTestBed::new();
+
+        assert_eq!(
+            test_bed.register_failures(&mut failure),
+            vec![FailureType::ContactorStuck("9XU1".to_owned())]
+        );
+    }
+}
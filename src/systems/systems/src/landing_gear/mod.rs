@@ -1,4 +1,6 @@
-use crate::simulation::{SimulationElement, SimulatorReader};
+use crate::simulation::{
+    DescriptionWriter, SimulationElement, SimulatorReader, VariableIdentifier, VariableUnit,
+};
 use uom::si::{f64::*, ratio::percent};
 
 /// Represents a landing gear on Airbus aircraft.
@@ -6,6 +8,7 @@ use uom::si::{f64::*, ratio::percent};
 /// The real aircraft also can only check whether or not the gear is up and
 /// locked or down and locked. No in between state.
 pub struct LandingGear {
+    position_id: VariableIdentifier<Ratio>,
     position: Ratio,
 }
 impl LandingGear {
@@ -13,6 +16,10 @@ impl LandingGear {
 
     pub fn new() -> Self {
         Self {
+            position_id: VariableIdentifier::new(
+                LandingGear::GEAR_CENTER_POSITION,
+                VariableUnit::Percent,
+            ),
             position: Ratio::new::<percent>(0.),
         }
     }
@@ -26,8 +33,15 @@ impl LandingGear {
     }
 }
 impl SimulationElement for LandingGear {
+    fn describe(&self, writer: &mut DescriptionWriter) {
+        writer.write(
+            "position",
+            format!("{:.1} %", self.position.get::<percent>()),
+        );
+    }
+
     fn read(&mut self, reader: &mut SimulatorReader) {
-        self.position = Ratio::new::<percent>(reader.read_f64(LandingGear::GEAR_CENTER_POSITION));
+        self.position = reader.read(&self.position_id);
     }
 }
 impl Default for LandingGear {
@@ -10,6 +10,7 @@ mod macros;
 pub mod apu;
 pub mod electrical;
 pub mod engine;
+pub mod failures;
 pub mod hydraulic;
 pub mod landing_gear;
 pub mod overhead;
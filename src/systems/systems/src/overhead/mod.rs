@@ -1,11 +1,49 @@
-use crate::simulation::{SimulationElement, SimulatorReader, SimulatorWriter};
+use crate::simulation::{
+    to_bool, DescriptionWriter, EventCollector, KeyEventIdentifier, SimulationElement,
+    SimulationEvent, SimulatorReader, SimulatorWriter, SnapshotReader, SnapshotWriter,
+    VariableIdentifier, VariableUnit,
+};
+
+/// The key events with which key bindings and home cockpit hardware operate a push button,
+/// without writing its variable. `OVHD_<NAME>_PB_PUSH` pushes the button, toggling it.
+/// `OVHD_<NAME>_PB_SET` sets the button to the value passed with the event, which suits
+/// hardware that reports the position of a switch, including hold-to-activate buttons
+/// which report both their press and their release.
+struct PushButtonKeyEvents {
+    push_id: KeyEventIdentifier,
+    set_id: KeyEventIdentifier,
+}
+impl PushButtonKeyEvents {
+    fn new(name: &str) -> Self {
+        Self {
+            push_id: KeyEventIdentifier::new(format!("OVHD_{}_PB_PUSH", name)),
+            set_id: KeyEventIdentifier::new(format!("OVHD_{}_PB_SET", name)),
+        }
+    }
+
+    /// Returns the state of the push button after handling the key events of this tick.
+    /// A set event takes precedence over push events raised during the same tick.
+    fn read(&self, reader: &mut SimulatorReader, is_pushed_in: bool) -> bool {
+        let pushes = reader.key_events(&self.push_id).count();
+        let is_pushed_in = is_pushed_in != (pushes % 2 == 1);
+
+        reader
+            .key_events(&self.set_id)
+            .last()
+            .map_or(is_pushed_in, to_bool)
+    }
+}
 
 pub struct OnOffFaultPushButton {
-    is_on_id: String,
-    has_fault_id: String,
+    is_on_id: VariableIdentifier<bool>,
+    has_fault_id: VariableIdentifier<bool>,
+    key_events: PushButtonKeyEvents,
 
     is_on: bool,
     has_fault: bool,
+
+    name: String,
+    events: Vec<SimulationEvent>,
 }
 impl OnOffFaultPushButton {
     pub fn new_on(name: &str) -> Self {
@@ -18,10 +56,19 @@ impl OnOffFaultPushButton {
 
     fn new(name: &str, is_on: bool) -> Self {
         Self {
-            is_on_id: format!("OVHD_{}_PB_IS_ON", name),
-            has_fault_id: format!("OVHD_{}_PB_HAS_FAULT", name),
+            is_on_id: VariableIdentifier::new(
+                format!("OVHD_{}_PB_IS_ON", name),
+                VariableUnit::Bool,
+            ),
+            has_fault_id: VariableIdentifier::new(
+                format!("OVHD_{}_PB_HAS_FAULT", name),
+                VariableUnit::Bool,
+            ),
+            key_events: PushButtonKeyEvents::new(name),
             is_on,
             has_fault: false,
+            name: name.to_owned(),
+            events: vec![],
         }
     }
 
@@ -30,6 +77,10 @@ impl OnOffFaultPushButton {
     }
 
     pub fn set_fault(&mut self, has_fault: bool) {
+        if self.has_fault != has_fault {
+            self.events.push(fault_changed_event(&self.name, has_fault));
+        }
+
         self.has_fault = has_fault;
     }
 
@@ -54,23 +105,55 @@ impl OnOffFaultPushButton {
     }
 }
 impl SimulationElement for OnOffFaultPushButton {
+    fn identifier(&self) -> String {
+        format!("{}_pb", self.name.to_lowercase())
+    }
+
+    fn describe(&self, writer: &mut DescriptionWriter) {
+        writer.write("on", self.is_on);
+        writer.write("fault", self.has_fault);
+    }
+
     fn write(&self, writer: &mut SimulatorWriter) {
-        writer.write_bool(&self.is_on_id, self.is_on());
-        writer.write_bool(&self.has_fault_id, self.has_fault());
+        writer.write(&self.is_on_id, self.is_on());
+        writer.write(&self.has_fault_id, self.has_fault());
     }
 
     fn read(&mut self, reader: &mut SimulatorReader) {
-        self.set_on(reader.read_bool(&self.is_on_id));
-        self.set_fault(reader.read_bool(&self.has_fault_id));
+        let is_on = reader.read(&self.is_on_id);
+        self.set_on(self.key_events.read(reader, is_on));
+        // Faults are raised by the aircraft system simulation, thus reading doesn't record an event.
+        self.has_fault = reader.read(&self.has_fault_id);
+    }
+
+    fn snapshot(&self, writer: &mut SnapshotWriter) {
+        writer.write_bool(self.is_on_id.name(), self.is_on);
+        writer.write_bool(self.has_fault_id.name(), self.has_fault);
+    }
+
+    fn restore(&mut self, reader: &SnapshotReader) {
+        if let Some(is_on) = reader.read_bool(self.is_on_id.name()) {
+            self.is_on = is_on;
+        }
+        if let Some(has_fault) = reader.read_bool(self.has_fault_id.name()) {
+            self.has_fault = has_fault;
+        }
+    }
+
+    fn collect_events(&mut self, collector: &mut EventCollector) {
+        collector.collect(&mut self.events);
     }
 }
 
 pub struct OnOffAvailablePushButton {
-    is_on_id: String,
-    is_available_id: String,
+    is_on_id: VariableIdentifier<bool>,
+    is_available_id: VariableIdentifier<bool>,
+    key_events: PushButtonKeyEvents,
 
     is_on: bool,
     is_available: bool,
+
+    name: String,
 }
 impl OnOffAvailablePushButton {
     pub fn new_on(name: &str) -> Self {
@@ -83,10 +166,18 @@ impl OnOffAvailablePushButton {
 
     fn new(name: &str, is_on: bool) -> Self {
         Self {
-            is_on_id: format!("OVHD_{}_PB_IS_ON", name),
-            is_available_id: format!("OVHD_{}_PB_IS_AVAILABLE", name),
+            is_on_id: VariableIdentifier::new(
+                format!("OVHD_{}_PB_IS_ON", name),
+                VariableUnit::Bool,
+            ),
+            is_available_id: VariableIdentifier::new(
+                format!("OVHD_{}_PB_IS_AVAILABLE", name),
+                VariableUnit::Bool,
+            ),
+            key_events: PushButtonKeyEvents::new(name),
             is_on,
             is_available: false,
+            name: name.to_owned(),
         }
     }
 
@@ -119,23 +210,51 @@ impl OnOffAvailablePushButton {
     }
 }
 impl SimulationElement for OnOffAvailablePushButton {
+    fn identifier(&self) -> String {
+        format!("{}_pb", self.name.to_lowercase())
+    }
+
+    fn describe(&self, writer: &mut DescriptionWriter) {
+        writer.write("on", self.is_on);
+        writer.write("available", self.is_available);
+    }
+
     fn write(&self, writer: &mut SimulatorWriter) {
-        writer.write_bool(&self.is_on_id, self.is_on());
-        writer.write_bool(&self.is_available_id, self.is_available());
+        writer.write(&self.is_on_id, self.is_on());
+        writer.write(&self.is_available_id, self.is_available());
     }
 
     fn read(&mut self, reader: &mut SimulatorReader) {
-        self.set_on(reader.read_bool(&self.is_on_id));
-        self.set_available(reader.read_bool(&self.is_available_id));
+        let is_on = reader.read(&self.is_on_id);
+        self.set_on(self.key_events.read(reader, is_on));
+        self.set_available(reader.read(&self.is_available_id));
+    }
+
+    fn snapshot(&self, writer: &mut SnapshotWriter) {
+        writer.write_bool(self.is_on_id.name(), self.is_on);
+        writer.write_bool(self.is_available_id.name(), self.is_available);
+    }
+
+    fn restore(&mut self, reader: &SnapshotReader) {
+        if let Some(is_on) = reader.read_bool(self.is_on_id.name()) {
+            self.is_on = is_on;
+        }
+        if let Some(is_available) = reader.read_bool(self.is_available_id.name()) {
+            self.is_available = is_available;
+        }
     }
 }
 
 pub struct NormalAltnFaultPushButton {
-    is_normal_id: String,
-    has_fault_id: String,
+    is_normal_id: VariableIdentifier<bool>,
+    has_fault_id: VariableIdentifier<bool>,
+    key_events: PushButtonKeyEvents,
 
     is_normal: bool,
     has_fault: bool,
+
+    name: String,
+    events: Vec<SimulationEvent>,
 }
 impl NormalAltnFaultPushButton {
     pub fn new_normal(name: &str) -> Self {
@@ -148,10 +267,19 @@ impl NormalAltnFaultPushButton {
 
     fn new(name: &str, is_normal: bool) -> Self {
         Self {
-            is_normal_id: format!("OVHD_{}_PB_IS_NORMAL", name),
-            has_fault_id: format!("OVHD_{}_PB_HAS_FAULT", name),
+            is_normal_id: VariableIdentifier::new(
+                format!("OVHD_{}_PB_IS_NORMAL", name),
+                VariableUnit::Bool,
+            ),
+            has_fault_id: VariableIdentifier::new(
+                format!("OVHD_{}_PB_HAS_FAULT", name),
+                VariableUnit::Bool,
+            ),
+            key_events: PushButtonKeyEvents::new(name),
             is_normal,
             has_fault: false,
+            name: name.to_owned(),
+            events: vec![],
         }
     }
 
@@ -176,27 +304,63 @@ impl NormalAltnFaultPushButton {
     }
 
     pub fn set_fault(&mut self, value: bool) {
+        if self.has_fault != value {
+            self.events.push(fault_changed_event(&self.name, value));
+        }
+
         self.has_fault = value;
     }
 }
 impl SimulationElement for NormalAltnFaultPushButton {
+    fn identifier(&self) -> String {
+        format!("{}_pb", self.name.to_lowercase())
+    }
+
+    fn describe(&self, writer: &mut DescriptionWriter) {
+        writer.write("normal", self.is_normal);
+        writer.write("fault", self.has_fault);
+    }
+
     fn write(&self, writer: &mut SimulatorWriter) {
-        writer.write_bool(&self.is_normal_id, self.is_normal());
-        writer.write_bool(&self.has_fault_id, self.has_fault());
+        writer.write(&self.is_normal_id, self.is_normal());
+        writer.write(&self.has_fault_id, self.has_fault());
     }
 
     fn read(&mut self, reader: &mut SimulatorReader) {
-        self.set_normal(reader.read_bool(&self.is_normal_id));
-        self.set_fault(reader.read_bool(&self.has_fault_id));
+        let is_normal = reader.read(&self.is_normal_id);
+        self.set_normal(self.key_events.read(reader, is_normal));
+        self.has_fault = reader.read(&self.has_fault_id);
+    }
+
+    fn snapshot(&self, writer: &mut SnapshotWriter) {
+        writer.write_bool(self.is_normal_id.name(), self.is_normal);
+        writer.write_bool(self.has_fault_id.name(), self.has_fault);
+    }
+
+    fn restore(&mut self, reader: &SnapshotReader) {
+        if let Some(is_normal) = reader.read_bool(self.is_normal_id.name()) {
+            self.is_normal = is_normal;
+        }
+        if let Some(has_fault) = reader.read_bool(self.has_fault_id.name()) {
+            self.has_fault = has_fault;
+        }
+    }
+
+    fn collect_events(&mut self, collector: &mut EventCollector) {
+        collector.collect(&mut self.events);
     }
 }
 
 pub struct AutoOffFaultPushButton {
-    is_auto_id: String,
-    has_fault_id: String,
+    is_auto_id: VariableIdentifier<bool>,
+    has_fault_id: VariableIdentifier<bool>,
+    key_events: PushButtonKeyEvents,
 
     is_auto: bool,
     has_fault: bool,
+
+    name: String,
+    events: Vec<SimulationEvent>,
 }
 impl AutoOffFaultPushButton {
     pub fn new_auto(name: &str) -> Self {
@@ -209,10 +373,19 @@ impl AutoOffFaultPushButton {
 
     fn new(name: &str, is_auto: bool) -> Self {
         Self {
-            is_auto_id: format!("OVHD_{}_PB_IS_AUTO", name),
-            has_fault_id: format!("OVHD_{}_PB_HAS_FAULT", name),
+            is_auto_id: VariableIdentifier::new(
+                format!("OVHD_{}_PB_IS_AUTO", name),
+                VariableUnit::Bool,
+            ),
+            has_fault_id: VariableIdentifier::new(
+                format!("OVHD_{}_PB_HAS_FAULT", name),
+                VariableUnit::Bool,
+            ),
+            key_events: PushButtonKeyEvents::new(name),
             is_auto,
             has_fault: false,
+            name: name.to_owned(),
+            events: vec![],
         }
     }
 
@@ -240,27 +413,62 @@ impl AutoOffFaultPushButton {
         self.has_fault
     }
 
-    fn set_fault(&mut self, value: bool) {
+    pub fn set_fault(&mut self, value: bool) {
+        if self.has_fault != value {
+            self.events.push(fault_changed_event(&self.name, value));
+        }
+
         self.has_fault = value;
     }
 }
 impl SimulationElement for AutoOffFaultPushButton {
+    fn identifier(&self) -> String {
+        format!("{}_pb", self.name.to_lowercase())
+    }
+
+    fn describe(&self, writer: &mut DescriptionWriter) {
+        writer.write("auto", self.is_auto);
+        writer.write("fault", self.has_fault);
+    }
+
     fn write(&self, writer: &mut SimulatorWriter) {
-        writer.write_bool(&self.is_auto_id, self.is_auto());
-        writer.write_bool(&self.has_fault_id, self.has_fault());
+        writer.write(&self.is_auto_id, self.is_auto());
+        writer.write(&self.has_fault_id, self.has_fault());
     }
 
     fn read(&mut self, reader: &mut SimulatorReader) {
-        self.set_auto(reader.read_bool(&self.is_auto_id));
-        self.set_fault(reader.read_bool(&self.has_fault_id));
+        let is_auto = reader.read(&self.is_auto_id);
+        self.set_auto(self.key_events.read(reader, is_auto));
+        self.has_fault = reader.read(&self.has_fault_id);
+    }
+
+    fn snapshot(&self, writer: &mut SnapshotWriter) {
+        writer.write_bool(self.is_auto_id.name(), self.is_auto);
+        writer.write_bool(self.has_fault_id.name(), self.has_fault);
+    }
+
+    fn restore(&mut self, reader: &SnapshotReader) {
+        if let Some(is_auto) = reader.read_bool(self.is_auto_id.name()) {
+            self.is_auto = is_auto;
+        }
+        if let Some(has_fault) = reader.read_bool(self.has_fault_id.name()) {
+            self.has_fault = has_fault;
+        }
+    }
+
+    fn collect_events(&mut self, collector: &mut EventCollector) {
+        collector.collect(&mut self.events);
     }
 }
 
 pub struct FaultReleasePushButton {
-    is_released_id: String,
-    has_fault_id: String,
+    is_released_id: VariableIdentifier<bool>,
+    has_fault_id: VariableIdentifier<bool>,
     is_released: bool,
     has_fault: bool,
+
+    name: String,
+    events: Vec<SimulationEvent>,
 }
 impl FaultReleasePushButton {
     #[cfg(test)]
@@ -274,10 +482,18 @@ impl FaultReleasePushButton {
 
     fn new(name: &str, is_released: bool) -> Self {
         Self {
-            is_released_id: format!("OVHD_{}_PB_IS_RELEASED", name),
-            has_fault_id: format!("OVHD_{}_PB_HAS_FAULT", name),
+            is_released_id: VariableIdentifier::new(
+                format!("OVHD_{}_PB_IS_RELEASED", name),
+                VariableUnit::Bool,
+            ),
+            has_fault_id: VariableIdentifier::new(
+                format!("OVHD_{}_PB_HAS_FAULT", name),
+                VariableUnit::Bool,
+            ),
             is_released,
             has_fault: false,
+            name: name.to_owned(),
+            events: vec![],
         }
     }
 
@@ -290,6 +506,10 @@ impl FaultReleasePushButton {
     }
 
     pub fn set_fault(&mut self, fault: bool) {
+        if self.has_fault != fault {
+            self.events.push(fault_changed_event(&self.name, fault));
+        }
+
         self.has_fault = fault;
     }
 
@@ -298,26 +518,59 @@ impl FaultReleasePushButton {
     }
 }
 impl SimulationElement for FaultReleasePushButton {
+    fn identifier(&self) -> String {
+        format!("{}_pb", self.name.to_lowercase())
+    }
+
+    fn describe(&self, writer: &mut DescriptionWriter) {
+        writer.write("released", self.is_released);
+        writer.write("fault", self.has_fault);
+    }
+
     fn write(&self, writer: &mut SimulatorWriter) {
-        writer.write_bool(&self.is_released_id, self.is_released());
-        writer.write_bool(&self.has_fault_id, self.has_fault());
+        writer.write(&self.is_released_id, self.is_released());
+        writer.write(&self.has_fault_id, self.has_fault());
     }
 
     fn read(&mut self, reader: &mut SimulatorReader) {
-        self.set_released(reader.read_bool(&self.is_released_id));
-        self.set_fault(reader.read_bool(&self.has_fault_id));
+        self.set_released(reader.read(&self.is_released_id));
+        self.has_fault = reader.read(&self.has_fault_id);
+    }
+
+    fn snapshot(&self, writer: &mut SnapshotWriter) {
+        writer.write_bool(self.is_released_id.name(), self.is_released);
+        writer.write_bool(self.has_fault_id.name(), self.has_fault);
+    }
+
+    fn restore(&mut self, reader: &SnapshotReader) {
+        if let Some(is_released) = reader.read_bool(self.is_released_id.name()) {
+            self.is_released = is_released;
+        }
+        if let Some(has_fault) = reader.read_bool(self.has_fault_id.name()) {
+            self.has_fault = has_fault;
+        }
+    }
+
+    fn collect_events(&mut self, collector: &mut EventCollector) {
+        collector.collect(&mut self.events);
     }
 }
 
 pub struct FirePushButton {
-    is_released_id: String,
+    is_released_id: VariableIdentifier<bool>,
     is_released: bool,
+
+    name: String,
 }
 impl FirePushButton {
     pub fn new(name: &str) -> Self {
         Self {
-            is_released_id: format!("FIRE_BUTTON_{}", name),
+            is_released_id: VariableIdentifier::new(
+                format!("FIRE_BUTTON_{}", name),
+                VariableUnit::Bool,
+            ),
             is_released: false,
+            name: name.to_owned(),
         }
     }
 
@@ -330,12 +583,37 @@ impl FirePushButton {
     }
 }
 impl SimulationElement for FirePushButton {
+    fn identifier(&self) -> String {
+        format!("{}_fire_pb", self.name.to_lowercase())
+    }
+
+    fn describe(&self, writer: &mut DescriptionWriter) {
+        writer.write("released", self.is_released);
+    }
+
     fn write(&self, writer: &mut SimulatorWriter) {
-        writer.write_bool(&self.is_released_id, self.is_released());
+        writer.write(&self.is_released_id, self.is_released());
     }
 
     fn read(&mut self, reader: &mut SimulatorReader) {
-        self.set(reader.read_bool(&self.is_released_id));
+        self.set(reader.read(&self.is_released_id));
+    }
+
+    fn snapshot(&self, writer: &mut SnapshotWriter) {
+        writer.write_bool(self.is_released_id.name(), self.is_released);
+    }
+
+    fn restore(&mut self, reader: &SnapshotReader) {
+        if let Some(is_released) = reader.read_bool(self.is_released_id.name()) {
+            self.is_released = is_released;
+        }
+    }
+}
+
+fn fault_changed_event(name: &str, has_fault: bool) -> SimulationEvent {
+    SimulationEvent::PushButtonFaultChanged {
+        push_button_id: name.to_owned(),
+        has_fault,
     }
 }
 
@@ -365,6 +643,87 @@ mod on_off_fault_push_button_tests {
         assert!(test_bed.contains_key("OVHD_ELEC_GEN_1_PB_IS_ON"));
         assert!(test_bed.contains_key("OVHD_ELEC_GEN_1_PB_HAS_FAULT"));
     }
+
+    #[test]
+    fn records_an_event_when_the_fault_changes() {
+        let mut button = OnOffFaultPushButton::new_on("ELEC_GEN_1");
+        let mut test_bed = SimulationTestBed::new();
+
+        test_bed.run(&mut button, |button, _| button.set_fault(true));
+
+        assert_eq!(
+            test_bed.events(),
+            &[SimulationEvent::PushButtonFaultChanged {
+                push_button_id: "ELEC_GEN_1".to_owned(),
+                has_fault: true
+            }]
+        );
+    }
+
+    #[test]
+    fn reading_a_fault_from_the_simulator_records_no_event() {
+        let mut button = OnOffFaultPushButton::new_on("ELEC_GEN_1");
+        let mut test_bed = SimulationTestBed::new();
+        test_bed.write_bool("OVHD_ELEC_GEN_1_PB_HAS_FAULT", true);
+
+        test_bed.run_without_update(&mut button);
+
+        assert!(button.has_fault());
+        assert!(test_bed.events().is_empty());
+    }
+
+    #[test]
+    fn push_key_event_toggles_the_push_button() {
+        let mut button = OnOffFaultPushButton::new_on("ELEC_BAT_1");
+        let mut test_bed = SimulationTestBed::seeded_with(&mut button);
+        test_bed.trigger_key_event("OVHD_ELEC_BAT_1_PB_PUSH");
+
+        test_bed.run_without_update(&mut button);
+
+        assert!(button.is_off());
+        assert!(!test_bed.read_bool("OVHD_ELEC_BAT_1_PB_IS_ON"));
+    }
+
+    #[test]
+    fn push_key_events_within_a_tick_toggle_the_push_button_once_for_every_push() {
+        let mut button = OnOffFaultPushButton::new_on("ELEC_BAT_1");
+        let mut test_bed = SimulationTestBed::seeded_with(&mut button);
+        test_bed.trigger_key_event("OVHD_ELEC_BAT_1_PB_PUSH");
+        test_bed.trigger_key_event("OVHD_ELEC_BAT_1_PB_PUSH");
+
+        test_bed.run_without_update(&mut button);
+
+        assert!(button.is_on());
+    }
+
+    #[test]
+    fn set_key_event_sets_the_push_button_while_held() {
+        let mut button = OnOffFaultPushButton::new_off("ELEC_BAT_1");
+        let mut test_bed = SimulationTestBed::seeded_with(&mut button);
+
+        test_bed.trigger_key_event_with_value("OVHD_ELEC_BAT_1_PB_SET", 1.);
+        test_bed.run_without_update(&mut button);
+        assert!(button.is_on());
+
+        test_bed.run_without_update(&mut button);
+        assert!(button.is_on());
+
+        test_bed.trigger_key_event_with_value("OVHD_ELEC_BAT_1_PB_SET", 0.);
+        test_bed.run_without_update(&mut button);
+        assert!(button.is_off());
+    }
+
+    #[test]
+    fn set_key_event_takes_precedence_over_push_key_events() {
+        let mut button = OnOffFaultPushButton::new_off("ELEC_BAT_1");
+        let mut test_bed = SimulationTestBed::seeded_with(&mut button);
+        test_bed.trigger_key_event_with_value("OVHD_ELEC_BAT_1_PB_SET", 0.);
+        test_bed.trigger_key_event("OVHD_ELEC_BAT_1_PB_PUSH");
+
+        test_bed.run_without_update(&mut button);
+
+        assert!(button.is_off());
+    }
 }
 
 #[cfg(test)]
@@ -449,6 +808,45 @@ mod auto_off_fault_push_button_tests {
         assert!(test_bed.contains_key("OVHD_ELEC_BUS_TIE_PB_IS_AUTO"));
         assert!(test_bed.contains_key("OVHD_ELEC_BUS_TIE_PB_HAS_FAULT"));
     }
+
+    #[test]
+    fn records_an_event_when_the_fault_changes() {
+        let mut button = AutoOffFaultPushButton::new_auto("ELEC_BUS_TIE");
+        let mut test_bed = SimulationTestBed::new();
+
+        test_bed.run(&mut button, |button, _| button.set_fault(true));
+
+        assert_eq!(
+            test_bed.events(),
+            &[SimulationEvent::PushButtonFaultChanged {
+                push_button_id: "ELEC_BUS_TIE".to_owned(),
+                has_fault: true
+            }]
+        );
+    }
+
+    #[test]
+    fn reading_a_fault_from_the_simulator_records_no_event() {
+        let mut button = AutoOffFaultPushButton::new_auto("ELEC_BUS_TIE");
+        let mut test_bed = SimulationTestBed::new();
+        test_bed.write_bool("OVHD_ELEC_BUS_TIE_PB_HAS_FAULT", true);
+
+        test_bed.run_without_update(&mut button);
+
+        assert!(button.has_fault());
+        assert!(test_bed.events().is_empty());
+    }
+
+    #[test]
+    fn push_key_event_toggles_the_push_button() {
+        let mut button = AutoOffFaultPushButton::new_auto("ELEC_BUS_TIE");
+        let mut test_bed = SimulationTestBed::seeded_with(&mut button);
+        test_bed.trigger_key_event("OVHD_ELEC_BUS_TIE_PB_PUSH");
+
+        test_bed.run_without_update(&mut button);
+
+        assert!(button.is_off());
+    }
 }
 
 #[cfg(test)]
@@ -1,14 +1,11 @@
 use crate::{
     electrical::{Potential, PotentialSource},
-    simulation::UpdateContext,
+    simulation::{SnapshotReader, SnapshotWriter, UpdateContext},
 };
 use num_derive::FromPrimitive;
 use std::time::Duration;
 use uom::si::{f64::*, thermodynamic_temperature::degree_celsius};
 
-mod random;
-pub use random::*;
-
 /// Signals to the APU start contactor what position it should be in.
 pub trait ApuStartContactorsController {
     fn should_close_start_contactors(&self) -> bool;
@@ -63,6 +60,26 @@ impl DelayedTrueLogicGate {
     pub fn output(&self) -> bool {
         self.expression_result && self.delay <= self.true_duration
     }
+
+    /// Writes the gate's state into a snapshot, using the given name as a prefix.
+    /// As the gate isn't a `SimulationElement`, this is to be called by its owner.
+    pub fn snapshot(&self, writer: &mut SnapshotWriter, name: &str) {
+        writer.write_bool(
+            &format!("{}_EXPRESSION_RESULT", name),
+            self.expression_result,
+        );
+        writer.write_duration(&format!("{}_TRUE_DURATION", name), self.true_duration);
+    }
+
+    pub fn restore(&mut self, reader: &SnapshotReader, name: &str) {
+        if let Some(expression_result) = reader.read_bool(&format!("{}_EXPRESSION_RESULT", name)) {
+            self.expression_result = expression_result;
+        }
+
+        if let Some(true_duration) = reader.read_duration(&format!("{}_TRUE_DURATION", name)) {
+            self.true_duration = true_duration;
+        }
+    }
 }
 
 /// Given a current and target temperature, takes a coefficient and delta to
@@ -182,6 +199,31 @@ mod delayed_true_logic_gate_tests {
 
         assert_eq!(aircraft.gate_output(), false);
     }
+
+    #[test]
+    fn when_the_expression_is_true_and_the_simulation_is_paused_the_delay_does_not_pass() {
+        let mut aircraft = TestAircraft::new(DelayedTrueLogicGate::new(Duration::from_millis(100)));
+        let mut test_bed = SimulationTestBed::new_with_delta(Duration::from_millis(1_000));
+
+        aircraft.set_expression(true);
+        test_bed.set_paused(true);
+        test_bed.run_aircraft(&mut aircraft);
+
+        assert!(!aircraft.gate_output());
+    }
+
+    #[test]
+    fn when_the_expression_is_true_the_delay_passes_sooner_at_a_higher_simulation_rate() {
+        let mut aircraft =
+            TestAircraft::new(DelayedTrueLogicGate::new(Duration::from_millis(1_000)));
+        let mut test_bed = SimulationTestBed::new_with_delta(Duration::from_millis(250));
+
+        aircraft.set_expression(true);
+        test_bed.set_simulation_rate(4.);
+        test_bed.run_aircraft(&mut aircraft);
+
+        assert!(aircraft.gate_output());
+    }
 }
 
 #[cfg(test)]
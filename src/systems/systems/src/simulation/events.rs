@@ -0,0 +1,106 @@
+use std::fmt;
+
+use crate::apu::TurbineState;
+
+/// A change in the state of the aircraft system simulation which may be of interest
+/// to the host, e.g. for playing sounds, logging or displaying ECAM messages.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SimulationEvent {
+    ContactorClosed {
+        contactor_id: String,
+    },
+    ContactorOpened {
+        contactor_id: String,
+    },
+    /// The battery contactor opened because the battery was discharging
+    /// while the aircraft was on the ground.
+    BatteryContactorOpenedDueToDischargeProtection {
+        contactor_id: String,
+    },
+    PushButtonFaultChanged {
+        push_button_id: String,
+        has_fault: bool,
+    },
+    ApuTurbineStateChanged {
+        from: TurbineState,
+        to: TurbineState,
+    },
+}
+impl fmt::Display for SimulationEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SimulationEvent::ContactorClosed { contactor_id } => {
+                write!(f, "contactor {} closed", contactor_id)
+            }
+            SimulationEvent::ContactorOpened { contactor_id } => {
+                write!(f, "contactor {} opened", contactor_id)
+            }
+            SimulationEvent::BatteryContactorOpenedDueToDischargeProtection { contactor_id } => {
+                write!(
+                    f,
+                    "battery contactor {} opened due to discharge protection",
+                    contactor_id
+                )
+            }
+            SimulationEvent::PushButtonFaultChanged {
+                push_button_id,
+                has_fault,
+            } => write!(
+                f,
+                "push button {} fault {}",
+                push_button_id,
+                if *has_fault { "on" } else { "off" }
+            ),
+            SimulationEvent::ApuTurbineStateChanged { from, to } => {
+                write!(f, "APU turbine state {:?} -> {:?}", from, to)
+            }
+        }
+    }
+}
+
+/// Collects the events elements recorded since they were last collected.
+/// Elements record events in a `Vec<SimulationEvent>` when their state changes,
+/// and hand them over when visited.
+pub struct EventCollector<'a> {
+    events: &'a mut Vec<SimulationEvent>,
+}
+impl<'a> EventCollector<'a> {
+    pub fn new(events: &'a mut Vec<SimulationEvent>) -> Self {
+        Self { events }
+    }
+
+    /// Moves the given recorded events into the collector.
+    pub fn collect(&mut self, recorded: &mut Vec<SimulationEvent>) {
+        self.events.append(recorded);
+    }
+}
+
+#[cfg(test)]
+mod events_tests {
+    use super::*;
+
+    #[test]
+    fn collecting_moves_the_recorded_events() {
+        let mut events = vec![];
+        let mut recorded = vec![SimulationEvent::ContactorClosed {
+            contactor_id: "9XU1".to_owned(),
+        }];
+
+        EventCollector::new(&mut events).collect(&mut recorded);
+
+        assert_eq!(events.len(), 1);
+        assert!(recorded.is_empty());
+    }
+
+    #[test]
+    fn describes_the_event() {
+        assert_eq!(
+            SimulationEvent::ApuTurbineStateChanged {
+                from: TurbineState::Starting,
+                to: TurbineState::Running
+            }
+            .to_string(),
+            "APU turbine state Starting -> Running"
+        );
+    }
+}
@@ -0,0 +1,317 @@
+//! Compares the variables written by an aircraft against a checked-in golden file.
+//!
+//! A golden file contains the value of every variable written by the aircraft at selected
+//! times of a scenario. Comparing against it catches unintended changes to any of these
+//! variables, instead of only to those a test asserts. When a change is intended, run the
+//! test with the `UPDATE_GOLDEN_FILES` environment variable set to `1` to regenerate the
+//! file, and review the changes to the file before committing them.
+//!
+//! As golden files are only compared by tests, this module is only available to tests.
+//! Dependent crates enable it through the `golden-file` feature of their `systems`
+//! dev-dependency.
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    env,
+    fmt::{self, Display},
+    fs,
+    path::Path,
+    time::Duration,
+};
+
+/// The written variables at selected times of a scenario.
+///
+/// # Examples
+/// ```rust,no_run
+/// # use std::time::Duration;
+/// # use systems::simulation::{golden_file::GoldenFile, test::SimulationTestBed};
+/// # let mut test_bed = SimulationTestBed::new();
+/// let mut golden_file = GoldenFile::new();
+/// // Run the aircraft in the test bed, and at the selected times:
+/// golden_file.record(Duration::from_secs(30), test_bed.written_variables());
+///
+/// golden_file.assert_matches(concat!(env!("CARGO_MANIFEST_DIR"), "/golden/apu_start.golden"));
+/// ```
+#[derive(Debug, Default, PartialEq)]
+pub struct GoldenFile {
+    snapshots: BTreeMap<Duration, BTreeMap<String, String>>,
+}
+impl GoldenFile {
+    const UPDATE_ENVIRONMENT_VARIABLE: &'static str = "UPDATE_GOLDEN_FILES";
+    const HEADER: &'static str =
+        "# Written variables per time. Regenerate by running the test with UPDATE_GOLDEN_FILES=1.";
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the given variables as those written at the given time.
+    pub fn record(&mut self, time: Duration, variables: BTreeMap<String, f64>) {
+        self.snapshots.insert(
+            time,
+            variables
+                .into_iter()
+                .map(|(name, value)| (name, format_value(value)))
+                .collect(),
+        );
+    }
+
+    /// Asserts that the recorded variables match those in the golden file at the given path.
+    /// When the `UPDATE_GOLDEN_FILES` environment variable is set to `1`, the golden file is
+    /// (re)generated from the recorded variables instead.
+    ///
+    /// # Panics
+    ///
+    /// Panics with the differences when the recorded variables don't match the golden file,
+    /// or when the golden file doesn't exist.
+    pub fn assert_matches<P: AsRef<Path>>(&self, path: P) {
+        let update = matches!(
+            env::var(GoldenFile::UPDATE_ENVIRONMENT_VARIABLE).as_deref(),
+            Ok("1")
+        );
+
+        self.compare(path.as_ref(), update);
+    }
+
+    fn compare(&self, path: &Path, update: bool) {
+        if update {
+            if let Some(directory) = path.parent() {
+                fs::create_dir_all(directory).unwrap();
+            }
+
+            fs::write(path, self.to_string()).unwrap_or_else(|error| {
+                panic!("Couldn't write golden file {}: {}", path.display(), error)
+            });
+            return;
+        }
+
+        let text = fs::read_to_string(path).unwrap_or_else(|_| {
+            panic!(
+                "Golden file {} doesn't exist. Run the test with {}=1 to create it.",
+                path.display(),
+                GoldenFile::UPDATE_ENVIRONMENT_VARIABLE
+            )
+        });
+        let expected = GoldenFile::parse(&text).unwrap_or_else(|message| {
+            panic!("Golden file {} is invalid: {}", path.display(), message)
+        });
+
+        let differences = expected.differences(self);
+        if !differences.is_empty() {
+            panic!(
+                "The written variables differ from golden file {} (- expected, + actual):\n{}\n\
+                When the change is intended, run the test with {}=1 to regenerate the file.",
+                path.display(),
+                differences,
+                GoldenFile::UPDATE_ENVIRONMENT_VARIABLE
+            );
+        }
+    }
+
+    fn parse(text: &str) -> Result<Self, String> {
+        let mut snapshots = BTreeMap::new();
+        let mut current = None;
+
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(time) = line.strip_prefix("t=") {
+                let time = parse_time(time)
+                    .ok_or_else(|| format!("invalid time on line {}", index + 1))?;
+                snapshots.insert(time, BTreeMap::new());
+                current = Some(time);
+            } else {
+                let variables = current
+                    .and_then(|time| snapshots.get_mut(&time))
+                    .ok_or_else(|| format!("line {} precedes the first time", index + 1))?;
+                let (name, value) = match line.rfind('=') {
+                    Some(position) => (&line[..position], &line[position + 1..]),
+                    None => return Err(format!("invalid variable on line {}", index + 1)),
+                };
+
+                variables.insert(name.to_owned(), value.to_owned());
+            }
+        }
+
+        Ok(Self { snapshots })
+    }
+
+    /// Describes the differences between this (expected) and the actual variables,
+    /// one line per removed (-) or added (+) value, grouped by time.
+    fn differences(&self, actual: &GoldenFile) -> String {
+        let empty = BTreeMap::new();
+        let times: BTreeSet<_> = self
+            .snapshots
+            .keys()
+            .chain(actual.snapshots.keys())
+            .collect();
+
+        let mut differences = String::new();
+        for time in times {
+            let expected_variables = self.snapshots.get(time).unwrap_or(&empty);
+            let actual_variables = actual.snapshots.get(time).unwrap_or(&empty);
+            let names: BTreeSet<_> = expected_variables
+                .keys()
+                .chain(actual_variables.keys())
+                .collect();
+
+            let mut lines = vec![];
+            for name in names {
+                let expected_value = expected_variables.get(name);
+                let actual_value = actual_variables.get(name);
+                if expected_value != actual_value {
+                    if let Some(value) = expected_value {
+                        lines.push(format!("- {}={}", name, value));
+                    }
+                    if let Some(value) = actual_value {
+                        lines.push(format!("+ {}={}", name, value));
+                    }
+                }
+            }
+
+            if !lines.is_empty() {
+                differences.push_str(&format!("t={}\n", format_time(*time)));
+                for line in lines {
+                    differences.push_str(&line);
+                    differences.push('\n');
+                }
+            }
+        }
+
+        differences
+    }
+}
+impl Display for GoldenFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", GoldenFile::HEADER)?;
+        for (time, variables) in &self.snapshots {
+            writeln!(f)?;
+            writeln!(f, "t={}", format_time(*time))?;
+            for (name, value) in variables {
+                writeln!(f, "{}={}", name, value)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Values are rounded, such that tiny differences in floating point
+/// arithmetic between platforms don't cause the comparison to fail.
+fn format_value(value: f64) -> String {
+    let text = format!("{:.6}", value);
+    let text = if text.contains('.') {
+        text.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        &text
+    };
+
+    if text == "-0" {
+        "0".to_owned()
+    } else {
+        text.to_owned()
+    }
+}
+
+fn format_time(time: Duration) -> String {
+    if time.subsec_millis() == 0 {
+        format!("{}s", time.as_secs())
+    } else {
+        format!("{}ms", time.as_millis())
+    }
+}
+
+fn parse_time(text: &str) -> Option<Duration> {
+    if let Some(milliseconds) = text.strip_suffix("ms") {
+        milliseconds.parse().ok().map(Duration::from_millis)
+    } else {
+        text.strip_suffix('s')
+            .and_then(|seconds| seconds.parse().ok())
+            .map(Duration::from_secs)
+    }
+}
+
+#[cfg(test)]
+mod golden_file_tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn golden_file(snapshots: &[(u64, &[(&str, f64)])]) -> GoldenFile {
+        let mut golden_file = GoldenFile::new();
+        for (milliseconds, variables) in snapshots {
+            golden_file.record(
+                Duration::from_millis(*milliseconds),
+                variables
+                    .iter()
+                    .map(|(name, value)| (name.to_string(), *value))
+                    .collect(),
+            );
+        }
+
+        golden_file
+    }
+
+    fn temporary_path(name: &str) -> PathBuf {
+        env::temp_dir()
+            .join(format!("systems_golden_file_tests_{}", std::process::id()))
+            .join(name)
+    }
+
+    #[test]
+    fn formats_values_without_trailing_zeros() {
+        assert_eq!(format_value(1.), "1");
+        assert_eq!(format_value(27.50), "27.5");
+        assert_eq!(format_value(0.1 + 0.2), "0.3");
+        assert_eq!(format_value(-0.0000001), "0");
+    }
+
+    #[test]
+    fn text_parses_into_the_same_golden_file() {
+        let golden_file = golden_file(&[
+            (0, &[("APU_N", 0.), ("ELEC_AC_1_BUS_IS_POWERED", 0.)]),
+            (1500, &[("APU_N", 12.25), ("ELEC_AC_1_BUS_IS_POWERED", 1.)]),
+        ]);
+
+        assert_eq!(
+            GoldenFile::parse(&golden_file.to_string()).unwrap(),
+            golden_file
+        );
+    }
+
+    #[test]
+    fn differences_list_changed_removed_and_added_values_per_time() {
+        let expected = golden_file(&[(0, &[("A", 1.), ("B", 2.)]), (1000, &[("A", 1.)])]);
+        let actual = golden_file(&[(0, &[("A", 1.), ("B", 3.), ("C", 4.)]), (2000, &[])]);
+
+        assert_eq!(
+            expected.differences(&actual),
+            "t=0s\n- B=2\n+ B=3\n+ C=4\nt=1s\n- A=1\n"
+        );
+    }
+
+    #[test]
+    fn matching_golden_file_passes() {
+        let path = temporary_path("matching.golden");
+        let golden_file = golden_file(&[(0, &[("A", 1.)])]);
+        golden_file.compare(&path, true);
+
+        golden_file.compare(&path, false);
+    }
+
+    #[test]
+    #[should_panic(expected = "+ A=2")]
+    fn differing_golden_file_panics_with_the_differences() {
+        let path = temporary_path("differing.golden");
+        golden_file(&[(0, &[("A", 1.)])]).compare(&path, true);
+
+        golden_file(&[(0, &[("A", 2.)])]).compare(&path, false);
+    }
+
+    #[test]
+    #[should_panic(expected = "doesn't exist")]
+    fn missing_golden_file_panics() {
+        golden_file(&[]).compare(&temporary_path("missing.golden"), false);
+    }
+}
@@ -0,0 +1,302 @@
+use std::fmt::{self, Display};
+
+use super::{SimulationElement, SimulationElementVisitor};
+
+/// Describes an element of the aircraft's system simulation, together with the
+/// elements it contains.
+///
+/// The description is displayed as one line per element, containing the path
+/// of identifiers leading to the element and the element's live values, e.g.
+/// `A320/electrical/direct_current/battery_10: charge=21.3 Ah, potential=27.1 V`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ElementDescription {
+    identifier: String,
+    element_type: &'static str,
+    values: Vec<(String, String)>,
+    children: Vec<ElementDescription>,
+}
+impl ElementDescription {
+    pub fn identifier(&self) -> &str {
+        &self.identifier
+    }
+
+    pub fn element_type(&self) -> &'static str {
+        self.element_type
+    }
+
+    /// The live values of the element, in the order in which they were described.
+    pub fn values(&self) -> &[(String, String)] {
+        &self.values
+    }
+
+    pub fn value(&self, name: &str) -> Option<&str> {
+        self.values
+            .iter()
+            .find(|(value_name, _)| value_name == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn children(&self) -> &[ElementDescription] {
+        &self.children
+    }
+
+    /// Finds a descendant by the path of identifiers leading to it, e.g.
+    /// `electrical/direct_current/battery_10`. When multiple children share an
+    /// identifier, the first is followed.
+    pub fn find(&self, path: &str) -> Option<&ElementDescription> {
+        path.split('/').try_fold(self, |element, identifier| {
+            element
+                .children
+                .iter()
+                .find(|child| child.identifier == identifier)
+        })
+    }
+
+    fn fmt_with_parent(&self, f: &mut fmt::Formatter<'_>, parent_path: &str) -> fmt::Result {
+        let path = if parent_path.is_empty() {
+            self.identifier.clone()
+        } else {
+            format!("{}/{}", parent_path, self.identifier)
+        };
+
+        write!(f, "{}", path)?;
+        for (index, (name, value)) in self.values.iter().enumerate() {
+            write!(
+                f,
+                "{}{}={}",
+                if index == 0 { ": " } else { ", " },
+                name,
+                value
+            )?;
+        }
+        writeln!(f)?;
+
+        for child in &self.children {
+            child.fmt_with_parent(f, &path)?;
+        }
+
+        Ok(())
+    }
+}
+impl Display for ElementDescription {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_with_parent(f, "")
+    }
+}
+
+/// Collects the live values of a [`SimulationElement`] for its [`ElementDescription`].
+///
+/// [`SimulationElement`]: trait.SimulationElement.html
+/// [`ElementDescription`]: struct.ElementDescription.html
+pub struct DescriptionWriter<'a> {
+    values: &'a mut Vec<(String, String)>,
+}
+impl<'a> DescriptionWriter<'a> {
+    pub fn new(values: &'a mut Vec<(String, String)>) -> Self {
+        Self { values }
+    }
+
+    pub fn write<T: Display>(&mut self, name: &str, value: T) {
+        self.values.push((name.to_owned(), value.to_string()));
+    }
+}
+
+/// Returns the name of the given type without its module path and generic arguments.
+pub(super) fn short_type_name(type_name: &'static str) -> &'static str {
+    let without_generics = match type_name.find('<') {
+        Some(index) => &type_name[..index],
+        None => type_name,
+    };
+
+    match without_generics.rfind("::") {
+        Some(index) => &without_generics[index + 2..],
+        None => without_generics,
+    }
+}
+
+/// Converts a type name such as `AuxiliaryPowerUnit` into `auxiliary_power_unit`.
+pub(super) fn to_snake_case(name: &str) -> String {
+    let characters: Vec<char> = name.chars().collect();
+    let mut snake_case = String::with_capacity(name.len() + 4);
+
+    for (index, character) in characters.iter().enumerate() {
+        if character.is_uppercase() && index > 0 {
+            let previous = characters[index - 1];
+            let next_is_lowercase =
+                matches!(characters.get(index + 1), Some(next) if next.is_lowercase());
+
+            if previous.is_lowercase()
+                || previous.is_numeric()
+                || (previous.is_uppercase() && next_is_lowercase)
+            {
+                snake_case.push('_');
+            }
+        }
+
+        snake_case.extend(character.to_lowercase());
+    }
+
+    snake_case
+}
+
+/// Visits aircraft components in order to describe the element tree.
+///
+/// As elements are visited after their children, the descriptions of the children
+/// are kept on a stack until the element which contains them is visited.
+pub(super) struct DescriptionVisitor {
+    levels: Vec<Vec<ElementDescription>>,
+    children: Vec<ElementDescription>,
+}
+impl DescriptionVisitor {
+    pub fn new() -> Self {
+        Self {
+            levels: vec![vec![]],
+            children: vec![],
+        }
+    }
+
+    /// Returns the description of the last visited element at the top level.
+    pub fn into_description(mut self) -> Option<ElementDescription> {
+        self.levels.first_mut().and_then(|level| level.pop())
+    }
+}
+impl SimulationElementVisitor for DescriptionVisitor {
+    fn visit<T: SimulationElement>(&mut self, visited: &mut T) {
+        let mut values = vec![];
+        visited.describe(&mut DescriptionWriter::new(&mut values));
+
+        let description = ElementDescription {
+            identifier: visited.identifier(),
+            element_type: visited.element_type(),
+            values,
+            children: std::mem::take(&mut self.children),
+        };
+
+        if let Some(level) = self.levels.last_mut() {
+            level.push(description);
+        }
+    }
+
+    fn enter_children(&mut self) {
+        self.levels.push(vec![]);
+    }
+
+    fn leave_children(&mut self) {
+        self.children = self.levels.pop().unwrap_or_default();
+    }
+}
+
+#[cfg(test)]
+mod introspection_tests {
+    use super::*;
+
+    #[test]
+    fn short_type_name_removes_module_path_and_generic_arguments() {
+        assert_eq!(
+            short_type_name("systems::apu::AuxiliaryPowerUnit<systems::apu::Aps3200ApuGenerator>"),
+            "AuxiliaryPowerUnit"
+        );
+    }
+
+    #[test]
+    fn converts_type_names_to_snake_case() {
+        assert_eq!(to_snake_case("Battery"), "battery");
+        assert_eq!(
+            to_snake_case("A320DirectCurrentElectrical"),
+            "a320_direct_current_electrical"
+        );
+        assert_eq!(to_snake_case("APUGenerator"), "apu_generator");
+    }
+
+    struct Leaf {
+        identifier: &'static str,
+        value: f64,
+    }
+    impl SimulationElement for Leaf {
+        fn identifier(&self) -> String {
+            self.identifier.to_owned()
+        }
+
+        fn describe(&self, writer: &mut DescriptionWriter) {
+            writer.write("value", self.value);
+        }
+    }
+
+    struct Branch {
+        first: Leaf,
+        second: Leaf,
+    }
+    impl SimulationElement for Branch {
+        fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+            visitor.enter_children();
+            self.first.accept(visitor);
+            self.second.accept(visitor);
+            visitor.leave_children();
+
+            visitor.visit(self);
+        }
+    }
+
+    struct Root {
+        branch: Branch,
+        leaf: Leaf,
+    }
+    impl SimulationElement for Root {
+        fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+            visitor.enter_children();
+            self.branch.accept(visitor);
+            self.leaf.accept(visitor);
+            visitor.leave_children();
+
+            visitor.visit(self);
+        }
+    }
+
+    fn describe<T: SimulationElement>(element: &mut T) -> ElementDescription {
+        let mut visitor = DescriptionVisitor::new();
+        element.accept(&mut visitor);
+
+        visitor.into_description().unwrap()
+    }
+
+    fn root() -> Root {
+        Root {
+            branch: Branch {
+                first: Leaf {
+                    identifier: "first",
+                    value: 1.,
+                },
+                second: Leaf {
+                    identifier: "second",
+                    value: 2.5,
+                },
+            },
+            leaf: Leaf {
+                identifier: "third",
+                value: 3.,
+            },
+        }
+    }
+
+    #[test]
+    fn describes_the_element_tree() {
+        let description = describe(&mut root());
+
+        assert_eq!(description.identifier(), "root");
+        assert_eq!(description.element_type(), "Root");
+        assert_eq!(description.children().len(), 2);
+        assert_eq!(description.children()[0].children().len(), 2);
+        assert_eq!(
+            description.find("branch/second").unwrap().value("value"),
+            Some("2.5")
+        );
+    }
+
+    #[test]
+    fn displays_one_line_per_element() {
+        assert_eq!(
+            describe(&mut root()).to_string(),
+            "root\nroot/branch\nroot/branch/first: value=1\nroot/branch/second: value=2.5\nroot/third: value=3\n"
+        );
+    }
+}
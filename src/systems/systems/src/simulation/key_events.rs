@@ -0,0 +1,46 @@
+use std::borrow::Cow;
+
+/// A discrete event raised by the simulator, e.g. when a key binding or a home cockpit
+/// switch is used. Unlike a variable, an event only exists within the tick in which
+/// it is delivered, which makes it suitable for momentary actions.
+#[derive(Clone, Debug, PartialEq)]
+pub struct KeyEvent {
+    name: String,
+    value: f64,
+}
+impl KeyEvent {
+    pub fn new<U: Into<String>>(name: U, value: f64) -> Self {
+        Self {
+            name: name.into(),
+            value,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The data passed along with the event. Events which don't carry any data have a value of `0.`.
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+}
+
+/// Identifies a [`KeyEvent`] which an element handles. Create the identifier once when
+/// constructing the element, and pass it to the [`SimulatorReader`] on every tick.
+///
+/// [`KeyEvent`]: struct.KeyEvent.html
+/// [`SimulatorReader`]: struct.SimulatorReader.html
+#[derive(Clone, Debug, PartialEq)]
+pub struct KeyEventIdentifier {
+    name: Cow<'static, str>,
+}
+impl KeyEventIdentifier {
+    pub fn new<U: Into<Cow<'static, str>>>(name: U) -> Self {
+        Self { name: name.into() }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
@@ -1,12 +1,58 @@
-use std::time::Duration;
+use std::{any::type_name, time::Duration};
 
 mod update_context;
 pub use update_context::*;
 
+mod update_period;
+pub use update_period::*;
+
+mod random;
+pub use random::*;
+
+mod variables;
+pub use variables::*;
+
+mod variable_audit;
+pub use variable_audit::*;
+
+mod snapshot;
+pub use snapshot::*;
+
+mod events;
+pub use events::*;
+
+mod key_events;
+pub use key_events::*;
+
+mod starting_state;
+pub use starting_state::*;
+
+mod write_on_change;
+pub use write_on_change::*;
+
+mod recording;
+pub use recording::*;
+
+mod telemetry;
+pub use telemetry::*;
+
+mod introspection;
+pub use introspection::*;
+
+mod profiler;
+pub use profiler::*;
+
+#[cfg(any(test, feature = "golden-file"))]
+pub mod golden_file;
+#[cfg(any(test, feature = "property-test"))]
+pub mod property_test;
 pub mod test;
 
-use crate::electrical::consumption::{
-    ElectricPower, PowerConsumption, PowerConsumptionReport, SuppliedPower,
+use crate::{
+    electrical::consumption::{
+        ElectricPower, PowerConsumption, PowerConsumptionReport, SuppliedPower,
+    },
+    failures::FailureType,
 };
 
 /// Trait for a type which can read and write simulator data.
@@ -18,6 +64,25 @@ pub trait SimulatorReaderWriter {
     fn read(&mut self, name: &str) -> f64;
     /// Writes a variable with the given name to the simulator.
     fn write(&mut self, name: &str, value: f64);
+    /// Called at the start of every [`Simulation`] tick, before any variable is read.
+    /// The `delta` is the simulated time which passes during the tick, given the
+    /// simulation rate and pause state with which the tick is simulated.
+    ///
+    /// [`Simulation`]: struct.Simulation.html
+    fn start_tick(&mut self, _delta: Duration, _simulation_rate: f64, _is_paused: bool) {}
+    /// Returns the [`KeyEvent`]s raised since the previous call, in the order in which
+    /// they occurred. Called once at the start of every [`Simulation`] tick, after
+    /// `start_tick`. The events are handed to elements when they read from the simulator.
+    ///
+    /// [`KeyEvent`]: struct.KeyEvent.html
+    /// [`Simulation`]: struct.Simulation.html
+    fn read_key_events(&mut self) -> Vec<KeyEvent> {
+        vec![]
+    }
+    /// Called at the end of every [`Simulation`] tick, after all variables are written.
+    ///
+    /// [`Simulation`]: struct.Simulation.html
+    fn end_tick(&mut self) {}
 }
 
 /// An [`Aircraft`] that can be simulated by the [`Simulation`].
@@ -47,7 +112,8 @@ pub trait SimulationElement {
     /// # Examples
     /// The default implementation only visits the element itself.
     /// If the element contains fields pointing to other elements, you need to override
-    /// the default implementation:
+    /// the default implementation. Enclosing the children in [`enter_children`] and
+    /// [`leave_children`] allows visitors to know which element contains them:
     /// ```rust
     /// # use systems::simulation::{SimulationElement, SimulationElementVisitor};
     /// # struct InnerElement {}
@@ -57,12 +123,16 @@ pub trait SimulationElement {
     /// }
     /// impl SimulationElement for OuterElement {
     ///     fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+    ///         visitor.enter_children();
     ///         self.inner_element.accept(visitor);
+    ///         visitor.leave_children();
     ///
     ///         visitor.visit(self);
     ///     }
     /// }
     /// ```
+    /// [`enter_children`]: trait.SimulationElementVisitor.html#method.enter_children
+    /// [`leave_children`]: trait.SimulationElementVisitor.html#method.leave_children
     /// [`SimulationElement`]: trait.SimulationElement.html
     fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T)
     where
@@ -75,35 +145,156 @@ pub trait SimulationElement {
     /// # Examples
     /// ```rust
     /// # use systems::simulation::{SimulationElement, SimulationElementVisitor,
-    /// #    SimulatorReader, SimulatorWriter};
+    /// #    SimulatorReader, SimulatorWriter, VariableIdentifier};
     /// struct MySimulationElement {
+    ///     is_on_id: VariableIdentifier<bool>,
     ///     is_on: bool,
     /// }
     /// impl SimulationElement for MySimulationElement {
     ///     fn read(&mut self, reader: &mut SimulatorReader) {
-    ///         self.is_on = reader.read_bool("MY_SIMULATOR_ELEMENT_IS_ON");
+    ///         self.is_on = reader.read(&self.is_on_id);
     ///     }
     /// }
     /// ```
     fn read(&mut self, _reader: &mut SimulatorReader) {}
 
+    /// Identifies the element within the element which contains it. Defaults to the
+    /// type name in snake case. Override it when there are multiple elements of the
+    /// same type, e.g. to include the number of a battery.
+    fn identifier(&self) -> String {
+        to_snake_case(self.element_type())
+    }
+
+    /// The name of the element's type, without its module path and generic arguments.
+    fn element_type(&self) -> &'static str {
+        short_type_name(type_name::<Self>())
+    }
+
+    /// Describes the live values of the element for an [`ElementDescription`],
+    /// which is used for debugging and tooling.
+    /// # Examples
+    /// ```rust
+    /// # use systems::simulation::{DescriptionWriter, SimulationElement};
+    /// struct MySimulationElement {
+    ///     is_on: bool,
+    /// }
+    /// impl SimulationElement for MySimulationElement {
+    ///     fn describe(&self, writer: &mut DescriptionWriter) {
+    ///         writer.write("on", self.is_on);
+    ///     }
+    /// }
+    /// ```
+    /// [`ElementDescription`]: struct.ElementDescription.html
+    fn describe(&self, _writer: &mut DescriptionWriter) {}
+
     /// Writes data from the aircraft system simulation to a model which can be passed to the simulator.
     /// # Examples
     /// ```rust
     /// # use systems::simulation::{SimulationElement, SimulationElementVisitor,
-    /// #    SimulatorReader, SimulatorWriter};
+    /// #    SimulatorReader, SimulatorWriter, VariableIdentifier};
     /// struct MySimulationElement {
+    ///     is_on_id: VariableIdentifier<bool>,
     ///     is_on: bool,
     /// }
     /// impl SimulationElement for MySimulationElement {
     ///     fn write(&self, writer: &mut SimulatorWriter) {
-    ///        writer.write_bool("MY_SIMULATOR_ELEMENT_IS_ON", self.is_on);
+    ///        writer.write(&self.is_on_id, self.is_on);
     ///     }
     /// }
     /// ```
     /// [`Simulation`]: struct.Simulation.html
     fn write(&self, _writer: &mut SimulatorWriter) {}
 
+    /// Writes the internal state of the element into a [`SimulationSnapshot`].
+    /// Only state which cannot be derived from the simulator or other elements
+    /// during the next tick needs to be included.
+    /// # Examples
+    /// ```rust
+    /// # use systems::simulation::{SimulationElement, SnapshotWriter};
+    /// struct MySimulationElement {
+    ///     charge: f64,
+    /// }
+    /// impl SimulationElement for MySimulationElement {
+    ///     fn snapshot(&self, writer: &mut SnapshotWriter) {
+    ///         writer.write_f64("MY_SIMULATION_ELEMENT_CHARGE", self.charge);
+    ///     }
+    /// }
+    /// ```
+    /// [`SimulationSnapshot`]: struct.SimulationSnapshot.html
+    fn snapshot(&self, _writer: &mut SnapshotWriter) {}
+
+    /// Restores the internal state of the element from a [`SimulationSnapshot`].
+    /// Values missing from the snapshot should leave the element's state untouched.
+    /// # Examples
+    /// ```rust
+    /// # use systems::simulation::{SimulationElement, SnapshotReader};
+    /// struct MySimulationElement {
+    ///     charge: f64,
+    /// }
+    /// impl SimulationElement for MySimulationElement {
+    ///     fn restore(&mut self, reader: &SnapshotReader) {
+    ///         if let Some(charge) = reader.read_f64("MY_SIMULATION_ELEMENT_CHARGE") {
+    ///             self.charge = charge;
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    /// [`SimulationSnapshot`]: struct.SimulationSnapshot.html
+    fn restore(&mut self, _reader: &SnapshotReader) {}
+
+    /// Initialises the internal state of the element to the given [`StartingState`].
+    /// This is called before the first tick of the simulation. Note that children are
+    /// visited before their parent, thus a parent can overwrite the state of its children.
+    /// # Examples
+    /// ```rust
+    /// # use systems::simulation::{SimulationElement, StartingState};
+    /// struct MySimulationElement {
+    ///     is_running: bool,
+    /// }
+    /// impl SimulationElement for MySimulationElement {
+    ///     fn initialise(&mut self, state: StartingState) {
+    ///         self.is_running = state.engines_are_running();
+    ///     }
+    /// }
+    /// ```
+    /// [`StartingState`]: enum.StartingState.html
+    fn initialise(&mut self, _state: StartingState) {}
+
+    /// Hands over the [`SimulationEvent`]s the element recorded since it was last visited.
+    /// Elements record events when their state changes during an update, rather than
+    /// calling into the host. The events are collected after every simulation step.
+    /// # Examples
+    /// ```rust
+    /// # use systems::simulation::{EventCollector, SimulationElement, SimulationEvent};
+    /// struct MySimulationElement {
+    ///     events: Vec<SimulationEvent>,
+    /// }
+    /// impl SimulationElement for MySimulationElement {
+    ///     fn collect_events(&mut self, collector: &mut EventCollector) {
+    ///         collector.collect(&mut self.events);
+    ///     }
+    /// }
+    /// ```
+    /// [`SimulationEvent`]: enum.SimulationEvent.html
+    fn collect_events(&mut self, _collector: &mut EventCollector) {}
+
+    /// Returns the [`UpdatePeriod`] of an element which doesn't update on every step.
+    /// The [`Simulation`] advances the period before every step, after which the element
+    /// only updates when [`UpdatePeriod::due`] returns a context.
+    ///
+    /// [`UpdatePeriod`]: struct.UpdatePeriod.html
+    /// [`UpdatePeriod::due`]: struct.UpdatePeriod.html#method.due
+    /// [`Simulation`]: struct.Simulation.html
+    fn update_period(&mut self) -> Option<&mut UpdatePeriod> {
+        None
+    }
+
+    /// Adds the failure modes of the element to the given list.
+    /// Only the [`Failure`] type needs to implement this.
+    ///
+    /// [`Failure`]: ../failures/struct.Failure.html
+    fn register_failures(&self, _failures: &mut Vec<FailureType>) {}
+
     /// Receive power from the aircraft's electrical systems.
     /// The easiest way to deal with power consumption is using the [`PowerConsumer`] type.
     ///
@@ -156,6 +347,13 @@ pub trait SimulationElement {
 /// [`SimulationElement`]: trait.SimulationElement.html
 pub trait SimulationElementVisitor {
     fn visit<T: SimulationElement>(&mut self, visited: &mut T);
+
+    /// Called by an element before it accepts the visitor on its children.
+    fn enter_children(&mut self) {}
+
+    /// Called by an element after it accepted the visitor on its children,
+    /// before the element itself is visited.
+    fn leave_children(&mut self) {}
 }
 
 /// Runs the aircraft simulation every time [`tick`] is called.
@@ -204,40 +402,360 @@ pub trait SimulationElementVisitor {
 pub struct Simulation<'a, T: Aircraft, U: SimulatorReaderWriter> {
     aircraft: &'a mut T,
     simulator_read_writer: &'a mut U,
+    random: RandomNumberGenerator,
+    context_identifiers: UpdateContextIdentifiers,
+    maximum_step_size: Option<Duration>,
+    simulation_rate: f64,
+    is_paused: bool,
+    events: Vec<SimulationEvent>,
 }
 impl<'a, T: Aircraft, U: SimulatorReaderWriter> Simulation<'a, T, U> {
+    /// Creates a simulation with a random number generator seeded from entropy.
     pub fn new(aircraft: &'a mut T, simulator_read_writer: &'a mut U) -> Self {
+        Self::new_with_random_number_generator(
+            aircraft,
+            simulator_read_writer,
+            RandomNumberGenerator::from_entropy(),
+        )
+    }
+
+    /// Creates a simulation which uses the given random number generator. Use this with
+    /// a generator created from a known seed to reproduce a simulation run.
+    pub fn new_with_random_number_generator(
+        aircraft: &'a mut T,
+        simulator_read_writer: &'a mut U,
+        random: RandomNumberGenerator,
+    ) -> Self {
         Simulation {
             aircraft,
             simulator_read_writer,
+            random,
+            context_identifiers: UpdateContextIdentifiers::new(),
+            maximum_step_size: None,
+            simulation_rate: 1.,
+            is_paused: false,
+            events: vec![],
         }
     }
 
+    /// Limits the amount of time a single update of the aircraft covers. A [`tick`]
+    /// with a larger `delta` is split into multiple equally sized steps, each of which
+    /// updates the aircraft and distributes and consumes electrical power. The simulator
+    /// is still read and written only once per [`tick`].
+    ///
+    /// By default a [`tick`] is executed in a single step.
+    ///
+    /// [`tick`]: #method.tick
+    pub fn set_maximum_step_size(&mut self, maximum_step_size: Duration) {
+        assert!(
+            maximum_step_size > Duration::from_secs(0),
+            "The maximum step size must be greater than zero."
+        );
+        self.maximum_step_size = Some(maximum_step_size);
+    }
+
+    /// Sets the rate at which simulated time passes relative to the `delta` passed to
+    /// [`tick`]. At a simulation rate of 4, a [`tick`] covers four times its `delta`.
+    ///
+    /// By default the simulation rate is 1.
+    ///
+    /// [`tick`]: #method.tick
+    pub fn set_simulation_rate(&mut self, simulation_rate: f64) {
+        assert!(
+            simulation_rate > 0.,
+            "The simulation rate must be greater than zero."
+        );
+        self.simulation_rate = simulation_rate;
+    }
+
+    /// While paused, no simulated time passes during a [`tick`]. The simulator is still
+    /// read and written, such that changes made by the pilot are processed.
+    ///
+    /// [`tick`]: #method.tick
+    pub fn set_paused(&mut self, is_paused: bool) {
+        self.is_paused = is_paused;
+    }
+
+    pub fn random_number_generator(&self) -> &RandomNumberGenerator {
+        &self.random
+    }
+
+    /// The reader and writer through which the simulation exchanges variables with
+    /// the simulator. Write to it between ticks to provide input to the aircraft.
+    pub fn simulator_reader_writer(&mut self) -> &mut U {
+        self.simulator_read_writer
+    }
+
     /// Execute a single run of the simulation using the specified `delta` duration
     /// as the amount of time that has passed since the previous run.
+    ///
+    /// The simulated time which passes is the `delta` multiplied by the simulation rate,
+    /// or no time at all while paused. See [`set_simulation_rate`] and [`set_paused`].
+    ///
+    /// When a maximum step size is set, a large `delta` is split into multiple steps.
+    /// See [`set_maximum_step_size`] for details.
+    ///
+    /// [`set_maximum_step_size`]: #method.set_maximum_step_size
+    /// [`set_simulation_rate`]: #method.set_simulation_rate
+    /// [`set_paused`]: #method.set_paused
     pub fn tick(&mut self, delta: Duration) {
-        let mut reader = SimulatorReader::new(self.simulator_read_writer);
-        let context = UpdateContext::from_reader(&mut reader, delta);
+        self.tick_simulated(self.simulated_delta(delta));
+    }
+
+    /// Executes a tick in which the given amount of simulated time passes, regardless
+    /// of the simulation rate and pause state. Used to replay recorded ticks.
+    fn tick_simulated(&mut self, delta: Duration) {
+        self.events.clear();
+        self.simulator_read_writer
+            .start_tick(delta, self.simulation_rate, self.is_paused);
+        let key_events = self.simulator_read_writer.read_key_events();
+
+        let mut reader =
+            SimulatorReader::new(self.simulator_read_writer).with_key_events(&key_events);
+        let context = UpdateContext::from_reader(
+            &mut reader,
+            &self.context_identifiers,
+            delta,
+            self.simulation_rate,
+            self.is_paused,
+            &self.random,
+        );
 
         let mut visitor = SimulatorToSimulationVisitor::new(&mut reader);
         self.aircraft.accept(&mut visitor);
 
-        self.aircraft.update_before_power_distribution(&context);
+        let steps = self.number_of_steps(delta);
+        let step_delta = delta / steps;
+        for step in 1..=steps {
+            // The last step also covers the remainder of the division.
+            let step_delta = if step == steps {
+                delta - step_delta * (steps - 1)
+            } else {
+                step_delta
+            };
+
+            Self::step(self.aircraft, &context.with_delta(step_delta));
+            Self::collect_events(self.aircraft, &mut self.events);
+        }
+
+        self.write_to_simulator();
+        self.simulator_read_writer.end_tick();
+    }
+
+    fn simulated_delta(&self, delta: Duration) -> Duration {
+        if self.is_paused {
+            Duration::from_secs(0)
+        } else if (self.simulation_rate - 1.).abs() < f64::EPSILON {
+            delta
+        } else {
+            delta.mul_f64(self.simulation_rate)
+        }
+    }
+
+    fn number_of_steps(&self, delta: Duration) -> u32 {
+        match self.maximum_step_size {
+            Some(maximum_step_size) if delta > maximum_step_size => {
+                // Rounds up, such that no step is larger than the maximum step size.
+                ((delta.as_nanos() - 1) / maximum_step_size.as_nanos() + 1) as u32
+            }
+            _ => 1,
+        }
+    }
+
+    fn step(aircraft: &mut T, context: &UpdateContext) {
+        aircraft.accept(&mut UpdatePeriodVisitor::new(context.delta()));
+        aircraft.update_before_power_distribution(context);
+
+        let mut electric_power =
+            ElectricPower::from(aircraft.get_supplied_power(), context.delta());
+        electric_power.distribute_to(aircraft);
+
+        aircraft.update_after_power_distribution(context);
+
+        electric_power.consume_in(aircraft);
+        electric_power.report_consumption_to(aircraft);
+    }
+
+    /// The events which occurred during the last [`tick`], in the order in which they were collected.
+    ///
+    /// [`tick`]: #method.tick
+    pub fn events(&self) -> &[SimulationEvent] {
+        &self.events
+    }
+
+    fn collect_events(aircraft: &mut T, events: &mut Vec<SimulationEvent>) {
+        let mut collector = EventCollector::new(events);
+        let mut visitor = EventCollectionVisitor::new(&mut collector);
+        aircraft.accept(&mut visitor);
+    }
+
+    /// Restoring and initialising aren't state changes the host should respond to.
+    fn discard_events(&mut self) {
+        Self::collect_events(self.aircraft, &mut self.events);
+        self.events.clear();
+    }
+
+    /// Takes a snapshot of the aircraft's internal state, which can be
+    /// restored at a later time using [`restore`].
+    ///
+    /// [`restore`]: #method.restore
+    pub fn snapshot(&mut self) -> SimulationSnapshot {
+        let mut snapshot = SimulationSnapshot::new();
+        let mut writer = SnapshotWriter::new(&mut snapshot);
+        let mut visitor = SnapshotVisitor::new(&mut writer);
+        self.aircraft.accept(&mut visitor);
+
+        snapshot
+    }
+
+    /// Restores the aircraft's internal state from the given snapshot.
+    /// The restored state is immediately written to the simulator, such that
+    /// simulator variables (e.g. push button positions) are in agreement with
+    /// the restored state when the next [`tick`] reads them.
+    ///
+    /// [`tick`]: #method.tick
+    pub fn restore(&mut self, snapshot: &SimulationSnapshot) {
+        let reader = SnapshotReader::new(snapshot);
+        let mut visitor = RestoreVisitor::new(&reader);
+        self.aircraft.accept(&mut visitor);
+        self.discard_events();
+
+        self.write_to_simulator();
+    }
+
+    /// Initialises the aircraft to the given starting state. Call this before the
+    /// first [`tick`]. The initialised state is immediately written to the simulator,
+    /// such that simulator variables (e.g. push button positions) are in agreement with
+    /// the starting state when the first [`tick`] reads them.
+    ///
+    /// [`tick`]: #method.tick
+    pub fn initialise(&mut self, state: StartingState) {
+        let mut visitor = StartingStateVisitor::new(state);
+        self.aircraft.accept(&mut visitor);
+        self.discard_events();
+
+        self.write_to_simulator();
+    }
+
+    /// Registers the variables the aircraft reads from and writes to the simulator. Call
+    /// this once after creating the simulation, to detect problems such as multiple
+    /// elements writing the same variable before the first [`tick`].
+    ///
+    /// Registration calls the `read` function of every element without accessing the
+    /// simulator. Therefore, elements read 0.0 for every variable. As all data read from
+    /// the simulator is read again at the start of every [`tick`], this has no lasting effect.
+    ///
+    /// [`tick`]: #method.tick
+    pub fn register_variables(&mut self) -> Result<VariableRegistry, Vec<VariableRegistryError>> {
+        let mut registry = VariableRegistry::default();
+
+        registry.begin_owner(type_name::<UpdateContext>());
+        let mut null_read_writer = NullReaderWriter;
+        UpdateContext::from_reader(
+            &mut SimulatorReader::new_registering(&mut null_read_writer, &mut registry),
+            &self.context_identifiers,
+            Duration::from_secs(0),
+            self.simulation_rate,
+            self.is_paused,
+            &self.random,
+        );
+
+        let mut visitor = VariableRegistrationVisitor::new(&mut registry);
+        self.aircraft.accept(&mut visitor);
+        self.discard_events();
+
+        registry.into_result()
+    }
+
+    /// Lists the failure modes declared by the aircraft's components.
+    pub fn register_failures(&mut self) -> Vec<FailureType> {
+        let mut failures = vec![];
+        let mut visitor = FailureRegistrationVisitor::new(&mut failures);
+        self.aircraft.accept(&mut visitor);
+
+        failures
+    }
+
+    /// Describes the element tree of the aircraft, including the live values of its elements.
+    pub fn describe(&mut self) -> ElementDescription {
+        let mut visitor = DescriptionVisitor::new();
+        self.aircraft.accept(&mut visitor);
 
-        let mut electric_power = ElectricPower::from(self.aircraft.get_supplied_power(), delta);
-        electric_power.distribute_to(self.aircraft);
+        visitor
+            .into_description()
+            .expect("The aircraft should visit itself.")
+    }
 
-        self.aircraft.update_after_power_distribution(&context);
+    /// Activates the given failure. The failure takes effect during the next [`tick`].
+    ///
+    /// [`tick`]: #method.tick
+    pub fn activate_failure(&mut self, failure_type: &FailureType) {
+        self.simulator_read_writer
+            .write(&failure_type.variable_name(), from_bool(true));
+    }
 
-        electric_power.consume_in(self.aircraft);
-        electric_power.report_consumption_to(self.aircraft);
+    /// Clears the given failure. The failure is cleared during the next [`tick`].
+    ///
+    /// [`tick`]: #method.tick
+    pub fn deactivate_failure(&mut self, failure_type: &FailureType) {
+        self.simulator_read_writer
+            .write(&failure_type.variable_name(), from_bool(false));
+    }
 
+    fn write_to_simulator(&mut self) {
         let mut writer = SimulatorWriter::new(self.simulator_read_writer);
         let mut visitor = SimulationToSimulatorVisitor::new(&mut writer);
         self.aircraft.accept(&mut visitor);
     }
 }
 
+/// Visits aircraft components in order to list the failure modes they declare.
+struct FailureRegistrationVisitor<'a> {
+    failures: &'a mut Vec<FailureType>,
+}
+impl<'a> FailureRegistrationVisitor<'a> {
+    fn new(failures: &'a mut Vec<FailureType>) -> Self {
+        FailureRegistrationVisitor { failures }
+    }
+}
+impl SimulationElementVisitor for FailureRegistrationVisitor<'_> {
+    fn visit<T: SimulationElement>(&mut self, visited: &mut T) {
+        visited.register_failures(self.failures);
+    }
+}
+
+/// Visits aircraft components in order to advance their update periods.
+struct UpdatePeriodVisitor {
+    delta: Duration,
+}
+impl UpdatePeriodVisitor {
+    fn new(delta: Duration) -> Self {
+        UpdatePeriodVisitor { delta }
+    }
+}
+impl SimulationElementVisitor for UpdatePeriodVisitor {
+    fn visit<T: SimulationElement>(&mut self, visited: &mut T) {
+        if let Some(update_period) = visited.update_period() {
+            update_period.advance(self.delta);
+        }
+    }
+}
+
+/// Visits aircraft components in order to collect the events they recorded.
+struct EventCollectionVisitor<'a, 'b> {
+    collector: &'a mut EventCollector<'b>,
+}
+impl<'a, 'b> EventCollectionVisitor<'a, 'b> {
+    fn new(collector: &'a mut EventCollector<'b>) -> Self {
+        EventCollectionVisitor { collector }
+    }
+}
+impl SimulationElementVisitor for EventCollectionVisitor<'_, '_> {
+    fn visit<T: SimulationElement>(&mut self, visited: &mut T) {
+        visited.collect_events(self.collector);
+    }
+}
+
 /// Visits aircraft components in order to pass data coming
 /// from the simulator into the aircraft system simulation.
 pub(crate) struct SimulatorToSimulationVisitor<'a> {
@@ -273,101 +791,202 @@ impl<'a> SimulationElementVisitor for SimulationToSimulatorVisitor<'a> {
 /// Reads data from the simulator into the aircraft system simulation.
 pub struct SimulatorReader<'a> {
     simulator_read_writer: &'a mut dyn SimulatorReaderWriter,
+    registry: Option<&'a mut VariableRegistry>,
+    key_events: &'a [KeyEvent],
 }
 impl<'a> SimulatorReader<'a> {
     pub fn new(simulator_read_writer: &'a mut dyn SimulatorReaderWriter) -> Self {
         Self {
             simulator_read_writer,
+            registry: None,
+            key_events: &[],
+        }
+    }
+
+    /// Hands the given key events to the elements which read from this reader.
+    pub fn with_key_events(mut self, key_events: &'a [KeyEvent]) -> Self {
+        self.key_events = key_events;
+        self
+    }
+
+    /// Creates a reader which registers the variables that are read, while reading
+    /// 0.0 for every variable instead of accessing the simulator.
+    fn new_registering(
+        null_read_writer: &'a mut NullReaderWriter,
+        registry: &'a mut VariableRegistry,
+    ) -> Self {
+        Self {
+            simulator_read_writer: null_read_writer,
+            registry: Some(registry),
+            key_events: &[],
         }
     }
 
-    /// Reads an `f64` from the simulator.
+    /// Reads the identified variable from the simulator.
     /// # Examples
     /// ```rust
-    /// # use systems::simulation::{SimulationElement, SimulationElementVisitor,
-    /// #    SimulatorReader, SimulatorWriter};
+    /// # use systems::simulation::{SimulationElement, SimulatorReader, VariableIdentifier,
+    /// #    VariableUnit};
     /// struct MySimulationElement {
-    ///     n: f64,
+    ///     is_on_id: VariableIdentifier<bool>,
+    ///     is_on: bool,
+    /// }
+    /// impl MySimulationElement {
+    ///     fn new() -> Self {
+    ///         Self {
+    ///             is_on_id: VariableIdentifier::new("MY_SIMULATOR_ELEMENT_IS_ON", VariableUnit::Bool),
+    ///             is_on: false,
+    ///         }
+    ///     }
     /// }
     /// impl SimulationElement for MySimulationElement {
     ///     fn read(&mut self, reader: &mut SimulatorReader) {
-    ///         self.n = reader.read_f64("MY_SIMULATOR_ELEMENT_IS_ON");
+    ///         self.is_on = reader.read(&self.is_on_id);
     ///     }
     /// }
     /// ```
-    pub fn read_f64(&mut self, name: &str) -> f64 {
-        self.simulator_read_writer.read(name)
+    pub fn read<T: VariableValue>(&mut self, identifier: &VariableIdentifier<T>) -> T {
+        if let Some(registry) = self.registry.as_mut() {
+            registry.register_read(identifier.name(), identifier.unit());
+        }
+
+        T::from_f64(
+            self.simulator_read_writer.read(identifier.name()),
+            identifier.unit(),
+        )
     }
 
-    /// Reads a `bool` from the simulator.
+    /// Returns the values of the identified key event raised during this tick, in the
+    /// order in which they occurred. Elements handle key events after reading their
+    /// variables, such that an event takes precedence over the state of a variable.
     /// # Examples
     /// ```rust
-    /// # use systems::simulation::{SimulationElement, SimulationElementVisitor,
-    /// #    SimulatorReader, SimulatorWriter};
+    /// # use systems::simulation::{KeyEventIdentifier, SimulationElement, SimulatorReader};
     /// struct MySimulationElement {
+    ///     toggle_id: KeyEventIdentifier,
     ///     is_on: bool,
     /// }
+    /// impl MySimulationElement {
+    ///     fn new() -> Self {
+    ///         Self {
+    ///             toggle_id: KeyEventIdentifier::new("MY_SIMULATOR_ELEMENT_TOGGLE"),
+    ///             is_on: false,
+    ///         }
+    ///     }
+    /// }
     /// impl SimulationElement for MySimulationElement {
     ///     fn read(&mut self, reader: &mut SimulatorReader) {
-    ///         self.is_on = reader.read_bool("MY_SIMULATOR_ELEMENT_IS_ON");
+    ///         for _ in reader.key_events(&self.toggle_id) {
+    ///             self.is_on = !self.is_on;
+    ///         }
     ///     }
     /// }
     /// ```
-    pub fn read_bool(&mut self, name: &str) -> bool {
-        to_bool(self.read_f64(name))
+    pub fn key_events<'b>(
+        &mut self,
+        identifier: &'b KeyEventIdentifier,
+    ) -> impl Iterator<Item = f64> + 'b
+    where
+        'a: 'b,
+    {
+        if let Some(registry) = self.registry.as_mut() {
+            registry.register_key_event(identifier.name());
+        }
+
+        self.key_events
+            .iter()
+            .filter(move |event| event.name() == identifier.name())
+            .map(|event| event.value())
     }
 }
 
 /// Writes data from the aircraft system simulation into the the simulator.
 pub struct SimulatorWriter<'a> {
     simulator_read_writer: &'a mut dyn SimulatorReaderWriter,
+    registry: Option<&'a mut VariableRegistry>,
 }
 impl<'a> SimulatorWriter<'a> {
     pub fn new(simulator_read_writer: &'a mut dyn SimulatorReaderWriter) -> Self {
         Self {
             simulator_read_writer,
+            registry: None,
         }
     }
 
-    /// Write an `f64` to the simulator.
-    /// # Examples
-    /// ```rust
-    /// # use systems::simulation::{SimulationElement, SimulationElementVisitor,
-    /// #    SimulatorReader, SimulatorWriter};
-    /// struct MySimulationElement {
-    ///     n: f64,
-    /// }
-    /// impl SimulationElement for MySimulationElement {
-    ///     fn write(&self, writer: &mut SimulatorWriter) {
-    ///        writer.write_f64("MY_SIMULATOR_ELEMENT_N", self.n);
-    ///     }
-    /// }
-    /// ```
-    pub fn write_f64(&mut self, name: &str, value: f64) {
-        self.simulator_read_writer.write(name, value);
+    /// Creates a writer which registers the variables that are written,
+    /// without writing them to the simulator.
+    fn new_registering(
+        null_read_writer: &'a mut NullReaderWriter,
+        registry: &'a mut VariableRegistry,
+    ) -> Self {
+        Self {
+            simulator_read_writer: null_read_writer,
+            registry: Some(registry),
+        }
     }
 
-    /// Write a `bool` to the simulator.
+    /// Writes the identified variable to the simulator. Quantities are converted
+    /// to the variable's unit, e.g. a `Ratio` of 0.5 is written as 50 percent.
     /// # Examples
     /// ```rust
-    /// # use systems::simulation::{SimulationElement, SimulationElementVisitor,
-    /// #    SimulatorReader, SimulatorWriter};
+    /// # use systems::simulation::{SimulationElement, SimulatorWriter, VariableIdentifier,
+    /// #    VariableUnit};
+    /// # use uom::si::{f64::*, ratio::percent};
     /// struct MySimulationElement {
-    ///     is_on: bool,
+    ///     n_id: VariableIdentifier<Ratio>,
+    ///     n: Ratio,
+    /// }
+    /// impl MySimulationElement {
+    ///     fn new() -> Self {
+    ///         Self {
+    ///             n_id: VariableIdentifier::new("MY_SIMULATOR_ELEMENT_N", VariableUnit::Percent),
+    ///             n: Ratio::new::<percent>(0.),
+    ///         }
+    ///     }
     /// }
     /// impl SimulationElement for MySimulationElement {
     ///     fn write(&self, writer: &mut SimulatorWriter) {
-    ///        writer.write_bool("MY_SIMULATOR_ELEMENT_IS_ON", self.is_on);
+    ///         writer.write(&self.n_id, self.n);
     ///     }
     /// }
     /// ```
-    pub fn write_bool(&mut self, name: &str, value: bool) {
-        self.simulator_read_writer.write(name, from_bool(value));
+    pub fn write<T: VariableValue>(&mut self, identifier: &VariableIdentifier<T>, value: T) {
+        if let Some(registry) = self.registry.as_mut() {
+            registry.register_write(identifier.name(), identifier.unit());
+        }
+
+        self.simulator_read_writer
+            .write(identifier.name(), value.into_f64(identifier.unit()));
+    }
+}
+
+/// Used while registering variables, such that no variables are read from
+/// or written to the simulator.
+struct NullReaderWriter;
+impl SimulatorReaderWriter for NullReaderWriter {
+    fn read(&mut self, _: &str) -> f64 {
+        0.
     }
+
+    fn write(&mut self, _: &str, _: f64) {}
+}
+
+/// Registers the variables the given element reads and writes, by
+/// calling its read and write functions without accessing the simulator.
+fn register_variables_of<T: SimulationElement>(element: &mut T, registry: &mut VariableRegistry) {
+    let mut null_read_writer = NullReaderWriter;
+    element.read(&mut SimulatorReader::new_registering(
+        &mut null_read_writer,
+        registry,
+    ));
+    element.write(&mut SimulatorWriter::new_registering(
+        &mut null_read_writer,
+        registry,
+    ));
 }
 
 /// Converts a given `f64` representing a boolean value in the simulator into an actual `bool` value.
-fn to_bool(value: f64) -> bool {
+pub(crate) fn to_bool(value: f64) -> bool {
     (value - 1.).abs() < f64::EPSILON
 }
 
@@ -0,0 +1,238 @@
+use std::{
+    fmt,
+    time::{Duration, Instant},
+};
+
+/// Measures the time spent in named sections of an aircraft's update, such that the
+/// systems which take up most of the frame budget can be found. The profiler doesn't
+/// measure anything until it is enabled, thus it can remain in place when not profiling.
+///
+/// ```rust
+/// # use systems::simulation::Profiler;
+/// let mut profiler = Profiler::new();
+/// profiler.enable();
+///
+/// profiler.begin("apu");
+/// // Update the APU.
+/// profiler.begin("electrical");
+/// // Update the electrical system.
+/// profiler.end();
+///
+/// assert_eq!(profiler.sections().len(), 2);
+/// ```
+#[derive(Default)]
+pub struct Profiler {
+    is_enabled: bool,
+    current: Option<(&'static str, Instant)>,
+    sections: Vec<ProfiledSection>,
+}
+impl Profiler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn enable(&mut self) {
+        self.is_enabled = true;
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.is_enabled
+    }
+
+    /// Begins measuring the named section, ending the section measured before it.
+    /// Time spent in sections with the same name is added up.
+    pub fn begin(&mut self, name: &'static str) {
+        if self.is_enabled {
+            self.end();
+            self.current = Some((name, Instant::now()));
+        }
+    }
+
+    /// Ends measuring the current section.
+    pub fn end(&mut self) {
+        if let Some((name, start)) = self.current.take() {
+            let elapsed = start.elapsed();
+            match self
+                .sections
+                .iter_mut()
+                .find(|section| section.name == name)
+            {
+                Some(section) => section.add(elapsed),
+                None => self.sections.push(ProfiledSection::new(name, elapsed)),
+            }
+        }
+    }
+
+    /// The measured sections in the order in which they were first measured.
+    pub fn sections(&self) -> &[ProfiledSection] {
+        &self.sections
+    }
+
+    pub fn total(&self) -> Duration {
+        self.sections.iter().map(|section| section.total).sum()
+    }
+
+    /// Discards all measurements.
+    pub fn reset(&mut self) {
+        self.current = None;
+        self.sections.clear();
+    }
+}
+impl fmt::Display for Profiler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let total = self.total().as_secs_f64();
+
+        writeln!(
+            f,
+            "{:<24} {:>8} {:>12} {:>12} {:>12} {:>7}",
+            "section", "count", "total", "mean", "maximum", "share"
+        )?;
+        for section in &self.sections {
+            writeln!(
+                f,
+                "{:<24} {:>8} {:>12.2?} {:>12.2?} {:>12.2?} {:>6.1}%",
+                section.name,
+                section.count,
+                section.total,
+                section.mean(),
+                section.maximum,
+                if total > 0. {
+                    section.total.as_secs_f64() / total * 100.
+                } else {
+                    0.
+                }
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The time spent in a section measured by the [`Profiler`].
+///
+/// [`Profiler`]: struct.Profiler.html
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProfiledSection {
+    name: &'static str,
+    count: u32,
+    total: Duration,
+    maximum: Duration,
+}
+impl ProfiledSection {
+    fn new(name: &'static str, elapsed: Duration) -> Self {
+        Self {
+            name,
+            count: 1,
+            total: elapsed,
+            maximum: elapsed,
+        }
+    }
+
+    fn add(&mut self, elapsed: Duration) {
+        self.count += 1;
+        self.total += elapsed;
+        self.maximum = self.maximum.max(elapsed);
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// The number of times the section was measured.
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    pub fn total(&self) -> Duration {
+        self.total
+    }
+
+    /// The mean time spent in the section per measurement.
+    pub fn mean(&self) -> Duration {
+        self.total / self.count
+    }
+
+    /// The longest time spent in the section during a single measurement.
+    pub fn maximum(&self) -> Duration {
+        self.maximum
+    }
+}
+
+#[cfg(test)]
+mod profiler_tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn measures_nothing_until_enabled() {
+        let mut profiler = Profiler::new();
+
+        profiler.begin("apu");
+        profiler.end();
+
+        assert!(profiler.sections().is_empty());
+    }
+
+    #[test]
+    fn beginning_a_section_ends_the_current_section() {
+        let mut profiler = Profiler::new();
+        profiler.enable();
+
+        profiler.begin("apu");
+        profiler.begin("electrical");
+        profiler.end();
+
+        let names: Vec<&str> = profiler
+            .sections()
+            .iter()
+            .map(|section| section.name())
+            .collect();
+        assert_eq!(names, vec!["apu", "electrical"]);
+    }
+
+    #[test]
+    fn adds_up_the_time_spent_in_sections_with_the_same_name() {
+        let mut profiler = Profiler::new();
+        profiler.enable();
+
+        for _ in 0..2 {
+            profiler.begin("apu");
+            thread::sleep(Duration::from_millis(1));
+            profiler.end();
+        }
+
+        let section = &profiler.sections()[0];
+        assert_eq!(section.count(), 2);
+        assert!(section.total() >= Duration::from_millis(2));
+        assert!(section.maximum() >= Duration::from_millis(1));
+        assert!(section.maximum() <= section.total());
+    }
+
+    #[test]
+    fn reset_discards_all_measurements() {
+        let mut profiler = Profiler::new();
+        profiler.enable();
+        profiler.begin("apu");
+        profiler.end();
+
+        profiler.reset();
+
+        assert!(profiler.sections().is_empty());
+        assert_eq!(profiler.total(), Duration::from_secs(0));
+    }
+
+    #[test]
+    fn reports_every_section() {
+        let mut profiler = Profiler::new();
+        profiler.enable();
+        profiler.begin("apu");
+        profiler.begin("electrical");
+        profiler.end();
+
+        let report = profiler.to_string();
+
+        assert!(report.starts_with("section"));
+        assert!(report.contains("\napu "));
+        assert!(report.contains("\nelectrical "));
+    }
+}
@@ -0,0 +1,330 @@
+//! Checks that a property holds for randomly generated sequences of steps.
+//!
+//! When a sequence is found for which the property doesn't hold, the sequence is shrunk
+//! to a smaller and simpler sequence for which it still doesn't hold, such that the
+//! cause of the failure is easier to find. A property doesn't hold when checking it panics,
+//! which includes failing debug assertions within the checked code.
+//!
+//! As checking replaces the panic hook, this module is only available to tests. Dependent
+//! crates enable it through the `property-test` feature of their `systems` dev-dependency.
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::{
+    cell::RefCell,
+    env,
+    fmt::Debug,
+    panic::{self, AssertUnwindSafe},
+    sync::Once,
+};
+
+/// A step of a generated sequence which can be made simpler while shrinking.
+pub trait Shrink: Sized {
+    /// Returns variants of this step which are simpler, e.g. by being closer to a default
+    /// value or covering less time. Returns no variants when the step cannot be simplified.
+    fn simplifications(&self) -> Vec<Self> {
+        vec![]
+    }
+}
+
+/// Generates the random values from which steps are built.
+pub struct Generator {
+    random: StdRng,
+}
+impl Generator {
+    fn new(seed: u64) -> Self {
+        Self {
+            random: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// Returns true with the given probability.
+    pub fn chance(&mut self, probability: f64) -> bool {
+        self.random.gen_bool(probability)
+    }
+
+    /// Returns a value in the range from `low` up to, but not including, `high`.
+    pub fn between(&mut self, low: f64, high: f64) -> f64 {
+        self.random.gen_range(low..high)
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.random.gen_range(0..items.len())]
+    }
+}
+
+/// Checks a property against randomly generated sequences of steps.
+///
+/// ```rust
+/// # use systems::simulation::property_test::{PropertyTest, Shrink};
+/// #[derive(Clone, Debug)]
+/// struct Deposit(f64);
+/// impl Shrink for Deposit {}
+///
+/// PropertyTest::new().with_seed(42).check(
+///     |generator| Deposit(generator.between(0., 100.)),
+///     |deposits| {
+///         let balance: f64 = deposits.iter().map(|deposit| deposit.0).sum();
+///         assert!(balance >= 0.);
+///     },
+/// );
+/// ```
+///
+/// Sequences are generated from a fixed seed by default, such that a test checks the same
+/// sequences on every run. Set the `PROPERTY_TEST_SEED` environment variable to another
+/// seed to explore other sequences. Every failure reports the seed with which the failing
+/// sequence was generated. Use [`with_seed`] with that seed, or set `PROPERTY_TEST_SEED`
+/// to it, to reproduce the failure. The `PROPERTY_TEST_CASES` environment variable sets
+/// the number of sequences to generate, e.g. to search for failures for longer.
+///
+/// [`with_seed`]: #method.with_seed
+pub struct PropertyTest {
+    seed: u64,
+    cases: usize,
+    maximum_length: usize,
+    maximum_shrink_attempts: usize,
+}
+impl PropertyTest {
+    const DEFAULT_SEED: u64 = 0x5eed;
+    const DEFAULT_CASES: usize = 32;
+    const DEFAULT_MAXIMUM_LENGTH: usize = 30;
+    const DEFAULT_MAXIMUM_SHRINK_ATTEMPTS: usize = 500;
+
+    /// Creates a property test which generates sequences from a fixed seed, unless
+    /// the environment variables mentioned above are set.
+    pub fn new() -> Self {
+        Self {
+            seed: from_environment("PROPERTY_TEST_SEED").unwrap_or(PropertyTest::DEFAULT_SEED),
+            cases: from_environment("PROPERTY_TEST_CASES").unwrap_or(PropertyTest::DEFAULT_CASES),
+            maximum_length: PropertyTest::DEFAULT_MAXIMUM_LENGTH,
+            maximum_shrink_attempts: PropertyTest::DEFAULT_MAXIMUM_SHRINK_ATTEMPTS,
+        }
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// The number of sequences to generate.
+    pub fn with_cases(mut self, cases: usize) -> Self {
+        self.cases = cases;
+        self
+    }
+
+    /// The maximum number of steps in a generated sequence.
+    pub fn with_maximum_length(mut self, maximum_length: usize) -> Self {
+        assert!(
+            maximum_length > 0,
+            "The maximum length must be greater than zero."
+        );
+        self.maximum_length = maximum_length;
+        self
+    }
+
+    /// Checks the property against the generated sequences. The property doesn't hold
+    /// for a sequence when checking it panics.
+    ///
+    /// # Panics
+    ///
+    /// Panics with the shrunk sequence and the seed when a sequence is found for which
+    /// the property doesn't hold.
+    pub fn check<T, G, P>(&self, generate: G, property: P)
+    where
+        T: Clone + Debug + Shrink,
+        G: Fn(&mut Generator) -> T,
+        P: Fn(&[T]),
+    {
+        let mut generator = Generator::new(self.seed);
+
+        for _ in 0..self.cases {
+            let length = generator.random.gen_range(1..=self.maximum_length);
+            let sequence: Vec<T> = (0..length).map(|_| generate(&mut generator)).collect();
+
+            if let Err(failure) = holds(&property, &sequence) {
+                let (sequence, failure) = self.shrink(&property, sequence, failure);
+                panic!(
+                    "The property doesn't hold for the following sequence (seed {}):\n{:#?}\n{}",
+                    self.seed, sequence, failure
+                );
+            }
+        }
+    }
+
+    /// Repeatedly replaces the sequence by the first smaller or simpler sequence for
+    /// which the property doesn't hold, until no such sequence remains.
+    fn shrink<T, P>(&self, property: &P, sequence: Vec<T>, failure: String) -> (Vec<T>, String)
+    where
+        T: Clone + Shrink,
+        P: Fn(&[T]),
+    {
+        let mut sequence = sequence;
+        let mut failure = failure;
+        let mut attempts = 0;
+
+        'shrinking: while attempts < self.maximum_shrink_attempts {
+            for candidate in shrink_candidates(&sequence) {
+                attempts += 1;
+                if let Err(candidate_failure) = holds(property, &candidate) {
+                    sequence = candidate;
+                    failure = candidate_failure;
+                    continue 'shrinking;
+                }
+
+                if attempts >= self.maximum_shrink_attempts {
+                    break;
+                }
+            }
+
+            break;
+        }
+
+        (sequence, failure)
+    }
+}
+impl Default for PropertyTest {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn from_environment<T: std::str::FromStr>(name: &str) -> Option<T> {
+    env::var(name).ok().and_then(|value| value.parse().ok())
+}
+
+/// Returns smaller sequences first, as removing steps shrinks a sequence the most.
+fn shrink_candidates<T: Clone + Shrink>(sequence: &[T]) -> Vec<Vec<T>> {
+    let mut candidates = vec![];
+
+    let mut chunk_size = sequence.len() / 2;
+    while chunk_size > 0 {
+        for start in (0..sequence.len()).step_by(chunk_size) {
+            let end = (start + chunk_size).min(sequence.len());
+            let mut candidate = sequence[..start].to_vec();
+            candidate.extend_from_slice(&sequence[end..]);
+            candidates.push(candidate);
+        }
+
+        chunk_size /= 2;
+    }
+
+    for (index, step) in sequence.iter().enumerate() {
+        for simplification in step.simplifications() {
+            let mut candidate = sequence.to_vec();
+            candidate[index] = simplification;
+            candidates.push(candidate);
+        }
+    }
+
+    candidates
+}
+
+/// The state of the check running on the current thread.
+#[derive(Default)]
+struct CheckState {
+    is_checking: bool,
+    failure: Option<String>,
+}
+
+thread_local! {
+    static CHECK_STATE: RefCell<CheckState> = RefCell::new(CheckState::default());
+}
+
+/// Checks the property, returning the message and location of the panic when it doesn't hold.
+fn holds<T, P: Fn(&[T])>(property: &P, sequence: &[T]) -> Result<(), String> {
+    install_panic_hook();
+
+    CHECK_STATE.with(|state| state.borrow_mut().is_checking = true);
+    let result = panic::catch_unwind(AssertUnwindSafe(|| property(sequence)));
+    let failure = CHECK_STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        state.is_checking = false;
+        state.failure.take()
+    });
+
+    result.map_err(|_| failure.unwrap_or_else(|| "The property panicked.".to_owned()))
+}
+
+/// Panics are expected while checking and shrinking. Instead of printing them, the panic
+/// of the current check is kept. Panics elsewhere, including those of tests running on
+/// other threads, are handled as before.
+fn install_panic_hook() {
+    static INSTALL: Once = Once::new();
+
+    INSTALL.call_once(|| {
+        let previous_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            let is_checking = CHECK_STATE.with(|state| {
+                let mut state = state.borrow_mut();
+                if state.is_checking {
+                    state.failure = Some(info.to_string());
+                }
+
+                state.is_checking
+            });
+
+            if !is_checking {
+                previous_hook(info);
+            }
+        }));
+    });
+}
+
+#[cfg(test)]
+mod property_test_tests {
+    use super::*;
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Step(u32);
+    impl Shrink for Step {
+        fn simplifications(&self) -> Vec<Self> {
+            if self.0 > 0 {
+                vec![Step(self.0 / 2), Step(self.0 - 1)]
+            } else {
+                vec![]
+            }
+        }
+    }
+
+    fn failure_of<F: FnOnce()>(f: F) -> String {
+        let payload = panic::catch_unwind(AssertUnwindSafe(f)).unwrap_err();
+
+        payload.downcast_ref::<String>().unwrap().clone()
+    }
+
+    #[test]
+    fn passes_when_the_property_holds() {
+        PropertyTest::new().with_seed(1).check(
+            |generator| Step(generator.between(0., 10.) as u32),
+            |steps| assert!(steps.iter().all(|step| step.0 < 10)),
+        );
+    }
+
+    #[test]
+    fn shrinks_to_the_smallest_failing_sequence() {
+        let failure = failure_of(|| {
+            PropertyTest::new().with_seed(1).check(
+                |generator| Step(generator.between(0., 1000.) as u32),
+                |steps| assert!(steps.iter().all(|step| step.0 < 100), "too large"),
+            )
+        });
+
+        assert!(failure.contains("(seed 1)"));
+        assert!(failure.contains("Step(\n        100,\n    ),\n]"));
+        assert!(failure.contains("too large"));
+    }
+
+    #[test]
+    fn shrinks_sequences_which_fail_only_as_a_whole() {
+        let failure = failure_of(|| {
+            PropertyTest::new().with_seed(2).check(
+                |generator| Step(generator.between(0., 10.) as u32),
+                |steps| assert!(steps.iter().map(|step| step.0).sum::<u32>() < 20),
+            )
+        });
+
+        let total: u32 = failure
+            .lines()
+            .filter_map(|line| line.trim().trim_end_matches(',').parse::<u32>().ok())
+            .sum();
+        assert_eq!(total, 20);
+    }
+}
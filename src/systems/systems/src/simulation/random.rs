@@ -0,0 +1,118 @@
+use std::cell::Cell;
+
+#[cfg(not(any(target_arch = "wasm32", doc)))]
+use rand::Rng;
+
+/// A seedable source of random numbers for the aircraft system simulation.
+///
+/// Elements obtain random numbers through the [`UpdateContext`]. Two simulations which are
+/// created using the same seed and which receive the same input therefore behave identically.
+/// The generator is implemented within this crate (SplitMix64), such that the numbers
+/// generated for a seed don't change when dependencies are updated.
+///
+/// [`UpdateContext`]: struct.UpdateContext.html
+#[derive(Clone, Debug)]
+pub struct RandomNumberGenerator {
+    seed: u64,
+    state: Cell<u64>,
+}
+impl RandomNumberGenerator {
+    /// Seeds created from entropy are limited to 53 bits, such that
+    /// they can be exactly represented by a simulator variable.
+    const ENTROPY_SEED_MASK: u64 = (1 << 53) - 1;
+
+    pub fn from_seed(seed: u64) -> Self {
+        Self {
+            seed,
+            state: Cell::new(seed),
+        }
+    }
+
+    pub fn from_entropy() -> Self {
+        Self::from_seed(entropy() & Self::ENTROPY_SEED_MASK)
+    }
+
+    /// The seed with which the generator was created. Use this to
+    /// reproduce a simulation run.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn random_number(&self) -> u8 {
+        (self.next_u64() >> 56) as u8
+    }
+
+    fn next_u64(&self) -> u64 {
+        let state = self.state.get().wrapping_add(0x9e37_79b9_7f4a_7c15);
+        self.state.set(state);
+
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+#[cfg(not(any(target_arch = "wasm32", doc)))]
+fn entropy() -> u64 {
+    rand::thread_rng().gen()
+}
+
+#[cfg(any(target_arch = "wasm32", doc))]
+fn entropy() -> u64 {
+    let mut buf = [0; 8];
+    unsafe { wasi_random_get(buf.as_mut_ptr(), buf.len()) };
+
+    u64::from_le_bytes(buf)
+}
+
+#[link(wasm_import_module = "wasi_snapshot_preview1")]
+extern "C" {
+    #[link_name = "random_get"]
+    #[cfg(any(target_arch = "wasm32", doc))]
+    fn wasi_random_get(buf: *mut u8, buf_len: usize) -> u16;
+}
+
+#[cfg(test)]
+mod random_tests {
+    use super::*;
+
+    #[test]
+    fn generates_the_same_numbers_for_the_same_seed() {
+        let first = RandomNumberGenerator::from_seed(42);
+        let second = RandomNumberGenerator::from_seed(42);
+
+        for _ in 0..100 {
+            assert_eq!(first.random_number(), second.random_number());
+        }
+    }
+
+    #[test]
+    fn generates_different_numbers_for_different_seeds() {
+        let first = RandomNumberGenerator::from_seed(1);
+        let second = RandomNumberGenerator::from_seed(2);
+
+        let first_numbers: Vec<u8> = (0..10).map(|_| first.random_number()).collect();
+        let second_numbers: Vec<u8> = (0..10).map(|_| second.random_number()).collect();
+
+        assert_ne!(first_numbers, second_numbers);
+    }
+
+    #[test]
+    fn clone_continues_from_the_same_state() {
+        let generator = RandomNumberGenerator::from_seed(7);
+        generator.random_number();
+
+        let clone = generator.clone();
+
+        assert_eq!(generator.random_number(), clone.random_number());
+        assert_eq!(clone.seed(), 7);
+    }
+
+    #[test]
+    fn entropy_seed_can_be_represented_as_f64() {
+        let generator = RandomNumberGenerator::from_entropy();
+
+        assert_eq!((generator.seed() as f64) as u64, generator.seed());
+    }
+}
@@ -0,0 +1,797 @@
+use std::{
+    collections::HashMap,
+    io::{self, Read, Write},
+    time::Duration,
+};
+
+use num_traits::FromPrimitive;
+
+use super::{
+    Aircraft, KeyEvent, RandomNumberGenerator, Simulation, SimulatorReaderWriter, StartingState,
+};
+
+const MAGIC: &[u8; 8] = b"A32NXREC";
+/// Version 2 added key events. Version 3 added the simulation rate and pause state
+/// of every tick. Recordings of earlier versions can still be read, and are replayed
+/// at a simulation rate of 1 while unpaused.
+const VERSION: u8 = 3;
+
+const NAME_TAG: u8 = 1;
+const TICK_TAG: u8 = 2;
+const READ_TAG: u8 = 3;
+const WRITE_TAG: u8 = 4;
+const KEY_EVENT_TAG: u8 = 5;
+
+/// The settings of the [`Simulation`] which are required to replay a recording.
+///
+/// [`Simulation`]: struct.Simulation.html
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RecordingHeader {
+    seed: u64,
+    starting_state: Option<StartingState>,
+    maximum_step_size: Option<Duration>,
+}
+impl RecordingHeader {
+    pub fn new(
+        seed: u64,
+        starting_state: Option<StartingState>,
+        maximum_step_size: Option<Duration>,
+    ) -> Self {
+        Self {
+            seed,
+            starting_state,
+            maximum_step_size,
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn starting_state(&self) -> Option<StartingState> {
+        self.starting_state
+    }
+
+    pub fn maximum_step_size(&self) -> Option<Duration> {
+        self.maximum_step_size
+    }
+
+    fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[VERSION])?;
+        writer.write_all(&self.seed.to_le_bytes())?;
+        writer.write_all(&[self.starting_state.map_or(0, |state| state as u8)])?;
+        writer.write_all(
+            &self
+                .maximum_step_size
+                .map_or(0, |size| size.as_nanos() as u64)
+                .to_le_bytes(),
+        )
+    }
+
+    fn read_from<R: Read>(reader: &mut R) -> io::Result<(Self, u8)> {
+        let mut magic = [0; 8];
+        reader.read_exact(&mut magic)?;
+        let version = read_u8(reader)?;
+        if &magic != MAGIC || !(1..=VERSION).contains(&version) {
+            return Err(invalid_data("not a recording of a supported version"));
+        }
+
+        let seed = read_u64(reader)?;
+        let starting_state = match read_u8(reader)? {
+            0 => None,
+            value => Some(
+                StartingState::from_u8(value)
+                    .ok_or_else(|| invalid_data("unknown starting state"))?,
+            ),
+        };
+        let maximum_step_size = match read_u64(reader)? {
+            0 => None,
+            nanos => Some(Duration::from_nanos(nanos)),
+        };
+
+        Ok((Self::new(seed, starting_state, maximum_step_size), version))
+    }
+}
+
+/// The reads, writes and key events of a single tick. Only reads and writes of a value
+/// which differs from the value last read or written for the same variable are contained.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecordedTick {
+    delta: Duration,
+    simulation_rate: f64,
+    is_paused: bool,
+    reads: Vec<(String, f64)>,
+    writes: Vec<(String, f64)>,
+    key_events: Vec<KeyEvent>,
+}
+impl RecordedTick {
+    fn new(delta: Duration, simulation_rate: f64, is_paused: bool) -> Self {
+        Self {
+            delta,
+            simulation_rate,
+            is_paused,
+            reads: vec![],
+            writes: vec![],
+            key_events: vec![],
+        }
+    }
+
+    fn read_from<R: Read>(reader: &mut R, version: u8) -> io::Result<Self> {
+        let delta = Duration::from_nanos(read_u64(reader)?);
+        if version < 3 {
+            return Ok(Self::new(delta, 1., false));
+        }
+
+        let simulation_rate = f64::from_bits(read_u64(reader)?);
+        if simulation_rate.is_nan() || simulation_rate <= 0. {
+            return Err(invalid_data(
+                "the simulation rate must be greater than zero",
+            ));
+        }
+
+        Ok(Self::new(delta, simulation_rate, read_u8(reader)? != 0))
+    }
+
+    /// The simulated time which passed during the tick.
+    pub fn delta(&self) -> Duration {
+        self.delta
+    }
+
+    pub fn simulation_rate(&self) -> f64 {
+        self.simulation_rate
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.is_paused
+    }
+
+    pub fn reads(&self) -> &[(String, f64)] {
+        &self.reads
+    }
+
+    pub fn writes(&self) -> &[(String, f64)] {
+        &self.writes
+    }
+
+    pub fn key_events(&self) -> &[KeyEvent] {
+        &self.key_events
+    }
+}
+
+/// A recording created by a [`RecordingReaderWriter`].
+///
+/// [`RecordingReaderWriter`]: struct.RecordingReaderWriter.html
+#[derive(Clone, Debug, PartialEq)]
+pub struct Recording {
+    header: RecordingHeader,
+    ticks: Vec<RecordedTick>,
+}
+impl Recording {
+    pub fn read_from<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let (header, version) = RecordingHeader::read_from(reader)?;
+        let mut names: Vec<String> = vec![];
+        let mut ticks: Vec<RecordedTick> = vec![];
+
+        let mut tag = [0];
+        while reader.read(&mut tag)? > 0 {
+            match tag[0] {
+                NAME_TAG => {
+                    let mut name = vec![0; read_u16(reader)? as usize];
+                    reader.read_exact(&mut name)?;
+                    names.push(
+                        String::from_utf8(name)
+                            .map_err(|_| invalid_data("variable name isn't valid UTF-8"))?,
+                    );
+                }
+                TICK_TAG => ticks.push(RecordedTick::read_from(reader, version)?),
+                READ_TAG | WRITE_TAG | KEY_EVENT_TAG => {
+                    let name = names
+                        .get(read_u16(reader)? as usize)
+                        .ok_or_else(|| invalid_data("undefined variable name"))?
+                        .clone();
+                    let value = f64::from_bits(read_u64(reader)?);
+                    let tick = ticks
+                        .last_mut()
+                        .ok_or_else(|| invalid_data("variable recorded outside of a tick"))?;
+
+                    match tag[0] {
+                        READ_TAG => tick.reads.push((name, value)),
+                        WRITE_TAG => tick.writes.push((name, value)),
+                        _ => tick.key_events.push(KeyEvent::new(name, value)),
+                    }
+                }
+                _ => return Err(invalid_data("unknown record")),
+            }
+        }
+
+        Ok(Self { header, ticks })
+    }
+
+    pub fn header(&self) -> &RecordingHeader {
+        &self.header
+    }
+
+    pub fn ticks(&self) -> &[RecordedTick] {
+        &self.ticks
+    }
+}
+
+/// Wraps a [`SimulatorReaderWriter`] and records the delta time, simulation rate and pause
+/// state of every tick together with the variables read and written and the key events
+/// raised during that tick, in a compact binary format.
+///
+/// Only values which differ from the value last read or written for the same variable
+/// are recorded. Reads and writes outside of a tick, e.g. while initialising the aircraft
+/// to a starting state, aren't recorded as they are repeated by the replay.
+///
+/// Writing stops at the first error, which is available through [`error`].
+///
+/// [`SimulatorReaderWriter`]: trait.SimulatorReaderWriter.html
+/// [`error`]: #method.error
+pub struct RecordingReaderWriter<T: SimulatorReaderWriter, W: Write> {
+    simulator_read_writer: T,
+    writer: W,
+    names: HashMap<String, u16>,
+    last_read: HashMap<String, f64>,
+    last_written: HashMap<String, f64>,
+    is_in_tick: bool,
+    error: Option<io::Error>,
+}
+impl<T: SimulatorReaderWriter, W: Write> RecordingReaderWriter<T, W> {
+    pub fn new(
+        simulator_read_writer: T,
+        header: RecordingHeader,
+        mut writer: W,
+    ) -> io::Result<Self> {
+        header.write_to(&mut writer)?;
+
+        Ok(Self {
+            simulator_read_writer,
+            writer,
+            names: HashMap::new(),
+            last_read: HashMap::new(),
+            last_written: HashMap::new(),
+            is_in_tick: false,
+            error: None,
+        })
+    }
+
+    pub fn error(&self) -> Option<&io::Error> {
+        self.error.as_ref()
+    }
+
+    pub fn inner(&self) -> &T {
+        &self.simulator_read_writer
+    }
+
+    pub fn inner_mut(&mut self) -> &mut T {
+        &mut self.simulator_read_writer
+    }
+
+    pub fn into_writer(self) -> W {
+        self.writer
+    }
+
+    fn store_error(&mut self, result: io::Result<()>) {
+        if let Err(error) = result {
+            self.error = Some(error);
+        }
+    }
+
+    fn record_value(&mut self, tag: u8, name: &str, value: f64) -> io::Result<()> {
+        let index = match self.names.get(name) {
+            Some(index) => *index,
+            None => {
+                if self.names.len() > u16::MAX as usize {
+                    return Err(invalid_data("too many variables to record"));
+                }
+
+                let index = self.names.len() as u16;
+                self.writer.write_all(&[NAME_TAG])?;
+                self.writer.write_all(&(name.len() as u16).to_le_bytes())?;
+                self.writer.write_all(name.as_bytes())?;
+                self.names.insert(name.to_owned(), index);
+
+                index
+            }
+        };
+
+        self.writer.write_all(&[tag])?;
+        self.writer.write_all(&index.to_le_bytes())?;
+        self.writer.write_all(&value.to_bits().to_le_bytes())
+    }
+
+    fn should_record(&self) -> bool {
+        self.is_in_tick && self.error.is_none()
+    }
+}
+impl<T: SimulatorReaderWriter, W: Write> SimulatorReaderWriter for RecordingReaderWriter<T, W> {
+    fn read(&mut self, name: &str) -> f64 {
+        let value = self.simulator_read_writer.read(name);
+        if self.should_record() && !is_same(self.last_read.get(name), value) {
+            self.last_read.insert(name.to_owned(), value);
+            let result = self.record_value(READ_TAG, name, value);
+            self.store_error(result);
+        }
+
+        value
+    }
+
+    fn write(&mut self, name: &str, value: f64) {
+        self.simulator_read_writer.write(name, value);
+        if self.should_record() && !is_same(self.last_written.get(name), value) {
+            self.last_written.insert(name.to_owned(), value);
+            let result = self.record_value(WRITE_TAG, name, value);
+            self.store_error(result);
+        }
+    }
+
+    fn read_key_events(&mut self) -> Vec<KeyEvent> {
+        let key_events = self.simulator_read_writer.read_key_events();
+        for key_event in &key_events {
+            if self.should_record() {
+                let result = self.record_value(KEY_EVENT_TAG, key_event.name(), key_event.value());
+                self.store_error(result);
+            }
+        }
+
+        key_events
+    }
+
+    fn start_tick(&mut self, delta: Duration, simulation_rate: f64, is_paused: bool) {
+        self.simulator_read_writer
+            .start_tick(delta, simulation_rate, is_paused);
+        self.is_in_tick = true;
+
+        if self.error.is_none() {
+            // Flushing every tick ensures the recording is complete up to the
+            // previous tick, even when the host is terminated without notice.
+            let result = self
+                .writer
+                .flush()
+                .and_then(|_| self.writer.write_all(&[TICK_TAG]))
+                .and_then(|_| {
+                    self.writer
+                        .write_all(&(delta.as_nanos() as u64).to_le_bytes())
+                })
+                .and_then(|_| {
+                    self.writer
+                        .write_all(&simulation_rate.to_bits().to_le_bytes())
+                })
+                .and_then(|_| self.writer.write_all(&[is_paused as u8]));
+            self.store_error(result);
+        }
+    }
+
+    fn end_tick(&mut self) {
+        self.simulator_read_writer.end_tick();
+    }
+}
+
+/// A variable which was written with a different value during the replay than
+/// during the recording.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WriteDifference {
+    tick: usize,
+    name: String,
+    recorded: Option<f64>,
+    replayed: Option<f64>,
+}
+impl WriteDifference {
+    /// The zero-based index of the tick in which the difference occurred.
+    pub fn tick(&self) -> usize {
+        self.tick
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The value written during the recording, or `None` when the variable
+    /// wasn't written during the recording.
+    pub fn recorded(&self) -> Option<f64> {
+        self.recorded
+    }
+
+    /// The value written during the replay, or `None` when the variable
+    /// wasn't written during the replay.
+    pub fn replayed(&self) -> Option<f64> {
+        self.replayed
+    }
+}
+
+/// Replays the recording on the given aircraft, which should be freshly created.
+/// The recorded reads are fed to the aircraft and its writes are compared to
+/// the recorded writes. Every tick is replayed with its recorded simulation rate
+/// and pause state. Returns the differences in the order in which they occurred.
+///
+/// Variables which weren't read during the recording are read as `0.`.
+pub fn replay<T: Aircraft>(aircraft: &mut T, recording: &Recording) -> Vec<WriteDifference> {
+    let header = recording.header();
+    let mut reader_writer = ReplayReaderWriter::new(recording.ticks());
+    {
+        let mut simulation = Simulation::new_with_random_number_generator(
+            aircraft,
+            &mut reader_writer,
+            RandomNumberGenerator::from_seed(header.seed()),
+        );
+        if let Some(maximum_step_size) = header.maximum_step_size() {
+            simulation.set_maximum_step_size(maximum_step_size);
+        }
+        if let Some(starting_state) = header.starting_state() {
+            simulation.initialise(starting_state);
+        }
+
+        for tick in recording.ticks() {
+            simulation.set_simulation_rate(tick.simulation_rate());
+            simulation.set_paused(tick.is_paused());
+            simulation.tick_simulated(tick.delta());
+        }
+    }
+
+    reader_writer.finish()
+}
+
+/// Feeds the recorded reads to the simulation and compares its writes.
+struct ReplayReaderWriter<'a> {
+    ticks: &'a [RecordedTick],
+    tick: Option<usize>,
+    read_values: HashMap<&'a str, f64>,
+    recorded_values: HashMap<&'a str, f64>,
+    replayed_writes: HashMap<String, f64>,
+    differences: Vec<WriteDifference>,
+}
+impl<'a> ReplayReaderWriter<'a> {
+    fn new(ticks: &'a [RecordedTick]) -> Self {
+        Self {
+            ticks,
+            tick: None,
+            read_values: HashMap::new(),
+            recorded_values: HashMap::new(),
+            replayed_writes: HashMap::new(),
+            differences: vec![],
+        }
+    }
+
+    fn compare_writes(&mut self) {
+        let tick = match self.tick {
+            Some(tick) => tick,
+            None => return,
+        };
+
+        let mut differences = vec![];
+        for (name, replayed) in &self.replayed_writes {
+            let recorded = self.recorded_values.get(name.as_str()).copied();
+            if !is_same(recorded.as_ref(), *replayed) {
+                differences.push(WriteDifference {
+                    tick,
+                    name: name.clone(),
+                    recorded,
+                    replayed: Some(*replayed),
+                });
+            }
+        }
+
+        if let Some(recorded_tick) = self.ticks.get(tick) {
+            for (name, recorded) in recorded_tick.writes() {
+                if !self.replayed_writes.contains_key(name) {
+                    differences.push(WriteDifference {
+                        tick,
+                        name: name.clone(),
+                        recorded: Some(*recorded),
+                        replayed: None,
+                    });
+                }
+            }
+        }
+
+        self.replayed_writes.clear();
+        differences.sort_by(|a, b| a.name.cmp(&b.name));
+        differences.dedup_by(|a, b| a.name == b.name);
+        self.differences.append(&mut differences);
+    }
+
+    fn finish(mut self) -> Vec<WriteDifference> {
+        self.compare_writes();
+        self.differences
+    }
+}
+impl<'a> SimulatorReaderWriter for ReplayReaderWriter<'a> {
+    fn read(&mut self, name: &str) -> f64 {
+        *self.read_values.get(name).unwrap_or(&0.)
+    }
+
+    fn read_key_events(&mut self) -> Vec<KeyEvent> {
+        self.tick
+            .and_then(|tick| self.ticks.get(tick))
+            .map_or_else(Vec::new, |tick| tick.key_events().to_vec())
+    }
+
+    fn write(&mut self, name: &str, value: f64) {
+        if self.tick.is_some() {
+            self.replayed_writes.insert(name.to_owned(), value);
+        }
+    }
+
+    fn start_tick(&mut self, _: Duration, _: f64, _: bool) {
+        self.compare_writes();
+
+        let tick = self.tick.map_or(0, |tick| tick + 1);
+        self.tick = Some(tick);
+        if let Some(recorded_tick) = self.ticks.get(tick) {
+            for (name, value) in recorded_tick.reads() {
+                self.read_values.insert(name, *value);
+            }
+            for (name, value) in recorded_tick.writes() {
+                self.recorded_values.insert(name, *value);
+            }
+        }
+    }
+}
+
+fn is_same(last_value: Option<&f64>, value: f64) -> bool {
+    matches!(last_value, Some(last_value) if last_value.to_bits() == value.to_bits())
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+pub(super) fn read_u8<R: Read>(reader: &mut R) -> io::Result<u8> {
+    let mut bytes = [0; 1];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes[0])
+}
+
+pub(super) fn read_u16<R: Read>(reader: &mut R) -> io::Result<u16> {
+    let mut bytes = [0; 2];
+    reader.read_exact(&mut bytes)?;
+    Ok(u16::from_le_bytes(bytes))
+}
+
+pub(super) fn read_u32<R: Read>(reader: &mut R) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+pub(super) fn read_u64<R: Read>(reader: &mut R) -> io::Result<u64> {
+    let mut bytes = [0; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+#[cfg(test)]
+mod recording_tests {
+    use super::*;
+    use crate::simulation::{
+        KeyEventIdentifier, SimulationElement, SimulatorReader, SimulatorWriter, UpdateContext,
+        VariableIdentifier, VariableUnit,
+    };
+
+    #[derive(Default)]
+    struct MemoryReaderWriter {
+        variables: HashMap<String, f64>,
+        key_events: Vec<KeyEvent>,
+    }
+    impl SimulatorReaderWriter for MemoryReaderWriter {
+        fn read(&mut self, name: &str) -> f64 {
+            *self.variables.get(name).unwrap_or(&0.)
+        }
+
+        fn write(&mut self, name: &str, value: f64) {
+            self.variables.insert(name.to_owned(), value);
+        }
+
+        fn read_key_events(&mut self) -> Vec<KeyEvent> {
+            self.key_events.drain(..).collect()
+        }
+    }
+
+    struct MultiplyingAircraft {
+        input_id: VariableIdentifier<f64>,
+        output_id: VariableIdentifier<f64>,
+        add_id: KeyEventIdentifier,
+        factor: f64,
+        input: f64,
+    }
+    impl MultiplyingAircraft {
+        fn new(factor: f64) -> Self {
+            Self {
+                input_id: VariableIdentifier::new("INPUT", VariableUnit::Number),
+                output_id: VariableIdentifier::new("OUTPUT", VariableUnit::Number),
+                add_id: KeyEventIdentifier::new("ADD"),
+                factor,
+                input: 0.,
+            }
+        }
+    }
+    impl Aircraft for MultiplyingAircraft {}
+    impl SimulationElement for MultiplyingAircraft {
+        fn read(&mut self, reader: &mut SimulatorReader) {
+            self.input = reader.read(&self.input_id);
+            self.input += reader.key_events(&self.add_id).sum::<f64>();
+        }
+
+        fn write(&self, writer: &mut SimulatorWriter) {
+            writer.write(&self.output_id, self.input * self.factor);
+        }
+    }
+
+    /// Writes the simulation rate and pause state with which it was last updated.
+    struct ClockAircraft {
+        rate_id: VariableIdentifier<f64>,
+        is_paused_id: VariableIdentifier<bool>,
+        rate: f64,
+        is_paused: bool,
+    }
+    impl ClockAircraft {
+        fn new() -> Self {
+            Self {
+                rate_id: VariableIdentifier::new("RATE", VariableUnit::Number),
+                is_paused_id: VariableIdentifier::new("IS_PAUSED", VariableUnit::Bool),
+                rate: 0.,
+                is_paused: false,
+            }
+        }
+    }
+    impl Aircraft for ClockAircraft {
+        fn update_before_power_distribution(&mut self, context: &UpdateContext) {
+            self.rate = context.simulation_rate();
+            self.is_paused = context.is_paused();
+        }
+    }
+    impl SimulationElement for ClockAircraft {
+        fn write(&self, writer: &mut SimulatorWriter) {
+            writer.write(&self.rate_id, self.rate);
+            writer.write(&self.is_paused_id, self.is_paused);
+        }
+    }
+
+    fn header() -> RecordingHeader {
+        RecordingHeader::new(
+            42,
+            Some(StartingState::GateWithApu),
+            Some(Duration::from_millis(100)),
+        )
+    }
+
+    fn record<T: Aircraft>(aircraft: &mut T, inputs: &[f64]) -> Recording {
+        let mut reader_writer =
+            RecordingReaderWriter::new(MemoryReaderWriter::default(), header(), vec![]).unwrap();
+
+        for input in inputs {
+            reader_writer.inner_mut().write("INPUT", *input);
+            Simulation::new(aircraft, &mut reader_writer).tick(Duration::from_millis(50));
+        }
+
+        assert!(reader_writer.error().is_none());
+        Recording::read_from(&mut reader_writer.into_writer().as_slice()).unwrap()
+    }
+
+    fn record_clock(clocks: &[(f64, bool)]) -> Recording {
+        let mut aircraft = ClockAircraft::new();
+        let mut reader_writer =
+            RecordingReaderWriter::new(MemoryReaderWriter::default(), header(), vec![]).unwrap();
+
+        for (simulation_rate, is_paused) in clocks {
+            let mut simulation = Simulation::new(&mut aircraft, &mut reader_writer);
+            simulation.set_simulation_rate(*simulation_rate);
+            simulation.set_paused(*is_paused);
+            simulation.tick(Duration::from_millis(50));
+        }
+
+        Recording::read_from(&mut reader_writer.into_writer().as_slice()).unwrap()
+    }
+
+    fn record_key_event(key_event: KeyEvent) -> Recording {
+        let mut reader_writer =
+            RecordingReaderWriter::new(MemoryReaderWriter::default(), header(), vec![]).unwrap();
+        reader_writer.inner_mut().key_events.push(key_event);
+        Simulation::new(&mut MultiplyingAircraft::new(2.), &mut reader_writer)
+            .tick(Duration::from_millis(50));
+
+        Recording::read_from(&mut reader_writer.into_writer().as_slice()).unwrap()
+    }
+
+    #[test]
+    fn records_the_header() {
+        let recording = record(&mut MultiplyingAircraft::new(2.), &[]);
+
+        assert_eq!(recording.header(), &header());
+    }
+
+    #[test]
+    fn records_the_reads_and_writes_of_every_tick() {
+        let recording = record(&mut MultiplyingAircraft::new(2.), &[1., 3.]);
+
+        let ticks = recording.ticks();
+        assert_eq!(ticks.len(), 2);
+        assert_eq!(ticks[1].delta(), Duration::from_millis(50));
+        assert_eq!(ticks[1].reads(), &[("INPUT".to_owned(), 3.)]);
+        assert_eq!(ticks[1].writes(), &[("OUTPUT".to_owned(), 6.)]);
+    }
+
+    #[test]
+    fn records_the_simulation_rate_and_pause_state_of_every_tick() {
+        let recording = record_clock(&[(4., false), (1., true)]);
+
+        let ticks = recording.ticks();
+        assert_eq!(ticks[0].delta(), Duration::from_millis(200));
+        assert!((ticks[0].simulation_rate() - 4.).abs() < f64::EPSILON);
+        assert!(!ticks[0].is_paused());
+        assert_eq!(ticks[1].delta(), Duration::from_secs(0));
+        assert!(ticks[1].is_paused());
+    }
+
+    #[test]
+    fn replay_restores_the_simulation_rate_and_pause_state_of_every_tick() {
+        let recording = record_clock(&[(4., false), (1., true), (2., false)]);
+
+        assert!(replay(&mut ClockAircraft::new(), &recording).is_empty());
+    }
+
+    #[test]
+    fn only_records_changed_values() {
+        let recording = record(&mut MultiplyingAircraft::new(2.), &[1., 1.]);
+
+        let tick = &recording.ticks()[1];
+        assert!(tick.reads().is_empty());
+        assert!(tick.writes().is_empty());
+    }
+
+    #[test]
+    fn does_not_record_outside_of_a_tick() {
+        let mut reader_writer =
+            RecordingReaderWriter::new(MemoryReaderWriter::default(), header(), vec![]).unwrap();
+        reader_writer.write("OUTPUT", 1.);
+
+        let recording = Recording::read_from(&mut reader_writer.into_writer().as_slice()).unwrap();
+
+        assert!(recording.ticks().is_empty());
+    }
+
+    #[test]
+    fn records_the_key_events_of_every_tick() {
+        let recording = record_key_event(KeyEvent::new("ADD", 2.));
+
+        assert_eq!(
+            recording.ticks()[0].key_events(),
+            &[KeyEvent::new("ADD", 2.)]
+        );
+        assert_eq!(recording.ticks()[0].writes(), &[("OUTPUT".to_owned(), 4.)]);
+    }
+
+    #[test]
+    fn replay_raises_the_recorded_key_events() {
+        let recording = record_key_event(KeyEvent::new("ADD", 2.));
+
+        assert!(replay(&mut MultiplyingAircraft::new(2.), &recording).is_empty());
+    }
+
+    #[test]
+    fn rejects_data_which_is_not_a_recording() {
+        assert!(Recording::read_from(&mut b"NOT A RECORDING".as_ref()).is_err());
+    }
+
+    #[test]
+    fn replay_of_the_same_aircraft_has_no_differences() {
+        let recording = record(&mut MultiplyingAircraft::new(2.), &[1., 1., 3.]);
+
+        assert!(replay(&mut MultiplyingAircraft::new(2.), &recording).is_empty());
+    }
+
+    #[test]
+    fn replay_of_a_changed_aircraft_reports_the_differences() {
+        let recording = record(&mut MultiplyingAircraft::new(2.), &[1., 3.]);
+
+        let differences = replay(&mut MultiplyingAircraft::new(3.), &recording);
+
+        assert_eq!(differences.len(), 2);
+        assert_eq!(differences[1].tick(), 1);
+        assert_eq!(differences[1].name(), "OUTPUT");
+        assert_eq!(differences[1].recorded(), Some(6.));
+        assert_eq!(differences[1].replayed(), Some(9.));
+    }
+}
@@ -0,0 +1,300 @@
+use std::{collections::BTreeMap, error::Error, fmt, str::FromStr, time::Duration};
+
+use super::{from_bool, to_bool, SimulationElement, SimulationElementVisitor};
+
+/// Contains the internal state of an aircraft's system simulation at a given moment in time.
+/// A snapshot can be converted to and from a versioned text format using [`to_string`]
+/// and [`from_str`], allowing it to be stored in a file and restored at a later time.
+///
+/// The text format starts with a header line containing the format version, followed by
+/// one `NAME=VALUE` line per value.
+///
+/// [`to_string`]: #method.to_string
+/// [`from_str`]: #method.from_str
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SimulationSnapshot {
+    values: BTreeMap<String, f64>,
+}
+impl SimulationSnapshot {
+    const HEADER: &'static str = "A32NX_SYSTEMS_SNAPSHOT";
+    pub const FORMAT_VERSION: u32 = 1;
+
+    pub fn new() -> Self {
+        Self {
+            values: BTreeMap::new(),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<f64> {
+        self.values.get(name).copied()
+    }
+
+    pub fn contains_key(&self, name: &str) -> bool {
+        self.values.contains_key(name)
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    fn insert(&mut self, name: &str, value: f64) {
+        self.values.insert(name.to_owned(), value);
+    }
+}
+impl fmt::Display for SimulationSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} {}", Self::HEADER, Self::FORMAT_VERSION)?;
+        for (name, value) in &self.values {
+            // The default f64 formatting is the shortest representation
+            // which parses back into the exact same value.
+            writeln!(f, "{}={}", name, value)?;
+        }
+
+        Ok(())
+    }
+}
+impl FromStr for SimulationSnapshot {
+    type Err = SnapshotParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s.lines().enumerate();
+
+        let version = match lines.next() {
+            Some((_, header)) => {
+                let mut parts = header.split_whitespace();
+                match (parts.next(), parts.next(), parts.next()) {
+                    (Some(Self::HEADER), Some(version), None) => version
+                        .parse::<u32>()
+                        .map_err(|_| SnapshotParseError::MissingHeader)?,
+                    _ => return Err(SnapshotParseError::MissingHeader),
+                }
+            }
+            None => return Err(SnapshotParseError::MissingHeader),
+        };
+
+        if version != Self::FORMAT_VERSION {
+            return Err(SnapshotParseError::UnsupportedVersion(version));
+        }
+
+        let mut snapshot = SimulationSnapshot::new();
+        for (index, line) in lines {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let line_number = index + 1;
+            let mut parts = line.rsplitn(2, '=');
+            match (parts.next(), parts.next()) {
+                (Some(value), Some(name)) if !name.is_empty() => {
+                    let value = value
+                        .parse::<f64>()
+                        .map_err(|_| SnapshotParseError::InvalidLine(line_number))?;
+                    snapshot.insert(name, value);
+                }
+                _ => return Err(SnapshotParseError::InvalidLine(line_number)),
+            }
+        }
+
+        Ok(snapshot)
+    }
+}
+
+/// The reason a [`SimulationSnapshot`] couldn't be parsed.
+///
+/// [`SimulationSnapshot`]: struct.SimulationSnapshot.html
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SnapshotParseError {
+    /// The first line doesn't identify the text as a snapshot.
+    MissingHeader,
+    /// The snapshot was written in a format version which isn't supported.
+    UnsupportedVersion(u32),
+    /// The line with the given (1-based) number isn't a valid `NAME=VALUE` pair.
+    InvalidLine(usize),
+}
+impl fmt::Display for SnapshotParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotParseError::MissingHeader => write!(f, "missing snapshot header"),
+            SnapshotParseError::UnsupportedVersion(version) => write!(
+                f,
+                "unsupported snapshot format version {} (expected {})",
+                version,
+                SimulationSnapshot::FORMAT_VERSION
+            ),
+            SnapshotParseError::InvalidLine(line) => {
+                write!(f, "invalid snapshot value on line {}", line)
+            }
+        }
+    }
+}
+impl Error for SnapshotParseError {}
+
+/// Writes the internal state of the aircraft system simulation into a [`SimulationSnapshot`].
+///
+/// [`SimulationSnapshot`]: struct.SimulationSnapshot.html
+pub struct SnapshotWriter<'a> {
+    snapshot: &'a mut SimulationSnapshot,
+}
+impl<'a> SnapshotWriter<'a> {
+    pub fn new(snapshot: &'a mut SimulationSnapshot) -> Self {
+        Self { snapshot }
+    }
+
+    pub fn write_f64(&mut self, name: &str, value: f64) {
+        self.snapshot.insert(name, value);
+    }
+
+    pub fn write_bool(&mut self, name: &str, value: bool) {
+        self.write_f64(name, from_bool(value));
+    }
+
+    /// Writes a `Duration` with nanosecond precision, such that restoring
+    /// it doesn't cause delays to be off by a rounding error.
+    pub fn write_duration(&mut self, name: &str, value: Duration) {
+        self.write_f64(name, value.as_nanos() as f64);
+    }
+}
+
+/// Reads the internal state of the aircraft system simulation from a [`SimulationSnapshot`].
+/// All functions return `None` when the snapshot doesn't contain the requested value,
+/// in which case the element should keep its current state.
+///
+/// [`SimulationSnapshot`]: struct.SimulationSnapshot.html
+pub struct SnapshotReader<'a> {
+    snapshot: &'a SimulationSnapshot,
+}
+impl<'a> SnapshotReader<'a> {
+    pub fn new(snapshot: &'a SimulationSnapshot) -> Self {
+        Self { snapshot }
+    }
+
+    pub fn read_f64(&self, name: &str) -> Option<f64> {
+        self.snapshot.get(name)
+    }
+
+    pub fn read_bool(&self, name: &str) -> Option<bool> {
+        self.read_f64(name).map(to_bool)
+    }
+
+    pub fn read_duration(&self, name: &str) -> Option<Duration> {
+        self.read_f64(name)
+            .map(|nanos| Duration::from_nanos(nanos.max(0.) as u64))
+    }
+}
+
+/// Visits aircraft components in order to take a snapshot of their internal state.
+pub(super) struct SnapshotVisitor<'a> {
+    writer: &'a mut SnapshotWriter<'a>,
+}
+impl<'a> SnapshotVisitor<'a> {
+    pub fn new(writer: &'a mut SnapshotWriter<'a>) -> Self {
+        SnapshotVisitor { writer }
+    }
+}
+impl SimulationElementVisitor for SnapshotVisitor<'_> {
+    fn visit<T: SimulationElement>(&mut self, visited: &mut T) {
+        visited.snapshot(self.writer);
+    }
+}
+
+/// Visits aircraft components in order to restore their internal state from a snapshot.
+pub(super) struct RestoreVisitor<'a> {
+    reader: &'a SnapshotReader<'a>,
+}
+impl<'a> RestoreVisitor<'a> {
+    pub fn new(reader: &'a SnapshotReader<'a>) -> Self {
+        RestoreVisitor { reader }
+    }
+}
+impl SimulationElementVisitor for RestoreVisitor<'_> {
+    fn visit<T: SimulationElement>(&mut self, visited: &mut T) {
+        visited.restore(self.reader);
+    }
+}
+
+#[cfg(test)]
+mod snapshot_tests {
+    use super::*;
+
+    #[test]
+    fn converts_to_and_from_text() {
+        let mut snapshot = SimulationSnapshot::new();
+        let mut writer = SnapshotWriter::new(&mut snapshot);
+        writer.write_f64("CHARGE", 22.123456789012345);
+        writer.write_bool("IS_ON", true);
+        writer.write_duration("SINCE", Duration::from_millis(225));
+
+        let parsed: SimulationSnapshot = snapshot.to_string().parse().unwrap();
+
+        assert_eq!(parsed, snapshot);
+        let reader = SnapshotReader::new(&parsed);
+        assert_eq!(reader.read_f64("CHARGE"), Some(22.123456789012345));
+        assert_eq!(reader.read_bool("IS_ON"), Some(true));
+        assert_eq!(
+            reader.read_duration("SINCE"),
+            Some(Duration::from_millis(225))
+        );
+    }
+
+    #[test]
+    fn text_starts_with_versioned_header() {
+        let snapshot = SimulationSnapshot::new();
+
+        assert_eq!(snapshot.to_string(), "A32NX_SYSTEMS_SNAPSHOT 1\n");
+    }
+
+    #[test]
+    fn reading_an_unknown_value_returns_none() {
+        let snapshot = SimulationSnapshot::new();
+        let reader = SnapshotReader::new(&snapshot);
+
+        assert_eq!(reader.read_f64("UNKNOWN"), None);
+        assert_eq!(reader.read_bool("UNKNOWN"), None);
+        assert_eq!(reader.read_duration("UNKNOWN"), None);
+    }
+
+    #[test]
+    fn parsing_without_header_fails() {
+        assert_eq!(
+            "CHARGE=1".parse::<SimulationSnapshot>(),
+            Err(SnapshotParseError::MissingHeader)
+        );
+        assert_eq!(
+            "".parse::<SimulationSnapshot>(),
+            Err(SnapshotParseError::MissingHeader)
+        );
+    }
+
+    #[test]
+    fn parsing_unsupported_version_fails() {
+        assert_eq!(
+            "A32NX_SYSTEMS_SNAPSHOT 2\nCHARGE=1".parse::<SimulationSnapshot>(),
+            Err(SnapshotParseError::UnsupportedVersion(2))
+        );
+    }
+
+    #[test]
+    fn parsing_invalid_line_fails() {
+        assert_eq!(
+            "A32NX_SYSTEMS_SNAPSHOT 1\nCHARGE=1\nIS_ON".parse::<SimulationSnapshot>(),
+            Err(SnapshotParseError::InvalidLine(3))
+        );
+        assert_eq!(
+            "A32NX_SYSTEMS_SNAPSHOT 1\nCHARGE=abc".parse::<SimulationSnapshot>(),
+            Err(SnapshotParseError::InvalidLine(2))
+        );
+    }
+
+    #[test]
+    fn parsing_ignores_empty_lines() {
+        let snapshot: SimulationSnapshot =
+            "A32NX_SYSTEMS_SNAPSHOT 1\n\nCHARGE=1\n\n".parse().unwrap();
+
+        assert_eq!(snapshot.get("CHARGE"), Some(1.));
+    }
+}
@@ -0,0 +1,54 @@
+use num_derive::FromPrimitive;
+
+use super::{SimulationElement, SimulationElementVisitor};
+
+/// The phase of flight in which the aircraft's system simulation starts.
+///
+/// Only the state owned by the system simulation is initialised. State which is read from
+/// the simulator, such as the engine N2, fuel quantities and whether the aircraft is on the
+/// ground, has to be provided by the host in agreement with the chosen starting state.
+///
+/// The values start at 1, such that an unset simulator variable (0) doesn't select a state.
+#[derive(Clone, Copy, Debug, FromPrimitive, PartialEq)]
+pub enum StartingState {
+    /// All systems are shut down and the batteries are off.
+    ColdAndDark = 1,
+    /// At the gate with the APU running and available, while the engines are shut down.
+    GateWithApu = 2,
+    /// On the runway with both engines running and the APU shut down.
+    RunwayReady = 3,
+    /// In cruise with both engines running and the APU shut down.
+    Cruise = 4,
+}
+impl StartingState {
+    pub fn is_cold_and_dark(&self) -> bool {
+        *self == StartingState::ColdAndDark
+    }
+
+    pub fn apu_is_running(&self) -> bool {
+        *self == StartingState::GateWithApu
+    }
+
+    pub fn engines_are_running(&self) -> bool {
+        matches!(self, StartingState::RunwayReady | StartingState::Cruise)
+    }
+
+    pub fn is_in_flight(&self) -> bool {
+        *self == StartingState::Cruise
+    }
+}
+
+/// Visits aircraft components in order to initialise them to a starting state.
+pub(super) struct StartingStateVisitor {
+    state: StartingState,
+}
+impl StartingStateVisitor {
+    pub fn new(state: StartingState) -> Self {
+        StartingStateVisitor { state }
+    }
+}
+impl SimulationElementVisitor for StartingStateVisitor {
+    fn visit<T: SimulationElement>(&mut self, visited: &mut T) {
+        visited.initialise(self.state);
+    }
+}
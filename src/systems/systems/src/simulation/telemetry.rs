@@ -0,0 +1,378 @@
+use std::{
+    collections::HashMap,
+    io::{self, Read, Write},
+    time::Duration,
+};
+
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+
+use super::{
+    recording::{read_u16, read_u32, read_u64, read_u8},
+    KeyEvent, SimulatorReaderWriter,
+};
+
+const MAGIC: &[u8; 8] = b"A32NXTLM";
+const VERSION: u8 = 1;
+
+/// A time series of the values written to chosen variables.
+///
+/// Values are stored per variable, in columns. A variable which wasn't
+/// written before a sample was taken has a `NaN` value in that sample.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Telemetry {
+    names: Vec<String>,
+    times: Vec<Duration>,
+    columns: Vec<Vec<f64>>,
+}
+impl Telemetry {
+    fn new(names: Vec<String>) -> Self {
+        Self {
+            columns: vec![vec![]; names.len()],
+            names,
+            times: vec![],
+        }
+    }
+
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    /// The simulation time of every sample, being the sum of the tick deltas up to the sample.
+    pub fn times(&self) -> &[Duration] {
+        &self.times
+    }
+
+    pub fn column(&self, name: &str) -> Option<&[f64]> {
+        self.names
+            .iter()
+            .position(|column_name| column_name == name)
+            .map(|index| self.columns[index].as_slice())
+    }
+
+    pub fn number_of_samples(&self) -> usize {
+        self.times.len()
+    }
+
+    fn sample(&mut self, time: Duration, values: &[f64]) {
+        self.times.push(time);
+        for (column, value) in self.columns.iter_mut().zip(values) {
+            column.push(*value);
+        }
+    }
+
+    /// Writes the samples as comma separated values, with the time in seconds in the
+    /// first column. Values of variables which weren't written yet are left empty.
+    pub fn write_csv<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        write!(writer, "time")?;
+        for name in &self.names {
+            write!(writer, ",{}", name)?;
+        }
+        writeln!(writer)?;
+
+        for (row, time) in self.times.iter().enumerate() {
+            write!(writer, "{}", time.as_secs_f64())?;
+            for column in &self.columns {
+                let value = column[row];
+                if value.is_nan() {
+                    write!(writer, ",")?;
+                } else {
+                    write!(writer, ",{}", value)?;
+                }
+            }
+            writeln!(writer)?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the samples in a gzip compressed columnar format. As the values of a
+    /// single variable are stored next to each other, values which rarely change
+    /// compress well.
+    pub fn write_columnar<W: Write>(&self, writer: W) -> io::Result<()> {
+        let mut encoder = GzEncoder::new(writer, Compression::default());
+        encoder.write_all(MAGIC)?;
+        encoder.write_all(&[VERSION])?;
+        encoder.write_all(&(self.names.len() as u32).to_le_bytes())?;
+        encoder.write_all(&(self.times.len() as u32).to_le_bytes())?;
+
+        for name in &self.names {
+            encoder.write_all(&(name.len() as u16).to_le_bytes())?;
+            encoder.write_all(name.as_bytes())?;
+        }
+
+        for time in &self.times {
+            encoder.write_all(&(time.as_nanos() as u64).to_le_bytes())?;
+        }
+
+        for column in &self.columns {
+            for value in column {
+                encoder.write_all(&value.to_bits().to_le_bytes())?;
+            }
+        }
+
+        encoder.finish()?;
+        Ok(())
+    }
+
+    /// Reads samples written by [`write_columnar`].
+    ///
+    /// [`write_columnar`]: #method.write_columnar
+    pub fn read_columnar<R: Read>(reader: R) -> io::Result<Self> {
+        let mut decoder = GzDecoder::new(reader);
+
+        let mut magic = [0; 8];
+        decoder.read_exact(&mut magic)?;
+        if &magic != MAGIC || read_u8(&mut decoder)? != VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not telemetry of a supported version",
+            ));
+        }
+
+        let number_of_columns = read_u32(&mut decoder)? as usize;
+        let number_of_samples = read_u32(&mut decoder)? as usize;
+
+        let mut names = Vec::with_capacity(number_of_columns);
+        for _ in 0..number_of_columns {
+            let mut name = vec![0; read_u16(&mut decoder)? as usize];
+            decoder.read_exact(&mut name)?;
+            names.push(String::from_utf8(name).map_err(|_| {
+                io::Error::new(io::ErrorKind::InvalidData, "name isn't valid UTF-8")
+            })?);
+        }
+
+        let mut telemetry = Telemetry::new(names);
+        for _ in 0..number_of_samples {
+            telemetry
+                .times
+                .push(Duration::from_nanos(read_u64(&mut decoder)?));
+        }
+
+        for column in telemetry.columns.iter_mut() {
+            for _ in 0..number_of_samples {
+                column.push(f64::from_bits(read_u64(&mut decoder)?));
+            }
+        }
+
+        Ok(telemetry)
+    }
+}
+
+/// Wraps a [`SimulatorReaderWriter`] and samples the values written to the chosen
+/// variables at the end of a tick, either after every tick or at a sampling interval.
+///
+/// [`SimulatorReaderWriter`]: trait.SimulatorReaderWriter.html
+pub struct TelemetryReaderWriter<T: SimulatorReaderWriter> {
+    simulator_read_writer: T,
+    sampling_interval: Option<Duration>,
+    columns: HashMap<String, usize>,
+    values: Vec<f64>,
+    time: Duration,
+    next_sample_time: Duration,
+    telemetry: Telemetry,
+}
+impl<T: SimulatorReaderWriter> TelemetryReaderWriter<T> {
+    /// Samples the given variables after every tick.
+    pub fn new(simulator_read_writer: T, names: &[&str]) -> Self {
+        Self::new_with_sampling_interval(simulator_read_writer, names, Duration::from_secs(0))
+    }
+
+    /// Samples the given variables after the tick which reaches or passes the next
+    /// multiple of the interval.
+    pub fn new_with_sampling_interval(
+        simulator_read_writer: T,
+        names: &[&str],
+        sampling_interval: Duration,
+    ) -> Self {
+        let names: Vec<String> = names.iter().map(|name| (*name).to_owned()).collect();
+
+        Self {
+            simulator_read_writer,
+            sampling_interval: if sampling_interval > Duration::from_secs(0) {
+                Some(sampling_interval)
+            } else {
+                None
+            },
+            next_sample_time: sampling_interval,
+            columns: names
+                .iter()
+                .enumerate()
+                .map(|(index, name)| (name.clone(), index))
+                .collect(),
+            values: vec![f64::NAN; names.len()],
+            time: Duration::from_secs(0),
+            telemetry: Telemetry::new(names),
+        }
+    }
+
+    pub fn inner(&self) -> &T {
+        &self.simulator_read_writer
+    }
+
+    pub fn inner_mut(&mut self) -> &mut T {
+        &mut self.simulator_read_writer
+    }
+
+    pub fn into_telemetry(self) -> Telemetry {
+        self.telemetry
+    }
+
+    fn sample_when_due(&mut self) {
+        match self.sampling_interval {
+            Some(interval) => {
+                if self.time >= self.next_sample_time {
+                    self.telemetry.sample(self.time, &self.values);
+                    while self.next_sample_time <= self.time {
+                        self.next_sample_time += interval;
+                    }
+                }
+            }
+            None => self.telemetry.sample(self.time, &self.values),
+        }
+    }
+}
+impl<T: SimulatorReaderWriter> SimulatorReaderWriter for TelemetryReaderWriter<T> {
+    fn read(&mut self, name: &str) -> f64 {
+        self.simulator_read_writer.read(name)
+    }
+
+    fn write(&mut self, name: &str, value: f64) {
+        self.simulator_read_writer.write(name, value);
+        if let Some(index) = self.columns.get(name) {
+            self.values[*index] = value;
+        }
+    }
+
+    fn start_tick(&mut self, delta: Duration, simulation_rate: f64, is_paused: bool) {
+        self.simulator_read_writer
+            .start_tick(delta, simulation_rate, is_paused);
+        self.time += delta;
+    }
+
+    fn read_key_events(&mut self) -> Vec<KeyEvent> {
+        self.simulator_read_writer.read_key_events()
+    }
+
+    fn end_tick(&mut self) {
+        self.simulator_read_writer.end_tick();
+        self.sample_when_due();
+    }
+}
+
+#[cfg(test)]
+mod telemetry_tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct MemoryReaderWriter {
+        variables: HashMap<String, f64>,
+    }
+    impl SimulatorReaderWriter for MemoryReaderWriter {
+        fn read(&mut self, name: &str) -> f64 {
+            *self.variables.get(name).unwrap_or(&0.)
+        }
+
+        fn write(&mut self, name: &str, value: f64) {
+            self.variables.insert(name.to_owned(), value);
+        }
+    }
+
+    fn tick<T: SimulatorReaderWriter>(reader_writer: &mut T, delta_millis: u64, apu_n: f64) {
+        reader_writer.start_tick(Duration::from_millis(delta_millis), 1., false);
+        reader_writer.write("APU_N", apu_n);
+        reader_writer.write("APU_EGT", apu_n * 10.);
+        reader_writer.end_tick();
+    }
+
+    #[test]
+    fn samples_every_tick_at_the_time_derived_from_the_deltas() {
+        let mut reader_writer =
+            TelemetryReaderWriter::new(MemoryReaderWriter::default(), &["APU_N"]);
+        tick(&mut reader_writer, 50, 1.);
+        tick(&mut reader_writer, 100, 2.);
+
+        let telemetry = reader_writer.into_telemetry();
+
+        assert_eq!(
+            telemetry.times(),
+            &[Duration::from_millis(50), Duration::from_millis(150)]
+        );
+        assert_eq!(telemetry.column("APU_N"), Some([1., 2.].as_ref()));
+    }
+
+    #[test]
+    fn samples_at_the_sampling_interval() {
+        let mut reader_writer = TelemetryReaderWriter::new_with_sampling_interval(
+            MemoryReaderWriter::default(),
+            &["APU_N"],
+            Duration::from_millis(100),
+        );
+        for n in 1..=5 {
+            tick(&mut reader_writer, 50, n as f64);
+        }
+
+        let telemetry = reader_writer.into_telemetry();
+
+        assert_eq!(
+            telemetry.times(),
+            &[Duration::from_millis(100), Duration::from_millis(200)]
+        );
+        assert_eq!(telemetry.column("APU_N"), Some([2., 4.].as_ref()));
+    }
+
+    #[test]
+    fn writes_in_between_ticks_are_part_of_the_next_sample() {
+        let mut reader_writer =
+            TelemetryReaderWriter::new(MemoryReaderWriter::default(), &["APU_N"]);
+        tick(&mut reader_writer, 50, 1.);
+        reader_writer.write("APU_N", 5.);
+
+        let telemetry = reader_writer.into_telemetry();
+
+        assert_eq!(telemetry.column("APU_N"), Some([1.].as_ref()));
+    }
+
+    #[test]
+    fn still_writes_to_the_simulator() {
+        let mut reader_writer =
+            TelemetryReaderWriter::new(MemoryReaderWriter::default(), &["APU_N"]);
+        tick(&mut reader_writer, 50, 1.);
+
+        assert_eq!(reader_writer.read("APU_EGT"), 10.);
+    }
+
+    #[test]
+    fn writes_csv_leaving_unwritten_values_empty() {
+        let mut reader_writer = TelemetryReaderWriter::new(
+            MemoryReaderWriter::default(),
+            &["APU_N", "ELEC_BAT_1_POTENTIAL"],
+        );
+        tick(&mut reader_writer, 500, 12.5);
+        let mut csv = vec![];
+
+        reader_writer.into_telemetry().write_csv(&mut csv).unwrap();
+
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "time,APU_N,ELEC_BAT_1_POTENTIAL\n0.5,12.5,\n"
+        );
+    }
+
+    #[test]
+    fn columnar_format_can_be_read() {
+        let mut reader_writer =
+            TelemetryReaderWriter::new(MemoryReaderWriter::default(), &["APU_N", "APU_EGT"]);
+        for n in 1..=100 {
+            tick(&mut reader_writer, 50, n as f64);
+        }
+        let telemetry = reader_writer.into_telemetry();
+        let mut columnar = vec![];
+
+        telemetry.write_columnar(&mut columnar).unwrap();
+
+        assert_eq!(
+            Telemetry::read_columnar(columnar.as_slice()).unwrap(),
+            telemetry
+        );
+    }
+}
@@ -1,11 +1,25 @@
-use std::{collections::HashMap, time::Duration};
-use uom::si::{f64::*, length::foot, thermodynamic_temperature::degree_celsius, velocity::knot};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    ops::{Bound, RangeBounds},
+    time::Duration,
+};
+use uom::si::{
+    acceleration::foot_per_second_squared,
+    angle::degree,
+    f64::*,
+    length::foot,
+    pressure::inch_of_mercury,
+    thermodynamic_temperature::degree_celsius,
+    velocity::{foot_per_minute, knot},
+};
 
-use crate::electrical::consumption::SuppliedPower;
+use crate::{electrical::consumption::SuppliedPower, failures::FailureType};
 
 use super::{
-    from_bool, to_bool, Aircraft, Simulation, SimulationElement, SimulationElementVisitor,
-    SimulationToSimulatorVisitor, SimulatorReaderWriter, SimulatorWriter, UpdateContext,
+    from_bool, to_bool, Aircraft, ElementDescription, KeyEvent, RandomNumberGenerator, Simulation,
+    SimulationElement, SimulationElementVisitor, SimulationEvent, SimulationSnapshot,
+    SimulationToSimulatorVisitor, SimulatorReaderWriter, SimulatorWriter, StartingState,
+    UpdateContext, VariableRegistry, VariableRegistryError,
 };
 
 /// The simulation test bed handles the testing of [`Aircraft`] and [`SimulationElement`]
@@ -17,8 +31,16 @@ pub struct SimulationTestBed {
     reader_writer: TestReaderWriter,
     get_supplied_power_fn: Box<dyn Fn() -> SuppliedPower>,
     delta: Duration,
+    random: RandomNumberGenerator,
+    maximum_step_size: Option<Duration>,
+    simulation_rate: f64,
+    is_paused: bool,
+    events: Vec<SimulationEvent>,
 }
 impl SimulationTestBed {
+    /// The seed of the random number generator, unless a test sets another seed.
+    const DEFAULT_RANDOM_SEED: u64 = 1;
+
     pub fn new() -> Self {
         Self::new_with_delta(Duration::from_secs(1))
     }
@@ -28,6 +50,11 @@ impl SimulationTestBed {
             reader_writer: TestReaderWriter::new(),
             get_supplied_power_fn: Box::new(SuppliedPower::new),
             delta,
+            random: RandomNumberGenerator::from_seed(Self::DEFAULT_RANDOM_SEED),
+            maximum_step_size: None,
+            simulation_rate: 1.,
+            is_paused: false,
+            events: vec![],
         };
 
         test_bed.set_indicated_airspeed(Velocity::new::<knot>(250.));
@@ -57,8 +84,100 @@ impl SimulationTestBed {
     /// [`Aircraft`]: ../trait.Aircraft.html
     /// [`Simulation`]: ../struct.Simulation.html
     pub fn run_aircraft<T: Aircraft>(&mut self, aircraft: &mut T) {
-        let mut simulation = Simulation::new(aircraft, &mut self.reader_writer);
+        let mut simulation = Simulation::new_with_random_number_generator(
+            aircraft,
+            &mut self.reader_writer,
+            self.random.clone(),
+        );
+        if let Some(maximum_step_size) = self.maximum_step_size {
+            simulation.set_maximum_step_size(maximum_step_size);
+        }
+        simulation.set_simulation_rate(self.simulation_rate);
+        simulation.set_paused(self.is_paused);
         simulation.tick(self.delta);
+
+        self.random = simulation.random_number_generator().clone();
+        self.events = simulation.events().to_vec();
+    }
+
+    /// Runs the provided [`Aircraft`] for the given duration, in steps of the test bed's delta.
+    /// The last step is shortened when the duration isn't a multiple of the delta.
+    ///
+    /// [`Aircraft`]: ../trait.Aircraft.html
+    pub fn run_aircraft_for<T: Aircraft>(&mut self, aircraft: &mut T, duration: Duration) {
+        self.run_aircraft_until(aircraft, duration, |_, _| false);
+    }
+
+    /// Runs the provided [`Aircraft`] in steps of the test bed's delta until the given predicate
+    /// holds. Returns the time at which the predicate first held, or `None` when it didn't hold
+    /// before the timeout. The predicate is checked before the first step, thus a predicate which
+    /// holds from the start returns a time of zero.
+    ///
+    /// [`Aircraft`]: ../trait.Aircraft.html
+    pub fn run_aircraft_until<T: Aircraft, U: FnMut(&mut Self, &T) -> bool>(
+        &mut self,
+        aircraft: &mut T,
+        timeout: Duration,
+        mut predicate: U,
+    ) -> Option<Duration> {
+        assert!(
+            self.delta > Duration::from_secs(0),
+            "Running for a period of time requires a delta greater than zero."
+        );
+
+        let delta = self.delta;
+        let mut elapsed = Duration::from_secs(0);
+        let time = loop {
+            if predicate(self, aircraft) {
+                break Some(elapsed);
+            } else if elapsed >= timeout {
+                break None;
+            }
+
+            self.delta = delta.min(timeout - elapsed);
+            self.run_aircraft(aircraft);
+            elapsed += self.delta;
+        };
+
+        self.delta = delta;
+        time
+    }
+
+    /// Runs the provided [`Aircraft`] in steps of the test bed's delta until the given variable
+    /// is true, and asserts that this happens within the given window of time, e.g.
+    /// `..=Duration::from_secs(60)` or `Duration::from_millis(225)..Duration::from_millis(250)`.
+    /// Returns the time at which the variable became true.
+    ///
+    /// # Panics
+    ///
+    /// Panics when the variable doesn't become true within the window, or when the window
+    /// has no end.
+    ///
+    /// [`Aircraft`]: ../trait.Aircraft.html
+    pub fn assert_becomes_true_within<T: Aircraft, U: RangeBounds<Duration>>(
+        &mut self,
+        aircraft: &mut T,
+        name: &str,
+        window: U,
+    ) -> Duration {
+        let timeout = match window.end_bound() {
+            Bound::Included(end) | Bound::Excluded(end) => *end,
+            Bound::Unbounded => panic!("The window in which {} becomes true needs an end.", name),
+        };
+
+        match self.run_aircraft_until(aircraft, timeout, |test_bed, _| test_bed.read_bool(name)) {
+            Some(time) => {
+                assert!(
+                    window.contains(&time),
+                    "{} became true after {:?}, which is outside of the expected window.",
+                    name,
+                    time
+                );
+
+                time
+            }
+            None => panic!("{} didn't become true within {:?}.", name, timeout),
+        }
     }
 
     /// Runs a single [`Simulation`] tick on the provided [`SimulationElement`], executing
@@ -130,10 +249,203 @@ impl SimulationTestBed {
         self.run_aircraft(&mut aircraft);
     }
 
+    /// Takes a snapshot of the internal state of the provided [`SimulationElement`].
+    ///
+    /// [`SimulationElement`]: ../trait.SimulationElement.html
+    pub fn snapshot<T: SimulationElement>(&mut self, element: &mut T) -> SimulationSnapshot {
+        let mut aircraft = TestAircraft::new(
+            element,
+            |_: &mut T, _: &UpdateContext| {},
+            SuppliedPower::new(),
+            false,
+        );
+
+        self.snapshot_aircraft(&mut aircraft)
+    }
+
+    /// Restores the internal state of the provided [`SimulationElement`] from the given
+    /// snapshot. The restored state is written into the test bed.
+    ///
+    /// [`SimulationElement`]: ../trait.SimulationElement.html
+    pub fn restore<T: SimulationElement>(
+        &mut self,
+        element: &mut T,
+        snapshot: &SimulationSnapshot,
+    ) {
+        let mut aircraft = TestAircraft::new(
+            element,
+            |_: &mut T, _: &UpdateContext| {},
+            SuppliedPower::new(),
+            false,
+        );
+
+        self.restore_aircraft(&mut aircraft, snapshot);
+    }
+
+    /// Takes a snapshot of the internal state of the provided [`Aircraft`].
+    ///
+    /// [`Aircraft`]: ../trait.Aircraft.html
+    pub fn snapshot_aircraft<T: Aircraft>(&mut self, aircraft: &mut T) -> SimulationSnapshot {
+        Simulation::new(aircraft, &mut self.reader_writer).snapshot()
+    }
+
+    /// Restores the internal state of the provided [`Aircraft`] from the given
+    /// snapshot. The restored state is written into the test bed.
+    ///
+    /// [`Aircraft`]: ../trait.Aircraft.html
+    pub fn restore_aircraft<T: Aircraft>(
+        &mut self,
+        aircraft: &mut T,
+        snapshot: &SimulationSnapshot,
+    ) {
+        Simulation::new(aircraft, &mut self.reader_writer).restore(snapshot);
+    }
+
+    /// Initialises the provided [`SimulationElement`] to the given [`StartingState`].
+    /// The initialised state is written into the test bed.
+    ///
+    /// [`SimulationElement`]: ../trait.SimulationElement.html
+    /// [`StartingState`]: ../enum.StartingState.html
+    pub fn initialise<T: SimulationElement>(&mut self, element: &mut T, state: StartingState) {
+        let mut aircraft = TestAircraft::new(
+            element,
+            |_: &mut T, _: &UpdateContext| {},
+            SuppliedPower::new(),
+            false,
+        );
+
+        self.initialise_aircraft(&mut aircraft, state);
+    }
+
+    /// Initialises the provided [`Aircraft`] to the given [`StartingState`].
+    /// The initialised state is written into the test bed.
+    ///
+    /// [`Aircraft`]: ../trait.Aircraft.html
+    /// [`StartingState`]: ../enum.StartingState.html
+    pub fn initialise_aircraft<T: Aircraft>(&mut self, aircraft: &mut T, state: StartingState) {
+        Simulation::new(aircraft, &mut self.reader_writer).initialise(state);
+    }
+
+    /// Registers the variables the provided [`SimulationElement`] reads and writes.
+    ///
+    /// [`SimulationElement`]: ../trait.SimulationElement.html
+    pub fn register_variables<T: SimulationElement>(
+        &mut self,
+        element: &mut T,
+    ) -> Result<VariableRegistry, Vec<VariableRegistryError>> {
+        let mut aircraft = TestAircraft::new(
+            element,
+            |_: &mut T, _: &UpdateContext| {},
+            SuppliedPower::new(),
+            false,
+        );
+
+        self.register_aircraft_variables(&mut aircraft)
+    }
+
+    /// Registers the variables the provided [`Aircraft`] reads and writes.
+    ///
+    /// [`Aircraft`]: ../trait.Aircraft.html
+    pub fn register_aircraft_variables<T: Aircraft>(
+        &mut self,
+        aircraft: &mut T,
+    ) -> Result<VariableRegistry, Vec<VariableRegistryError>> {
+        Simulation::new(aircraft, &mut self.reader_writer).register_variables()
+    }
+
+    /// Lists the failure modes declared by the provided [`SimulationElement`].
+    ///
+    /// [`SimulationElement`]: ../trait.SimulationElement.html
+    pub fn register_failures<T: SimulationElement>(&mut self, element: &mut T) -> Vec<FailureType> {
+        let mut aircraft = TestAircraft::new(
+            element,
+            |_: &mut T, _: &UpdateContext| {},
+            SuppliedPower::new(),
+            false,
+        );
+
+        self.register_aircraft_failures(&mut aircraft)
+    }
+
+    /// Lists the failure modes declared by the provided [`Aircraft`].
+    ///
+    /// [`Aircraft`]: ../trait.Aircraft.html
+    pub fn register_aircraft_failures<T: Aircraft>(
+        &mut self,
+        aircraft: &mut T,
+    ) -> Vec<FailureType> {
+        Simulation::new(aircraft, &mut self.reader_writer).register_failures()
+    }
+
+    /// Describes the element tree of the provided [`SimulationElement`].
+    ///
+    /// [`SimulationElement`]: ../trait.SimulationElement.html
+    pub fn describe<T: SimulationElement>(&mut self, element: &mut T) -> ElementDescription {
+        let mut aircraft = TestAircraft::new(
+            element,
+            |_: &mut T, _: &UpdateContext| {},
+            SuppliedPower::new(),
+            false,
+        );
+
+        self.describe_aircraft(&mut aircraft)
+    }
+
+    /// Describes the element tree of the provided [`Aircraft`].
+    ///
+    /// [`Aircraft`]: ../trait.Aircraft.html
+    pub fn describe_aircraft<T: Aircraft>(&mut self, aircraft: &mut T) -> ElementDescription {
+        Simulation::new(aircraft, &mut self.reader_writer).describe()
+    }
+
+    /// Activates the given failure from the next run onwards.
+    pub fn activate_failure(&mut self, failure_type: &FailureType) {
+        self.write_bool(&failure_type.variable_name(), true);
+    }
+
+    /// Clears the given failure from the next run onwards.
+    pub fn deactivate_failure(&mut self, failure_type: &FailureType) {
+        self.write_bool(&failure_type.variable_name(), false);
+    }
+
+    /// The events which occurred during the last run.
+    pub fn events(&self) -> &[SimulationEvent] {
+        &self.events
+    }
+
+    /// Seeds the random number generator used by subsequent runs. By default the generator
+    /// is created from a fixed seed, such that every run of a test has the same outcome.
+    /// Use this function when a test depends on specific random numbers.
+    pub fn set_random_seed(&mut self, seed: u64) {
+        self.random = RandomNumberGenerator::from_seed(seed);
+    }
+
+    /// Splits each run with a delta larger than the given maximum into multiple steps.
+    /// See [`Simulation::set_maximum_step_size`] for details.
+    ///
+    /// [`Simulation::set_maximum_step_size`]: ../struct.Simulation.html#method.set_maximum_step_size
+    pub fn set_maximum_step_size(&mut self, maximum_step_size: Duration) {
+        self.maximum_step_size = Some(maximum_step_size);
+    }
+
     pub fn set_delta(&mut self, delta: Duration) {
         self.delta = delta;
     }
 
+    /// See [`Simulation::set_simulation_rate`] for details.
+    ///
+    /// [`Simulation::set_simulation_rate`]: ../struct.Simulation.html#method.set_simulation_rate
+    pub fn set_simulation_rate(&mut self, simulation_rate: f64) {
+        self.simulation_rate = simulation_rate;
+    }
+
+    /// See [`Simulation::set_paused`] for details.
+    ///
+    /// [`Simulation::set_paused`]: ../struct.Simulation.html#method.set_paused
+    pub fn set_paused(&mut self, is_paused: bool) {
+        self.is_paused = is_paused;
+    }
+
     pub fn set_indicated_airspeed(&mut self, indicated_airspeed: Velocity) {
         self.reader_writer.write_f64(
             UpdateContext::INDICATED_AIRSPEED_KEY,
@@ -141,6 +453,25 @@ impl SimulationTestBed {
         );
     }
 
+    pub fn set_true_airspeed(&mut self, true_airspeed: Velocity) {
+        self.reader_writer.write_f64(
+            UpdateContext::TRUE_AIRSPEED_KEY,
+            true_airspeed.get::<knot>(),
+        );
+    }
+
+    pub fn set_ground_speed(&mut self, ground_speed: Velocity) {
+        self.reader_writer
+            .write_f64(UpdateContext::GROUND_SPEED_KEY, ground_speed.get::<knot>());
+    }
+
+    pub fn set_vertical_speed(&mut self, vertical_speed: Velocity) {
+        self.reader_writer.write_f64(
+            UpdateContext::VERTICAL_SPEED_KEY,
+            vertical_speed.get::<foot_per_minute>(),
+        );
+    }
+
     pub fn set_indicated_altitude(&mut self, indicated_altitude: Length) {
         self.reader_writer.write_f64(
             UpdateContext::INDICATED_ALTITUDE_KEY,
@@ -148,6 +479,13 @@ impl SimulationTestBed {
         );
     }
 
+    pub fn set_pressure_altitude(&mut self, pressure_altitude: Length) {
+        self.reader_writer.write_f64(
+            UpdateContext::PRESSURE_ALTITUDE_KEY,
+            pressure_altitude.get::<foot>(),
+        );
+    }
+
     pub fn set_ambient_temperature(&mut self, ambient_temperature: ThermodynamicTemperature) {
         self.reader_writer.write_f64(
             UpdateContext::AMBIENT_TEMPERATURE_KEY,
@@ -155,6 +493,41 @@ impl SimulationTestBed {
         );
     }
 
+    pub fn set_ambient_pressure(&mut self, ambient_pressure: Pressure) {
+        self.reader_writer.write_f64(
+            UpdateContext::AMBIENT_PRESSURE_KEY,
+            ambient_pressure.get::<inch_of_mercury>(),
+        );
+    }
+
+    /// Sets the pitch attitude, which is positive when the nose is up.
+    pub fn set_pitch(&mut self, pitch: Angle) {
+        // The simulator's pitch is positive when the nose is down.
+        self.reader_writer
+            .write_f64(UpdateContext::PITCH_KEY, -pitch.get::<degree>());
+    }
+
+    /// Sets the bank angle, which is positive when banking right.
+    pub fn set_bank(&mut self, bank: Angle) {
+        // The simulator's bank is positive when banking left.
+        self.reader_writer
+            .write_f64(UpdateContext::BANK_KEY, -bank.get::<degree>());
+    }
+
+    pub fn set_longitudinal_acceleration(&mut self, longitudinal_acceleration: Acceleration) {
+        self.reader_writer.write_f64(
+            UpdateContext::LONGITUDINAL_ACCELERATION_KEY,
+            longitudinal_acceleration.get::<foot_per_second_squared>(),
+        );
+    }
+
+    pub fn set_vertical_acceleration(&mut self, vertical_acceleration: Acceleration) {
+        self.reader_writer.write_f64(
+            UpdateContext::VERTICAL_ACCELERATION_KEY,
+            vertical_acceleration.get::<foot_per_second_squared>(),
+        );
+    }
+
     pub fn set_on_ground(&mut self, on_ground: bool) {
         self.reader_writer
             .write_bool(UpdateContext::IS_ON_GROUND_KEY, on_ground);
@@ -187,6 +560,30 @@ impl SimulationTestBed {
     pub fn contains_key(&self, name: &str) -> bool {
         self.reader_writer.contains_key(name)
     }
+
+    /// The current value of every variable written by the simulation, as opposed to
+    /// those only written by the test.
+    pub fn written_variables(&self) -> BTreeMap<String, f64> {
+        self.reader_writer.written_variables()
+    }
+
+    /// The variables written by the test rather than by the simulation, i.e. those
+    /// which the test bed writes in place of the simulator.
+    pub fn simulator_variables(&self) -> BTreeSet<String> {
+        self.reader_writer.simulator_variables()
+    }
+
+    /// Raises a key event without data, which is handed to the elements during the next tick.
+    pub fn trigger_key_event(&mut self, name: &str) {
+        self.trigger_key_event_with_value(name, 0.);
+    }
+
+    /// Raises a key event, which is handed to the elements during the next tick.
+    pub fn trigger_key_event_with_value(&mut self, name: &str, value: f64) {
+        self.reader_writer
+            .key_events
+            .push(KeyEvent::new(name, value));
+    }
 }
 impl Default for SimulationTestBed {
     fn default() -> Self {
@@ -197,7 +594,7 @@ impl Default for SimulationTestBed {
 struct TestAircraft<'a, T: SimulationElement, U: Fn(&mut T, &UpdateContext)> {
     element: &'a mut T,
     update_fn: U,
-    supplied_power: Option<SuppliedPower>,
+    supplied_power: SuppliedPower,
     update_before_power_distribution: bool,
 }
 impl<'a, T: SimulationElement, U: Fn(&mut T, &UpdateContext)> TestAircraft<'a, T, U> {
@@ -210,7 +607,7 @@ impl<'a, T: SimulationElement, U: Fn(&mut T, &UpdateContext)> TestAircraft<'a, T
         Self {
             element,
             update_fn,
-            supplied_power: Some(supplied_power),
+            supplied_power,
             update_before_power_distribution,
         }
     }
@@ -229,24 +626,28 @@ impl<'a, T: SimulationElement, U: Fn(&mut T, &UpdateContext)> Aircraft for TestA
     }
 
     fn get_supplied_power(&mut self) -> SuppliedPower {
-        self.supplied_power.take().unwrap()
+        self.supplied_power.clone()
     }
 }
 impl<'a, T: SimulationElement, U: Fn(&mut T, &UpdateContext)> SimulationElement
     for TestAircraft<'a, T, U>
 {
     fn accept<W: SimulationElementVisitor>(&mut self, visitor: &mut W) {
-        visitor.visit(self.element);
+        self.element.accept(visitor);
     }
 }
 
 struct TestReaderWriter {
     variables: HashMap<String, f64>,
+    written_by_simulation: HashSet<String>,
+    key_events: Vec<KeyEvent>,
 }
 impl TestReaderWriter {
     fn new() -> Self {
         Self {
             variables: HashMap::new(),
+            written_by_simulation: HashSet::new(),
+            key_events: vec![],
         }
     }
 
@@ -254,12 +655,27 @@ impl TestReaderWriter {
         self.variables.contains_key(name)
     }
 
+    fn written_variables(&self) -> BTreeMap<String, f64> {
+        self.written_by_simulation
+            .iter()
+            .map(|name| (name.clone(), self.variables[name]))
+            .collect()
+    }
+
+    fn simulator_variables(&self) -> BTreeSet<String> {
+        self.variables
+            .keys()
+            .filter(|name| !self.written_by_simulation.contains(*name))
+            .cloned()
+            .collect()
+    }
+
     fn write_bool(&mut self, name: &str, value: bool) {
-        self.write(name, from_bool(value));
+        self.write_f64(name, from_bool(value));
     }
 
     fn write_f64(&mut self, name: &str, value: f64) {
-        self.write(name, value);
+        self.variables.insert(name.to_owned(), value);
     }
 
     fn read_bool(&mut self, name: &str) -> bool {
@@ -276,8 +692,16 @@ impl SimulatorReaderWriter for TestReaderWriter {
     }
 
     fn write(&mut self, name: &str, value: f64) {
+        if !self.written_by_simulation.contains(name) {
+            self.written_by_simulation.insert(name.to_owned());
+        }
+
         self.variables.insert(name.to_owned(), value);
     }
+
+    fn read_key_events(&mut self) -> Vec<KeyEvent> {
+        self.key_events.drain(..).collect()
+    }
 }
 impl Default for TestReaderWriter {
     fn default() -> Self {
@@ -290,7 +714,10 @@ mod tests {
     use super::*;
     use crate::{
         electrical::consumption::{PowerConsumption, PowerConsumptionReport, SuppliedPower},
-        simulation::{SimulatorReader, SimulatorWriter},
+        simulation::{
+            KeyEventIdentifier, SimulatorReader, SimulatorWriter, SnapshotReader, SnapshotWriter,
+            VariableDirection, VariableIdentifier, VariableUnit,
+        },
     };
 
     #[derive(Clone, Copy, Debug, PartialEq)]
@@ -396,4 +823,475 @@ mod tests {
             Some(CallOrder::Before)
         );
     }
+
+    struct ElementWithState {
+        counter_id: VariableIdentifier<f64>,
+        add_id: KeyEventIdentifier,
+        counter: f64,
+    }
+    impl ElementWithState {
+        fn new(counter: f64) -> Self {
+            Self {
+                counter_id: VariableIdentifier::new("COUNTER", VariableUnit::Number),
+                add_id: KeyEventIdentifier::new("COUNTER_ADD"),
+                counter,
+            }
+        }
+    }
+    impl SimulationElement for ElementWithState {
+        fn read(&mut self, reader: &mut SimulatorReader) {
+            for value in reader.key_events(&self.add_id) {
+                self.counter += value;
+            }
+        }
+
+        fn write(&self, writer: &mut SimulatorWriter) {
+            writer.write(&self.counter_id, self.counter);
+        }
+
+        fn snapshot(&self, writer: &mut SnapshotWriter) {
+            writer.write_f64("COUNTER", self.counter);
+        }
+
+        fn restore(&mut self, reader: &SnapshotReader) {
+            if let Some(counter) = reader.read_f64("COUNTER") {
+                self.counter = counter;
+            }
+        }
+
+        fn initialise(&mut self, state: StartingState) {
+            if state.engines_are_running() {
+                self.counter = 100.;
+            }
+        }
+    }
+
+    #[test]
+    fn restores_the_state_found_in_a_snapshot() {
+        let mut test_bed = SimulationTestBed::new();
+        let snapshot = test_bed.snapshot(&mut ElementWithState::new(42.));
+
+        let mut element = ElementWithState::new(0.);
+        test_bed.restore(&mut element, &snapshot);
+
+        assert_eq!(element.counter, 42.);
+    }
+
+    #[test]
+    fn restoring_writes_the_restored_state_to_the_simulator() {
+        let mut test_bed = SimulationTestBed::new();
+        let snapshot = test_bed.snapshot(&mut ElementWithState::new(42.));
+
+        test_bed.restore(&mut ElementWithState::new(0.), &snapshot);
+
+        assert_eq!(test_bed.read_f64("COUNTER"), 42.);
+    }
+
+    #[test]
+    fn restoring_an_empty_snapshot_keeps_the_current_state() {
+        let mut test_bed = SimulationTestBed::new();
+        let mut element = ElementWithState::new(42.);
+
+        test_bed.restore(&mut element, &SimulationSnapshot::new());
+
+        assert_eq!(element.counter, 42.);
+    }
+
+    #[test]
+    fn initialises_to_the_starting_state() {
+        let mut test_bed = SimulationTestBed::new();
+        let mut element = ElementWithState::new(0.);
+
+        test_bed.initialise(&mut element, StartingState::Cruise);
+
+        assert_eq!(element.counter, 100.);
+    }
+
+    #[test]
+    fn initialising_writes_the_initialised_state_to_the_simulator() {
+        let mut test_bed = SimulationTestBed::new();
+
+        test_bed.initialise(&mut ElementWithState::new(0.), StartingState::RunwayReady);
+
+        assert_eq!(test_bed.read_f64("COUNTER"), 100.);
+    }
+
+    #[test]
+    fn registers_the_variables_written_by_an_element() {
+        let mut test_bed = SimulationTestBed::new();
+
+        let registry = test_bed
+            .register_variables(&mut ElementWithState::new(0.))
+            .unwrap();
+
+        let counter = registry.get("COUNTER").unwrap();
+        assert_eq!(counter.unit(), VariableUnit::Number);
+        assert_eq!(counter.direction(), VariableDirection::Write);
+    }
+
+    #[test]
+    fn registers_the_key_events_handled_by_an_element() {
+        let mut test_bed = SimulationTestBed::new();
+
+        let registry = test_bed
+            .register_variables(&mut ElementWithState::new(0.))
+            .unwrap();
+
+        assert_eq!(
+            registry.key_events().collect::<Vec<_>>(),
+            vec!["COUNTER_ADD"]
+        );
+    }
+
+    #[test]
+    fn hands_key_events_to_elements_in_the_order_in_which_they_occurred() {
+        let mut test_bed = SimulationTestBed::new();
+        let mut element = ElementWithState::new(0.);
+        test_bed.trigger_key_event_with_value("COUNTER_ADD", 2.);
+        test_bed.trigger_key_event("OTHER");
+        test_bed.trigger_key_event_with_value("COUNTER_ADD", 3.);
+
+        test_bed.run_without_update(&mut element);
+
+        assert_eq!(element.counter, 5.);
+    }
+
+    #[test]
+    fn key_events_are_only_handed_to_elements_during_the_next_tick() {
+        let mut test_bed = SimulationTestBed::new();
+        let mut element = ElementWithState::new(0.);
+        test_bed.trigger_key_event_with_value("COUNTER_ADD", 2.);
+
+        test_bed.run_without_update(&mut element);
+        test_bed.run_without_update(&mut element);
+
+        assert_eq!(element.counter, 2.);
+    }
+
+    #[test]
+    fn registers_the_variables_read_by_the_update_context() {
+        let mut test_bed = SimulationTestBed::new();
+
+        let registry = test_bed
+            .register_variables(&mut ElementWithState::new(0.))
+            .unwrap();
+
+        assert!(registry.contains(UpdateContext::AMBIENT_TEMPERATURE_KEY));
+        assert!(registry.contains(UpdateContext::PRESSURE_ALTITUDE_KEY));
+        assert!(registry.contains(UpdateContext::VERTICAL_ACCELERATION_KEY));
+    }
+
+    #[test]
+    fn provides_the_flight_parameters_in_the_update_context() {
+        #[derive(Default)]
+        struct ContextObservingElement {
+            observed: Vec<f64>,
+        }
+        impl SimulationElement for ContextObservingElement {}
+
+        let mut element = ContextObservingElement::default();
+        let mut test_bed = SimulationTestBed::new();
+        test_bed.set_true_airspeed(Velocity::new::<knot>(250.));
+        test_bed.set_ground_speed(Velocity::new::<knot>(270.));
+        test_bed.set_vertical_speed(Velocity::new::<foot_per_minute>(-1500.));
+        test_bed.set_pressure_altitude(Length::new::<foot>(12000.));
+        test_bed.set_ambient_pressure(Pressure::new::<inch_of_mercury>(19.));
+        test_bed.set_pitch(Angle::new::<degree>(2.5));
+        test_bed.set_bank(Angle::new::<degree>(-15.));
+        test_bed.set_longitudinal_acceleration(Acceleration::new::<foot_per_second_squared>(1.5));
+        test_bed.set_vertical_acceleration(Acceleration::new::<foot_per_second_squared>(-3.));
+
+        test_bed.run(&mut element, |el, context| {
+            el.observed = vec![
+                context.true_airspeed().get::<knot>(),
+                context.ground_speed().get::<knot>(),
+                context.vertical_speed().get::<foot_per_minute>(),
+                context.pressure_altitude().get::<foot>(),
+                context.ambient_pressure().get::<inch_of_mercury>(),
+                context.pitch().get::<degree>(),
+                context.bank().get::<degree>(),
+                context
+                    .longitudinal_acceleration()
+                    .get::<foot_per_second_squared>(),
+                context
+                    .vertical_acceleration()
+                    .get::<foot_per_second_squared>(),
+            ];
+        });
+
+        let expected = [250., 270., -1500., 12000., 19., 2.5, -15., 1.5, -3.];
+        for (observed, expected) in element.observed.iter().zip(expected.iter()) {
+            assert!((observed - expected).abs() < 1e-9);
+        }
+        assert_eq!(element.observed.len(), expected.len());
+    }
+
+    #[test]
+    fn stores_pitch_and_bank_in_the_simulator_convention() {
+        let mut test_bed = SimulationTestBed::new();
+
+        test_bed.set_pitch(Angle::new::<degree>(2.5));
+        test_bed.set_bank(Angle::new::<degree>(-15.));
+
+        assert!((test_bed.read_f64(UpdateContext::PITCH_KEY) + 2.5).abs() < 1e-9);
+        assert!((test_bed.read_f64(UpdateContext::BANK_KEY) - 15.).abs() < 1e-9);
+    }
+
+    #[test]
+    fn registering_variables_does_not_write_to_the_simulator() {
+        let mut test_bed = SimulationTestBed::new();
+
+        test_bed
+            .register_variables(&mut ElementWithState::new(42.))
+            .unwrap();
+
+        assert!(!test_bed.contains_key("COUNTER"));
+    }
+
+    #[derive(Default)]
+    struct StepCountingElement {
+        reads: usize,
+        update_deltas: Vec<Duration>,
+        power_consumption_reports: usize,
+    }
+    impl SimulationElement for StepCountingElement {
+        fn read(&mut self, _: &mut SimulatorReader) {
+            self.reads += 1;
+        }
+
+        fn process_power_consumption_report<T: PowerConsumptionReport>(&mut self, _: &T) {
+            self.power_consumption_reports += 1;
+        }
+    }
+
+    #[test]
+    fn executes_a_single_step_by_default() {
+        let mut element = StepCountingElement::default();
+        let mut test_bed = SimulationTestBed::new_with_delta(Duration::from_secs(10));
+        test_bed.run(&mut element, |el, context| {
+            el.update_deltas.push(context.delta());
+        });
+
+        assert_eq!(element.update_deltas, vec![Duration::from_secs(10)]);
+    }
+
+    #[test]
+    fn splits_a_delta_larger_than_the_maximum_step_size_into_equal_steps() {
+        let mut element = StepCountingElement::default();
+        let mut test_bed = SimulationTestBed::new_with_delta(Duration::from_millis(3500));
+        test_bed.set_maximum_step_size(Duration::from_secs(1));
+        test_bed.run(&mut element, |el, context| {
+            el.update_deltas.push(context.delta());
+        });
+
+        assert_eq!(element.update_deltas, vec![Duration::from_millis(875); 4]);
+        assert_eq!(element.power_consumption_reports, 4);
+    }
+
+    #[test]
+    fn steps_cover_the_full_delta_when_it_cannot_be_divided_equally() {
+        let mut element = StepCountingElement::default();
+        let mut test_bed = SimulationTestBed::new_with_delta(Duration::from_nanos(1_000_000_001));
+        test_bed.set_maximum_step_size(Duration::from_millis(300));
+        test_bed.run(&mut element, |el, context| {
+            el.update_deltas.push(context.delta());
+        });
+
+        assert_eq!(element.update_deltas.len(), 4);
+        assert_eq!(
+            element.update_deltas.iter().sum::<Duration>(),
+            Duration::from_nanos(1_000_000_001)
+        );
+    }
+
+    #[test]
+    fn multiplies_the_delta_by_the_simulation_rate() {
+        let mut element = StepCountingElement::default();
+        let mut test_bed = SimulationTestBed::new_with_delta(Duration::from_millis(50));
+        test_bed.set_simulation_rate(4.);
+        test_bed.run(&mut element, |el, context| {
+            el.update_deltas.push(context.delta());
+            assert!((context.simulation_rate() - 4.).abs() < f64::EPSILON);
+        });
+
+        assert_eq!(element.update_deltas, vec![Duration::from_millis(200)]);
+    }
+
+    #[test]
+    fn splits_the_simulated_delta_into_steps() {
+        let mut element = StepCountingElement::default();
+        let mut test_bed = SimulationTestBed::new_with_delta(Duration::from_millis(100));
+        test_bed.set_simulation_rate(8.);
+        test_bed.set_maximum_step_size(Duration::from_millis(200));
+        test_bed.run(&mut element, |el, context| {
+            el.update_deltas.push(context.delta());
+        });
+
+        assert_eq!(element.update_deltas, vec![Duration::from_millis(200); 4]);
+    }
+
+    #[test]
+    fn no_time_passes_while_paused() {
+        let mut element = StepCountingElement::default();
+        let mut test_bed = SimulationTestBed::new_with_delta(Duration::from_millis(50));
+        test_bed.set_paused(true);
+        test_bed.run(&mut element, |el, context| {
+            el.update_deltas.push(context.delta());
+            assert!(context.is_paused());
+        });
+
+        assert_eq!(element.update_deltas, vec![Duration::from_secs(0)]);
+        assert_eq!(element.reads, 1);
+    }
+
+    struct TimedAircraft {
+        done_id: VariableIdentifier<bool>,
+        update_deltas: Vec<Duration>,
+    }
+    impl TimedAircraft {
+        const DONE_AFTER: Duration = Duration::from_millis(2500);
+
+        fn new() -> Self {
+            Self {
+                done_id: VariableIdentifier::new("DONE", VariableUnit::Bool),
+                update_deltas: vec![],
+            }
+        }
+
+        fn elapsed(&self) -> Duration {
+            self.update_deltas.iter().sum()
+        }
+    }
+    impl Aircraft for TimedAircraft {
+        fn update_before_power_distribution(&mut self, context: &UpdateContext) {
+            self.update_deltas.push(context.delta());
+        }
+    }
+    impl SimulationElement for TimedAircraft {
+        fn write(&self, writer: &mut SimulatorWriter) {
+            writer.write(&self.done_id, self.elapsed() >= TimedAircraft::DONE_AFTER);
+        }
+    }
+
+    #[test]
+    fn run_aircraft_for_runs_in_steps_of_the_delta() {
+        let mut aircraft = TimedAircraft::new();
+        let mut test_bed = SimulationTestBed::new();
+        test_bed.run_aircraft_for(&mut aircraft, Duration::from_millis(2500));
+
+        assert_eq!(
+            aircraft.update_deltas,
+            vec![
+                Duration::from_secs(1),
+                Duration::from_secs(1),
+                Duration::from_millis(500)
+            ]
+        );
+    }
+
+    #[test]
+    fn run_aircraft_for_restores_the_delta() {
+        let mut aircraft = TimedAircraft::new();
+        let mut test_bed = SimulationTestBed::new();
+        test_bed.run_aircraft_for(&mut aircraft, Duration::from_millis(500));
+        test_bed.run_aircraft(&mut aircraft);
+
+        assert_eq!(aircraft.update_deltas[1], Duration::from_secs(1));
+    }
+
+    #[test]
+    fn run_aircraft_until_returns_the_time_at_which_the_predicate_first_holds() {
+        let mut aircraft = TimedAircraft::new();
+        let mut test_bed = SimulationTestBed::new_with_delta(Duration::from_millis(500));
+
+        let time =
+            test_bed.run_aircraft_until(&mut aircraft, Duration::from_secs(10), |_, aircraft| {
+                aircraft.elapsed() >= Duration::from_secs(2)
+            });
+
+        assert_eq!(time, Some(Duration::from_secs(2)));
+        assert_eq!(aircraft.update_deltas.len(), 4);
+    }
+
+    #[test]
+    fn run_aircraft_until_returns_none_when_the_predicate_doesnt_hold_before_the_timeout() {
+        let mut aircraft = TimedAircraft::new();
+        let mut test_bed = SimulationTestBed::new();
+
+        let time = test_bed.run_aircraft_until(&mut aircraft, Duration::from_secs(3), |_, _| false);
+
+        assert_eq!(time, None);
+        assert_eq!(aircraft.elapsed(), Duration::from_secs(3));
+    }
+
+    #[test]
+    fn assert_becomes_true_within_returns_the_time_at_which_the_variable_became_true() {
+        let mut aircraft = TimedAircraft::new();
+        let mut test_bed = SimulationTestBed::new_with_delta(Duration::from_millis(100));
+
+        let time = test_bed.assert_becomes_true_within(
+            &mut aircraft,
+            "DONE",
+            Duration::from_secs(2)..=Duration::from_secs(3),
+        );
+
+        assert_eq!(time, TimedAircraft::DONE_AFTER);
+    }
+
+    #[test]
+    #[should_panic]
+    fn assert_becomes_true_within_panics_when_the_variable_becomes_true_too_soon() {
+        let mut aircraft = TimedAircraft::new();
+        let mut test_bed = SimulationTestBed::new_with_delta(Duration::from_millis(100));
+
+        test_bed.assert_becomes_true_within(
+            &mut aircraft,
+            "DONE",
+            Duration::from_secs(3)..=Duration::from_secs(4),
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn assert_becomes_true_within_panics_when_the_variable_becomes_true_too_late() {
+        let mut aircraft = TimedAircraft::new();
+        let mut test_bed = SimulationTestBed::new_with_delta(Duration::from_millis(100));
+
+        test_bed.assert_becomes_true_within(&mut aircraft, "DONE", ..Duration::from_secs(2));
+    }
+
+    #[test]
+    fn written_variables_contain_those_written_by_the_simulation_only() {
+        let mut aircraft = TimedAircraft::new();
+        let mut test_bed = SimulationTestBed::new();
+        test_bed.write_bool("SET_BY_TEST", true);
+        test_bed.run_aircraft(&mut aircraft);
+
+        assert_eq!(
+            test_bed.written_variables().into_iter().collect::<Vec<_>>(),
+            vec![("DONE".to_owned(), 0.)]
+        );
+    }
+
+    #[test]
+    fn simulator_variables_contain_those_written_by_the_test_only() {
+        let mut aircraft = TimedAircraft::new();
+        let mut test_bed = SimulationTestBed::new();
+        test_bed.write_bool("SET_BY_TEST", true);
+        test_bed.run_aircraft(&mut aircraft);
+
+        let variables = test_bed.simulator_variables();
+        assert!(variables.contains("SET_BY_TEST"));
+        assert!(!variables.contains("DONE"));
+    }
+
+    #[test]
+    fn reads_the_simulator_once_when_splitting_into_steps() {
+        let mut element = StepCountingElement::default();
+        let mut test_bed = SimulationTestBed::new_with_delta(Duration::from_secs(5));
+        test_bed.set_maximum_step_size(Duration::from_secs(1));
+        test_bed.run_without_update(&mut element);
+
+        assert_eq!(element.reads, 1);
+    }
 }
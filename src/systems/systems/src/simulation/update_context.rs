@@ -1,78 +1,281 @@
 use std::time::Duration;
-use uom::si::{f64::*, length::foot, thermodynamic_temperature::degree_celsius, velocity::knot};
+use uom::si::f64::*;
 
-use super::SimulatorReader;
+use super::{RandomNumberGenerator, SimulatorReader, VariableIdentifier, VariableUnit};
 
 /// Provides data unowned by any system in the aircraft system simulation
 /// for the purpose of handling a simulation tick.
 #[derive(Debug)]
-pub struct UpdateContext {
+pub struct UpdateContext<'a> {
     delta: Duration,
+    simulation_rate: f64,
+    is_paused: bool,
     indicated_airspeed: Velocity,
+    true_airspeed: Velocity,
+    ground_speed: Velocity,
+    vertical_speed: Velocity,
     indicated_altitude: Length,
+    pressure_altitude: Length,
     ambient_temperature: ThermodynamicTemperature,
+    ambient_pressure: Pressure,
+    pitch: Angle,
+    bank: Angle,
+    longitudinal_acceleration: Acceleration,
+    vertical_acceleration: Acceleration,
     is_on_ground: bool,
+    random: &'a RandomNumberGenerator,
 }
-impl UpdateContext {
+impl<'a> UpdateContext<'a> {
     pub(crate) const AMBIENT_TEMPERATURE_KEY: &'static str = "AMBIENT TEMPERATURE";
+    pub(crate) const AMBIENT_PRESSURE_KEY: &'static str = "AMBIENT PRESSURE";
     pub(crate) const INDICATED_AIRSPEED_KEY: &'static str = "AIRSPEED INDICATED";
+    pub(crate) const TRUE_AIRSPEED_KEY: &'static str = "AIRSPEED TRUE";
+    pub(crate) const GROUND_SPEED_KEY: &'static str = "GROUND VELOCITY";
+    pub(crate) const VERTICAL_SPEED_KEY: &'static str = "VERTICAL SPEED";
     pub(crate) const INDICATED_ALTITUDE_KEY: &'static str = "INDICATED ALTITUDE";
+    pub(crate) const PRESSURE_ALTITUDE_KEY: &'static str = "PRESSURE ALTITUDE";
+    pub(crate) const PITCH_KEY: &'static str = "PLANE PITCH DEGREES";
+    pub(crate) const BANK_KEY: &'static str = "PLANE BANK DEGREES";
+    pub(crate) const LONGITUDINAL_ACCELERATION_KEY: &'static str = "ACCELERATION BODY Z";
+    pub(crate) const VERTICAL_ACCELERATION_KEY: &'static str = "ACCELERATION BODY Y";
     pub(crate) const IS_ON_GROUND_KEY: &'static str = "SIM ON GROUND";
 
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         delta: Duration,
+        simulation_rate: f64,
+        is_paused: bool,
         indicated_airspeed: Velocity,
+        true_airspeed: Velocity,
+        ground_speed: Velocity,
+        vertical_speed: Velocity,
         indicated_altitude: Length,
+        pressure_altitude: Length,
         ambient_temperature: ThermodynamicTemperature,
+        ambient_pressure: Pressure,
+        pitch: Angle,
+        bank: Angle,
+        longitudinal_acceleration: Acceleration,
+        vertical_acceleration: Acceleration,
         is_on_ground: bool,
-    ) -> UpdateContext {
+        random: &'a RandomNumberGenerator,
+    ) -> UpdateContext<'a> {
         UpdateContext {
             delta,
+            simulation_rate,
+            is_paused,
             indicated_airspeed,
+            true_airspeed,
+            ground_speed,
+            vertical_speed,
             indicated_altitude,
+            pressure_altitude,
             ambient_temperature,
+            ambient_pressure,
+            pitch,
+            bank,
+            longitudinal_acceleration,
+            vertical_acceleration,
             is_on_ground,
+            random,
         }
     }
 
     /// Creates a context based on the data that was read from the simulator.
-    pub(super) fn from_reader(reader: &mut SimulatorReader, delta_time: Duration) -> UpdateContext {
+    pub(super) fn from_reader(
+        reader: &mut SimulatorReader,
+        identifiers: &UpdateContextIdentifiers,
+        delta_time: Duration,
+        simulation_rate: f64,
+        is_paused: bool,
+        random: &'a RandomNumberGenerator,
+    ) -> UpdateContext<'a> {
         UpdateContext {
-            ambient_temperature: ThermodynamicTemperature::new::<degree_celsius>(
-                reader.read_f64(UpdateContext::AMBIENT_TEMPERATURE_KEY),
-            ),
-            indicated_airspeed: Velocity::new::<knot>(
-                reader.read_f64(UpdateContext::INDICATED_AIRSPEED_KEY),
-            ),
-            indicated_altitude: Length::new::<foot>(
-                reader.read_f64(UpdateContext::INDICATED_ALTITUDE_KEY),
-            ),
-            is_on_ground: reader.read_bool(UpdateContext::IS_ON_GROUND_KEY),
+            ambient_temperature: reader.read(&identifiers.ambient_temperature_id),
+            indicated_airspeed: reader.read(&identifiers.indicated_airspeed_id),
+            ambient_pressure: reader.read(&identifiers.ambient_pressure_id),
+            true_airspeed: reader.read(&identifiers.true_airspeed_id),
+            ground_speed: reader.read(&identifiers.ground_speed_id),
+            vertical_speed: reader.read(&identifiers.vertical_speed_id),
+            indicated_altitude: reader.read(&identifiers.indicated_altitude_id),
+            pressure_altitude: reader.read(&identifiers.pressure_altitude_id),
+            // The simulator's pitch and bank are positive when the nose is down and
+            // when banking left respectively. The context uses the opposite convention.
+            pitch: -reader.read(&identifiers.pitch_id),
+            bank: -reader.read(&identifiers.bank_id),
+            longitudinal_acceleration: reader.read(&identifiers.longitudinal_acceleration_id),
+            vertical_acceleration: reader.read(&identifiers.vertical_acceleration_id),
+            is_on_ground: reader.read(&identifiers.is_on_ground_id),
             delta: delta_time,
+            simulation_rate,
+            is_paused,
+            random,
         }
     }
 
+    /// Creates a copy of this context which covers the given amount of time.
+    pub(super) fn with_delta(&self, delta: Duration) -> UpdateContext<'a> {
+        UpdateContext { delta, ..*self }
+    }
+
     pub fn is_in_flight(&self) -> bool {
         !self.is_on_ground
     }
 
+    /// The amount of simulated time covered by the update. While the simulation is
+    /// paused, no simulated time passes.
     pub fn delta(&self) -> Duration {
         self.delta
     }
 
+    /// The rate at which simulated time passes relative to real time.
+    pub fn simulation_rate(&self) -> f64 {
+        self.simulation_rate
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.is_paused
+    }
+
     pub fn indicated_airspeed(&self) -> Velocity {
         self.indicated_airspeed
     }
 
+    pub fn true_airspeed(&self) -> Velocity {
+        self.true_airspeed
+    }
+
+    pub fn ground_speed(&self) -> Velocity {
+        self.ground_speed
+    }
+
+    /// The vertical speed, which is positive when climbing.
+    pub fn vertical_speed(&self) -> Velocity {
+        self.vertical_speed
+    }
+
     pub fn indicated_altitude(&self) -> Length {
         self.indicated_altitude
     }
 
+    /// The altitude in the standard atmosphere at which the static pressure equals
+    /// the ambient pressure.
+    pub fn pressure_altitude(&self) -> Length {
+        self.pressure_altitude
+    }
+
     pub fn ambient_temperature(&self) -> ThermodynamicTemperature {
         self.ambient_temperature
     }
 
+    /// The static pressure of the air surrounding the aircraft.
+    pub fn ambient_pressure(&self) -> Pressure {
+        self.ambient_pressure
+    }
+
+    /// The pitch attitude, which is positive when the nose is up.
+    pub fn pitch(&self) -> Angle {
+        self.pitch
+    }
+
+    /// The bank angle, which is positive when banking right.
+    pub fn bank(&self) -> Angle {
+        self.bank
+    }
+
+    /// The acceleration along the aircraft's longitudinal axis, which is positive
+    /// when accelerating forward.
+    pub fn longitudinal_acceleration(&self) -> Acceleration {
+        self.longitudinal_acceleration
+    }
+
+    /// The acceleration along the aircraft's vertical axis, which is positive
+    /// when accelerating upward.
+    pub fn vertical_acceleration(&self) -> Acceleration {
+        self.vertical_acceleration
+    }
+
     pub fn is_on_ground(&self) -> bool {
         self.is_on_ground
     }
+
+    /// Returns a random number from the simulation's seedable random number generator.
+    /// Always use this function instead of other sources of randomness, such that
+    /// a simulation run can be reproduced.
+    pub fn random_number(&self) -> u8 {
+        self.random.random_number()
+    }
+}
+
+/// The identifiers of the variables from which an [`UpdateContext`] is created. They
+/// are created once by the [`Simulation`], instead of once for every tick.
+///
+/// [`UpdateContext`]: struct.UpdateContext.html
+/// [`Simulation`]: struct.Simulation.html
+pub(super) struct UpdateContextIdentifiers {
+    ambient_temperature_id: VariableIdentifier<ThermodynamicTemperature>,
+    indicated_airspeed_id: VariableIdentifier<Velocity>,
+    ambient_pressure_id: VariableIdentifier<Pressure>,
+    true_airspeed_id: VariableIdentifier<Velocity>,
+    ground_speed_id: VariableIdentifier<Velocity>,
+    vertical_speed_id: VariableIdentifier<Velocity>,
+    indicated_altitude_id: VariableIdentifier<Length>,
+    pressure_altitude_id: VariableIdentifier<Length>,
+    pitch_id: VariableIdentifier<Angle>,
+    bank_id: VariableIdentifier<Angle>,
+    longitudinal_acceleration_id: VariableIdentifier<Acceleration>,
+    vertical_acceleration_id: VariableIdentifier<Acceleration>,
+    is_on_ground_id: VariableIdentifier<bool>,
+}
+impl UpdateContextIdentifiers {
+    pub(super) fn new() -> Self {
+        Self {
+            ambient_temperature_id: VariableIdentifier::new(
+                UpdateContext::AMBIENT_TEMPERATURE_KEY,
+                VariableUnit::DegreeCelsius,
+            ),
+            indicated_airspeed_id: VariableIdentifier::new(
+                UpdateContext::INDICATED_AIRSPEED_KEY,
+                VariableUnit::Knot,
+            ),
+            ambient_pressure_id: VariableIdentifier::new(
+                UpdateContext::AMBIENT_PRESSURE_KEY,
+                VariableUnit::InchOfMercury,
+            ),
+            true_airspeed_id: VariableIdentifier::new(
+                UpdateContext::TRUE_AIRSPEED_KEY,
+                VariableUnit::Knot,
+            ),
+            ground_speed_id: VariableIdentifier::new(
+                UpdateContext::GROUND_SPEED_KEY,
+                VariableUnit::Knot,
+            ),
+            vertical_speed_id: VariableIdentifier::new(
+                UpdateContext::VERTICAL_SPEED_KEY,
+                VariableUnit::FootPerMinute,
+            ),
+            indicated_altitude_id: VariableIdentifier::new(
+                UpdateContext::INDICATED_ALTITUDE_KEY,
+                VariableUnit::Foot,
+            ),
+            pressure_altitude_id: VariableIdentifier::new(
+                UpdateContext::PRESSURE_ALTITUDE_KEY,
+                VariableUnit::Foot,
+            ),
+            pitch_id: VariableIdentifier::new(UpdateContext::PITCH_KEY, VariableUnit::Degree),
+            bank_id: VariableIdentifier::new(UpdateContext::BANK_KEY, VariableUnit::Degree),
+            longitudinal_acceleration_id: VariableIdentifier::new(
+                UpdateContext::LONGITUDINAL_ACCELERATION_KEY,
+                VariableUnit::FootPerSecondSquared,
+            ),
+            vertical_acceleration_id: VariableIdentifier::new(
+                UpdateContext::VERTICAL_ACCELERATION_KEY,
+                VariableUnit::FootPerSecondSquared,
+            ),
+            is_on_ground_id: VariableIdentifier::new(
+                UpdateContext::IS_ON_GROUND_KEY,
+                VariableUnit::Bool,
+            ),
+        }
+    }
 }
@@ -0,0 +1,243 @@
+use std::time::Duration;
+
+use super::UpdateContext;
+
+/// Schedules the updates of an element or subsystem which doesn't update on every simulation
+/// step, such as a computer with a fixed cycle time or a slowly changing model. Updating less
+/// frequently reduces the cost of the element and matches the behaviour of the real computer.
+///
+/// The element returns its period from [`SimulationElement::update_period`]. Before every
+/// step, the [`Simulation`] advances the period of every element it visits. The element
+/// updates on the first step, and from then on once the period elapsed, with the delta
+/// accumulated since its previous update. Time in excess of the period counts towards the
+/// next update, such that the element updates at its period on average, regardless of the
+/// step size. In between updates, the element keeps the state of its last update, such
+/// that its state is consistent at every tick.
+/// # Examples
+/// ```rust
+/// # use std::time::Duration;
+/// # use systems::simulation::{SimulationElement, UpdateContext, UpdatePeriod};
+/// struct MyComputer {
+///     update_period: UpdatePeriod,
+/// }
+/// impl MyComputer {
+///     fn new() -> Self {
+///         Self {
+///             update_period: UpdatePeriod::new(Duration::from_millis(100)),
+///         }
+///     }
+///
+///     fn update(&mut self, context: &UpdateContext) {
+///         if let Some(context) = self.update_period.due(context) {
+///             // Update the computer using the accumulated delta of the context.
+///         }
+///     }
+/// }
+/// impl SimulationElement for MyComputer {
+///     fn update_period(&mut self) -> Option<&mut UpdatePeriod> {
+///         Some(&mut self.update_period)
+///     }
+/// }
+/// ```
+/// [`SimulationElement::update_period`]: trait.SimulationElement.html#method.update_period
+/// [`Simulation`]: struct.Simulation.html
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct UpdatePeriod {
+    period: Duration,
+    accumulated: Duration,
+    elapsed: Duration,
+    is_due: bool,
+    has_updated: bool,
+}
+impl UpdatePeriod {
+    /// # Panics
+    ///
+    /// Panics when the period is zero.
+    pub fn new(period: Duration) -> Self {
+        assert!(
+            period > Duration::from_secs(0),
+            "The update period must be greater than zero."
+        );
+
+        Self {
+            period,
+            accumulated: Duration::from_secs(0),
+            elapsed: Duration::from_secs(0),
+            is_due: false,
+            has_updated: false,
+        }
+    }
+
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+
+    /// Advances the period by the delta of a step. Called by the [`Simulation`]
+    /// before every step.
+    ///
+    /// [`Simulation`]: struct.Simulation.html
+    pub(super) fn advance(&mut self, delta: Duration) {
+        if self.is_due {
+            self.elapsed = Duration::from_secs(0);
+        }
+
+        self.elapsed += delta;
+        self.accumulated += delta;
+        self.is_due = !self.has_updated || self.accumulated >= self.period;
+
+        if self.is_due {
+            // A step longer than the period doesn't cause the following steps to catch up.
+            self.accumulated = if self.has_updated {
+                Duration::from_nanos((self.accumulated.as_nanos() % self.period.as_nanos()) as u64)
+            } else {
+                Duration::from_secs(0)
+            };
+            self.has_updated = true;
+        }
+    }
+
+    /// When the update is due during the current step, returns a context with the
+    /// delta accumulated since the previous update.
+    pub fn due<'a>(&self, context: &UpdateContext<'a>) -> Option<UpdateContext<'a>> {
+        if self.is_due {
+            Some(context.with_delta(self.elapsed))
+        } else {
+            None
+        }
+    }
+
+    /// Causes the next step to update the element, e.g. after it was initialised
+    /// to a starting state or restored from a snapshot.
+    pub fn reset(&mut self) {
+        self.accumulated = Duration::from_secs(0);
+        self.elapsed = Duration::from_secs(0);
+        self.is_due = false;
+        self.has_updated = false;
+    }
+}
+
+#[cfg(test)]
+mod update_period_tests {
+    use super::*;
+    use crate::simulation::{
+        test::SimulationTestBed, Aircraft, SimulationElement, SimulationElementVisitor,
+    };
+
+    struct PeriodicElement {
+        update_period: UpdatePeriod,
+        update_deltas: Vec<Duration>,
+    }
+    impl PeriodicElement {
+        fn new(period: Duration) -> Self {
+            Self {
+                update_period: UpdatePeriod::new(period),
+                update_deltas: vec![],
+            }
+        }
+
+        fn update(&mut self, context: &UpdateContext) {
+            if let Some(context) = self.update_period.due(context) {
+                self.update_deltas.push(context.delta());
+            }
+        }
+    }
+    impl Aircraft for PeriodicElement {
+        fn update_before_power_distribution(&mut self, context: &UpdateContext) {
+            self.update(context);
+        }
+    }
+    impl SimulationElement for PeriodicElement {
+        fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+            visitor.visit(self);
+        }
+
+        fn update_period(&mut self) -> Option<&mut UpdatePeriod> {
+            Some(&mut self.update_period)
+        }
+    }
+
+    fn run(element: &mut PeriodicElement, delta: Duration, ticks: usize) {
+        let mut test_bed = SimulationTestBed::new_with_delta(delta);
+        for _ in 0..ticks {
+            test_bed.run_aircraft(element);
+        }
+    }
+
+    #[test]
+    fn updates_on_the_first_step() {
+        let mut element = PeriodicElement::new(Duration::from_millis(100));
+
+        run(&mut element, Duration::from_millis(30), 1);
+
+        assert_eq!(element.update_deltas, vec![Duration::from_millis(30)]);
+    }
+
+    #[test]
+    fn updates_with_the_accumulated_delta_once_the_period_elapsed() {
+        let mut element = PeriodicElement::new(Duration::from_millis(100));
+
+        run(&mut element, Duration::from_millis(30), 9);
+
+        assert_eq!(
+            element.update_deltas,
+            vec![
+                Duration::from_millis(30),
+                Duration::from_millis(120),
+                Duration::from_millis(90)
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_its_cycle_when_the_steps_do_not_divide_the_period() {
+        let mut element = PeriodicElement::new(Duration::from_millis(70));
+
+        run(&mut element, Duration::from_millis(50), 15);
+
+        // After the first update, 700ms pass in which the element updates ten times.
+        assert_eq!(element.update_deltas.len(), 11);
+        assert_eq!(
+            element.update_deltas[..5],
+            [
+                Duration::from_millis(50),
+                Duration::from_millis(100),
+                Duration::from_millis(50),
+                Duration::from_millis(100),
+                Duration::from_millis(50)
+            ]
+        );
+        assert_eq!(
+            element.update_deltas.iter().sum::<Duration>(),
+            Duration::from_millis(750)
+        );
+    }
+
+    #[test]
+    fn updates_every_step_when_the_delta_exceeds_the_period() {
+        let mut element = PeriodicElement::new(Duration::from_millis(100));
+
+        run(&mut element, Duration::from_millis(250), 3);
+
+        assert_eq!(element.update_deltas, vec![Duration::from_millis(250); 3]);
+    }
+
+    #[test]
+    fn updates_on_the_next_step_after_a_reset() {
+        let mut element = PeriodicElement::new(Duration::from_millis(100));
+        run(&mut element, Duration::from_millis(30), 2);
+
+        element.update_period.reset();
+        run(&mut element, Duration::from_millis(30), 1);
+
+        assert_eq!(
+            element.update_deltas,
+            vec![Duration::from_millis(30), Duration::from_millis(30)]
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn a_period_of_zero_panics() {
+        UpdatePeriod::new(Duration::from_secs(0));
+    }
+}
@@ -0,0 +1,456 @@
+use std::{
+    collections::BTreeSet,
+    fmt::{self, Display},
+};
+
+use super::{ElementDescription, VariableRegistry};
+
+/// A variable found by the [`VariableAudit`], together with the elements reading
+/// or writing it.
+///
+/// [`VariableAudit`]: struct.VariableAudit.html
+#[derive(Clone, Debug, PartialEq)]
+pub struct AuditedVariable {
+    name: String,
+    owners: Vec<&'static str>,
+}
+impl AuditedVariable {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The type names of the elements reading or writing the variable. Empty when
+    /// the variable is only read by consumers outside of the aircraft.
+    pub fn owners(&self) -> &[&'static str] {
+        &self.owners
+    }
+}
+impl Display for AuditedVariable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.owners.is_empty() {
+            write!(f, "{} (consumers)", self.name)
+        } else {
+            write!(f, "{} ({})", self.name, self.owners.join(", "))
+        }
+    }
+}
+
+/// Audits the variables registered by an aircraft against the variables written by the
+/// simulator, or its stand-in in tests, and the variables read by consumers outside of
+/// the aircraft, such as instruments. Given the element tree reached by the visitor, it
+/// also audits which of the elements the aircraft is constructed with are never reached.
+///
+/// # Examples
+/// ```rust,no_run
+/// # use systems::simulation::{test::SimulationTestBed, VariableAudit, VariableRegistry};
+/// # let registry = VariableRegistry::default();
+/// # let test_bed = SimulationTestBed::new();
+/// let report = VariableAudit::new(&registry)
+///     .with_simulator_variables(test_bed.simulator_variables())
+///     .with_consumed_variables(vec!["APU_N", "ELEC_AC_1_BUS_IS_POWERED"])
+///     .report();
+///
+/// assert!(report.is_empty(), "{}", report);
+/// ```
+pub struct VariableAudit<'a> {
+    registry: &'a VariableRegistry,
+    simulator_variables: BTreeSet<String>,
+    consumed_variables: BTreeSet<String>,
+    element_tree: Option<&'a ElementDescription>,
+    constructed_elements: Vec<String>,
+}
+impl<'a> VariableAudit<'a> {
+    pub fn new(registry: &'a VariableRegistry) -> Self {
+        Self {
+            registry,
+            simulator_variables: BTreeSet::new(),
+            consumed_variables: BTreeSet::new(),
+            element_tree: None,
+            constructed_elements: vec![],
+        }
+    }
+
+    /// The variables written by the simulator, or by its stand-in in tests.
+    pub fn with_simulator_variables<I: IntoIterator<Item = S>, S: Into<String>>(
+        mut self,
+        names: I,
+    ) -> Self {
+        self.simulator_variables
+            .extend(names.into_iter().map(Into::into));
+        self
+    }
+
+    /// The variables read by consumers outside of the aircraft, such as instruments.
+    pub fn with_consumed_variables<I: IntoIterator<Item = S>, S: Into<String>>(
+        mut self,
+        names: I,
+    ) -> Self {
+        self.consumed_variables
+            .extend(names.into_iter().map(Into::into));
+        self
+    }
+
+    /// The element tree as reached by the visitor, such as returned by `Simulation::describe`,
+    /// together with the paths of the elements the aircraft is constructed with. A path
+    /// consists of the identifiers leading to the element, e.g. `electrical/direct_current`.
+    pub fn with_constructed_elements<I: IntoIterator<Item = S>, S: Into<String>>(
+        mut self,
+        element_tree: &'a ElementDescription,
+        paths: I,
+    ) -> Self {
+        self.element_tree = Some(element_tree);
+        self.constructed_elements
+            .extend(paths.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn report(&self) -> VariableAuditReport {
+        let read_but_never_written = self
+            .registry
+            .iter()
+            .filter(|definition| {
+                !definition.direction().is_written()
+                    && !self.simulator_variables.contains(definition.name())
+            })
+            .map(|definition| AuditedVariable {
+                name: definition.name().to_owned(),
+                owners: definition.owners().to_vec(),
+            })
+            .chain(
+                self.consumed_variables
+                    .iter()
+                    .filter(|name| {
+                        !self.registry.contains(name) && !self.simulator_variables.contains(*name)
+                    })
+                    .map(|name| AuditedVariable {
+                        name: name.clone(),
+                        owners: vec![],
+                    }),
+            )
+            .collect();
+
+        let written_but_never_read = self
+            .registry
+            .iter()
+            .filter(|definition| {
+                !definition.direction().is_read()
+                    && !self.consumed_variables.contains(definition.name())
+            })
+            .map(|definition| AuditedVariable {
+                name: definition.name().to_owned(),
+                owners: definition.owners().to_vec(),
+            })
+            .collect();
+
+        let unreached_elements = self
+            .element_tree
+            .map(|element_tree| {
+                self.constructed_elements
+                    .iter()
+                    .filter(|path| element_tree.find(path).is_none())
+                    .map(|path| UnreachedElement { path: path.clone() })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        VariableAuditReport {
+            read_but_never_written,
+            written_but_never_read,
+            unreached_elements,
+        }
+    }
+}
+
+/// A constructed element which isn't found in the element tree reached by the visitor.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UnreachedElement {
+    path: String,
+}
+impl UnreachedElement {
+    /// The path of identifiers leading to the element, e.g. `electrical/direct_current`.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+}
+impl Display for UnreachedElement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.path)
+    }
+}
+
+/// The findings of a [`VariableAudit`].
+///
+/// [`VariableAudit`]: struct.VariableAudit.html
+#[derive(Clone, Debug, PartialEq)]
+pub struct VariableAuditReport {
+    read_but_never_written: Vec<AuditedVariable>,
+    written_but_never_read: Vec<AuditedVariable>,
+    unreached_elements: Vec<UnreachedElement>,
+}
+impl VariableAuditReport {
+    /// The variables read by the aircraft or by consumers, which are written
+    /// by neither the aircraft nor the simulator. Reading these results in 0.0.
+    pub fn read_but_never_written(&self) -> &[AuditedVariable] {
+        &self.read_but_never_written
+    }
+
+    /// The variables written by the aircraft, which are read by
+    /// neither the aircraft nor any of the declared consumers.
+    pub fn written_but_never_read(&self) -> &[AuditedVariable] {
+        &self.written_but_never_read
+    }
+
+    /// The constructed elements which aren't reached by the visitor, thus
+    /// whose `read` and `write` functions are never called.
+    pub fn unreached_elements(&self) -> &[UnreachedElement] {
+        &self.unreached_elements
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.read_but_never_written.is_empty()
+            && self.written_but_never_read.is_empty()
+            && self.unreached_elements.is_empty()
+    }
+}
+impl Display for VariableAuditReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.read_but_never_written.is_empty() {
+            writeln!(f, "Read but never written:")?;
+            for variable in &self.read_but_never_written {
+                writeln!(f, "  {}", variable)?;
+            }
+        }
+
+        if !self.written_but_never_read.is_empty() {
+            writeln!(f, "Written but never read:")?;
+            for variable in &self.written_but_never_read {
+                writeln!(f, "  {}", variable)?;
+            }
+        }
+
+        if !self.unreached_elements.is_empty() {
+            writeln!(f, "Never reached by the visitor:")?;
+            for element in &self.unreached_elements {
+                writeln!(f, "  {}", element)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod variable_audit_tests {
+    use super::*;
+    use crate::simulation::{
+        test::SimulationTestBed, SimulationElement, SimulationElementVisitor, SimulatorReader,
+        SimulatorWriter, VariableIdentifier, VariableUnit,
+    };
+
+    struct Pump {
+        name: String,
+        is_on_id: VariableIdentifier<bool>,
+        pressure_id: VariableIdentifier<f64>,
+        is_on: bool,
+    }
+    impl Pump {
+        fn new(name: &str) -> Self {
+            Self {
+                name: name.to_owned(),
+                is_on_id: VariableIdentifier::new(format!("{}_IS_ON", name), VariableUnit::Bool),
+                pressure_id: VariableIdentifier::new(
+                    format!("{}_PRESSURE", name),
+                    VariableUnit::Number,
+                ),
+                is_on: false,
+            }
+        }
+    }
+    impl SimulationElement for Pump {
+        fn identifier(&self) -> String {
+            format!("{}_pump", self.name.to_lowercase())
+        }
+
+        fn read(&mut self, reader: &mut SimulatorReader) {
+            self.is_on = reader.read(&self.is_on_id);
+        }
+
+        fn write(&self, writer: &mut SimulatorWriter) {
+            writer.write(&self.pressure_id, if self.is_on { 3000. } else { 0. });
+        }
+    }
+
+    /// Reads and writes no variables, thus the registry doesn't show whether it is visited.
+    struct Reservoir {}
+    impl SimulationElement for Reservoir {}
+
+    struct Hydraulic {
+        is_pressurised_id: VariableIdentifier<bool>,
+        green: Pump,
+        blue: Pump,
+        reservoir: Reservoir,
+    }
+    impl Hydraulic {
+        fn new() -> Self {
+            Self {
+                is_pressurised_id: VariableIdentifier::new(
+                    "HYD_IS_PRESSURISED",
+                    VariableUnit::Bool,
+                ),
+                green: Pump::new("GREEN"),
+                blue: Pump::new("BLUE"),
+                reservoir: Reservoir {},
+            }
+        }
+
+        fn constructed_elements(&self) -> Vec<String> {
+            vec![
+                self.green.identifier(),
+                self.blue.identifier(),
+                self.reservoir.identifier(),
+            ]
+        }
+    }
+    impl SimulationElement for Hydraulic {
+        fn accept<T: SimulationElementVisitor>(&mut self, visitor: &mut T) {
+            // The blue pump and the reservoir are never visited.
+            visitor.enter_children();
+            self.green.accept(visitor);
+            visitor.leave_children();
+
+            visitor.visit(self);
+        }
+
+        fn write(&self, writer: &mut SimulatorWriter) {
+            writer.write(&self.is_pressurised_id, self.green.is_on || self.blue.is_on);
+        }
+    }
+
+    fn registry<T: SimulationElement>(element: &mut T) -> VariableRegistry {
+        SimulationTestBed::new()
+            .register_variables(element)
+            .unwrap()
+    }
+
+    fn audit_report(hydraulic: &mut Hydraulic) -> VariableAuditReport {
+        let registry = registry(hydraulic);
+        let element_tree = SimulationTestBed::new().describe(hydraulic);
+
+        VariableAudit::new(&registry)
+            .with_constructed_elements(&element_tree, hydraulic.constructed_elements())
+            .report()
+    }
+
+    fn names(variables: &[AuditedVariable]) -> Vec<&str> {
+        variables.iter().map(|variable| variable.name()).collect()
+    }
+
+    fn paths(elements: &[UnreachedElement]) -> Vec<&str> {
+        elements.iter().map(|element| element.path()).collect()
+    }
+
+    #[test]
+    fn reports_variables_read_but_never_written() {
+        let registry = registry(&mut Hydraulic::new());
+
+        let report = VariableAudit::new(&registry)
+            .with_consumed_variables(vec!["GREEN_PRESSURE", "YELLOW_PRESSURE"])
+            .report();
+
+        assert!(names(report.read_but_never_written()).contains(&"GREEN_IS_ON"));
+        assert!(names(report.read_but_never_written()).contains(&"YELLOW_PRESSURE"));
+    }
+
+    #[test]
+    fn variables_written_by_the_simulator_are_not_reported_as_never_written() {
+        let registry = registry(&mut Hydraulic::new());
+
+        let report = VariableAudit::new(&registry)
+            .with_simulator_variables(registry.iter().map(|definition| definition.name()))
+            .report();
+
+        assert!(report.read_but_never_written().is_empty());
+    }
+
+    #[test]
+    fn reports_variables_written_but_never_read() {
+        let registry = registry(&mut Hydraulic::new());
+
+        let report = VariableAudit::new(&registry).report();
+
+        assert_eq!(
+            names(report.written_but_never_read()),
+            vec!["GREEN_PRESSURE", "HYD_IS_PRESSURISED"]
+        );
+        assert!(report.written_but_never_read()[0].owners()[0].ends_with("Pump"));
+    }
+
+    #[test]
+    fn variables_read_by_consumers_are_not_reported_as_never_read() {
+        let registry = registry(&mut Hydraulic::new());
+
+        let report = VariableAudit::new(&registry)
+            .with_consumed_variables(vec!["GREEN_PRESSURE", "HYD_IS_PRESSURISED"])
+            .report();
+
+        assert!(report.written_but_never_read().is_empty());
+    }
+
+    #[test]
+    fn reports_constructed_elements_never_reached_by_the_visitor() {
+        let report = audit_report(&mut Hydraulic::new());
+
+        assert_eq!(
+            paths(report.unreached_elements()),
+            vec!["blue_pump", "reservoir"]
+        );
+    }
+
+    #[test]
+    fn reports_unreached_elements_which_read_and_write_no_variables() {
+        let report = audit_report(&mut Hydraulic::new());
+
+        assert!(paths(report.unreached_elements()).contains(&"reservoir"));
+    }
+
+    #[test]
+    fn reached_elements_are_not_reported() {
+        let report = audit_report(&mut Hydraulic::new());
+
+        assert!(!paths(report.unreached_elements()).contains(&"green_pump"));
+    }
+
+    #[test]
+    fn elements_are_not_audited_without_the_element_tree() {
+        let registry = registry(&mut Hydraulic::new());
+
+        let report = VariableAudit::new(&registry).report();
+
+        assert!(report.unreached_elements().is_empty());
+    }
+
+    #[test]
+    fn report_lists_the_findings() {
+        let report = audit_report(&mut Hydraulic::new());
+
+        let text = report.to_string();
+        let section = |heading: &str| -> Vec<&str> {
+            text.lines()
+                .skip_while(|line| *line != heading)
+                .skip(1)
+                .take_while(|line| line.starts_with("  "))
+                .collect()
+        };
+        let lists = |heading: &str, name: &str| {
+            section(heading)
+                .iter()
+                .any(|line| line.starts_with(&format!("  {} (", name)))
+        };
+        assert!(lists("Read but never written:", "GREEN_IS_ON"));
+        assert!(lists("Written but never read:", "GREEN_PRESSURE"));
+        assert_eq!(
+            section("Never reached by the visitor:"),
+            vec!["  blue_pump", "  reservoir"]
+        );
+    }
+}
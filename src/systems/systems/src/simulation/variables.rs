@@ -0,0 +1,544 @@
+use std::{any::type_name, borrow::Cow, collections::HashMap, fmt, marker::PhantomData};
+use uom::si::{
+    acceleration::foot_per_second_squared, angle::degree, electric_current::ampere,
+    electric_potential::volt, f64::*, frequency::hertz, length::foot, mass::pound,
+    pressure::inch_of_mercury, ratio::percent, thermodynamic_temperature::degree_celsius,
+    velocity::foot_per_minute, velocity::knot,
+};
+
+use super::{from_bool, to_bool, SimulationElement, SimulationElementVisitor};
+
+/// The unit in which the value of a variable is exchanged with the simulator.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum VariableUnit {
+    Bool,
+    Number,
+    Percent,
+    Ampere,
+    Volt,
+    Hertz,
+    DegreeCelsius,
+    Knot,
+    FootPerMinute,
+    Foot,
+    Pound,
+    InchOfMercury,
+    Degree,
+    FootPerSecondSquared,
+}
+impl fmt::Display for VariableUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VariableUnit::Bool => write!(f, "Bool"),
+            VariableUnit::Number => write!(f, "Number"),
+            VariableUnit::Percent => write!(f, "Percent"),
+            VariableUnit::Ampere => write!(f, "Amperes"),
+            VariableUnit::Volt => write!(f, "Volts"),
+            VariableUnit::Hertz => write!(f, "Hertz"),
+            VariableUnit::DegreeCelsius => write!(f, "Celsius"),
+            VariableUnit::Knot => write!(f, "Knots"),
+            VariableUnit::FootPerMinute => write!(f, "Feet per minute"),
+            VariableUnit::Foot => write!(f, "Feet"),
+            VariableUnit::Pound => write!(f, "Pounds"),
+            VariableUnit::InchOfMercury => write!(f, "Inches of mercury"),
+            VariableUnit::Degree => write!(f, "Degrees"),
+            VariableUnit::FootPerSecondSquared => write!(f, "Feet per second squared"),
+        }
+    }
+}
+
+/// A type which can be read from and written to the simulator. The simulator exchanges
+/// every value as a number in the unit of the variable. Quantities are converted from and
+/// to that unit, such that elements read and write them without converting them manually.
+pub trait VariableValue: Copy {
+    /// Whether a variable with the given unit can be read and written as this type.
+    fn is_measured_in(unit: VariableUnit) -> bool;
+    fn from_f64(value: f64, unit: VariableUnit) -> Self;
+    fn into_f64(self, unit: VariableUnit) -> f64;
+}
+impl VariableValue for f64 {
+    fn is_measured_in(_: VariableUnit) -> bool {
+        true
+    }
+
+    fn from_f64(value: f64, _: VariableUnit) -> Self {
+        value
+    }
+
+    fn into_f64(self, _: VariableUnit) -> f64 {
+        self
+    }
+}
+impl VariableValue for bool {
+    fn is_measured_in(unit: VariableUnit) -> bool {
+        unit == VariableUnit::Bool
+    }
+
+    fn from_f64(value: f64, _: VariableUnit) -> Self {
+        to_bool(value)
+    }
+
+    fn into_f64(self, _: VariableUnit) -> f64 {
+        from_bool(self)
+    }
+}
+
+/// Implements [`VariableValue`] for a quantity, given the units in which
+/// the simulator can exchange it.
+macro_rules! quantity_variable_value {
+    ($quantity: ident, $($unit: ident => $quantity_unit: ty),+) => {
+        impl VariableValue for $quantity {
+            fn is_measured_in(unit: VariableUnit) -> bool {
+                matches!(unit, $(VariableUnit::$unit)|+)
+            }
+
+            fn from_f64(value: f64, unit: VariableUnit) -> Self {
+                match unit {
+                    $(VariableUnit::$unit => $quantity::new::<$quantity_unit>(value),)+
+                    _ => panic!("{} cannot be measured in {}.", stringify!($quantity), unit),
+                }
+            }
+
+            fn into_f64(self, unit: VariableUnit) -> f64 {
+                match unit {
+                    $(VariableUnit::$unit => self.get::<$quantity_unit>(),)+
+                    _ => panic!("{} cannot be measured in {}.", stringify!($quantity), unit),
+                }
+            }
+        }
+    };
+}
+
+quantity_variable_value!(Ratio, Percent => percent);
+quantity_variable_value!(ElectricCurrent, Ampere => ampere);
+quantity_variable_value!(ElectricPotential, Volt => volt);
+quantity_variable_value!(Frequency, Hertz => hertz);
+quantity_variable_value!(ThermodynamicTemperature, DegreeCelsius => degree_celsius);
+quantity_variable_value!(Velocity, Knot => knot, FootPerMinute => foot_per_minute);
+quantity_variable_value!(Length, Foot => foot);
+quantity_variable_value!(Mass, Pound => pound);
+quantity_variable_value!(Pressure, InchOfMercury => inch_of_mercury);
+quantity_variable_value!(Angle, Degree => degree);
+quantity_variable_value!(Acceleration, FootPerSecondSquared => foot_per_second_squared);
+
+/// Identifies a variable which is read from or written to the simulator.
+/// Create the identifier once when constructing the element, and pass it to the
+/// [`SimulatorReader`] and [`SimulatorWriter`] on every tick. The type parameter
+/// determines the type of the value which is read and written, e.g. an identifier
+/// of type `VariableIdentifier<Mass>` with the unit [`VariableUnit::Pound`] reads
+/// the pounds stored in the simulator as a `Mass`.
+///
+/// [`VariableUnit::Pound`]: enum.VariableUnit.html#variant.Pound
+/// [`SimulatorReader`]: struct.SimulatorReader.html
+/// [`SimulatorWriter`]: struct.SimulatorWriter.html
+#[derive(Clone, Debug, PartialEq)]
+pub struct VariableIdentifier<T: VariableValue> {
+    name: Cow<'static, str>,
+    unit: VariableUnit,
+    value_type: PhantomData<T>,
+}
+impl<T: VariableValue> VariableIdentifier<T> {
+    /// # Panics
+    ///
+    /// Panics when the value type cannot be measured in the given unit.
+    pub fn new<U: Into<Cow<'static, str>>>(name: U, unit: VariableUnit) -> Self {
+        let name = name.into();
+        assert!(
+            T::is_measured_in(unit),
+            "Variable '{}' cannot be read or written as {} in {}.",
+            name,
+            type_name::<T>(),
+            unit
+        );
+
+        Self {
+            name,
+            unit,
+            value_type: PhantomData,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn unit(&self) -> VariableUnit {
+        self.unit
+    }
+}
+
+/// Whether the aircraft system simulation reads a variable, writes it, or both.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum VariableDirection {
+    Read,
+    Write,
+    ReadWrite,
+}
+impl VariableDirection {
+    pub fn is_read(&self) -> bool {
+        matches!(self, VariableDirection::Read | VariableDirection::ReadWrite)
+    }
+
+    pub fn is_written(&self) -> bool {
+        matches!(
+            self,
+            VariableDirection::Write | VariableDirection::ReadWrite
+        )
+    }
+
+    fn merge(self, other: VariableDirection) -> VariableDirection {
+        if self == other {
+            self
+        } else {
+            VariableDirection::ReadWrite
+        }
+    }
+}
+
+/// Describes a variable which the aircraft system simulation reads from or writes to the simulator.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VariableDefinition {
+    name: String,
+    unit: VariableUnit,
+    direction: VariableDirection,
+    owners: Vec<&'static str>,
+}
+impl VariableDefinition {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn unit(&self) -> VariableUnit {
+        self.unit
+    }
+
+    pub fn direction(&self) -> VariableDirection {
+        self.direction
+    }
+
+    /// The type names of the elements which read or write the variable.
+    pub fn owners(&self) -> &[&'static str] {
+        &self.owners
+    }
+}
+
+/// A problem found while registering the variables of an aircraft.
+#[derive(Clone, Debug, PartialEq)]
+pub enum VariableRegistryError {
+    /// The variable is written by more than one element.
+    DuplicateWriter {
+        name: String,
+        first_owner: &'static str,
+        second_owner: &'static str,
+    },
+    /// The variable is registered using different units.
+    ConflictingUnit {
+        name: String,
+        registered: VariableUnit,
+        requested: VariableUnit,
+    },
+}
+impl fmt::Display for VariableRegistryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VariableRegistryError::DuplicateWriter {
+                name,
+                first_owner,
+                second_owner,
+            } => write!(
+                f,
+                "variable '{}' is written by both '{}' and '{}'",
+                name, first_owner, second_owner
+            ),
+            VariableRegistryError::ConflictingUnit {
+                name,
+                registered,
+                requested,
+            } => write!(
+                f,
+                "variable '{}' is registered as '{}' and as '{}'",
+                name, registered, requested
+            ),
+        }
+    }
+}
+impl std::error::Error for VariableRegistryError {}
+
+/// Lists every variable the aircraft system simulation reads from and writes to the simulator.
+/// Use [`Simulation::register_variables`] to create the registry of an aircraft.
+///
+/// [`Simulation::register_variables`]: struct.Simulation.html#method.register_variables
+#[derive(Debug, Default)]
+pub struct VariableRegistry {
+    definitions: Vec<VariableDefinition>,
+    indices: HashMap<String, usize>,
+    writers: HashMap<usize, usize>,
+    key_events: Vec<String>,
+    errors: Vec<VariableRegistryError>,
+    owner: Option<(usize, &'static str)>,
+    number_of_owners: usize,
+}
+impl VariableRegistry {
+    pub fn get(&self, name: &str) -> Option<&VariableDefinition> {
+        self.indices
+            .get(name)
+            .map(|&index| &self.definitions[index])
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.indices.contains_key(name)
+    }
+
+    /// Iterates over the variables in the order in which they were registered.
+    pub fn iter(&self) -> impl Iterator<Item = &VariableDefinition> {
+        self.definitions.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.definitions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.definitions.is_empty()
+    }
+
+    /// Iterates over the names of the key events handled by the aircraft, in the
+    /// order in which they were registered.
+    pub fn key_events(&self) -> impl Iterator<Item = &str> {
+        self.key_events.iter().map(|name| name.as_str())
+    }
+
+    /// Attributes the variables registered from now on to a new owner.
+    pub(super) fn begin_owner(&mut self, owner: &'static str) {
+        self.owner = Some((self.number_of_owners, owner));
+        self.number_of_owners += 1;
+    }
+
+    pub(super) fn register_read(&mut self, name: &str, unit: VariableUnit) {
+        self.register(name, unit, VariableDirection::Read);
+    }
+
+    pub(super) fn register_write(&mut self, name: &str, unit: VariableUnit) {
+        let index = self.register(name, unit, VariableDirection::Write);
+
+        let (owner, owner_name) = self.current_owner();
+        let first_writer = *self.writers.entry(index).or_insert(owner);
+        if first_writer != owner {
+            self.errors.push(VariableRegistryError::DuplicateWriter {
+                name: name.to_owned(),
+                first_owner: self.definitions[index].owners[0],
+                second_owner: owner_name,
+            });
+        }
+    }
+
+    pub(super) fn register_key_event(&mut self, name: &str) {
+        if !self.key_events.iter().any(|registered| registered == name) {
+            self.key_events.push(name.to_owned());
+        }
+    }
+
+    fn register(&mut self, name: &str, unit: VariableUnit, direction: VariableDirection) -> usize {
+        let (_, owner_name) = self.current_owner();
+
+        match self.indices.get(name) {
+            Some(&index) => {
+                let definition = &mut self.definitions[index];
+                if definition.unit != unit {
+                    self.errors.push(VariableRegistryError::ConflictingUnit {
+                        name: name.to_owned(),
+                        registered: definition.unit,
+                        requested: unit,
+                    });
+                }
+
+                definition.direction = definition.direction.merge(direction);
+                if !definition.owners.contains(&owner_name) {
+                    definition.owners.push(owner_name);
+                }
+
+                index
+            }
+            None => {
+                let index = self.definitions.len();
+                self.definitions.push(VariableDefinition {
+                    name: name.to_owned(),
+                    unit,
+                    direction,
+                    owners: vec![owner_name],
+                });
+                self.indices.insert(name.to_owned(), index);
+
+                index
+            }
+        }
+    }
+
+    fn current_owner(&self) -> (usize, &'static str) {
+        self.owner
+            .expect("An owner must be set before registering variables.")
+    }
+
+    pub(super) fn into_result(self) -> Result<VariableRegistry, Vec<VariableRegistryError>> {
+        if self.errors.is_empty() {
+            Ok(self)
+        } else {
+            Err(self.errors)
+        }
+    }
+}
+
+/// Visits aircraft components in order to register the variables they read and write.
+pub(super) struct VariableRegistrationVisitor<'a> {
+    registry: &'a mut VariableRegistry,
+}
+impl<'a> VariableRegistrationVisitor<'a> {
+    pub fn new(registry: &'a mut VariableRegistry) -> Self {
+        VariableRegistrationVisitor { registry }
+    }
+}
+impl SimulationElementVisitor for VariableRegistrationVisitor<'_> {
+    fn visit<T: SimulationElement>(&mut self, visited: &mut T) {
+        self.registry.begin_owner(type_name::<T>());
+        super::register_variables_of(visited, self.registry);
+    }
+}
+
+#[cfg(test)]
+mod variables_tests {
+    use super::*;
+
+    #[test]
+    fn registers_variables_in_order() {
+        let mut registry = VariableRegistry::default();
+        registry.begin_owner("Owner");
+        registry.register_read("FIRST", VariableUnit::Bool);
+        registry.register_write("SECOND", VariableUnit::Percent);
+
+        let names: Vec<&str> = registry
+            .iter()
+            .map(|definition| definition.name())
+            .collect();
+        assert_eq!(names, vec!["FIRST", "SECOND"]);
+        assert_eq!(
+            registry.get("SECOND").unwrap().unit(),
+            VariableUnit::Percent
+        );
+    }
+
+    #[test]
+    fn variable_read_and_written_has_read_write_direction() {
+        let mut registry = VariableRegistry::default();
+        registry.begin_owner("Owner");
+        registry.register_read("VARIABLE", VariableUnit::Bool);
+        registry.register_write("VARIABLE", VariableUnit::Bool);
+
+        assert_eq!(
+            registry.get("VARIABLE").unwrap().direction(),
+            VariableDirection::ReadWrite
+        );
+        assert!(registry.into_result().is_ok());
+    }
+
+    #[test]
+    fn variable_read_by_multiple_owners_is_allowed() {
+        let mut registry = VariableRegistry::default();
+        registry.begin_owner("Writer");
+        registry.register_write("VARIABLE", VariableUnit::Bool);
+        registry.begin_owner("Reader");
+        registry.register_read("VARIABLE", VariableUnit::Bool);
+
+        assert_eq!(
+            registry.get("VARIABLE").unwrap().owners(),
+            &["Writer", "Reader"]
+        );
+        assert!(registry.into_result().is_ok());
+    }
+
+    #[test]
+    fn variable_written_by_multiple_owners_is_an_error() {
+        let mut registry = VariableRegistry::default();
+        registry.begin_owner("First");
+        registry.register_write("VARIABLE", VariableUnit::Bool);
+        registry.begin_owner("Second");
+        registry.register_write("VARIABLE", VariableUnit::Bool);
+
+        assert_eq!(
+            registry.into_result().unwrap_err(),
+            vec![VariableRegistryError::DuplicateWriter {
+                name: "VARIABLE".to_owned(),
+                first_owner: "First",
+                second_owner: "Second"
+            }]
+        );
+    }
+
+    #[test]
+    fn variable_written_by_multiple_instances_of_the_same_type_is_an_error() {
+        let mut registry = VariableRegistry::default();
+        registry.begin_owner("Owner");
+        registry.register_write("VARIABLE", VariableUnit::Bool);
+        registry.begin_owner("Owner");
+        registry.register_write("VARIABLE", VariableUnit::Bool);
+
+        assert!(registry.into_result().is_err());
+    }
+
+    #[test]
+    fn variable_registered_with_different_units_is_an_error() {
+        let mut registry = VariableRegistry::default();
+        registry.begin_owner("Owner");
+        registry.register_read("VARIABLE", VariableUnit::Percent);
+        registry.register_read("VARIABLE", VariableUnit::Number);
+
+        assert_eq!(
+            registry.into_result().unwrap_err(),
+            vec![VariableRegistryError::ConflictingUnit {
+                name: "VARIABLE".to_owned(),
+                registered: VariableUnit::Percent,
+                requested: VariableUnit::Number
+            }]
+        );
+    }
+
+    #[test]
+    fn quantity_is_converted_from_the_unit_of_the_variable() {
+        let speed = Velocity::from_f64(1000., VariableUnit::FootPerMinute);
+
+        assert!((speed.get::<foot_per_minute>() - 1000.).abs() < 1e-9);
+        assert!((speed.get::<knot>() - 9.8747).abs() < 1e-3);
+    }
+
+    #[test]
+    fn quantity_is_converted_into_the_unit_of_the_variable() {
+        let mass = Mass::new::<uom::si::mass::kilogram>(1000.);
+
+        assert!((mass.into_f64(VariableUnit::Pound) - 2204.62).abs() < 1e-2);
+    }
+
+    #[test]
+    fn ratio_is_exchanged_as_percent() {
+        assert_eq!(
+            Ratio::new::<percent>(42.).into_f64(VariableUnit::Percent),
+            42.
+        );
+    }
+
+    #[test]
+    fn f64_is_exchanged_unconverted_in_any_unit() {
+        assert_eq!(f64::from_f64(42., VariableUnit::Pound), 42.);
+        assert_eq!(42_f64.into_f64(VariableUnit::Knot), 42.);
+    }
+
+    #[test]
+    #[should_panic]
+    fn identifier_of_quantity_in_a_unit_of_another_quantity_panics() {
+        VariableIdentifier::<Mass>::new("FUEL TANK LEFT MAIN QUANTITY", VariableUnit::Volt);
+    }
+
+    #[test]
+    #[should_panic]
+    fn identifier_of_bool_in_a_unit_other_than_bool_panics() {
+        VariableIdentifier::<bool>::new("GENERAL ENG STARTER ACTIVE", VariableUnit::Percent);
+    }
+}
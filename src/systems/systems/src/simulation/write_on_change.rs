@@ -0,0 +1,244 @@
+use std::{collections::HashMap, time::Duration};
+
+use super::{KeyEvent, SimulatorReaderWriter};
+
+/// Counts the writes requested by the aircraft system simulation
+/// and the writes which were forwarded to the simulator.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct WriteStatistics {
+    requested: u64,
+    forwarded: u64,
+}
+impl WriteStatistics {
+    pub fn requested(&self) -> u64 {
+        self.requested
+    }
+
+    pub fn forwarded(&self) -> u64 {
+        self.forwarded
+    }
+
+    pub fn skipped(&self) -> u64 {
+        self.requested - self.forwarded
+    }
+}
+
+/// Wraps a [`SimulatorReaderWriter`] and only forwards writes of values which
+/// differ from the value last written to or read from the simulator.
+///
+/// Values are considered equal when they differ by no more than the configured epsilon.
+/// As booleans are written as `0.` or `1.`, they are unaffected by any epsilon smaller than `1.`.
+/// Reading a variable also updates the cached value, so that a change made by the
+/// simulator to a variable which is both read and written is overwritten when needed.
+///
+/// [`SimulatorReaderWriter`]: trait.SimulatorReaderWriter.html
+pub struct WriteOnChangeReaderWriter<T: SimulatorReaderWriter> {
+    simulator_read_writer: T,
+    epsilon: f64,
+    values: HashMap<String, f64>,
+    statistics: WriteStatistics,
+}
+impl<T: SimulatorReaderWriter> WriteOnChangeReaderWriter<T> {
+    pub fn new(simulator_read_writer: T) -> Self {
+        Self::new_with_epsilon(simulator_read_writer, 0.)
+    }
+
+    pub fn new_with_epsilon(simulator_read_writer: T, epsilon: f64) -> Self {
+        Self {
+            simulator_read_writer,
+            epsilon,
+            values: HashMap::new(),
+            statistics: WriteStatistics::default(),
+        }
+    }
+
+    pub fn statistics(&self) -> WriteStatistics {
+        self.statistics
+    }
+
+    pub fn reset_statistics(&mut self) {
+        self.statistics = WriteStatistics::default();
+    }
+
+    /// Forgets all cached values, causing the next write of every variable to be forwarded.
+    pub fn invalidate(&mut self) {
+        self.values.clear();
+    }
+
+    pub fn inner(&self) -> &T {
+        &self.simulator_read_writer
+    }
+
+    pub fn inner_mut(&mut self) -> &mut T {
+        &mut self.simulator_read_writer
+    }
+
+    fn is_unchanged(&self, name: &str, value: f64) -> bool {
+        match self.values.get(name) {
+            Some(last_value) => (value - last_value).abs() <= self.epsilon,
+            None => false,
+        }
+    }
+}
+impl<T: SimulatorReaderWriter> SimulatorReaderWriter for WriteOnChangeReaderWriter<T> {
+    fn read(&mut self, name: &str) -> f64 {
+        let value = self.simulator_read_writer.read(name);
+        if let Some(last_value) = self.values.get_mut(name) {
+            *last_value = value;
+        }
+
+        value
+    }
+
+    fn write(&mut self, name: &str, value: f64) {
+        self.statistics.requested += 1;
+        if self.is_unchanged(name, value) {
+            return;
+        }
+
+        self.statistics.forwarded += 1;
+        self.values.insert(name.to_owned(), value);
+        self.simulator_read_writer.write(name, value);
+    }
+
+    fn start_tick(&mut self, delta: Duration, simulation_rate: f64, is_paused: bool) {
+        self.simulator_read_writer
+            .start_tick(delta, simulation_rate, is_paused);
+    }
+
+    fn read_key_events(&mut self) -> Vec<KeyEvent> {
+        self.simulator_read_writer.read_key_events()
+    }
+
+    fn end_tick(&mut self) {
+        self.simulator_read_writer.end_tick();
+    }
+}
+
+#[cfg(test)]
+mod write_on_change_tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct RecordingReaderWriter {
+        variables: HashMap<String, f64>,
+        writes: Vec<(String, f64)>,
+    }
+    impl SimulatorReaderWriter for RecordingReaderWriter {
+        fn read(&mut self, name: &str) -> f64 {
+            *self.variables.get(name).unwrap_or(&0.)
+        }
+
+        fn write(&mut self, name: &str, value: f64) {
+            self.variables.insert(name.to_owned(), value);
+            self.writes.push((name.to_owned(), value));
+        }
+    }
+
+    #[test]
+    fn forwards_the_first_write() {
+        let mut reader_writer = WriteOnChangeReaderWriter::new(RecordingReaderWriter::default());
+
+        reader_writer.write("VARIABLE", 1.);
+
+        assert_eq!(reader_writer.inner().writes.len(), 1);
+    }
+
+    #[test]
+    fn does_not_forward_an_unchanged_value() {
+        let mut reader_writer = WriteOnChangeReaderWriter::new(RecordingReaderWriter::default());
+
+        reader_writer.write("VARIABLE", 1.);
+        reader_writer.write("VARIABLE", 1.);
+
+        assert_eq!(reader_writer.inner().writes.len(), 1);
+    }
+
+    #[test]
+    fn forwards_a_changed_value() {
+        let mut reader_writer = WriteOnChangeReaderWriter::new(RecordingReaderWriter::default());
+
+        reader_writer.write("VARIABLE", 1.);
+        reader_writer.write("VARIABLE", 2.);
+
+        assert_eq!(reader_writer.inner().writes.len(), 2);
+    }
+
+    #[test]
+    fn does_not_forward_a_change_within_epsilon() {
+        let mut reader_writer =
+            WriteOnChangeReaderWriter::new_with_epsilon(RecordingReaderWriter::default(), 0.1);
+
+        reader_writer.write("VARIABLE", 1.);
+        reader_writer.write("VARIABLE", 1.05);
+
+        assert_eq!(reader_writer.inner().writes.len(), 1);
+    }
+
+    #[test]
+    fn change_is_measured_against_the_last_forwarded_value() {
+        let mut reader_writer =
+            WriteOnChangeReaderWriter::new_with_epsilon(RecordingReaderWriter::default(), 0.1);
+
+        reader_writer.write("VARIABLE", 1.);
+        reader_writer.write("VARIABLE", 1.06);
+        reader_writer.write("VARIABLE", 1.12);
+
+        assert_eq!(
+            reader_writer.inner().writes,
+            vec![("VARIABLE".to_owned(), 1.), ("VARIABLE".to_owned(), 1.12)]
+        );
+    }
+
+    #[test]
+    fn forwards_a_write_which_restores_a_value_changed_by_the_simulator() {
+        let mut reader_writer = WriteOnChangeReaderWriter::new(RecordingReaderWriter::default());
+        reader_writer.write("VARIABLE", 1.);
+        reader_writer
+            .inner_mut()
+            .variables
+            .insert("VARIABLE".to_owned(), 0.);
+
+        reader_writer.read("VARIABLE");
+        reader_writer.write("VARIABLE", 1.);
+
+        assert_eq!(reader_writer.inner().writes.len(), 2);
+    }
+
+    #[test]
+    fn forwards_all_writes_after_invalidation() {
+        let mut reader_writer = WriteOnChangeReaderWriter::new(RecordingReaderWriter::default());
+        reader_writer.write("VARIABLE", 1.);
+
+        reader_writer.invalidate();
+        reader_writer.write("VARIABLE", 1.);
+
+        assert_eq!(reader_writer.inner().writes.len(), 2);
+    }
+
+    #[test]
+    fn counts_requested_and_forwarded_writes() {
+        let mut reader_writer = WriteOnChangeReaderWriter::new(RecordingReaderWriter::default());
+
+        reader_writer.write("VARIABLE", 1.);
+        reader_writer.write("VARIABLE", 1.);
+        reader_writer.write("OTHER", 1.);
+
+        let statistics = reader_writer.statistics();
+        assert_eq!(statistics.requested(), 3);
+        assert_eq!(statistics.forwarded(), 2);
+        assert_eq!(statistics.skipped(), 1);
+    }
+
+    #[test]
+    fn resetting_statistics_keeps_the_cached_values() {
+        let mut reader_writer = WriteOnChangeReaderWriter::new(RecordingReaderWriter::default());
+        reader_writer.write("VARIABLE", 1.);
+
+        reader_writer.reset_statistics();
+        reader_writer.write("VARIABLE", 1.);
+
+        assert_eq!(reader_writer.statistics().requested(), 1);
+        assert_eq!(reader_writer.statistics().forwarded(), 0);
+    }
+}